@@ -0,0 +1,260 @@
+// Continuum-Aider: Transparent wrapper for Aider CLI
+// Automatically captures all conversations to plain-text JSONL files
+
+use std::process::{Command, Stdio};
+use color_eyre::{eyre::Context, Result};
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+
+    // A broken config file shouldn't stop the assistant from starting
+    let mut config = continuum_core::Config::load().unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring continuum config: {:#}", e);
+        continuum_core::Config::default()
+    });
+
+    // Get all arguments passed to continuum-aider
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Our own flags; aider would reject them
+    if let Some(level) = continuum_core::take_noise_flag(&mut args)? {
+        config.noise_filter.level = level;
+    }
+    if continuum_core::take_redact_flag(&mut args) {
+        config.redact = true;
+    }
+
+    // Find the real aider binary
+    let aider_path = which::which("aider")
+        .context("Failed to find aider binary")?;
+
+    // Resolve symlinks to get the actual binary path
+    let resolved_aider = std::fs::canonicalize(&aider_path)
+        .unwrap_or_else(|_| aider_path.clone());
+
+    let aider_path_str = resolved_aider
+        .to_str()
+        .ok_or_else(|| color_eyre::eyre::eyre!("Invalid path"))?
+        .to_string();
+
+    // If the found aider IS this wrapper, search for the real aider binary
+    let real_aider = if aider_path_str.contains("continuum-aider") {
+        let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+
+        // Try common installation locations (Linux-first for platform neutrality)
+        let fallback_paths = [
+            "/usr/bin/aider".to_string(),                               // Linux standard (pacman, apt)
+            "/usr/local/bin/aider".to_string(),                         // User install (both platforms)
+            format!("{}/.local/bin/aider-real", home),                  // Backed up binary
+            format!("{}/.local/bin/aider", home),                       // pipx / uv tool install
+            "/opt/homebrew/bin/aider".to_string(),                      // macOS Homebrew
+        ];
+
+        fallback_paths
+            .iter()
+            .find(|path| {
+                let p = std::path::Path::new(path);
+                p.exists() && !p.to_string_lossy().contains("continuum-aider")
+            })
+            .ok_or_else(|| color_eyre::eyre::eyre!(
+                "Could not find real aider binary. Tried: {}",
+                fallback_paths.join(", ")
+            ))?
+            .to_string()
+    } else {
+        aider_path_str
+    };
+
+    // Skipped via $CONTINUUM_NOSAVE or the single-use ~/.continuum-nosave marker
+    let skip_saving = continuum_core::config::should_skip_saving();
+
+    if skip_saving {
+        eprintln!("⚠ This conversation will NOT be saved to continuum logs");
+    }
+
+    // Get the most recently modified session file BEFORE running aider
+    let sessions_dir = config.assistant_path("aider").unwrap_or_default();
+    let cwd = std::env::current_dir().context("Failed to read current directory")?;
+
+    let before_session = find_latest_session_file(&sessions_dir, &cwd);
+
+    // Spawn aider as a child process
+    let status = Command::new(&real_aider)
+        .args(&args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .context("Failed to spawn aider process")?
+        .wait()?;
+
+    // After aider exits, find the session that was just modified
+    let after_session = find_latest_session_file(&sessions_dir, &cwd);
+
+    // Import the session if it changed while aider ran (and we're not skipping)
+    let mut session_dir: Option<std::path::PathBuf> = None;
+    if !skip_saving {
+        if let Some((session_path, modified)) = after_session {
+            let changed = match before_session {
+                Some((ref before_path, before_modified)) => {
+                    before_path != &session_path || modified > before_modified
+                }
+                None => true,
+            };
+
+            if changed {
+                eprintln!("\n📝 Importing session to continuum logs...");
+                match import_session_to_continuum(&session_path, &config) {
+                    Ok((dir, true)) => {
+                        session_dir = Some(dir);
+                    }
+                    // The history continues one saved on a previous run, so there is nothing to discard
+                    Ok((_, false)) => {}
+                    Err(e) => {
+                        eprintln!("⚠ Warning: Failed to import session: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    // Post-conversation review prompt (if session was saved)
+    if let Some(ref dir) = session_dir {
+        if !prompt_save_conversation()? {
+            // User chose to discard - delete the session directory
+            discard_session(dir, "aider", &config);
+            eprintln!("✗ Conversation discarded");
+        } else {
+            eprintln!("✓ Conversation saved");
+        }
+    }
+
+    std::process::exit(status.code().unwrap_or(1))
+}
+
+/// Find the most recently modified Aider session: either the project's
+/// .aider.chat.history.md or a markdown file in the flat sessions directory
+fn find_latest_session_file(
+    sessions_dir: &std::path::Path,
+    cwd: &std::path::Path,
+) -> Option<(std::path::PathBuf, std::time::SystemTime)> {
+    let history = cwd.join(continuum_core::adapters::aider::HISTORY_FILE);
+    let mut latest: Option<(std::path::PathBuf, std::time::SystemTime)> = std::fs::metadata(&history)
+        .and_then(|m| m.modified())
+        .ok()
+        .map(|modified| (history, modified));
+
+    let Ok(entries) = std::fs::read_dir(sessions_dir) else {
+        return latest;
+    };
+
+    for file_entry in entries.flatten() {
+        let file_path = file_entry.path();
+
+        if file_path.extension().and_then(|s| s.to_str()) == Some("md") {
+            if let Ok(modified) = std::fs::metadata(&file_path).and_then(|m| m.modified()) {
+                if latest.is_none() || modified > latest.as_ref().unwrap().1 {
+                    latest = Some((file_path, modified));
+                }
+            }
+        }
+    }
+
+    latest
+}
+
+/// Import a session, returning its directory and whether this run created it
+fn import_session_to_continuum(session_path: &std::path::Path, config: &continuum_core::Config) -> Result<(std::path::PathBuf, bool)> {
+    use continuum_core::{ImportSpool, MessageCompressor, PlainTextWriter};
+    use continuum_core::adapters::aider::{parse_aider_session, session_id_for};
+
+    let writer = PlainTextWriter::from_config(config);
+
+    let session_id = session_id_for(session_path);
+    let session_id = session_id.as_str();
+
+    let compressor = MessageCompressor::from_config(&config.noise_filter).unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring noise config: {:#}", e);
+        MessageCompressor::new()
+    });
+    let start_time = chrono::Utc::now().to_rfc3339();
+
+    let messages = parse_aider_session(session_path)?;
+
+    // Spool what the noise filter keeps
+    let mut stream = compressor.stream();
+    let mut spool = ImportSpool::new()?;
+    for (role, content) in &messages {
+        if let Some(cleaned) = stream.compress(role, content) {
+            spool.push(role, &cleaned, Some(&start_time), None)?;
+        }
+    }
+    let message_count = spool.len();
+
+    if message_count == 0 {
+        return Err(color_eyre::eyre::eyre!("No messages to import"));
+    }
+
+    let date = writer.session_date(session_id, "aider", Some(&start_time));
+
+    // Only a session this run creates may be discarded afterwards; earlier
+    // runs' history in a continued session must not be
+    let existed = continuum_core::store::find_session(writer.base_dir(), session_id, Some("aider")).is_ok();
+
+    // Aider appends to the same file across runs: a continued history only
+    // gets its new messages appended, an edited one is rewritten
+    writer.import_spool(session_id, "aider", &date, &spool, Some(&start_time), false)?;
+
+    // Write session
+    let session_dir = writer.write_session(
+        session_id,
+        "aider",
+        Some(&start_time),
+        None,
+        "closed",
+        message_count,
+        &[],
+        None,
+        None,
+    )?;
+
+    // Keep the search index fresh; a failure here shouldn't lose the import
+    if config.search_index {
+        if let Err(e) = writer.index_spool(session_id, "aider", &date, &spool) {
+            eprintln!("⚠ Warning: Failed to update search index: {}", e);
+        }
+    }
+
+    eprintln!("✓ Saved {} messages to continuum logs", message_count);
+
+    Ok((session_dir, !existed))
+}
+
+/// Prompt user whether to save the conversation
+/// Returns true to save, false to discard
+fn prompt_save_conversation() -> Result<bool> {
+    use std::io::{self, Write};
+
+    eprintln!("\n─────────────────────────────────────────");
+    eprint!("Save this conversation? [Y/n] ");
+    io::stderr().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    match input.trim().to_lowercase().as_str() {
+        "n" | "no" => Ok(false),
+        _ => Ok(true), // Default to save (Y or Enter)
+    }
+}
+
+/// Delete a discarded session, dropping it from the search and session indexes
+fn discard_session(session_dir: &std::path::Path, assistant: &str, config: &continuum_core::Config) {
+    let writer = continuum_core::PlainTextWriter::from_config(config);
+    let result = continuum_core::SessionRecord::from_dir(session_dir).and_then(|record| {
+        writer.delete_session_confirmed(&record.id, assistant, record.date.as_deref().unwrap_or_default())
+    });
+    if let Err(e) = result {
+        eprintln!("⚠ Warning: Failed to discard session: {}", e);
+    }
+}
@@ -3,51 +3,88 @@
 
 use std::process::Stdio;
 use color_eyre::{eyre::Context, Result};
-use continuum_core::{PlainTextWriter, NoiseFilter};
+use continuum_core::{AsyncPlainTextWriter, Config, LoopDetector, LoopSeverity, PlainTextWriter, NoiseFilter, SessionCost, ToolCapture, take_noise_flag, take_redact_flag};
+use continuum_core::adapters::claude_code::ClaudeCodeAdapter;
+use continuum_core::thinking::{thinking_from_env, THINKING_ROLE};
+use continuum_core::tool_capture::{result_text, INCLUDE_TOOLS_ENV, TOOL_ROLE};
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
+/// Kill the claude process when a Critical loop is detected mid-stream (print mode)
+const ABORT_ON_LOOP_FLAG: &str = "--abort-on-loop";
+
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
 
+    // A broken config file shouldn't stop the assistant from starting
+    let mut config = Config::load().unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring continuum config: {:#}", e);
+        Config::default()
+    });
+
     // Get all arguments passed to continuum-claude
-    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Our own flags; claude would reject them
+    let abort_on_loop = args.iter().any(|a| a == ABORT_ON_LOOP_FLAG);
+    args.retain(|a| a != ABORT_ON_LOOP_FLAG);
+    if let Some(level) = take_noise_flag(&mut args)? {
+        config.noise_filter.level = level;
+    }
+    if take_redact_flag(&mut args) {
+        config.redact = true;
+    }
 
     // Check if this is a non-interactive call (has --print or uses stdin)
     let is_print_mode = args.contains(&"--print".to_string());
 
     if is_print_mode {
         // Already in print mode, just wrap it
-        run_with_logging(&args).await?;
+        run_with_logging(&args, abort_on_loop, &config).await?;
     } else {
         // Interactive mode - pass through all arguments to real claude
-        run_interactive_mode(&args).await?;
+        run_interactive_mode(&args, &config).await?;
     }
 
     Ok(())
 }
 
-async fn run_with_logging(original_args: &[String]) -> Result<()> {
-    // Check for no-save marker file
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    let marker_path = std::path::Path::new(&home).join(".continuum-nosave");
-    let skip_saving = marker_path.exists();
+async fn run_with_logging(original_args: &[String], abort_on_loop: bool, config: &Config) -> Result<()> {
+    // Skipped via $CONTINUUM_NOSAVE or the single-use ~/.continuum-nosave marker
+    let skip_saving = continuum_core::config::should_skip_saving();
 
     if skip_saving {
-        // Delete marker file immediately
-        let _ = std::fs::remove_file(&marker_path);
         eprintln!("⚠ This conversation will NOT be saved to continuum logs");
     }
 
     // Build claude command with stream-json output
     let mut args = original_args.to_vec();
 
-    // Ensure we have stream-json output
-    if !args.contains(&"--output-format".to_string()) {
-        args.push("--output-format".to_string());
-        args.push("stream-json".to_string());
+    // Ensure we have stream-json output, unless the user picked a format.
+    // With json, claude prints every event as one array at exit, so stdout
+    // is buffered and parsed once it closes.
+    let buffer_json = match output_format(&args) {
+        None => {
+            args.push("--output-format".to_string());
+            args.push("stream-json".to_string());
+            false
+        }
+        Some("stream-json") => false,
+        Some("json") => true,
+        Some(format) => {
+            eprintln!(
+                "⚠ --output-format {} carries no session events; use stream-json or json to log this session",
+                format
+            );
+            false
+        }
+    };
+
+    // json output is only parsed after claude exits, too late to stop it
+    if abort_on_loop && buffer_json {
+        eprintln!("⚠ {} has no effect with --output-format json; loops are only reported once claude exits", ABORT_ON_LOOP_FLAG);
     }
 
     // Ensure verbose for full output
@@ -95,153 +132,269 @@ async fn run_with_logging(original_args: &[String]) -> Result<()> {
 
     // Create plain-text writer and noise filter (only if saving)
     let writer = if !skip_saving {
-        Some(PlainTextWriter::new()?)
+        Some(AsyncPlainTextWriter::from_config(config))
     } else {
         None
     };
-    let filter = NoiseFilter::new();
+    let filter = NoiseFilter::from_config(&config.noise_filter).unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring noise config: {:#}", e);
+        NoiseFilter::new()
+    });
+    // Tool calls and results are only logged when CONTINUUM_INCLUDE_TOOLS is set
+    let tools = ToolCapture::from_env().unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring {}: {:#}", INCLUDE_TOOLS_ENV, e);
+        None
+    });
+    // Extended-thinking blocks are only logged when CONTINUUM_INCLUDE_THINKING is set
+    let include_thinking = thinking_from_env();
 
     let mut session_id: Option<String> = None;
     let mut session_start_time: Option<String> = None;
     let mut message_count: usize = 0;
+    // Tool names by tool_use id, so each result can be stored under the tool that produced it
+    let mut tool_names: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+
+    // Watch the logged messages for runaway loops while the session is still running
+    let mut detector = config.loop_detector.build();
+    let mut critical_loop = false;
 
     // Process stdout line by line
     let mut reader = BufReader::new(stdout).lines();
-
-    while let Some(line) = reader.next_line().await? {
-        // Print the line for user
-        println!("{}", line);
-
-        // Try to parse as JSON and log to plain-text
-        if let Ok(event) = serde_json::from_str::<ClaudeEvent>(&line) {
-            match event {
-                ClaudeEvent::System { session_id: sid, .. } => {
-                    let start_time = chrono::Utc::now().to_rfc3339();
-                    session_id = Some(sid.clone());
-                    session_start_time = Some(start_time.clone());
-
-                    // Only log if we're saving
-                    if let Some(ref writer) = writer {
-                        // Extract date from start time
-                        let date = PlainTextWriter::extract_date(Some(&start_time));
-
-                        // Create session record (will be updated with message count later)
-                        writer.write_session(
-                            &sid,
-                            "claude-code",
-                            Some(&start_time),
-                            None,
-                            "active",
-                            0,
-                            &[],
-                        )?;
-
-                        // Log user prompt if we captured it from stdin
-                        if let Some(ref prompt) = user_prompt {
-                            // Apply noise filtering
-                            if let Some(cleaned) = filter.filter(prompt) {
-                                message_count += 1;
-                                writer.append_message(
-                                    &sid,
-                                    "claude-code",
-                                    &date,
-                                    message_count,
-                                    "user",
-                                    &cleaned,
-                                    Some(&start_time),
-                                )?;
-                            }
-                        }
+    let mut json_output = String::new();
+    let mut pending: std::collections::VecDeque<ClaudeEvent> = std::collections::VecDeque::new();
+
+    loop {
+        let event = match pending.pop_front() {
+            Some(event) => event,
+            None => match reader.next_line().await? {
+                Some(line) => {
+                    // Print the line for user
+                    println!("{}", line);
+
+                    if buffer_json {
+                        json_output.push_str(&line);
+                        json_output.push('\n');
+                        continue;
+                    }
+                    // Try to parse as JSON and log to plain-text
+                    match serde_json::from_str::<ClaudeEvent>(&line) {
+                        Ok(event) => event,
+                        Err(_) => continue,
                     }
                 }
-                ClaudeEvent::User { message, session_id: sid, .. } => {
-                    // Extract text content from user message
-                    let content = message.content
-                        .iter()
-                        .filter_map(|c| match c {
-                            Content::Text { text } => Some(text.clone()),
-                            _ => None,
-                        })
-                        .collect::<Vec<_>>()
-                        .join("\n");
-
-                    // Apply noise filtering and log if saving
-                    if let Some(cleaned) = filter.filter(&content) {
-                        // Only log if we're saving
-                        if let Some(ref writer) = writer {
-                            let sess_id = session_id.as_ref().unwrap_or(&sid);
-                            let timestamp = chrono::Utc::now().to_rfc3339();
-                            let date = PlainTextWriter::extract_date(session_start_time.as_deref().or(Some(&timestamp)));
+                None if !json_output.is_empty() => {
+                    pending = parse_json_output(&std::mem::take(&mut json_output));
+                    continue;
+                }
+                None => break,
+            },
+        };
 
+        match event {
+            ClaudeEvent::System { session_id: sid, cwd, .. } => {
+                let start_time = chrono::Utc::now().to_rfc3339();
+                session_id = Some(sid.clone());
+                session_start_time = Some(start_time.clone());
+
+                // Only log if we're saving
+                if let Some(ref writer) = writer {
+                    // Extract date from start time
+                    let date = PlainTextWriter::extract_date_in(Some(&start_time), config.timezone);
+
+                    // Create session record (will be updated with message count later)
+                    writer.write_session(
+                        &sid,
+                        "claude-code",
+                        Some(&start_time),
+                        None,
+                        "active",
+                        0,
+                        &[],
+                        None,
+                        Some(&cwd),
+                    ).await?;
+                    writer.update_session_metadata(
+                        &sid,
+                        "claude-code",
+                        &date,
+                        serde_json::json!({ "project": ClaudeCodeAdapter::encode_project_path(&cwd) }),
+                    ).await?;
+
+                    // Log user prompt if we captured it from stdin
+                    if let Some(ref prompt) = user_prompt {
+                        // Apply noise filtering
+                        if let Some(cleaned) = filter.filter(prompt) {
                             message_count += 1;
                             writer.append_message(
-                                sess_id,
+                                &sid,
                                 "claude-code",
                                 &date,
                                 message_count,
                                 "user",
                                 &cleaned,
-                                Some(&timestamp),
-                            )?;
+                                Some(&start_time),
+                                Some(PlainTextWriter::count_tokens(&cleaned)),
+                                None,
+                            ).await?;
                         }
                     }
                 }
-                ClaudeEvent::Assistant { message, session_id: sid, .. } => {
-                    // Extract text content from message
-                    let content = message.content
-                        .iter()
-                        .filter_map(|c| match c {
-                            Content::Text { text } => Some(text.clone()),
-                            _ => None,
-                        })
-                        .collect::<Vec<_>>()
-                        .join("\n");
-
-                    // Apply noise filtering and only log if content passes
-                    if let Some(cleaned) = filter.filter(&content) {
-                        // Only log if we're saving
-                        if let Some(ref writer) = writer {
-                            let sess_id = session_id.as_ref().unwrap_or(&sid);
-                            let timestamp = chrono::Utc::now().to_rfc3339();
-                            let date = PlainTextWriter::extract_date(session_start_time.as_deref().or(Some(&timestamp)));
+            }
+            ClaudeEvent::User { message, session_id: sid, .. } => {
+                // Extract text content from user message
+                let content = message.content
+                    .iter()
+                    .filter_map(|c| match c {
+                        Content::Text { text } => Some(text.clone()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                // Apply noise filtering and log if saving
+                if let Some(cleaned) = filter.filter(&content) {
+                    // Only log if we're saving
+                    if let Some(ref writer) = writer {
+                        let sess_id = session_id.as_ref().unwrap_or(&sid);
+                        let timestamp = chrono::Utc::now().to_rfc3339();
+                        let date = PlainTextWriter::extract_date_in(session_start_time.as_deref().or(Some(&timestamp)), config.timezone);
+
+                        message_count += 1;
+                        writer.append_message(
+                            sess_id,
+                            "claude-code",
+                            &date,
+                            message_count,
+                            "user",
+                            &cleaned,
+                            Some(&timestamp),
+                            Some(PlainTextWriter::count_tokens(&cleaned)),
+                            None,
+                        ).await?;
+                    }
+                    critical_loop |= check_for_loop(&mut detector, "user", &cleaned);
+                }
+
+                if let Some(ref tools) = tools {
+                    let sess_id = session_id.as_ref().unwrap_or(&sid);
+                    let timestamp = chrono::Utc::now().to_rfc3339();
+                    let date = PlainTextWriter::extract_date_in(session_start_time.as_deref().or(Some(&timestamp)), config.timezone);
+                    let log = ToolLog { writer: writer.as_ref(), session_id: sess_id, date: &date, timestamp: &timestamp };
 
+                    for block in &message.content {
+                        if let Some((tool_name, text)) = block.tool_entry(tools, &filter, &mut tool_names) {
+                            critical_loop |= log.append(&tool_name, &text, &mut message_count, &mut detector).await?;
+                        }
+                    }
+                }
+            }
+            ClaudeEvent::Assistant { message, session_id: sid, .. } => {
+                // Thinking precedes the reply it led to; it isn't output, so loop detection skips it
+                if let (true, Some(writer)) = (include_thinking, &writer) {
+                    let sess_id = session_id.as_ref().unwrap_or(&sid);
+                    let timestamp = chrono::Utc::now().to_rfc3339();
+                    let date = PlainTextWriter::extract_date_in(session_start_time.as_deref().or(Some(&timestamp)), config.timezone);
+
+                    for thought in message.content.iter().filter_map(Content::thinking_text) {
+                        if let Some(cleaned) = filter.filter(thought) {
                             message_count += 1;
                             writer.append_message(
                                 sess_id,
                                 "claude-code",
                                 &date,
                                 message_count,
-                                "assistant",
+                                THINKING_ROLE,
                                 &cleaned,
                                 Some(&timestamp),
-                            )?;
+                                Some(PlainTextWriter::count_tokens(&cleaned)),
+                                Some(&message.model),
+                            ).await?;
                         }
                     }
                 }
-                ClaudeEvent::Result { session_id: sid, .. } => {
-                    // Only update metadata if we're saving
+
+                // Extract text content from message
+                let content = message.content
+                    .iter()
+                    .filter_map(|c| match c {
+                        Content::Text { text } => Some(text.clone()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                // Apply noise filtering and only log if content passes
+                if let Some(cleaned) = filter.filter(&content) {
+                    // Only log if we're saving
                     if let Some(ref writer) = writer {
                         let sess_id = session_id.as_ref().unwrap_or(&sid);
-                        let end_time = chrono::Utc::now().to_rfc3339();
-                        let date = PlainTextWriter::extract_date(session_start_time.as_deref());
-
-                        // Update session metadata with final message count and closed status
-                        let updates = serde_json::json!({
-                            "status": "closed",
-                            "end_time": end_time,
-                            "message_count": message_count,
-                        });
+                        let timestamp = chrono::Utc::now().to_rfc3339();
+                        let date = PlainTextWriter::extract_date_in(session_start_time.as_deref().or(Some(&timestamp)), config.timezone);
 
-                        writer.update_session_metadata(
+                        message_count += 1;
+                        writer.append_message(
                             sess_id,
                             "claude-code",
                             &date,
-                            updates,
-                        )?;
+                            message_count,
+                            "assistant",
+                            &cleaned,
+                            Some(&timestamp),
+                            Some(PlainTextWriter::count_tokens(&cleaned)),
+                            Some(&message.model),
+                        ).await?;
+                    }
+                    critical_loop |= check_for_loop(&mut detector, "assistant", &cleaned);
+                }
+
+                if let Some(ref tools) = tools {
+                    let sess_id = session_id.as_ref().unwrap_or(&sid);
+                    let timestamp = chrono::Utc::now().to_rfc3339();
+                    let date = PlainTextWriter::extract_date_in(session_start_time.as_deref().or(Some(&timestamp)), config.timezone);
+                    let log = ToolLog { writer: writer.as_ref(), session_id: sess_id, date: &date, timestamp: &timestamp };
+
+                    for block in &message.content {
+                        if let Some((tool_name, text)) = block.tool_entry(tools, &filter, &mut tool_names) {
+                            critical_loop |= log.append(&tool_name, &text, &mut message_count, &mut detector).await?;
+                        }
                     }
                 }
-                _ => {} // Ignore other event types for now
             }
+            ClaudeEvent::Result { session_id: sid, total_cost_usd, duration_ms, usage, .. } => {
+                // Only update metadata if we're saving
+                if let Some(ref writer) = writer {
+                    let sess_id = session_id.as_ref().unwrap_or(&sid);
+                    let end_time = chrono::Utc::now().to_rfc3339();
+                    let date = PlainTextWriter::extract_date_in(session_start_time.as_deref(), config.timezone);
+
+                    // Update session metadata with final message count, closed status and cost
+                    let cost = SessionCost {
+                        total_cost_usd,
+                        duration_ms,
+                        input_tokens: usage.as_ref().and_then(|u| u.input_tokens),
+                        output_tokens: usage.as_ref().and_then(|u| u.output_tokens),
+                    };
+                    let mut updates = cost.to_metadata();
+                    updates["status"] = serde_json::json!("closed");
+                    updates["end_time"] = serde_json::json!(end_time);
+                    updates["message_count"] = serde_json::json!(message_count);
+
+                    writer.update_session_metadata(
+                        sess_id,
+                        "claude-code",
+                        &date,
+                        updates,
+                    ).await?;
+                }
+            }
+            _ => {} // Ignore other event types for now
+        }
+
+        // Buffered json output is only parsed once claude has exited
+        if critical_loop && abort_on_loop && !buffer_json {
+            eprintln!("🛑 Stopping claude (--abort-on-loop)");
+            child.kill().await?;
+            break;
         }
     }
 
@@ -260,7 +413,83 @@ async fn run_with_logging(original_args: &[String]) -> Result<()> {
 }
 
 
-async fn run_interactive_mode(args: &[String]) -> Result<()> {
+/// Value of `--output-format` in claude's arguments, in either `--flag value` or `--flag=value` form
+fn output_format(args: &[String]) -> Option<&str> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--output-format" {
+            args.get(i + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix("--output-format=")
+        }
+    })
+}
+
+/// Events from `--output-format json` output: an array of events with
+/// --verbose, otherwise just the result object. Unrecognized entries are skipped.
+fn parse_json_output(output: &str) -> std::collections::VecDeque<ClaudeEvent> {
+    match serde_json::from_str::<serde_json::Value>(output) {
+        Ok(serde_json::Value::Array(events)) => events
+            .into_iter()
+            .filter_map(|event| serde_json::from_value(event).ok())
+            .collect(),
+        Ok(event) => serde_json::from_value(event).into_iter().collect(),
+        Err(e) => {
+            eprintln!("⚠ Warning: Could not parse claude's JSON output, session not logged: {}", e);
+            Default::default()
+        }
+    }
+}
+
+/// Where a message's tool calls and results are logged, and when
+struct ToolLog<'a> {
+    /// `None` when the session isn't being saved; tools are still checked for loops
+    writer: Option<&'a AsyncPlainTextWriter>,
+    session_id: &'a str,
+    date: &'a str,
+    timestamp: &'a str,
+}
+
+impl ToolLog<'_> {
+    /// Append one tool call or result as the next message and check it for loops
+    /// Returns true when it tipped a check into Critical
+    async fn append(&self, tool_name: &str, text: &str, message_count: &mut usize, detector: &mut LoopDetector) -> Result<bool> {
+        if let Some(writer) = self.writer {
+            *message_count += 1;
+            writer.append_tool_message(
+                self.session_id,
+                "claude-code",
+                self.date,
+                *message_count,
+                tool_name,
+                text,
+                Some(self.timestamp),
+                Some(PlainTextWriter::count_tokens(text)),
+            ).await?;
+        }
+        Ok(check_for_loop(detector, TOOL_ROLE, text))
+    }
+}
+
+/// Feed a logged message to the live loop detector, warning on stderr
+/// Returns true when the message tipped a check into Critical
+fn check_for_loop(detector: &mut LoopDetector, role: &str, content: &str) -> bool {
+    let Some(detection) = detector.push(role, content) else {
+        return false;
+    };
+
+    match detection.severity {
+        LoopSeverity::Warning => eprintln!("⚠️  Possible loop: {}", detection.message),
+        LoopSeverity::Critical => {
+            eprintln!("\n🚨 LOOP DETECTED 🚨");
+            eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+            eprintln!("{}", detection.message);
+            eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+        }
+    }
+    detection.severity == LoopSeverity::Critical
+}
+
+async fn run_interactive_mode(args: &[String], config: &Config) -> Result<()> {
     // Find the real claude binary (not the wrapper)
     let claude_path = which::which("claude")
         .context("Failed to find claude binary")?;
@@ -334,21 +563,15 @@ async fn run_interactive_mode(args: &[String]) -> Result<()> {
         claude_path_str
     };
 
-    // Check for no-save marker file
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    let marker_path = std::path::Path::new(&home).join(".continuum-nosave");
-    let skip_saving = marker_path.exists();
+    // Skipped via $CONTINUUM_NOSAVE or the single-use ~/.continuum-nosave marker
+    let skip_saving = continuum_core::config::should_skip_saving();
 
     if skip_saving {
-        // Delete marker file immediately
-        let _ = std::fs::remove_file(&marker_path);
         eprintln!("⚠ This conversation will NOT be saved to continuum logs");
     }
 
     // Get the most recently modified session file BEFORE running claude
-    let projects_dir = std::path::PathBuf::from(&home).join(".claude/projects");
-
-    let before_session = find_latest_session_file(&projects_dir);
+    let before_session = find_latest_session_file(config);
 
     // Spawn claude as a child process (not exec) so we can capture the session after
     let status = Command::new(&real_claude)
@@ -362,7 +585,7 @@ async fn run_interactive_mode(args: &[String]) -> Result<()> {
         .await?;
 
     // After claude exits, find the session that was just modified
-    let after_session = find_latest_session_file(&projects_dir);
+    let after_session = find_latest_session_file(config);
 
     if skip_saving {
         // Ephemeral mode: delete the session from Claude's storage too
@@ -380,7 +603,7 @@ async fn run_interactive_mode(args: &[String]) -> Result<()> {
         if let Some(ref session_path) = after_session {
             if before_session.as_ref() != Some(session_path) {
                 eprintln!("\n📝 Importing session to continuum logs...");
-                match import_session_to_continuum(session_path) {
+                match import_session_to_continuum(session_path, config) {
                     Ok(_) => {
                         // Silently saved - no prompt needed
                     }
@@ -399,238 +622,77 @@ async fn run_interactive_mode(args: &[String]) -> Result<()> {
     std::process::exit(status.code().unwrap_or(1))
 }
 
-fn find_latest_session_file(projects_dir: &std::path::Path) -> Option<std::path::PathBuf> {
-    use std::time::SystemTime;
+fn find_latest_session_file(config: &Config) -> Option<std::path::PathBuf> {
+    use continuum_core::LogAdapter;
 
-    if !projects_dir.exists() {
-        return None;
-    }
-
-    let mut latest: Option<(std::path::PathBuf, SystemTime)> = None;
-
-    // Walk through all project directories
-    if let Ok(entries) = std::fs::read_dir(projects_dir) {
-        for project_entry in entries.flatten() {
-            let project_dir = project_entry.path();
-            if !project_dir.is_dir() {
-                continue;
-            }
-
-            // Find session files in this project
-            if let Ok(files) = std::fs::read_dir(&project_dir) {
-                for file_entry in files.flatten() {
-                    let file_path = file_entry.path();
-
-                    // Skip agent files
-                    if let Some(filename) = file_path.file_name().and_then(|s| s.to_str()) {
-                        if filename.starts_with("agent-") {
-                            continue;
-                        }
-                    }
-
-                    // Only process .jsonl files
-                    if file_path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                        if let Ok(metadata) = std::fs::metadata(&file_path) {
-                            if let Ok(modified) = metadata.modified() {
-                                if latest.is_none() || modified > latest.as_ref().unwrap().1 {
-                                    latest = Some((file_path, modified));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    latest.map(|(path, _)| path)
+    ClaudeCodeAdapter::from_config(config).find_latest_session().ok()
 }
 
-fn hash_content(role: &str, content: &str) -> u64 {
-    use std::hash::{Hash, Hasher};
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    role.hash(&mut hasher);
-    content.hash(&mut hasher);
-    hasher.finish()
-}
+fn import_session_to_continuum(session_path: &std::path::Path, config: &Config) -> Result<()> {
+    use continuum_core::{ImportSpool, MessageCompressor};
 
-fn import_session_to_continuum(session_path: &std::path::Path) -> Result<()> {
-    use continuum_core::{MessageCompressor, PlainTextWriter};
-    use std::io::{BufRead, BufReader};
-
-    let writer = PlainTextWriter::new()?;
+    let writer = PlainTextWriter::from_config(config);
 
     let session_id = session_path
         .file_stem()
         .and_then(|s| s.to_str())
         .unwrap_or("unknown");
 
-    let compressor = MessageCompressor::new();
-    let mut messages: Vec<(String, String)> = Vec::new();
-    let mut seen: std::collections::HashSet<u64> = std::collections::HashSet::new();
-    let mut start_time: Option<String> = None;
-    let mut skills: Vec<String> = Vec::new();
-
-    // Read all messages from the session file, deduplicating content.
-    // CC sessions with context compression re-serialize earlier messages,
-    // causing massive duplication in long sessions without this check.
-    let file = std::fs::File::open(session_path)
-        .with_context(|| format!("Failed to open {}", session_path.display()))?;
-    let reader = BufReader::new(file);
-
-    for line in reader.lines() {
-        let line = line?;
-
-        #[derive(serde::Deserialize)]
-        struct ClaudeCodeEntry {
-            #[serde(rename = "type")]
-            entry_type: String,
-            message: Option<serde_json::Value>,
-            timestamp: Option<String>,
-        }
-
-        let entry: ClaudeCodeEntry = serde_json::from_str(&line)?;
-
-        // Capture first timestamp
-        if start_time.is_none() {
-            if let Some(ref ts) = entry.timestamp {
-                start_time = Some(ts.clone());
-            }
-        }
-
-        // Process user and assistant messages
-        if entry.entry_type == "user" || entry.entry_type == "assistant" {
-            if let Some(msg) = entry.message {
-                let role = msg["role"].as_str().unwrap_or("");
-
-                if role == "user" {
-                    if let Some(content) = msg["content"].as_str() {
-                        let hash = hash_content("user", content);
-                        if seen.insert(hash) {
-                            messages.push(("user".to_string(), content.to_string()));
-                        }
-                    }
-                } else if role == "assistant" {
-                    if let Some(content_array) = msg["content"].as_array() {
-                        // Extract skills from Skill tool_use blocks
-                        for block in content_array {
-                            if block.get("type").and_then(|v| v.as_str()) == Some("tool_use")
-                                && block.get("name").and_then(|v| v.as_str()) == Some("Skill")
-                            {
-                                if let Some(skill) =
-                                    block.pointer("/input/skill").and_then(|v| v.as_str())
-                                {
-                                    if !skills.contains(&skill.to_string()) {
-                                        skills.push(skill.to_string());
-                                    }
-                                }
-                            }
-                        }
-
-                        // Process all content blocks: text and tool_use
-                        for block in content_array {
-                            let block_type = block.get("type").and_then(|v| v.as_str()).unwrap_or("");
-
-                            match block_type {
-                                "text" => {
-                                    if let Some(text) = block["text"].as_str() {
-                                        if !text.is_empty() {
-                                            let hash = hash_content("assistant-text", text);
-                                            if seen.insert(hash) {
-                                                messages.push(("assistant".to_string(), text.to_string()));
-                                            }
-                                        }
-                                    }
-                                }
-                                "tool_use" => {
-                                    let tool_name = block["name"].as_str().unwrap_or("unknown");
-                                    let input = block.get("input")
-                                        .map(|i| serde_json::to_string(i).unwrap_or_default())
-                                        .unwrap_or_default();
-                                    let tool_entry = format!(
-                                        "TOOL_USE: {} -> {}",
-                                        tool_name, input
-                                    );
-                                    let hash = hash_content("assistant-tool", &tool_entry);
-                                    if seen.insert(hash) {
-                                        messages.push(("assistant".to_string(), tool_entry));
-                                    }
-                                }
-                                "tool_result" => {
-                                    // Extract tool result content
-                                    let output = match block.get("content") {
-                                        Some(serde_json::Value::String(s)) => s.clone(),
-                                        Some(serde_json::Value::Array(blocks)) => blocks
-                                            .iter()
-                                            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
-                                            .collect::<Vec<_>>()
-                                            .join("\n"),
-                                        _ => String::new(),
-                                    };
-                                    if !output.is_empty() {
-                                        // Truncate large outputs to keep logs manageable
-                                        let truncated = if output.len() > 500 {
-                                            format!("{}... [truncated]", &output[..500])
-                                        } else {
-                                            output
-                                        };
-                                        let result_entry = format!("TOOL_RESULT: {}", truncated);
-                                        let hash = hash_content("user-result", &result_entry);
-                                        if seen.insert(hash) {
-                                            messages.push(("user".to_string(), result_entry));
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                }
-            }
+    let compressor = MessageCompressor::from_config(&config.noise_filter).unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring noise config: {:#}", e);
+        MessageCompressor::new()
+    });
+    let tools = ToolCapture::from_env().unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring {}: {:#}", INCLUDE_TOOLS_ENV, e);
+        None
+    });
+
+    let adapter = ClaudeCodeAdapter::from_config(config);
+    let transcript = adapter.parse_entries(session_path, tools.as_ref(), thinking_from_env())?;
+    let timestamp = transcript.start_time.clone().unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    // Spool what the noise filter keeps (it removes pleasantries/boilerplate)
+    let mut stream = compressor.stream();
+    let mut spool = ImportSpool::new()?;
+    for (role, content, message_time) in &transcript.entries {
+        if let Some(cleaned) = stream.compress(role, content) {
+            let message_time = message_time.as_deref().and_then(PlainTextWriter::normalize_timestamp);
+            spool.push(role, &cleaned, message_time.as_deref().or(Some(&timestamp)), None)?;
         }
     }
-
-    // Compress messages (noise filter removes pleasantries/boilerplate)
-    let compressed = compressor.compress_batch(&messages);
-    let message_count = compressed.len();
+    let message_count = spool.len();
 
     if message_count == 0 {
         return Err(color_eyre::eyre::eyre!("No messages to import"));
     }
 
-    let timestamp = start_time.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
-    let date = PlainTextWriter::extract_date(Some(&timestamp));
+    let date = writer.session_date(session_id, "claude-code", Some(&timestamp));
+
+    // Write messages first: a resumed session (CC sessions can be resumed,
+    // triggering re-import) only gets what's new appended
+    writer.import_spool(session_id, "claude-code", &date, &spool, Some(&timestamp), false)?;
 
     // Write session (overwrites session.json)
-    let session_dir = writer.write_session(
+    writer.write_session(
         session_id,
         "claude-code",
         Some(&timestamp),
         None,
         "closed",
         message_count,
-        &skills,
+        &transcript.skills,
+        None,
+        None,
     )?;
-
-    // Delete existing messages.jsonl before writing to prevent duplication
-    // on session re-import (CC sessions can be resumed, triggering re-import).
-    let messages_path = session_dir.join("messages.jsonl");
-    if messages_path.exists() {
-        std::fs::remove_file(&messages_path)
-            .with_context(|| format!("Failed to remove old messages.jsonl: {}", messages_path.display()))?;
+    if let Some(project) = adapter.project_of(session_path) {
+        writer.update_session_metadata(session_id, "claude-code", &date, serde_json::json!({ "project": project }))?;
     }
 
-    // Write messages
-    for (idx, (role, content)) in compressed.iter().enumerate() {
-        writer.append_message(
-            session_id,
-            "claude-code",
-            &date,
-            idx + 1,
-            role,
-            content,
-            Some(&timestamp),
-        )?;
+    // Keep the search index fresh; a failure here shouldn't lose the import
+    if config.search_index {
+        if let Err(e) = writer.index_spool(session_id, "claude-code", &date, &spool) {
+            eprintln!("⚠ Warning: Failed to update search index: {}", e);
+        }
     }
 
     eprintln!("✓ Saved {} messages to continuum logs", message_count);
@@ -667,7 +729,7 @@ fn check_farewell_and_log(session_path: &std::path::Path) {
     let mut first_timestamp: Option<String> = None;
     let mut last_timestamp: Option<String> = None;
 
-    for line in reader.lines().flatten() {
+    for line in reader.lines().map_while(Result::ok) {
         let entry: serde_json::Value = match serde_json::from_str(&line) {
             Ok(v) => v,
             Err(_) => continue,
@@ -814,11 +876,20 @@ enum ClaudeEvent {
         duration_ms: u64,
         session_id: String,
         total_cost_usd: f64,
+        #[serde(default)]
+        usage: Option<ResultUsage>,
     },
     #[serde(other)]
     Other,
 }
 
+/// Token usage summary carried by the `result` event
+#[derive(Debug, Deserialize, Serialize)]
+struct ResultUsage {
+    input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct UserMessage {
     role: String,
@@ -839,6 +910,99 @@ struct MessageData {
 #[serde(rename_all = "lowercase")]
 enum Content {
     Text { text: String },
+    Thinking { thinking: String },
+    #[serde(rename = "tool_use")]
+    ToolUse {
+        #[serde(default)]
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    #[serde(rename = "tool_result")]
+    ToolResult {
+        #[serde(default)]
+        tool_use_id: String,
+        /// Plain string or array of text blocks, flattened to text
+        #[serde(default, deserialize_with = "deserialize_result_text")]
+        content: String,
+    },
     #[serde(other)]
     Other,
 }
+
+fn deserialize_result_text<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    serde_json::Value::deserialize(deserializer).map(|content| result_text(&content))
+}
+
+impl Content {
+    /// Tool name and text to log for a tool call or result block; `None` for
+    /// anything else, or a result the noise filter drops as empty boilerplate
+    /// Calls are recorded in `tool_names` so their results can be attributed.
+    fn tool_entry(
+        &self,
+        tools: &ToolCapture,
+        filter: &NoiseFilter,
+        tool_names: &mut std::collections::HashMap<String, String>,
+    ) -> Option<(String, String)> {
+        match self {
+            Content::ToolUse { id, name, input } => {
+                tool_names.insert(id.clone(), name.clone());
+                Some((name.clone(), tools.format_call(name, &input.to_string())))
+            }
+            Content::ToolResult { tool_use_id, content } => {
+                let name = tool_names.get(tool_use_id).map(String::as_str).unwrap_or("unknown");
+                let output = filter.filter_tool_result(content, name)?;
+                Some((name.to_string(), tools.format_result(&output)?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Text of a non-empty thinking block; `None` for anything else
+    fn thinking_text(&self) -> Option<&str> {
+        match self {
+            Content::Thinking { thinking } => Some(thinking.trim()).filter(|t| !t.is_empty()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_output_reads_verbose_event_array() {
+        let output = r#"[
+            {"type": "system", "subtype": "init", "session_id": "s1", "cwd": "/tmp", "tools": [], "model": "m"},
+            {"type": "assistant", "session_id": "s1", "uuid": "u1", "message": {"model": "m", "id": "msg1", "role": "assistant", "content": [{"type": "text", "text": "Hi"}], "stop_reason": null}},
+            {"type": "unknown_future_event"},
+            {"type": "result", "subtype": "success", "is_error": false, "duration_ms": 10, "session_id": "s1", "total_cost_usd": 0.01}
+        ]"#;
+
+        let events: Vec<_> = parse_json_output(output).into_iter().collect();
+        assert_eq!(events.len(), 4);
+        assert!(matches!(events[0], ClaudeEvent::System { .. }));
+        assert!(matches!(events[1], ClaudeEvent::Assistant { .. }));
+        assert!(matches!(events[2], ClaudeEvent::Other));
+        assert!(matches!(events[3], ClaudeEvent::Result { .. }));
+    }
+
+    #[test]
+    fn test_parse_json_output_reads_single_result_object() {
+        let output = r#"{"type": "result", "subtype": "success", "is_error": false, "duration_ms": 10, "session_id": "s1", "total_cost_usd": 0.01}"#;
+
+        let events = parse_json_output(output);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], ClaudeEvent::Result { ref session_id, .. } if session_id == "s1"));
+    }
+
+    #[test]
+    fn test_parse_json_output_skips_malformed_entries() {
+        // A known event type with missing fields is dropped, the rest are kept
+        let output = r#"[{"type": "assistant", "session_id": "s1"}, {"type": "result", "subtype": "success", "is_error": false, "duration_ms": 10, "session_id": "s1", "total_cost_usd": 0.0}]"#;
+        assert_eq!(parse_json_output(output).len(), 1);
+
+        assert!(parse_json_output("not json").is_empty());
+    }
+}
@@ -0,0 +1,337 @@
+// Interactive session browser
+// A read-only terminal view over PlainTextReader: sessions on the left, the selected session's messages on the right
+
+use color_eyre::Result;
+use continuum_core::{Message, PlainTextReader, Role, SessionRecord};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+/// Lines moved by PageUp / PageDown in the message pane
+const PAGE: u16 = 20;
+
+/// Which pane the arrow keys move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Sessions,
+    Messages,
+}
+
+/// Browses stored sessions, newest first
+///
+/// Up/Down pick a session, Tab (or Enter/Right) moves to its messages where
+/// Up/Down and PageUp/PageDown scroll. `/` filters the list by assistant,
+/// date, session ID or tag; Esc clears the filter and `q` quits.
+pub struct Browser {
+    reader: PlainTextReader,
+    sessions: Vec<SessionRecord>,
+    /// Indexes into `sessions` that match the filter
+    visible: Vec<usize>,
+    list: ListState,
+    filter: String,
+    editing_filter: bool,
+    focus: Focus,
+    scroll: u16,
+    /// Messages of the session at this index into `sessions`, or why they couldn't be read
+    loaded: Option<(usize, Result<Vec<Message>, String>)>,
+}
+
+impl Browser {
+    /// Browser over every stored session, or one assistant's
+    pub fn new(reader: PlainTextReader, assistant: Option<&str>) -> Result<Self> {
+        let mut sessions = reader.list_sessions(assistant, None)?;
+        sessions.sort_by(|a, b| (&b.date, &b.start_time).cmp(&(&a.date, &a.start_time)));
+
+        let mut browser = Browser {
+            reader,
+            sessions,
+            visible: Vec::new(),
+            list: ListState::default(),
+            filter: String::new(),
+            editing_filter: false,
+            focus: Focus::Sessions,
+            scroll: 0,
+            loaded: None,
+        };
+        browser.apply_filter();
+        Ok(browser)
+    }
+
+    /// Take over the terminal until the user quits
+    pub fn run(mut self) -> Result<()> {
+        let mut terminal = ratatui::try_init()?;
+        let result = self.event_loop(&mut terminal);
+        ratatui::try_restore()?;
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            self.load_selected();
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if self.editing_filter {
+                match key.code {
+                    KeyCode::Enter => self.editing_filter = false,
+                    KeyCode::Esc => {
+                        self.editing_filter = false;
+                        self.filter.clear();
+                        self.apply_filter();
+                    }
+                    KeyCode::Backspace => {
+                        self.filter.pop();
+                        self.apply_filter();
+                    }
+                    KeyCode::Char(c) => {
+                        self.filter.push(c);
+                        self.apply_filter();
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            match (key.code, self.focus) {
+                (KeyCode::Char('q'), _) => return Ok(()),
+                (KeyCode::Char('/'), _) => {
+                    self.editing_filter = true;
+                    self.focus = Focus::Sessions;
+                }
+                (KeyCode::Esc, Focus::Messages) | (KeyCode::Left, _) => self.focus = Focus::Sessions,
+                (KeyCode::Esc, Focus::Sessions) if !self.filter.is_empty() => {
+                    self.filter.clear();
+                    self.apply_filter();
+                }
+                (KeyCode::Esc, Focus::Sessions) => return Ok(()),
+                (KeyCode::Tab, Focus::Sessions) | (KeyCode::Enter | KeyCode::Right, _) => self.focus = Focus::Messages,
+                (KeyCode::Tab, Focus::Messages) => self.focus = Focus::Sessions,
+                (KeyCode::Up | KeyCode::Char('k'), Focus::Sessions) => self.select(-1),
+                (KeyCode::Down | KeyCode::Char('j'), Focus::Sessions) => self.select(1),
+                (KeyCode::Up | KeyCode::Char('k'), Focus::Messages) => self.scroll = self.scroll.saturating_sub(1),
+                (KeyCode::Down | KeyCode::Char('j'), Focus::Messages) => self.scroll = self.scroll.saturating_add(1),
+                (KeyCode::PageUp, _) => self.scroll = self.scroll.saturating_sub(PAGE),
+                (KeyCode::PageDown, _) => self.scroll = self.scroll.saturating_add(PAGE),
+                (KeyCode::Home, Focus::Messages) => self.scroll = 0,
+                _ => {}
+            }
+        }
+    }
+
+    /// Recompute the visible sessions, keeping the selection on the first match
+    fn apply_filter(&mut self) {
+        let needle = self.filter.to_lowercase();
+        self.visible = self
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| {
+                needle.is_empty()
+                    || record.id.to_lowercase().contains(&needle)
+                    || record.assistant.to_lowercase().contains(&needle)
+                    || record.date.as_deref().is_some_and(|d| d.contains(&needle))
+                    || record.tags.iter().any(|t| t.to_lowercase().contains(&needle))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        self.list.select((!self.visible.is_empty()).then_some(0));
+        self.scroll = 0;
+    }
+
+    fn select(&mut self, step: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let current = self.list.selected().unwrap_or(0);
+        let next = current.saturating_add_signed(step).min(self.visible.len() - 1);
+        if next != current {
+            self.list.select(Some(next));
+            self.scroll = 0;
+        }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.list.selected().and_then(|pos| self.visible.get(pos)).copied()
+    }
+
+    /// Read the selected session's messages, unless they are already loaded
+    fn load_selected(&mut self) {
+        let Some(idx) = self.selected() else {
+            self.loaded = None;
+            return;
+        };
+        if self.loaded.as_ref().is_some_and(|(loaded, _)| *loaded == idx) {
+            return;
+        }
+
+        let record = &self.sessions[idx];
+        let messages = self
+            .reader
+            .read_session(&record.assistant, record.date.as_deref().unwrap_or_default(), &record.id)
+            .map(|(_, messages)| messages)
+            .map_err(|e| e.to_string());
+        self.loaded = Some((idx, messages));
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [body, status] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] = Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(body);
+
+        let border = |focused: bool| Style::default().fg(if focused { Color::Cyan } else { Color::DarkGray });
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&idx| {
+                let record = &self.sessions[idx];
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{} ", record.date.as_deref().unwrap_or("?")), Style::default().fg(Color::DarkGray)),
+                    Span::styled(format!("{} ", record.assistant), Style::default().fg(Color::Yellow)),
+                    Span::raw(record.id.clone()),
+                    Span::styled(
+                        record.message_count.map(|n| format!(" ({})", n)).unwrap_or_default(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .title(format!(" Sessions {}/{} ", self.visible.len(), self.sessions.len()))
+                    .border_style(border(self.focus == Focus::Sessions)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, left, &mut self.list);
+
+        let (title, lines) = match (self.selected(), &self.loaded) {
+            (Some(idx), Some((_, Ok(messages)))) => (format!(" {} ", self.sessions[idx].id), message_lines(messages)),
+            (Some(idx), Some((_, Err(e)))) => (format!(" {} ", self.sessions[idx].id), vec![Line::from(format!("Failed to read session: {}", e))]),
+            _ => (" Messages ".to_string(), vec![Line::from("No sessions")]),
+        };
+        let messages = Paragraph::new(lines)
+            .block(Block::bordered().title(title).border_style(border(self.focus == Focus::Messages)))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        frame.render_widget(messages, right);
+
+        let status_line = if self.editing_filter || !self.filter.is_empty() {
+            format!("/{}", self.filter)
+        } else {
+            "↑↓ move  Tab switch pane  PgUp/PgDn scroll  / filter  q quit".to_string()
+        };
+        frame.render_widget(Paragraph::new(status_line).style(Style::default().fg(Color::DarkGray)), status);
+    }
+}
+
+/// Each message as a role-coloured header, its content, and a blank line
+fn message_lines(messages: &[Message]) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    for message in messages {
+        let (name, color) = match message.role {
+            Role::User => ("user", Color::Cyan),
+            Role::Assistant => ("assistant", Color::Green),
+            Role::System => ("system", Color::Yellow),
+            Role::Tool => ("tool", Color::Magenta),
+            Role::Thinking => ("thinking", Color::DarkGray),
+        };
+        let mut header = vec![Span::styled(name, Style::default().fg(color).add_modifier(Modifier::BOLD))];
+        if let Some(ref timestamp) = message.timestamp {
+            header.push(Span::styled(format!("  {}", timestamp), Style::default().fg(Color::DarkGray)));
+        }
+        lines.push(Line::from(header));
+        lines.extend(message.content.lines().map(|line| Line::from(line.to_string())));
+        lines.push(Line::default());
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use continuum_core::PlainTextWriter;
+    use tempfile::TempDir;
+
+    /// Browser over three sessions: codex-old (tagged `parser`), goose-mid and codex-new
+    fn browser(temp_dir: &TempDir) -> Result<Browser> {
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        for (id, assistant, start_time) in [
+            ("codex-old", "codex", "2025-11-07T10:00:00Z"),
+            ("goose-mid", "goose", "2025-11-08T10:00:00Z"),
+            ("codex-new", "codex", "2025-11-09T10:00:00Z"),
+        ] {
+            writer.write_session(id, assistant, Some(start_time), None, "closed", 0, &[], None, None)?;
+        }
+        writer.add_tag("codex-old", "codex", "2025-11-07", "parser")?;
+        Browser::new(PlainTextReader::with_base_dir(temp_dir.path().to_path_buf()), None)
+    }
+
+    fn visible_ids(browser: &Browser) -> Vec<&str> {
+        browser.visible.iter().map(|&idx| browser.sessions[idx].id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_filter_matches_assistant_date_id_and_tag() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut browser = browser(&temp_dir)?;
+        assert_eq!(visible_ids(&browser), vec!["codex-new", "goose-mid", "codex-old"]);
+
+        for (filter, expected) in [
+            ("CODEX", vec!["codex-new", "codex-old"]),
+            ("2025-11-08", vec!["goose-mid"]),
+            ("new", vec!["codex-new"]),
+            ("parser", vec!["codex-old"]),
+            ("cursor", vec![]),
+        ] {
+            browser.filter = filter.to_string();
+            browser.apply_filter();
+            assert_eq!(visible_ids(&browser), expected, "filter {:?}", filter);
+            assert_eq!(browser.list.selected(), (!expected.is_empty()).then_some(0));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_stays_within_the_visible_sessions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut browser = browser(&temp_dir)?;
+
+        browser.select(-1);
+        assert_eq!(browser.list.selected(), Some(0));
+        browser.scroll = 5;
+        browser.select(10);
+        assert_eq!(browser.list.selected(), Some(2));
+        assert_eq!(browser.scroll, 0);
+        browser.scroll = 5;
+        browser.select(1);
+        assert_eq!(browser.list.selected(), Some(2));
+        assert_eq!(browser.scroll, 5, "scroll is kept when the selection doesn't move");
+        assert_eq!(browser.selected().map(|idx| browser.sessions[idx].id.as_str()), Some("codex-old"));
+
+        browser.filter = "nothing".to_string();
+        browser.apply_filter();
+        browser.select(1);
+        assert_eq!(browser.list.selected(), None);
+        assert_eq!(browser.selected(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_message_lines_put_a_header_before_each_message() {
+        let messages = [
+            Message { role: Role::User, content: "Why?\nReally?".to_string(), timestamp: Some("2025-11-09T10:00:00Z".to_string()) },
+            Message { role: Role::Assistant, content: "Because.".to_string(), timestamp: None },
+        ];
+        let lines: Vec<String> = message_lines(&messages).iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines, vec!["user  2025-11-09T10:00:00Z", "Why?", "Really?", "", "assistant", "Because.", ""]);
+    }
+}
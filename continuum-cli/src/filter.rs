@@ -0,0 +1,172 @@
+// Standalone transcript cleaning
+// Reads a JSONL or plain-text transcript for `continuum filter`, without going through an adapter
+
+use color_eyre::Result;
+use continuum_core::{export, StoredMessage};
+use serde_json::{json, Value};
+
+/// Output format for `continuum filter`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterFormat {
+    /// One `{"role", "content"}` record per line
+    Jsonl,
+    /// One `### Role` section per message
+    Markdown,
+}
+
+/// Messages of a transcript in order, with a parallel list of timestamps
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    pub messages: Vec<(String, String)>,
+    pub timestamps: Vec<Option<String>>,
+}
+
+/// Parse `text` as JSONL when every non-blank line is a `{role, content}` record, else as plain text
+pub fn parse_transcript(text: &str) -> Transcript {
+    parse_jsonl(text).unwrap_or_else(|| parse_plain(text))
+}
+
+/// JSONL records with string `role` and `content`, and an optional `timestamp`
+fn parse_jsonl(text: &str) -> Option<Transcript> {
+    let mut transcript = Transcript::default();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let record: Value = serde_json::from_str(line).ok()?;
+        let role = record.get("role")?.as_str()?;
+        let content = record.get("content")?.as_str()?;
+        transcript.messages.push((role.to_string(), content.to_string()));
+        transcript.timestamps.push(record.get("timestamp").and_then(Value::as_str).map(str::to_string));
+    }
+    (!transcript.messages.is_empty()).then_some(transcript)
+}
+
+/// Plain text where a line starting `User:`, `Assistant:`, `System:` or `Tool:`
+/// begins a message and the lines after it continue it
+///
+/// `Human:` and `AI:` are read as user and assistant. Text before the first
+/// such line (all of it, if there is none) is a single user message.
+fn parse_plain(text: &str) -> Transcript {
+    let mut messages: Vec<(String, Vec<&str>)> = Vec::new();
+    for line in text.lines() {
+        match speaker(line) {
+            Some((role, rest)) => messages.push((role.to_string(), vec![rest])),
+            None => match messages.last_mut() {
+                Some((_, lines)) => lines.push(line),
+                None => messages.push(("user".to_string(), vec![line])),
+            },
+        }
+    }
+
+    let messages: Vec<(String, String)> = messages
+        .into_iter()
+        .map(|(role, lines)| (role, lines.join("\n").trim().to_string()))
+        .filter(|(_, content)| !content.is_empty())
+        .collect();
+    Transcript { timestamps: vec![None; messages.len()], messages }
+}
+
+/// Role named by a `Speaker:` prefix, and the text after it
+fn speaker(line: &str) -> Option<(&'static str, &str)> {
+    let (name, rest) = line.split_once(':')?;
+    let role = match name.trim().to_lowercase().as_str() {
+        "user" | "human" => "user",
+        "assistant" | "ai" => "assistant",
+        "system" => "system",
+        "tool" => "tool",
+        _ => return None,
+    };
+    Some((role, rest.trim_start()))
+}
+
+/// Render the cleaned messages; JSONL keeps any timestamps the input had
+pub fn render(format: FilterFormat, messages: &[(String, String)], timestamps: &[Option<String>]) -> Result<String> {
+    let timestamp = |idx: usize| timestamps.get(idx).cloned().flatten();
+    match format {
+        FilterFormat::Jsonl => {
+            let mut out = String::new();
+            for (idx, (role, content)) in messages.iter().enumerate() {
+                let mut record = json!({ "role": role, "content": content });
+                if let Some(timestamp) = timestamp(idx) {
+                    record["timestamp"] = json!(timestamp);
+                }
+                out.push_str(&serde_json::to_string(&record)?);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+        FilterFormat::Markdown => {
+            let stored: Vec<StoredMessage> = messages
+                .iter()
+                .enumerate()
+                .map(|(idx, (role, content))| StoredMessage {
+                    id: idx + 1,
+                    role: role.clone(),
+                    content: content.clone(),
+                    timestamp: timestamp(idx),
+                    tool_name: None,
+                    tokens: None,
+                    model: None,
+                })
+                .collect();
+            Ok(export::render_markdown(&stored))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> (String, String) {
+        (role.to_string(), content.to_string())
+    }
+
+    #[test]
+    fn test_parse_jsonl_transcript() {
+        let text = "{\"role\":\"user\",\"content\":\"Hi\",\"timestamp\":\"2025-11-09T10:00:00Z\"}\n\n{\"role\":\"assistant\",\"content\":\"Hello\"}\n";
+        let transcript = parse_transcript(text);
+        assert_eq!(transcript.messages, vec![message("user", "Hi"), message("assistant", "Hello")]);
+        assert_eq!(transcript.timestamps, vec![Some("2025-11-09T10:00:00Z".to_string()), None]);
+    }
+
+    #[test]
+    fn test_non_record_lines_fall_back_to_plain_text() {
+        // One line without content makes the whole input plain text
+        let text = "{\"role\":\"user\",\"content\":\"Hi\"}\n{\"role\":\"assistant\"}";
+        let transcript = parse_transcript(text);
+        assert_eq!(transcript.messages, vec![message("user", text)]);
+        assert_eq!(transcript.timestamps, vec![None]);
+    }
+
+    #[test]
+    fn test_parse_plain_transcript() {
+        let text = "Some preamble\nHuman: How do I list files?\n\nAI: Run ls.\nAdd -la for details.\nnote: not a speaker\nTOOL: ls output\nSystem:   \n";
+        let transcript = parse_transcript(text);
+        assert_eq!(
+            transcript.messages,
+            vec![
+                message("user", "Some preamble"),
+                message("user", "How do I list files?"),
+                message("assistant", "Run ls.\nAdd -la for details.\nnote: not a speaker"),
+                message("tool", "ls output"),
+            ]
+        );
+        assert_eq!(transcript.timestamps, vec![None; 4]);
+    }
+
+    #[test]
+    fn test_render_keeps_timestamps() -> Result<()> {
+        let messages = vec![message("user", "Hi"), message("assistant", "Hello")];
+        let timestamps = vec![Some("2025-11-09T10:00:00Z".to_string())];
+
+        let jsonl = render(FilterFormat::Jsonl, &messages, &timestamps)?;
+        assert_eq!(
+            jsonl,
+            "{\"content\":\"Hi\",\"role\":\"user\",\"timestamp\":\"2025-11-09T10:00:00Z\"}\n{\"content\":\"Hello\",\"role\":\"assistant\"}\n"
+        );
+        assert_eq!(parse_transcript(&jsonl).timestamps, vec![Some("2025-11-09T10:00:00Z".to_string()), None]);
+
+        let markdown = render(FilterFormat::Markdown, &messages, &timestamps)?;
+        assert!(markdown.contains("Hi") && markdown.contains("Hello"));
+        Ok(())
+    }
+}
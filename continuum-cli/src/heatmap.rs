@@ -0,0 +1,202 @@
+// Activity heatmap
+// Counts messages and sessions per day from the date each session is filed under, for `continuum timeline`
+
+use chrono::{Datelike, Duration, NaiveDate};
+use color_eyre::Result;
+use continuum_core::store;
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::path::Path;
+
+/// Output format for the activity heatmap
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeatmapFormat {
+    /// Contribution graph: weeks as columns, weekdays as rows
+    Ascii,
+    /// One record per day, for scripts
+    Json,
+}
+
+/// Activity filed under one date directory
+#[derive(Debug, Clone)]
+pub struct DayActivity {
+    pub date: NaiveDate,
+    pub message_count: usize,
+    pub session_count: usize,
+}
+
+/// Longest span `--days` accepts, about ten years
+pub const MAX_DAYS: u32 = 3660;
+
+/// Cells from no activity up to the busiest day
+const LEVELS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// Messages and sessions for every day from `from` to `to`, days without sessions included
+///
+/// A session counts towards the date directory it is filed under, however
+/// long it ran. Date directories that aren't YYYY-MM-DD are ignored.
+pub fn daily_activity(base_dir: &Path, from: NaiveDate, to: NaiveDate, assistant: Option<&str>) -> Result<Vec<DayActivity>> {
+    let mut days: BTreeMap<NaiveDate, DayActivity> = from
+        .iter_days()
+        .take_while(|date| *date <= to)
+        .map(|date| (date, DayActivity { date, message_count: 0, session_count: 0 }))
+        .collect();
+
+    for name in store::list_assistants(base_dir)? {
+        if assistant.is_some_and(|a| !a.eq_ignore_ascii_case(&name)) {
+            continue;
+        }
+
+        for location in store::list_assistant_sessions(base_dir, &name)? {
+            let Some(day) = NaiveDate::parse_from_str(&location.date, "%Y-%m-%d").ok().and_then(|date| days.get_mut(&date)) else {
+                continue;
+            };
+            day.session_count += 1;
+            if let Some(path) = store::messages_file(&location.path) {
+                day.message_count += count_lines(&path)?;
+            }
+        }
+    }
+
+    Ok(days.into_values().collect())
+}
+
+/// `days` as a JSON array of `{"date", "message_count", "session_count"}` records
+pub fn render_json(days: &[DayActivity]) -> Result<String> {
+    let records: Vec<serde_json::Value> = days
+        .iter()
+        .map(|day| {
+            serde_json::json!({
+                "date": day.date.to_string(),
+                "message_count": day.message_count,
+                "session_count": day.session_count,
+            })
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&records)?)
+}
+
+/// Non-empty lines in a (possibly compressed) messages file
+fn count_lines(path: &Path) -> Result<usize> {
+    let mut count = 0;
+    for line in store::open_messages(path)?.lines() {
+        if !line?.trim().is_empty() {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Render `days` GitHub-style: one column per week starting Monday, one row per weekday
+///
+/// Intensity is relative to the busiest day in range; days outside it are blank.
+pub fn render_ascii(days: &[DayActivity]) -> String {
+    let (Some(first), Some(last)) = (days.first(), days.last()) else {
+        return String::new();
+    };
+
+    let start = first.date - Duration::days(i64::from(first.date.weekday().num_days_from_monday()));
+    let weeks = (last.date - start).num_days() as usize / 7 + 1;
+    let busiest = days.iter().map(|day| day.message_count).max().unwrap_or(0);
+    let counts: BTreeMap<NaiveDate, usize> = days.iter().map(|day| (day.date, day.message_count)).collect();
+
+    // Month abbreviation over the first column of each month
+    let mut header = String::from("    ");
+    let mut month = None;
+    for week in 0..weeks {
+        let monday = start + Duration::weeks(week as i64);
+        let shown = monday.max(first.date);
+        let column = 4 + week * 2;
+        if month != Some(shown.month()) && header.chars().count() <= column {
+            header.push_str(&" ".repeat(column - header.chars().count()));
+            header.push_str(&shown.format("%b").to_string());
+            month = Some(shown.month());
+        }
+    }
+
+    let mut out = header.trim_end().to_string();
+    out.push('\n');
+    for weekday in 0..7 {
+        let mut row = String::from(match weekday {
+            0 => "Mon ",
+            2 => "Wed ",
+            4 => "Fri ",
+            _ => "    ",
+        });
+        for week in 0..weeks {
+            let date = start + Duration::days((week * 7 + weekday) as i64);
+            let cell = match counts.get(&date) {
+                None => ' ',
+                Some(0) => LEVELS[0],
+                Some(&count) => LEVELS[(count * 4).div_ceil(busiest).clamp(1, 4)],
+            };
+            row.push(cell);
+            row.push(' ');
+        }
+        out.push_str(row.trim_end());
+        out.push('\n');
+    }
+
+    let messages: usize = days.iter().map(|day| day.message_count).sum();
+    let sessions: usize = days.iter().map(|day| day.session_count).sum();
+    out.push_str(&format!(
+        "\n    Less {} More\n    {} messages in {} sessions, {} to {}\n",
+        LEVELS.iter().map(char::to_string).collect::<Vec<_>>().join(" "),
+        messages,
+        sessions,
+        first.date,
+        last.date,
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use continuum_core::PlainTextWriter;
+    use tempfile::TempDir;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+
+    fn day(day: u32, message_count: usize, session_count: usize) -> DayActivity {
+        DayActivity { date: date(day), message_count, session_count }
+    }
+
+    #[test]
+    fn test_daily_activity() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        for (id, assistant, date, messages) in [("s1", "codex", "2025-11-03", 3), ("s2", "goose", "2025-11-03", 1), ("s3", "codex", "2025-11-05", 2), ("s4", "codex", "2025-10-01", 4)] {
+            for n in 1..=messages {
+                writer.append_message(id, assistant, date, n, "user", "hello there", None, None, None)?;
+            }
+        }
+        std::fs::create_dir_all(temp_dir.path().join("codex/not-a-date/s5"))?;
+
+        let days = daily_activity(temp_dir.path(), date(2), date(5), None)?;
+        let counts: Vec<(NaiveDate, usize, usize)> = days.iter().map(|d| (d.date, d.message_count, d.session_count)).collect();
+        assert_eq!(counts, vec![(date(2), 0, 0), (date(3), 4, 2), (date(4), 0, 0), (date(5), 2, 1)]);
+
+        let codex = daily_activity(temp_dir.path(), date(3), date(3), Some("Codex"))?;
+        assert_eq!((codex[0].message_count, codex[0].session_count), (3, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        assert_eq!(render_ascii(&[]), "");
+
+        // Wednesday the 5th to Tuesday the 11th: two week columns, Mon/Tue of the first left blank
+        let days: Vec<DayActivity> = (5..=11).map(|d| day(d, if d == 6 { 8 } else if d == 10 { 2 } else { 0 }, 0)).collect();
+        let rendered = render_ascii(&days);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "    Nov");
+        assert_eq!(lines[1], "Mon   ░");
+        assert_eq!(lines[2], "      ·");
+        assert_eq!(lines[3], "Wed ·");
+        assert_eq!(lines[4], "    █");
+        assert!(rendered.ends_with("10 messages in 0 sessions, 2025-11-05 to 2025-11-11\n"));
+    }
+}
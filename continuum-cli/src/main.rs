@@ -1,21 +1,66 @@
 // Continuum CLI - Plain-Text Assistant Log Management
 // Manages conversation logs stored as JSONL files in ~/Assistants/continuum-logs
 
-use std::path::PathBuf;
+mod browse;
+mod filter;
+mod heatmap;
+mod pipeline;
+mod replay;
+mod sources;
+mod watch;
+
+use std::path::{Path, PathBuf};
 
 use clap::{Args, Parser, Subcommand};
 use color_eyre::{eyre::Context, Result};
-use continuum_core::{CodexLogEntry, LogAdapter, PlainTextWriter, MessageCompressor, LoopDetector, LoopSeverity};
+use continuum_core::{coalesce_consecutive, parse_date_filter, DateFilter, DateZone, ArchiveManifest, DeleteError, Compactor, CompressionStats, Config, DryRunWriter, FilterConfig, FilterExplanation, NoiseFilter, WriterBackend, AssistantStats, AssistantSummary, DuplicateSession, ImportOptions, ImportOutcome, ImportSpool, StatsSummary, LogAdapter, PlainTextWriter, MessageCompressor, NoiseLevel, LoopDetector, LoopDetectorBuilder, LoopSeverity, PlainTextReader, ContextualHit, QueryMatcher, Redactor, ScanFilter, SearchHit, SearchIndex, SessionFilter, SessionRecord, SessionSort, SessionStatus, SessionValidator, StoredMessage, ToolCapture};
+use continuum_core::{export, search, session_index, session_ops, store};
+use continuum_core::adapters::aider::AiderAdapter;
+use continuum_core::adapters::chatgpt::{self, ChatGptAdapter};
+use continuum_core::adapters::claude_ai::{self, ClaudeAiAdapter};
 use continuum_core::adapters::claude_code::ClaudeCodeAdapter;
 use continuum_core::adapters::codex::CodexAdapter;
-use continuum_core::adapters::goose::{GooseAdapter, parse_goose_content};
+use continuum_core::adapters::cursor::CursorAdapter;
+use continuum_core::adapters::gemini::GeminiAdapter;
+use continuum_core::adapters::goose::{GooseAdapter, GooseSessionRef};
+use continuum_core::adapters::lmstudio::LmStudioAdapter;
+use continuum_core::adapters::openwebui::OpenWebUiAdapter;
+use continuum_core::adapters::zed::ZedAdapter;
+use pipeline::{ImportPipeline, Spooled};
+use sources::MessageSink;
+use browse::Browser;
+use replay::ReplayEngine;
+use heatmap::HeatmapFormat;
+use watch::{WatchFormat, WatchMode};
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let cli = Cli::parse();
+    let config = Config::load()?;
     match &cli.command {
-        Command::Import(cmd) => handle_import(cmd)?,
-        Command::Stats => handle_stats()?,
+        Command::Import(cmd) => handle_import(cmd, &config)?,
+        Command::Stats(cmd) => handle_stats(cmd, &config)?,
+        Command::Tail(cmd) => handle_tail(cmd, &config)?,
+        Command::Search(cmd) => handle_search(cmd, &config)?,
+        Command::Export(cmd) => handle_export(cmd, &config)?,
+        Command::Tag(cmd) => handle_tag(cmd, &config)?,
+        Command::Note(cmd) => handle_note(cmd, &config)?,
+        Command::Merge(cmd) => handle_merge(cmd, &config)?,
+        Command::Watch(cmd) => handle_watch(cmd, &config)?,
+        Command::Archive(cmd) => handle_archive(cmd, &config)?,
+        Command::List(cmd) => handle_list(cmd, &config)?,
+        Command::Diff(cmd) => handle_diff(cmd, &config)?,
+        Command::Delete(cmd) => handle_delete(cmd, &config)?,
+        Command::Validate(cmd) => handle_validate(cmd, &config)?,
+        Command::Compact(cmd) => handle_compact(cmd, &config)?,
+        Command::Timeline(cmd) => handle_timeline(cmd, &config)?,
+        Command::Browse(cmd) => handle_browse(cmd, &config)?,
+        Command::Redact(cmd) => handle_redact(cmd, &config)?,
+        Command::Replay(cmd) => handle_replay(cmd, &config)?,
+        Command::Reindex(cmd) => handle_reindex(cmd, &config)?,
+        Command::FilterTest(cmd) => handle_filter_test(cmd, &config)?,
+        Command::Filter(cmd) => handle_filter(cmd, &config)?,
+        Command::Dedupe(cmd) => handle_dedupe(cmd, &config)?,
     }
     Ok(())
 }
@@ -26,7 +71,7 @@ fn main() -> Result<()> {
     author,
     version,
     about = "Continuum: Plain-text assistant conversation logs",
-    long_about = "Manage assistant conversations as plain-text JSONL files.\nUse Nushell functions for querying: continuum-search, continuum-timeline, continuum-stats"
+    long_about = "Manage assistant conversations as plain-text JSONL files.\nBrowse a day across assistants with `continuum timeline <date>`; the Nushell functions continuum-search and continuum-stats add more querying"
 )]
 struct Cli {
     #[command(subcommand)]
@@ -38,56 +83,906 @@ enum Command {
     /// Import sessions from assistant native logs to plain-text JSONL
     Import(ImportArgs),
     /// Show statistics about stored conversations
-    Stats,
+    Stats(StatsArgs),
+    /// Show the last N messages of the latest stored session
+    Tail(TailArgs),
+    /// Full-text search across stored conversations
+    Search(SearchArgs),
+    /// Export a stored session as Markdown, HTML or JSONL
+    Export(ExportArgs),
+    /// Manage freeform session tags (e.g. project:myapp)
+    Tag(TagArgs),
+    /// Write, show or clear a session's one-line note
+    Note(NoteArgs),
+    /// Combine two stored sessions into a new one
+    Merge(MergeArgs),
+    /// Follow a stored session live, printing messages as they are logged
+    Watch(WatchArgs),
+    /// Bundle old sessions into a .tar.zst archive and remove them from the log directory
+    Archive(ArchiveArgs),
+    /// List stored sessions with their message count and status
+    List(ListArgs),
+    /// Compare a stored session with its assistant's current log
+    Diff(DiffArgs),
+    /// Delete a stored session, or every session dated before a day
+    Delete(DeleteArgs),
+    /// Check stored sessions against the on-disk log format
+    Validate(ValidateArgs),
+    /// Replace all but a session's last messages with a summary marker
+    Compact(CompactArgs),
+    /// Show one day's messages from every assistant in time order, or a heatmap of recent activity
+    Timeline(TimelineArgs),
+    /// Browse stored sessions and their messages in the terminal
+    Browse(BrowseArgs),
+    /// Mask API keys, tokens, emails and IPs in a stored session
+    Redact(RedactArgs),
+    /// Send a stored session's user prompts to an assistant again, one process per prompt
+    Replay(ReplayArgs),
+    /// Rebuild each assistant's index.jsonl and the search index from the stored sessions
+    Reindex(ReindexArgs),
+    /// Show whether the noise filter keeps a message, and which rule decides
+    FilterTest(FilterTestArgs),
+    /// Clean a JSONL or plain-text transcript with the noise filter, without importing it
+    Filter(FilterArgs),
+    /// Find stored sessions with identical messages, and optionally delete the later copies
+    Dedupe(DedupeArgs),
 }
 
-#[derive(Args, Debug)]
+#[derive(Args, Debug, Clone)]
 struct ImportArgs {
-    /// Assistant to import from (codex, goose)
+    /// Assistant to import from (codex, goose, claude-code, gemini, aider, chatgpt, claude-ai, cursor, zed, lmstudio, openwebui)
     #[arg(short, long)]
     assistant: String,
-    /// Session ID to import (uses adapter's latest if not specified)
+    /// Session ID to import (uses adapter's latest if not specified); for chatgpt and
+    /// claude-ai, an export file here imports every conversation in it
     #[arg(short, long)]
     session: Option<String>,
     /// Output directory (default: ~/Assistants/continuum-logs)
     #[arg(short, long)]
     output: Option<PathBuf>,
+    /// Tag to attach to the imported session (repeatable)
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+    /// Rewrite every message instead of appending only what's new since the last import,
+    /// and import even if another stored session has identical content
+    #[arg(long)]
+    force: bool,
+    /// Store messages gzip-compressed (messages.jsonl.gz) for newly imported sessions
+    #[arg(long)]
+    compress: bool,
+    /// File sessions under their start date in this zone: utc, local or an offset like -08:00
+    /// (default: `timezone` in config.toml, else utc)
+    #[arg(long, value_name = "ZONE", allow_hyphen_values = true)]
+    tz: Option<DateZone>,
+    /// List the conversations available to import (chatgpt only) and exit
+    #[arg(long)]
+    list: bool,
+    /// Also store tool calls and tool results as `tool` messages (codex, claude-code, gemini, goose)
+    #[arg(long)]
+    include_tools: bool,
+    /// Truncate each stored tool result to this many bytes
+    #[arg(long, value_name = "BYTES", requires = "include_tools")]
+    max_tool_bytes: Option<usize>,
+    /// Also store extended-thinking blocks as `thinking` messages (claude-code only)
+    #[arg(long)]
+    include_thinking: bool,
+    /// Leave out system messages (system prompts and notices)
+    #[arg(long)]
+    no_system: bool,
+    /// Keep consecutive same-role messages separate instead of joining them (codex only)
+    #[arg(long)]
+    no_coalesce: bool,
+    /// Stop joining a run of same-role messages before it passes this many characters (codex only)
+    #[arg(long, value_name = "CHARS", conflicts_with = "no_coalesce")]
+    coalesce_max_chars: Option<usize>,
+    /// Claude Code project to import from, as its directory name or working directory
+    /// (claude-code only); --session then takes a session ID within it
+    #[arg(long, value_name = "NAME", allow_hyphen_values = true)]
+    project: Option<String>,
+    /// Import every session in the assistant's logs that isn't stored yet
+    #[arg(long, conflicts_with_all = ["session", "project"])]
+    all: bool,
+    /// With --all, only import sessions started on or after this day (YYYY-MM-DD, 7d, month, ...)
+    #[arg(long, value_name = "DATE", requires = "all")]
+    since: Option<String>,
+    /// When importing many sessions (--all, a chatgpt export), pause this many milliseconds between batches
+    #[arg(long, value_name = "MS")]
+    rate_limit: Option<u64>,
+    /// Sessions imported between --rate-limit pauses
+    #[arg(long, value_name = "COUNT", default_value_t = 1, requires = "rate_limit")]
+    batch_size: usize,
+    /// Goose profile whose sessions database to import from (goose only)
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+    /// Loop detection thresholds file (default: ~/.config/continuum/loops.toml)
+    #[arg(long, value_name = "FILE")]
+    loop_config: Option<PathBuf>,
+    /// Warn about a possible loop once a session has this many messages (default: 100)
+    #[arg(long, value_name = "COUNT")]
+    loop_warn: Option<usize>,
+    /// Report a critical loop once a session has this many messages (default: 200)
+    #[arg(long, value_name = "COUNT")]
+    loop_critical: Option<usize>,
+    /// Repetitions of the same message or pattern that count as a loop (default: 10)
+    #[arg(long, value_name = "COUNT")]
+    loop_min_repetitions: Option<usize>,
+    /// Longest repeating message sequence to look for (default: 10)
+    #[arg(long, value_name = "SIZE")]
+    loop_max_pattern: Option<usize>,
+    /// Don't print the source preview or how much the noise filter compressed the session
+    #[arg(short, long)]
+    quiet: bool,
+    /// Truncate each stored message to this many bytes, marking how much was cut
+    #[arg(long, value_name = "BYTES")]
+    max_message_bytes: Option<usize>,
+    /// Drop a message that repeats the one just before it word for word, e.g. a logged retry
+    #[arg(long)]
+    dedupe: bool,
+    /// How much the noise filter drops: off, light, standard or aggressive (default: the config's level)
+    #[arg(long, value_name = "LEVEL")]
+    noise: Option<NoiseLevel>,
+    /// Mask API keys, tokens and other secrets before writing, as `redact = true` in the config does
+    #[arg(long)]
+    redact: bool,
+    /// Parse and filter the session but only report (with a [DRY RUN] prefix) what would be written
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+struct TagArgs {
+    #[command(subcommand)]
+    command: TagCommand,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long, global = true)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum TagCommand {
+    /// Attach tags to a stored session
+    Add(TagEditArgs),
+    /// Detach tags from a stored session
+    Remove(TagEditArgs),
+    /// List a session's tags, the sessions carrying --tag, or every tag in use
+    List {
+        /// Session ID whose tags to show
+        session: Option<String>,
+        /// Assistant the session belongs to (needed when an ID exists under several)
+        #[arg(short, long)]
+        assistant: Option<String>,
+        /// List sessions carrying this tag
+        #[arg(short, long, conflicts_with = "session")]
+        tag: Option<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct NoteArgs {
+    #[command(subcommand)]
+    command: NoteCommand,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long, global = true)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum NoteCommand {
+    /// Write or replace a session's note
+    Set {
+        #[command(flatten)]
+        target: NoteTarget,
+        /// The note (e.g. "solved the async bug in worker.rs")
+        #[arg(required = true)]
+        text: Vec<String>,
+    },
+    /// Print a session's note
+    Get(NoteTarget),
+    /// Remove a session's note
+    Clear(NoteTarget),
+}
+
+#[derive(Args, Debug)]
+struct NoteTarget {
+    /// Session ID
+    #[arg(short, long)]
+    session: String,
+    /// Assistant the session belongs to (needed when an ID exists under several)
+    #[arg(short, long)]
+    assistant: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct MergeArgs {
+    /// Session ID to merge; give two or more, in any order (they're ordered by timestamp)
+    #[arg(short, long = "session", value_name = "ID", aliases = ["session-a", "session-b"], required = true)]
+    sessions: Vec<String>,
+    /// Assistant the sessions belong to, when an ID alone is ambiguous
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// ID for the merged session
+    #[arg(long)]
+    output_id: String,
+    /// Delete the original sessions once the merged one is written
+    #[arg(long, alias = "delete-originals")]
+    remove_sources: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ArchiveArgs {
+    /// Assistant whose sessions to archive
+    #[arg(short, long)]
+    assistant: String,
+    /// Archive sessions dated before this day or period (YYYY-MM-DD, YYYY-MM, 30d, last-month, ...)
+    #[arg(long, value_name = "DATE")]
+    before: Option<String>,
+    /// Archive sessions older than this many days (ignored with --before;
+    /// default: retention_days from config.toml, or 90)
+    #[arg(long, value_name = "DAYS")]
+    older_than: Option<u32>,
+    #[command(flatten)]
+    filters: SessionFilterArgs,
+    /// Log directory (default: ~/Assistants/continuum-logs); archives go to a
+    /// continuum-archives directory next to it
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ListArgs {
+    /// Only list sessions from this assistant
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Only list sessions dated on or after this day (YYYY-MM-DD, YYYY-MM, 7d, last-week, ...)
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+    /// Only list sessions dated on or before this day (same forms as --since)
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+    /// Only list sessions from this day or period (e.g. 2025-11-08, 2025-11, last-week)
+    #[arg(short, long, value_name = "DATE", conflicts_with_all = ["since", "until"])]
+    date: Option<String>,
+    /// Sort order
+    #[arg(long, value_enum, default_value_t = ListSort::Date)]
+    sort: ListSort,
+    /// Maximum number of sessions to print
+    #[arg(short, long, default_value_t = 50)]
+    limit: usize,
+    /// Skip this many sessions first (for paging with --limit)
+    #[arg(long, default_value_t = 0)]
+    offset: usize,
+    #[command(flatten)]
+    filters: SessionFilterArgs,
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = ListFormat::Table)]
+    format: ListFormat,
+    /// Shorthand for --format json
+    #[arg(long, conflicts_with = "format")]
+    json: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Session filters shared by list, stats, archive and export
+#[derive(Args, Debug, Default)]
+struct SessionFilterArgs {
+    /// Only sessions carrying this tag
+    #[arg(long, value_name = "TAG")]
+    tag: Option<String>,
+    /// Only sessions with this status (active, closed, compacted)
+    #[arg(long, value_name = "STATUS")]
+    status: Option<SessionStatus>,
+    /// Only sessions that recorded at least this cost
+    #[arg(long, value_name = "USD")]
+    min_cost: Option<f64>,
+    /// Only sessions that cost at most this much (sessions without a cost count as free)
+    #[arg(long, value_name = "USD")]
+    max_cost: Option<f64>,
+    /// Only sessions whose note contains this text (ignoring case)
+    #[arg(long, value_name = "TEXT")]
+    note_contains: Option<String>,
+    /// Only sessions run in this working directory (relative paths resolve against the current one)
+    #[arg(long, value_name = "PATH")]
+    cwd: Option<PathBuf>,
+    /// Only Claude Code sessions from this project, as its directory name or working directory
+    #[arg(long, value_name = "NAME", allow_hyphen_values = true)]
+    project: Option<String>,
+}
+
+impl SessionFilterArgs {
+    /// Whether any filter flag was given
+    fn is_empty(&self) -> bool {
+        self.tag.is_none()
+            && self.status.is_none()
+            && self.min_cost.is_none()
+            && self.max_cost.is_none()
+            && self.note_contains.is_none()
+            && self.cwd.is_none()
+            && self.project.is_none()
+    }
+
+    /// Add the flags given to `filter`
+    fn apply(&self, filter: SessionFilter) -> Result<SessionFilter> {
+        let cwd = match self.cwd {
+            Some(ref path) if path.is_relative() => {
+                let current = std::env::current_dir().context("Failed to read current directory")?;
+                // components() drops the `.` in e.g. `--cwd .`
+                Some(current.join(path).components().collect::<PathBuf>().to_string_lossy().into_owned())
+            }
+            Some(ref path) => Some(path.to_string_lossy().into_owned()),
+            None => None,
+        };
+
+        Ok(SessionFilter {
+            tag: self.tag.as_deref().map(|tag| tag.trim().to_string()),
+            status: self.status,
+            min_cost: self.min_cost,
+            max_cost: self.max_cost,
+            note_contains: self.note_contains.clone(),
+            cwd,
+            project: self.project.clone(),
+            ..filter
+        })
+    }
+}
+
+#[derive(Args, Debug)]
+struct DiffArgs {
+    /// Stored session ID to compare
+    #[arg(short, long)]
+    session: String,
+    /// Assistant the session belongs to
+    #[arg(short, long)]
+    assistant: String,
+    /// Native log to re-read instead of the default location (session file for
+    /// codex, claude-code, aider, zed and lmstudio; export file for chatgpt; webui.db for openwebui; storage directory for cursor)
+    #[arg(long, value_name = "PATH")]
+    source: Option<PathBuf>,
+    /// Goose profile whose sessions database holds the session (goose only)
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+    /// Include tool calls and results, as for a session imported with --include-tools
+    #[arg(long)]
+    include_tools: bool,
+    /// Truncate each tool result to this many bytes
+    #[arg(long, value_name = "BYTES", requires = "include_tools")]
+    max_tool_bytes: Option<usize>,
+    /// Include thinking blocks, as for a session imported with --include-thinking
+    #[arg(long)]
+    include_thinking: bool,
+    /// Leave out system messages, as for a session imported with --no-system
+    #[arg(long)]
+    no_system: bool,
+    /// Keep consecutive same-role messages separate, as for a session imported with --no-coalesce
+    #[arg(long)]
+    no_coalesce: bool,
+    /// Cap joined runs, as for a session imported with --coalesce-max-chars
+    #[arg(long, value_name = "CHARS", conflicts_with = "no_coalesce")]
+    coalesce_max_chars: Option<usize>,
+    /// Noise filter level the session was imported at, as for --noise (default: the config's level)
+    #[arg(long, value_name = "LEVEL")]
+    noise: Option<NoiseLevel>,
+    /// Truncate each source message to this many bytes, as for a session imported with --max-message-bytes
+    #[arg(long, value_name = "BYTES")]
+    max_message_bytes: Option<usize>,
+    /// Mask secrets in the source, as for a session imported with --redact (on anyway with `redact = true` in the config)
+    #[arg(long)]
+    redact: bool,
+    /// Also print each source message the noise filter drops, with the rule that matched
+    #[arg(short, long)]
+    verbose: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct DeleteArgs {
+    /// Session ID to delete
+    #[arg(short, long, required_unless_present = "all_before", conflicts_with = "all_before")]
+    session: Option<String>,
+    /// Assistant the session belongs to (with --all-before, only delete this assistant's sessions)
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Day or period the session is filed under (e.g. 2025-11-08, 2025-11, yesterday), when the ID alone is ambiguous
+    #[arg(short, long, value_name = "DATE", requires = "session")]
+    date: Option<String>,
+    /// Delete every session dated before this day or period (YYYY-MM-DD, 30d, last-month, ...), across all assistants
+    #[arg(long, value_name = "DATE")]
+    all_before: Option<String>,
+    /// Delete without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct DedupeArgs {
+    /// Only look at this assistant's sessions
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Delete every copy but the oldest in each group
+    #[arg(long)]
+    remove: bool,
+    /// With --remove, delete without asking for confirmation
+    #[arg(short, long, requires = "remove")]
+    yes: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ValidateArgs {
+    /// Session ID to check (checks every stored session if not specified)
+    #[arg(short, long)]
+    session: Option<String>,
+    /// Only check this assistant's sessions
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Check every stored session (the default without --session)
+    #[arg(long, conflicts_with = "session")]
+    all: bool,
+    /// Renumber message IDs and correct session.json's message_count
+    #[arg(long)]
+    fix: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ReindexArgs {
+    /// Only rebuild this assistant's search index entries
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Print each session as it is indexed, instead of a running count
+    #[arg(short, long)]
+    verbose: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct FilterTestArgs {
+    /// Message text to run through the filter (`-` reads it from stdin)
+    text: String,
+    /// Noise filter config to test instead of the configured one (same keys as noise.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Filter level to test at: off, light, standard or aggressive (default: the config's level)
+    #[arg(long, value_name = "LEVEL")]
+    noise: Option<NoiseLevel>,
+}
+
+#[derive(Args, Debug)]
+struct FilterArgs {
+    /// Transcript to clean: JSONL `{role, content}` records or `User:`/`Assistant:` text (default: stdin)
+    input: Option<PathBuf>,
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "jsonl")]
+    format: filter::FilterFormat,
+    /// Noise filter config to use instead of the configured one (same keys as noise.toml)
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Filter level: off, light, standard or aggressive (default: the config's level)
+    #[arg(long, value_name = "LEVEL")]
+    noise: Option<NoiseLevel>,
+}
+
+#[derive(Args, Debug)]
+struct CompactArgs {
+    /// Session ID to compact
+    #[arg(short, long)]
+    session: String,
+    /// Assistant the session belongs to, when the ID alone is ambiguous
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Messages to keep verbatim at the end of the session
+    #[arg(short, long, value_name = "N")]
+    keep_last: usize,
+    /// Compact without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct RedactArgs {
+    /// Session ID to redact
+    #[arg(short, long)]
+    session: String,
+    /// Assistant the session belongs to, when the ID alone is ambiguous
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Extra regex to redact as `custom_N`, on top of the built-in patterns (repeatable)
+    #[arg(short, long, value_name = "REGEX")]
+    pattern: Vec<String>,
+    /// Redact without asking for confirmation
+    #[arg(short, long)]
+    yes: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ReplayArgs {
+    /// Stored session whose prompts to replay
+    #[arg(short, long)]
+    session: String,
+    /// Assistant to replay the prompts against (claude-code, codex, goose, gemini)
+    #[arg(short, long)]
+    assistant: String,
+    /// Assistant the stored session belongs to, when the ID alone is ambiguous
+    #[arg(long, value_name = "ASSISTANT")]
+    from: Option<String>,
+    /// Print the prompts instead of sending them
+    #[arg(long)]
+    dry_run: bool,
+    /// Wait for Enter before sending each prompt
+    #[arg(short, long)]
+    interactive: bool,
+    /// Assistant executable to run (default: its usual binary on PATH)
+    #[arg(long, value_name = "PATH")]
+    binary: Option<PathBuf>,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Order for `continuum list`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ListSort {
+    /// Newest first, then by assistant and session ID
+    Date,
+    /// Most messages first (ties newest first)
+    Messages,
+}
+
+impl From<ListSort> for SessionSort {
+    fn from(sort: ListSort) -> Self {
+        match sort {
+            ListSort::Date => SessionSort::Date,
+            ListSort::Messages => SessionSort::Messages,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ListFormat {
+    Table,
+    Json,
+    Tsv,
+}
+
+#[derive(Args, Debug)]
+struct WatchArgs {
+    /// Session ID to follow
+    #[arg(long)]
+    session: String,
+    /// Assistant the session belongs to (needed when an ID exists under several)
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = WatchFormat::Text)]
+    format: WatchFormat,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct TagEditArgs {
+    /// Session ID to tag
+    session: String,
+    /// Tags to add or remove (e.g. project:myapp topic:debugging)
+    #[arg(required = true)]
+    tags: Vec<String>,
+    /// Assistant the session belongs to (needed when an ID exists under several)
+    #[arg(short, long)]
+    assistant: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct StatsArgs {
+    /// Show per-session statistics for one assistant instead of per-assistant totals
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Only count sessions dated on or after this day (YYYY-MM-DD, YYYY-MM, 7d, last-week, ...)
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+    /// Only count sessions dated on or before this day (same forms as --since)
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+    #[command(flatten)]
+    filters: SessionFilterArgs,
+    /// Output format; json prints per-assistant totals keyed by assistant (for jq and dashboards)
+    #[arg(short, long, value_enum, default_value_t = StatsFormat::Table)]
+    format: StatsFormat,
+    /// Shorthand for --format json
+    #[arg(long, conflicts_with = "format")]
+    json: bool,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum StatsFormat {
+    Table,
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct TailArgs {
+    /// Assistant whose latest session to show (e.g. claude-code, codex, goose)
+    #[arg(short, long)]
+    assistant: String,
+    /// Number of messages to show
+    #[arg(short = 'n', long, default_value_t = 10)]
+    lines: usize,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct TimelineArgs {
+    /// Day to show: YYYY-MM-DD, today, yesterday or Nd (without it, a heatmap of daily activity)
+    date: Option<String>,
+    /// Only show this assistant's sessions
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Print each message in full instead of a one-line snippet
+    #[arg(long, requires = "date")]
+    full: bool,
+    /// Days covered by the heatmap, ending today (at most 3660)
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..=i64::from(heatmap::MAX_DAYS)), conflicts_with = "date")]
+    days: u32,
+    /// Heatmap output format
+    #[arg(long, value_enum, default_value_t = HeatmapFormat::Ascii, conflicts_with = "date")]
+    format: HeatmapFormat,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
 }
 
-fn handle_import(args: &ImportArgs) -> Result<()> {
+#[derive(Args, Debug)]
+struct BrowseArgs {
+    /// Only list this assistant's sessions
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct SearchArgs {
+    /// Text to search for (substring unless --regex is given)
+    #[arg(required_unless_present = "rebuild")]
+    query: Vec<String>,
+    /// Only search sessions from this assistant
+    #[arg(short, long)]
+    assistant: Option<String>,
+    /// Only search this session (restored from its archive if it was archived)
+    #[arg(short, long)]
+    session: Option<String>,
+    /// Only search messages with this role (user, assistant)
+    #[arg(short, long)]
+    role: Option<String>,
+    /// Only search sessions dated on or after this day (YYYY-MM-DD, YYYY-MM, 7d, last-week, ...)
+    #[arg(long, value_name = "DATE")]
+    since: Option<String>,
+    /// Only search sessions dated on or before this day (same forms as --since)
+    #[arg(long, value_name = "DATE")]
+    until: Option<String>,
+    /// Only search sessions from this day or period (e.g. 2025-11-08, 2025-11, last-week)
+    #[arg(short, long, value_name = "DATE", conflicts_with_all = ["since", "until"])]
+    date: Option<String>,
+    /// Match case exactly
+    #[arg(long)]
+    case_sensitive: bool,
+    /// Treat the query as a regular expression
+    #[arg(long, conflicts_with = "index")]
+    regex: bool,
+    /// Use the FTS5 search index instead of scanning the log files
+    #[arg(long, conflicts_with = "case_sensitive")]
+    index: bool,
+    /// Rebuild the search index from the stored logs before searching
+    #[arg(long)]
+    rebuild: bool,
+    /// Also show N messages before and after each match, like grep -C (matches are then listed in session order)
+    #[arg(short = 'C', long, default_value_t = 0)]
+    context: usize,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct ExportArgs {
+    /// Session ID to export
+    #[arg(
+        required_unless_present_any = ["latest", "session_flag"],
+        conflicts_with_all = ["latest", "session_flag"]
+    )]
+    session: Option<String>,
+    /// Session ID to export (alternative to the positional argument)
+    #[arg(long = "session", value_name = "ID", conflicts_with = "latest")]
+    session_flag: Option<String>,
+    /// Export the most recent session for --assistant (that matches the filter flags, if any)
+    #[arg(long, requires = "assistant")]
+    latest: bool,
+    /// Assistant the session belongs to (needed when an ID exists under several)
+    #[arg(short, long)]
+    assistant: Option<String>,
+    #[command(flatten)]
+    filters: SessionFilterArgs,
+    /// Output format
+    #[arg(short, long, value_enum, default_value_t = ExportFormat::Markdown)]
+    format: ExportFormat,
+    /// File to write (default: stdout)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Log directory (default: ~/Assistants/continuum-logs)
+    #[arg(long)]
+    log_dir: Option<PathBuf>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum ExportFormat {
+    Markdown,
+    Html,
+    Jsonl,
+}
+
+impl ExportFormat {
+    fn writer(self) -> Box<dyn export::FormatWriter> {
+        match self {
+            ExportFormat::Markdown => Box::new(export::MarkdownWriter),
+            ExportFormat::Html => Box::new(export::HtmlWriter),
+            ExportFormat::Jsonl => Box::new(export::JsonlWriter),
+        }
+    }
+}
+
+fn handle_import(args: &ImportArgs, config: &Config) -> Result<()> {
+    let zone = args.tz.unwrap_or(config.timezone);
     let writer = if let Some(ref output) = args.output {
         PlainTextWriter::with_base_dir(output.clone())
     } else {
-        PlainTextWriter::new()?
+        PlainTextWriter::from_config(config)
+    }
+    .with_compression(args.compress)
+    .with_zone(zone)
+    .with_redactor((args.redact || config.redact).then(|| config.redactor.clone()));
+
+    // A dry run reads the log tree but reports every write instead of making it
+    let dry_run = if args.dry_run {
+        Some(
+            DryRunWriter::with_base_dir(writer.base_dir().to_path_buf())
+                .with_compression(args.compress)
+                .with_zone(zone)
+                .with_loop_detector(loop_detector(args, config)?),
+        )
+    } else {
+        None
+    };
+    let backend: &dyn WriterBackend = match dry_run {
+        Some(ref dry_run) => dry_run,
+        None => &writer,
     };
 
-    let adapter_name = args.assistant.to_lowercase();
+    // Gemini CLI sessions are stored as gemini-cli, as the wrapper logs them
+    let adapter_name = match args.assistant.to_lowercase().as_str() {
+        "gemini" => "gemini-cli".to_string(),
+        name => name.to_string(),
+    };
+
+    if args.profile.is_some() && adapter_name != "goose" {
+        eprintln!("Error: --profile is only supported for goose");
+        std::process::exit(1);
+    }
+
+    if args.project.is_some() && adapter_name != "claude-code" {
+        eprintln!("Error: --project is only supported for claude-code");
+        std::process::exit(1);
+    }
+
+    if args.include_thinking && adapter_name != "claude-code" {
+        eprintln!("Error: --include-thinking is only supported for claude-code");
+        std::process::exit(1);
+    }
+
+    if args.list && adapter_name != "chatgpt" {
+        eprintln!("Error: --list is only supported for chatgpt");
+        std::process::exit(1);
+    }
 
-    match adapter_name.as_str() {
+    let imported = match adapter_name.as_str() {
         "codex" => {
-            let adapter = CodexAdapter::new();
-            import_codex_session(&writer, &adapter, args)
+            let adapter = CodexAdapter::from_config(config);
+            import_codex_session(backend, &adapter, args, config)
         }
         "goose" => {
-            let adapter = GooseAdapter::new()?;
-            import_goose_session(&writer, &adapter, args)
+            let adapter = GooseAdapter::from_config(config, args.profile.clone())?;
+            import_goose_session(backend, &adapter, args, config)
         }
         "claude-code" => {
-            let adapter = ClaudeCodeAdapter::new();
-            import_claude_code_session(&writer, &adapter, args)
+            let adapter = ClaudeCodeAdapter::from_config(config);
+            import_claude_code_session(backend, &adapter, args, config)
+        }
+        "gemini-cli" => {
+            let adapter = GeminiAdapter::from_config(config);
+            import_gemini_session(backend, &adapter, args, config)
+        }
+        "aider" => {
+            let adapter = AiderAdapter::from_config(config);
+            import_aider_session(backend, &adapter, args, config)
+        }
+        "chatgpt" => {
+            let adapter = ChatGptAdapter::from_config(config);
+            import_chatgpt_session(backend, &adapter, args, config)
+        }
+        "claude-ai" => {
+            let adapter = ClaudeAiAdapter::from_config(config);
+            import_claude_ai_session(backend, &adapter, args, config)
+        }
+        "cursor" => {
+            let adapter = CursorAdapter::from_config(config);
+            import_cursor_session(backend, &adapter, args, config)
+        }
+        "zed" => {
+            let adapter = ZedAdapter::from_config(config);
+            import_role_content_session(backend, &adapter, "Zed", ZedAdapter::session_path, sources::read_zed, args, config)
+        }
+        "lmstudio" => {
+            let adapter = LmStudioAdapter::from_config(config);
+            import_role_content_session(backend, &adapter, "LM Studio", LmStudioAdapter::session_path, sources::read_lmstudio, args, config)
+        }
+        "openwebui" => {
+            let adapter = OpenWebUiAdapter::from_config(config);
+            import_role_content_session(backend, &adapter, "Open WebUI", OpenWebUiAdapter::session_path, sources::read_openwebui, args, config)
         }
         _ => {
-            eprintln!("Error: Unknown assistant '{}'. Supported: codex, goose, claude-code", args.assistant);
+            eprintln!("Error: Unknown assistant '{}'. Supported: codex, goose, claude-code, gemini, aider, chatgpt, claude-ai, cursor, zed, lmstudio, openwebui", args.assistant);
             std::process::exit(1);
         }
+    };
+    imported?;
+
+    if config.auto_archive && !args.list && !args.dry_run {
+        let archived = retention_cutoff(config.retention_days())
+            .and_then(|before| archive_before(config, &writer, &adapter_name, before, &SessionFilter::new()));
+        if let Err(e) = archived {
+            eprintln!("⚠ Warning: Failed to archive old sessions: {}", e);
+        }
     }
+
+    Ok(())
 }
 
 fn import_codex_session(
-    writer: &PlainTextWriter,
+    writer: &dyn WriterBackend,
     adapter: &CodexAdapter,
     args: &ImportArgs,
+    config: &Config,
 ) -> Result<()> {
+    if args.all {
+        return import_all_sessions(writer, adapter, "Codex", args, |args| import_codex_session(writer, adapter, args, config));
+    }
+
     let session_path = if let Some(ref session) = args.session {
         PathBuf::from(session)
     } else {
@@ -95,45 +990,24 @@ fn import_codex_session(
     };
 
     eprintln!("Importing Codex session: {}", session_path.display());
+    preview_session(args, adapter, &session_path);
 
-    let session_id = session_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown");
-
-    let compressor = MessageCompressor::new();
-    let mut messages: Vec<(String, String)> = Vec::new();
-    let start_time = chrono::Utc::now().to_rfc3339();
-
-    // Read all messages
-    for line_result in adapter.stream_session(&session_path)? {
-        let line = line_result?;
-        let entry: CodexLogEntry = serde_json::from_str(&line)?;
-
-        if entry.entry_type == "response_item" {
-            if let Some(ref payload) = entry.payload {
-                if let Some(ref role) = payload.role {
-                    if let Some(ref content_array) = payload.content {
-                        let text = content_array
-                            .iter()
-                            .filter_map(|c| c.text.as_deref())
-                            .collect::<Vec<_>>()
-                            .join("");
-
-                        messages.push((role.clone(), text));
-                    }
-                }
-            }
-        }
-    }
-
-    // Compress messages to remove noise
-    let compressed = compressor.compress_batch(&messages);
-    let message_count = compressed.len();
+    let tools = args.include_tools.then(|| ToolCapture::new(args.max_tool_bytes));
+    let compressor = import_compressor(args, config)?;
+    // Codex can split one assistant turn over several response items
+    let mut pipeline = ImportPipeline::new(&compressor)?
+        .system(!args.no_system)
+        .coalesce(!args.no_coalesce)
+        .coalesce_max_chars(args.coalesce_max_chars)
+        .detect_loops(loop_detector(args, config)?);
+    let source = sources::read_codex(adapter, &session_path, tools.as_ref(), &mut pipeline)?;
+    let Spooled { spool, stats, detections } = pipeline.finish()?;
+    report_compression(args, stats);
 
-    // Loop detection - analyze messages before writing
-    let detector = LoopDetector::new();
-    let detections = detector.analyze(&messages);
+    let session_id = source.id.as_str();
+    let start_time = previous_start_time(config, writer, session_id, "codex")
+        .or(source.start_time)
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
 
     // Report any detected loops
     if !detections.is_empty() {
@@ -149,273 +1023,2178 @@ fn import_codex_session(
         eprintln!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
     }
 
-    // Extract date from start time
-    let date = PlainTextWriter::extract_date(Some(&start_time));
-
-    // Write session
-    writer.write_session(
-        session_id,
-        "codex",
-        Some(&start_time),
-        None,
-        "closed",
-        message_count,
-        &[],
-    )?;
-
-    // Write messages
-    for (idx, (role, content)) in compressed.iter().enumerate() {
-        writer.append_message(
-            session_id,
-            "codex",
-            &date,
-            idx + 1,
-            role,
-            content,
-            Some(&start_time),
-        )?;
-    }
-
-    println!("✓ Imported {} messages from Codex session: {}", message_count, session_id);
-    println!("  Location: {}", writer.base_dir().join("codex").join(&date).join(session_id).display());
+    store_import(config, writer, args, &spool, session_id, "codex", &start_time, &[], None, "Codex session")?;
 
     Ok(())
 }
 
 fn import_goose_session(
-    writer: &PlainTextWriter,
+    writer: &dyn WriterBackend,
     adapter: &GooseAdapter,
     args: &ImportArgs,
+    config: &Config,
 ) -> Result<()> {
-    let session_path = if let Some(ref session) = args.session {
-        // User provided session ID, construct pseudo-path
-        let home = std::env::var("HOME").context("HOME not set")?;
-        let db_path = PathBuf::from(home).join(".local/share/goose/sessions/sessions.db");
-        PathBuf::from(format!("{}#{}", db_path.display(), session))
-    } else {
-        adapter.find_latest_session()?
-    };
+    if args.all {
+        return import_all_goose_sessions(writer, adapter, args, config);
+    }
 
-    // Extract session ID from pseudo-path
-    let path_str = session_path.to_string_lossy();
-    let session_id = if let Some(hash_pos) = path_str.rfind('#') {
-        &path_str[hash_pos + 1..]
+    let session = if let Some(ref session) = args.session {
+        adapter.session_ref(session)
     } else {
-        eprintln!("Error: Invalid Goose session path");
-        std::process::exit(1);
+        adapter.latest_session()?
     };
 
-    eprintln!("Importing Goose session: {}", session_id);
+    match session.profile {
+        Some(ref profile) => eprintln!("Importing Goose session: {} (profile {})", session.session_id, profile),
+        None => eprintln!("Importing Goose session: {}", session.session_id),
+    }
+    preview_session(args, adapter, &session.to_path());
 
-    let compressor = MessageCompressor::new();
-    let mut messages: Vec<(String, String)> = Vec::new();
-    let start_time = chrono::Utc::now().to_rfc3339();
+    import_goose_one(writer, adapter, &session, args, config).map(|_| ())
+}
 
-    // Read all messages
-    for msg_result in adapter.stream_session(&session_path)? {
-        let msg_json = msg_result?;
+/// Import every Goose session that isn't stored (or archived) yet, oldest first
+fn import_all_goose_sessions(
+    writer: &dyn WriterBackend,
+    adapter: &GooseAdapter,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<()> {
+    let since = args.since.as_deref().map(parse_date_filter).transpose()?.map(|f| f.start());
+    let known = known_session_ids(writer, "goose")?;
 
-        #[derive(serde::Deserialize)]
-        struct GooseMessage {
-            role: String,
-            content_json: String,
+    let (mut already_stored, mut empty) = (0, 0);
+    let mut pending = Vec::new();
+    for session in adapter.list_all_sessions()? {
+        // Sessions without a readable creation time can't be placed in the range
+        let created = session
+            .created_at
+            .as_deref()
+            .and_then(PlainTextWriter::normalize_timestamp)
+            .and_then(|t| chrono::NaiveDate::parse_from_str(&writer.date_of(Some(&t)), "%Y-%m-%d").ok());
+        if since.is_some_and(|since| created.is_none_or(|created| created < since)) {
+            continue;
         }
 
-        let msg: GooseMessage = serde_json::from_str(&msg_json)?;
-        let content = parse_goose_content(&msg.content_json)?;
-
-        if !content.is_empty() {
-            messages.push((msg.role, content));
+        if known.contains(&session.session_id) {
+            already_stored += 1;
+        } else if session.message_count == Some(0) {
+            empty += 1;
+        } else {
+            pending.push(session);
         }
     }
 
-    // Compress messages
-    let compressed = compressor.compress_batch(&messages);
-    let message_count = compressed.len();
+    let options = import_options(args);
+    let (mut imported, mut skipped, mut failed) = (0, 0, 0);
+    for (index, session) in pending.iter().enumerate() {
+        eprintln!("Importing session {} of {}: {}", index + 1, pending.len(), session.session_id);
+        let result = session_ops::check_path_component("session ID", &session.session_id)
+            .and_then(|_| import_goose_one(writer, adapter, session, args, config));
+        match result {
+            Ok(true) => imported += 1,
+            Ok(false) => skipped += 1,
+            Err(e) => {
+                eprintln!("⚠ Warning: Failed to import Goose session {}: {}", session.session_id, e);
+                failed += 1;
+            }
+        }
+        options.session_done(index + 1, pending.len());
+    }
 
-    if message_count == 0 {
-        eprintln!("⚠ No messages found in Goose session: {}", session_id);
-        return Ok(());
+    let mut summary = format!(
+        "✓ {} {} of {} new Goose sessions ({} already stored, {} empty",
+        if writer.is_dry_run() { "Would import" } else { "Imported" },
+        imported,
+        pending.len(),
+        already_stored,
+        empty
+    );
+    if skipped > 0 {
+        summary.push_str(&format!(", {} skipped", skipped));
+    }
+    if failed > 0 {
+        summary.push_str(&format!(", {} failed", failed));
     }
+    println!("{})", summary);
 
-    // Extract date
-    let date = PlainTextWriter::extract_date(Some(&start_time));
+    Ok(())
+}
 
-    // Write session
-    writer.write_session(
-        session_id,
-        "goose",
-        Some(&start_time),
-        None,
-        "closed",
-        message_count,
-        &[],
-    )?;
+/// IDs of an assistant's sessions that are stored or archived, for --all to skip
+fn known_session_ids(writer: &dyn WriterBackend, assistant: &str) -> Result<std::collections::HashSet<String>> {
+    let mut known: std::collections::HashSet<String> = store::list_assistant_sessions(writer.base_dir(), assistant)?
+        .into_iter()
+        .map(|s| s.session_id)
+        .collect();
+    match ArchiveManifest::load(&writer.archive_dir()) {
+        Ok(archive) => known.extend(
+            archive
+                .sessions
+                .into_iter()
+                .filter(|s| s.assistant.eq_ignore_ascii_case(assistant))
+                .map(|s| s.id),
+        ),
+        Err(e) => eprintln!("⚠ Warning: Failed to read archive manifest: {}", e),
+    }
+    Ok(known)
+}
+
+/// Import every session `adapter` lists that isn't stored (or archived) yet, oldest first
+///
+/// Each one goes through `import_one` as if it had been named with
+/// --session. With --since, sessions are placed by the start time
+/// [`LogAdapter::session_metadata`] reports; those without one are left out.
+fn import_all_sessions(
+    writer: &dyn WriterBackend,
+    adapter: &dyn LogAdapter,
+    label: &str,
+    args: &ImportArgs,
+    import_one: impl Fn(&ImportArgs) -> Result<()>,
+) -> Result<()> {
+    let assistant = adapter.name();
+    let since = args.since.as_deref().map(parse_date_filter).transpose()?.map(|f| f.start());
+    let known = known_session_ids(writer, assistant)?;
+
+    let mut already_stored = 0;
+    let mut pending = Vec::new();
+    // Oldest first, so sessions are stored in the order they happened
+    for path in adapter.list_sessions()?.into_iter().rev() {
+        if let Some(since) = since {
+            let started = adapter
+                .session_metadata(&path)
+                .ok()
+                .and_then(|metadata| metadata.start_time)
+                .and_then(|t| PlainTextWriter::normalize_timestamp(&t))
+                .and_then(|t| chrono::NaiveDate::parse_from_str(&writer.date_of(Some(&t)), "%Y-%m-%d").ok());
+            if started.is_none_or(|started| started < since) {
+                continue;
+            }
+        }
+
+        match sources::source_session_id(assistant, &path) {
+            Ok(id) if known.contains(&id) => already_stored += 1,
+            _ => pending.push(path),
+        }
+    }
 
-    // Write messages
-    for (idx, (role, content)) in compressed.iter().enumerate() {
-        writer.append_message(
-            session_id,
-            "goose",
-            &date,
-            idx + 1,
-            role,
-            content,
-            Some(&start_time),
-        )?;
+    let options = import_options(args);
+    let mut failed = 0;
+    for (index, path) in pending.iter().enumerate() {
+        eprintln!("Importing session {} of {}: {}", index + 1, pending.len(), path.display());
+        let session_args = ImportArgs {
+            session: Some(path.to_string_lossy().into_owned()),
+            all: false,
+            ..args.clone()
+        };
+        if let Err(e) = import_one(&session_args) {
+            eprintln!("⚠ Warning: Failed to import {} session {}: {}", label, path.display(), e);
+            failed += 1;
+        }
+        options.session_done(index + 1, pending.len());
     }
 
-    println!("✓ Imported {} messages from Goose session: {}", message_count, session_id);
-    println!("  Location: {}", writer.base_dir().join("goose").join(&date).join(session_id).display());
+    let mut summary = format!(
+        "✓ Processed {} of {} new {} sessions ({} already stored",
+        pending.len() - failed,
+        pending.len(),
+        label,
+        already_stored
+    );
+    if failed > 0 {
+        summary.push_str(&format!(", {} failed", failed));
+    }
+    println!("{})", summary);
 
     Ok(())
 }
 
+/// Import one Goose session; false if it had no messages left or duplicated a stored one
+fn import_goose_one(
+    writer: &dyn WriterBackend,
+    adapter: &GooseAdapter,
+    session: &GooseSessionRef,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<bool> {
+    let session_id = session.session_id.as_str();
+    let tools = args.include_tools.then(|| ToolCapture::new(args.max_tool_bytes));
+    let compressor = import_compressor(args, config)?;
+    let mut pipeline = ImportPipeline::new(&compressor)?.system(!args.no_system);
+    let source = sources::read_goose(adapter, session, tools.as_ref(), &mut pipeline)?;
+    let Spooled { spool, stats, .. } = pipeline.finish()?;
+    report_compression(args, stats);
+    let message_count = spool.len();
+    let start_time = previous_start_time(config, writer, session_id, "goose")
+        .or(source.start_time)
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    if message_count == 0 {
+        eprintln!("⚠ No messages found in Goose session: {}", session_id);
+        return Ok(false);
+    }
+
+    let stored = store_import(config, writer, args, &spool, session_id, "goose", &start_time, &[], None, "Goose session")?;
+
+    Ok(stored)
+}
+
 fn import_claude_code_session(
-    writer: &PlainTextWriter,
+    writer: &dyn WriterBackend,
     adapter: &ClaudeCodeAdapter,
     args: &ImportArgs,
+    config: &Config,
 ) -> Result<()> {
-    let session_path = if let Some(ref session) = args.session {
+    if args.all {
+        return import_all_sessions(writer, adapter, "Claude Code", args, |args| import_claude_code_session(writer, adapter, args, config));
+    }
+
+    let session_path = if let Some(ref project) = args.project {
+        claude_code_project_session(adapter, project, args.session.as_deref())?
+    } else if let Some(ref session) = args.session {
         PathBuf::from(session)
     } else {
         adapter.find_latest_session()?
     };
 
     eprintln!("Importing Claude Code session: {}", session_path.display());
+    preview_session(args, adapter, &session_path);
 
-    let session_id = session_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("unknown");
+    let tools = args.include_tools.then(|| ToolCapture::new(args.max_tool_bytes));
+    let compressor = import_compressor(args, config)?;
+    let mut pipeline = ImportPipeline::new(&compressor)?.system(!args.no_system);
+    let source = sources::read_claude_code(adapter, &session_path, tools.as_ref(), args.include_thinking, &mut pipeline)?;
+    let Spooled { spool, stats, .. } = pipeline.finish()?;
+    report_compression(args, stats);
+    let session_id = source.id.as_str();
+    let message_count = spool.len();
 
-    let compressor = MessageCompressor::new();
-    let mut messages: Vec<(String, String)> = Vec::new();
-    let mut start_time: Option<String> = None;
-    let mut skills: Vec<String> = Vec::new();
+    if message_count == 0 {
+        eprintln!("⚠ No messages found in Claude Code session: {}", session_id);
+        return Ok(());
+    }
+
+    // Use captured timestamp or fallback to current time
+    let timestamp = source.start_time.clone().unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let project = adapter.project_of(&session_path);
+    store_import(config, writer, args, &spool, session_id, "claude-code", &timestamp, &source.skills, project, "Claude Code session")?;
+
+    Ok(())
+}
+
+/// Session file for `--project`: the given session ID (or path), or the project's latest session
+fn claude_code_project_session(adapter: &ClaudeCodeAdapter, project: &str, session: Option<&str>) -> Result<PathBuf> {
+    let project_dir = adapter.project_dir(project).map_err(|e| {
+        let known: Vec<String> = adapter
+            .list_projects()
+            .unwrap_or_default()
+            .iter()
+            .map(|name| ClaudeCodeAdapter::decode_project_path(name))
+            .collect();
+        if known.is_empty() {
+            e
+        } else {
+            color_eyre::eyre::eyre!("{} (known projects: {})", e, known.join(", "))
+        }
+    })?;
+
+    match session {
+        Some(session) if Path::new(session).is_file() => Ok(PathBuf::from(session)),
+        Some(session) => {
+            let session_id = session.strip_suffix(".jsonl").unwrap_or(session);
+            session_ops::check_path_component("session ID", session_id)?;
+            let path = project_dir.join(format!("{}.jsonl", session_id));
+            if !path.is_file() {
+                color_eyre::eyre::bail!("Session {} not found in Claude Code project {}", session_id, project);
+            }
+            Ok(path)
+        }
+        None => {
+            let (session_id, _) = adapter
+                .list_sessions_for_project(project)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| color_eyre::eyre::eyre!("No sessions in Claude Code project {}", project))?;
+            Ok(project_dir.join(format!("{}.jsonl", session_id)))
+        }
+    }
+}
+
+fn import_gemini_session(
+    writer: &dyn WriterBackend,
+    adapter: &GeminiAdapter,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<()> {
+    if args.all {
+        return import_all_sessions(writer, adapter, "Gemini CLI", args, |args| import_gemini_session(writer, adapter, args, config));
+    }
+
+    let session_path = if let Some(ref session) = args.session {
+        PathBuf::from(session)
+    } else {
+        adapter.find_latest_session()?
+    };
+
+    eprintln!("Importing Gemini CLI session: {}", session_path.display());
+    preview_session(args, adapter, &session_path);
+
+    let tools = args.include_tools.then(|| ToolCapture::new(args.max_tool_bytes));
+    let compressor = import_compressor(args, config)?;
+    let mut pipeline = ImportPipeline::new(&compressor)?.system(!args.no_system);
+    let source = sources::read_gemini(&session_path, tools.as_ref(), &mut pipeline)?;
+    let Spooled { spool, stats, .. } = pipeline.finish()?;
+    report_compression(args, stats);
+    let session_id = source.id.as_str();
+    let message_count = spool.len();
+
+    if message_count == 0 {
+        eprintln!("⚠ No messages found in Gemini CLI session: {}", session_id);
+        return Ok(());
+    }
+
+    let timestamp = source.start_time.clone().unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    store_import(config, writer, args, &spool, session_id, "gemini-cli", &timestamp, &[], None, "Gemini CLI session")?;
+
+    Ok(())
+}
+
+fn import_aider_session(
+    writer: &dyn WriterBackend,
+    adapter: &AiderAdapter,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<()> {
+    if args.all {
+        return import_all_sessions(writer, adapter, "Aider", args, |args| import_aider_session(writer, adapter, args, config));
+    }
+
+    let session_path = if let Some(ref session) = args.session {
+        PathBuf::from(session)
+    } else {
+        adapter.find_latest_session()?
+    };
 
-    // Read all messages
-    for line_result in adapter.stream_session(&session_path)? {
-        let line = line_result?;
+    eprintln!("Importing Aider session: {}", session_path.display());
+    preview_session(args, adapter, &session_path);
 
-        #[derive(serde::Deserialize)]
-        struct ClaudeCodeEntry {
-            #[serde(rename = "type")]
-            entry_type: String,
-            message: Option<serde_json::Value>,
-            timestamp: Option<String>,
+    let compressor = import_compressor(args, config)?;
+    let mut pipeline = ImportPipeline::new(&compressor)?.system(!args.no_system);
+    let source = sources::read_aider(adapter, &session_path, &mut pipeline)?;
+    let Spooled { spool, stats, .. } = pipeline.finish()?;
+    report_compression(args, stats);
+    let session_id = source.id;
+    let message_count = spool.len();
+
+    let start_time = previous_start_time(config, writer, &session_id, "aider")
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    if message_count == 0 {
+        eprintln!("⚠ No messages found in Aider session: {}", session_id);
+        return Ok(());
+    }
+
+    // Aider appends to the same history file across runs, so usually only the tail is new
+    store_import(config, writer, args, &spool, &session_id, "aider", &start_time, &[], None, "Aider session")?;
+
+    Ok(())
+}
+
+fn import_chatgpt_session(
+    writer: &dyn WriterBackend,
+    adapter: &ChatGptAdapter,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<()> {
+    if args.list {
+        for (id, title) in chatgpt::list_sessions(&adapter.export_file()?)? {
+            println!("{}  {}", id, title);
         }
+        return Ok(());
+    }
+
+    // --session is either a conversation ID or an export file to import whole
+    let export_path = args.session.as_deref().map(Path::new).filter(|path| path.exists());
+    let export_file = match export_path {
+        Some(path) => ChatGptAdapter::with_export_path(path.to_path_buf()).export_file()?,
+        None => adapter.export_file()?,
+    };
+    let conversations = chatgpt::read_export(&export_file)?;
 
-        let entry: ClaudeCodeEntry = serde_json::from_str(&line)?;
+    // With --all the export is read once, rather than per conversation as other assistants' sources are
+    let known = if args.all { known_session_ids(writer, "chatgpt")? } else { Default::default() };
+    let since = args.since.as_deref().map(parse_date_filter).transpose()?.map(|f| f.start());
+    let is_new = |conversation: &chatgpt::ExportedConversation| {
+        let started = conversation
+            .create_time
+            .as_deref()
+            .and_then(|t| chrono::NaiveDate::parse_from_str(&writer.date_of(Some(t)), "%Y-%m-%d").ok());
+        !known.contains(&conversation.id) && since.is_none_or(|since| started.is_some_and(|started| started >= since))
+    };
+
+    let selected: Vec<&chatgpt::ExportedConversation> = match (&args.session, export_path) {
+        (None, _) if args.all => conversations.iter().rev().filter(|c| is_new(c)).collect(),
+        (Some(_), Some(_)) => conversations.iter().collect(),
+        (Some(id), None) => vec![conversations
+            .iter()
+            .find(|c| c.id == *id)
+            .ok_or_else(|| color_eyre::eyre::eyre!("Conversation '{}' not found in {}", id, export_file.display()))?],
+        (None, _) => vec![conversations
+            .first()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No conversations in {}", export_file.display()))?],
+    };
+
+    if export_path.is_some() || args.all {
+        eprintln!("Importing {} ChatGPT conversations from {}", selected.len(), export_file.display());
+    }
 
-        // Capture first timestamp as session start time
-        if start_time.is_none() {
-            if let Some(ref ts) = entry.timestamp {
-                start_time = Some(ts.clone());
+    // One bad conversation in a whole export is reported and skipped
+    let compressor = import_compressor(args, config)?;
+    let options = import_options(args);
+    let mut failed = 0;
+    for (index, conversation) in selected.iter().enumerate() {
+        match import_chatgpt_conversation(writer, conversation, &compressor, args, config) {
+            Ok(()) => {}
+            Err(e) if selected.len() > 1 => {
+                eprintln!("✗ Failed to import ChatGPT conversation {}: {:#}", conversation.id, e);
+                failed += 1;
             }
+            Err(e) => return Err(e),
         }
+        options.session_done(index + 1, selected.len());
+    }
+
+    if failed > 0 {
+        return Err(color_eyre::eyre::eyre!("{} of {} ChatGPT conversations failed to import", failed, selected.len()));
+    }
+    if args.all {
+        println!(
+            "✓ Processed {} new ChatGPT conversations ({} already stored)",
+            selected.len(),
+            conversations.iter().filter(|c| known.contains(&c.id)).count()
+        );
+    } else if export_path.is_some() {
+        println!("✓ Processed {} ChatGPT conversations", selected.len());
+    }
 
-        // Process user and assistant messages
-        if entry.entry_type == "user" || entry.entry_type == "assistant" {
-            if let Some(msg) = entry.message {
-                let role = msg["role"].as_str().unwrap_or("");
+    Ok(())
+}
 
-                if role == "user" {
-                    // User messages have content as a string
-                    if let Some(content) = msg["content"].as_str() {
-                        messages.push(("user".to_string(), content.to_string()));
-                    }
-                } else if role == "assistant" {
-                    // Assistant messages have content as an array
-                    if let Some(content_array) = msg["content"].as_array() {
-                        // Extract skills from Skill tool_use blocks
-                        for block in content_array {
-                            if block.get("type").and_then(|v| v.as_str()) == Some("tool_use")
-                                && block.get("name").and_then(|v| v.as_str()) == Some("Skill")
-                            {
-                                if let Some(skill) =
-                                    block.pointer("/input/skill").and_then(|v| v.as_str())
-                                {
-                                    if !skills.contains(&skill.to_string()) {
-                                        skills.push(skill.to_string());
-                                    }
-                                }
-                            }
-                        }
-
-                        let text = content_array
-                            .iter()
-                            .filter_map(|c| {
-                                // Only include "text" type, skip "thinking"
-                                if c["type"].as_str() == Some("text") {
-                                    c["text"].as_str().map(String::from)
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect::<Vec<_>>()
-                            .join("\n");
-
-                        if !text.is_empty() {
-                            messages.push(("assistant".to_string(), text));
-                        }
-                    }
-                }
+/// Store one conversation from a ChatGPT export as its own session
+fn import_chatgpt_conversation(
+    writer: &dyn WriterBackend,
+    conversation: &chatgpt::ExportedConversation,
+    compressor: &MessageCompressor,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<()> {
+    let session_id = conversation.id.as_str();
+    session_ops::check_path_component("session ID", session_id)?;
+
+    eprintln!("Importing ChatGPT conversation: {}", session_id);
+
+    let mut pipeline = ImportPipeline::new(compressor)?.system(!args.no_system);
+    for turn in &conversation.turns {
+        let timestamp = turn.timestamp.as_deref().and_then(PlainTextWriter::normalize_timestamp);
+        pipeline.push(turn.role.clone(), turn.content.clone(), timestamp)?;
+    }
+    let Spooled { spool, stats, .. } = pipeline.finish()?;
+    report_compression(args, stats);
+    let message_count = spool.len();
+
+    if message_count == 0 {
+        eprintln!("⚠ No messages found in ChatGPT conversation: {}", session_id);
+        return Ok(());
+    }
+
+    // File the conversation under the day it was started
+    let timestamp = conversation
+        .create_time
+        .clone()
+        .or_else(|| conversation.turns.iter().find_map(|turn| turn.timestamp.clone()))
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    store_import(config, writer, args, &spool, session_id, "chatgpt", &timestamp, &[], None, "ChatGPT conversation")?;
+
+    Ok(())
+}
+
+fn import_claude_ai_session(
+    writer: &dyn WriterBackend,
+    adapter: &ClaudeAiAdapter,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<()> {
+    // --session is either a conversation UUID or an export file to import whole
+    let export_path = args.session.as_deref().map(Path::new).filter(|path| path.is_file());
+    let export_file = match export_path {
+        Some(path) => path.to_path_buf(),
+        None => adapter.export_file()?,
+    };
+    let conversations = claude_ai::read_export(&export_file)?;
+
+    let known = if args.all { known_session_ids(writer, claude_ai::ASSISTANT)? } else { Default::default() };
+    let since = args.since.as_deref().map(parse_date_filter).transpose()?.map(|f| f.start());
+    let is_new = |conversation: &claude_ai::ExportedConversation| {
+        let started = conversation
+            .created_at
+            .as_deref()
+            .and_then(|t| chrono::NaiveDate::parse_from_str(&writer.date_of(Some(t)), "%Y-%m-%d").ok());
+        !known.contains(&conversation.uuid) && since.is_none_or(|since| started.is_some_and(|started| started >= since))
+    };
+
+    let selected: Vec<&claude_ai::ExportedConversation> = match (&args.session, export_path) {
+        (None, _) if args.all => conversations.iter().rev().filter(|c| is_new(c)).collect(),
+        (Some(_), Some(_)) => conversations.iter().collect(),
+        (Some(uuid), None) => vec![conversations
+            .iter()
+            .find(|c| c.uuid == *uuid)
+            .ok_or_else(|| color_eyre::eyre::eyre!("Conversation '{}' not found in {}", uuid, export_file.display()))?],
+        (None, _) => vec![conversations
+            .first()
+            .ok_or_else(|| color_eyre::eyre::eyre!("No conversations in {}", export_file.display()))?],
+    };
+    if selected.len() > 1 {
+        eprintln!("Importing {} Claude.ai conversations from {}", selected.len(), export_file.display());
+    }
+
+    // One bad conversation in a whole export is reported and skipped
+    let compressor = import_compressor(args, config)?;
+    let options = import_options(args);
+    let mut failed = 0;
+    for (index, conversation) in selected.iter().enumerate() {
+        match import_claude_ai_conversation(writer, conversation, &compressor, args, config) {
+            Ok(()) => {}
+            Err(e) if selected.len() > 1 => {
+                eprintln!("✗ Failed to import Claude.ai conversation {}: {:#}", conversation.uuid, e);
+                failed += 1;
             }
+            Err(e) => return Err(e),
         }
+        options.session_done(index + 1, selected.len());
+    }
+
+    if failed > 0 {
+        return Err(color_eyre::eyre::eyre!("{} of {} Claude.ai conversations failed to import", failed, selected.len()));
+    }
+    if selected.len() > 1 {
+        println!("✓ Processed {} Claude.ai conversations", selected.len());
+    }
+    Ok(())
+}
+
+/// Store one conversation from a Claude.ai export as its own session
+fn import_claude_ai_conversation(
+    writer: &dyn WriterBackend,
+    conversation: &claude_ai::ExportedConversation,
+    compressor: &MessageCompressor,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<()> {
+    let session_id = conversation.uuid.as_str();
+    session_ops::check_path_component("session ID", session_id)?;
+
+    eprintln!("Importing Claude.ai conversation: {}", session_id);
+    for warning in &conversation.warnings {
+        eprintln!("⚠ Warning: {}", warning);
     }
 
-    // Compress messages to remove noise
-    let compressed = compressor.compress_batch(&messages);
-    let message_count = compressed.len();
+    let mut pipeline = ImportPipeline::new(compressor)?.system(!args.no_system);
+    for (role, content, timestamp) in &conversation.messages {
+        let timestamp = timestamp.as_deref().and_then(PlainTextWriter::normalize_timestamp);
+        pipeline.push(role.clone(), content.clone(), timestamp)?;
+    }
+    let Spooled { spool, stats, .. } = pipeline.finish()?;
+    report_compression(args, stats);
+    let message_count = spool.len();
 
     if message_count == 0 {
-        eprintln!("⚠ No messages found in Claude Code session: {}", session_id);
+        eprintln!("⚠ No messages found in Claude.ai conversation: {}", session_id);
         return Ok(());
     }
 
-    // Use captured timestamp or fallback to current time
-    let timestamp = start_time.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
-    let date = PlainTextWriter::extract_date(Some(&timestamp));
+    // File the conversation under the day it was started
+    let timestamp = conversation
+        .created_at
+        .clone()
+        .or_else(|| conversation.messages.iter().find_map(|(_, _, timestamp)| timestamp.clone()))
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    store_import(config, writer, args, &spool, session_id, claude_ai::ASSISTANT, &timestamp, &[], None, "Claude.ai conversation")?;
+
+    Ok(())
+}
+
+fn import_cursor_session(
+    writer: &dyn WriterBackend,
+    adapter: &CursorAdapter,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<()> {
+    if args.all {
+        return import_all_sessions(writer, adapter, "Cursor", args, |args| import_cursor_session(writer, adapter, args, config));
+    }
+
+    let session_path = if let Some(ref session) = args.session {
+        adapter.session_path(session)?
+    } else {
+        adapter.find_latest_session()?
+    };
+
+    eprintln!("Importing Cursor chat: {}", session_path.display());
+    preview_session(args, adapter, &session_path);
+
+    let compressor = import_compressor(args, config)?;
+    let mut pipeline = ImportPipeline::new(&compressor)?.system(!args.no_system);
+    let source = sources::read_cursor(adapter, &session_path, &mut pipeline)?;
+    let Spooled { spool, stats, .. } = pipeline.finish()?;
+    report_compression(args, stats);
+    let session_id = source.id.as_str();
+    let message_count = spool.len();
+
+    // Chat tabs carry no timestamps; JSON logs do
+    let start_time = source
+        .start_time
+        .clone()
+        .or_else(|| previous_start_time(config, writer, session_id, "cursor"))
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    if message_count == 0 {
+        eprintln!("⚠ No messages found in Cursor chat: {}", session_id);
+        return Ok(());
+    }
+
+    // Cursor keeps adding bubbles to the same tab, so usually only the tail is new
+    store_import(config, writer, args, &spool, session_id, "cursor", &start_time, &[], None, "Cursor chat")?;
+
+    Ok(())
+}
+
+/// Import a Zed, LM Studio or Open WebUI session, whose sources read as plain role/content messages
+///
+/// `label` names the source in messages (e.g. "LM Studio"); `session_path`
+/// resolves --session and `read` streams the messages into the pipeline.
+fn import_role_content_session<A: LogAdapter>(
+    writer: &dyn WriterBackend,
+    adapter: &A,
+    label: &str,
+    session_path: fn(&A, &str) -> Result<PathBuf>,
+    read: fn(&A, &Path, &mut dyn MessageSink) -> Result<sources::SourceSession>,
+    args: &ImportArgs,
+    config: &Config,
+) -> Result<()> {
+    if args.all {
+        return import_all_sessions(writer, adapter, label, args, |args| {
+            import_role_content_session(writer, adapter, label, session_path, read, args, config)
+        });
+    }
+
+    let assistant = adapter.name();
+    let session_path = match args.session {
+        Some(ref session) => session_path(adapter, session)?,
+        None => adapter.find_latest_session()?,
+    };
+
+    eprintln!("Importing {} conversation: {}", label, session_path.display());
+    preview_session(args, adapter, &session_path);
+
+    let compressor = import_compressor(args, config)?;
+    let mut pipeline = ImportPipeline::new(&compressor)?.system(!args.no_system);
+    let source = read(adapter, &session_path, &mut pipeline)?;
+    let Spooled { spool, stats, .. } = pipeline.finish()?;
+    report_compression(args, stats);
+    let session_id = source.id.as_str();
+    session_ops::check_path_component("session ID", session_id)?;
+    let message_count = spool.len();
+
+    // Messages without timestamps (all of Zed's) leave the first import's start in place
+    let start_time = source
+        .start_time
+        .clone()
+        .or_else(|| previous_start_time(config, writer, session_id, assistant))
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+    if message_count == 0 {
+        eprintln!("⚠ No messages found in {} conversation: {}", label, session_id);
+        return Ok(());
+    }
+
+    store_import(config, writer, args, &spool, session_id, assistant, &start_time, &[], None, &format!("{} conversation", label))?;
+
+    Ok(())
+}
+
+/// Pacing for imports of many sessions, from --rate-limit and --batch-size
+/// With --all, progress is also reported on stderr after each session
+fn import_options(args: &ImportArgs) -> ImportOptions {
+    let progress_callback: Option<Box<dyn Fn(usize, usize)>> = if args.all {
+        Some(Box::new(|completed, total| eprintln!("  {}/{} done", completed, total)))
+    } else {
+        None
+    };
+    ImportOptions {
+        rate_limit: args.rate_limit.map(std::time::Duration::from_millis),
+        batch_size: args.batch_size,
+        progress_callback,
+    }
+}
+
+/// Noise filter from the config, truncating messages to --max-message-bytes
+fn import_compressor(args: &ImportArgs, config: &Config) -> Result<MessageCompressor> {
+    let mut noise_filter = config.noise_filter.clone();
+    if let Some(level) = args.noise {
+        noise_filter.level = level;
+    }
+    Ok(MessageCompressor::from_config(&noise_filter)?
+        .with_max_message_bytes(args.max_message_bytes)
+        .with_dedupe_adjacent(args.dedupe))
+}
+
+/// Loop detector from the config (or --loop-config), with any --loop-* flags applied on top
+fn loop_detector(args: &ImportArgs, config: &Config) -> Result<LoopDetector> {
+    let mut builder = match args.loop_config {
+        Some(ref path) => LoopDetectorBuilder::load(path)?,
+        None => config.loop_detector.clone(),
+    };
+
+    if let Some(count) = args.loop_warn {
+        builder.max_messages_warning(count);
+    }
+    if let Some(count) = args.loop_critical {
+        builder.max_messages_critical(count);
+    }
+    if let Some(count) = args.loop_min_repetitions {
+        builder.min_repetitions(count);
+    }
+    if let Some(size) = args.loop_max_pattern {
+        builder.max_pattern_size(size);
+    }
+
+    Ok(builder.build())
+}
+
+/// Attach the tags given with --tag to a freshly imported session
+fn apply_tags(writer: &dyn WriterBackend, session_id: &str, assistant: &str, date: &str, tags: &[String]) -> Result<()> {
+    for tag in tags {
+        writer.add_tag(session_id, assistant, date, tag)?;
+    }
+    Ok(())
+}
+
+/// Start time recorded by an earlier import of this session, so a re-import
+/// lands in the same date directory instead of duplicating it under today
+fn previous_start_time(config: &Config, writer: &dyn WriterBackend, session_id: &str, assistant: &str) -> Option<String> {
+    restore_archived(config, writer, session_id, Some(assistant));
+    let location = store::find_session(writer.base_dir(), session_id, Some(assistant)).ok()?;
+    let content = std::fs::read_to_string(location.path.join("session.json")).ok()?;
+    let metadata: serde_json::Value = serde_json::from_str(&content).ok()?;
+    metadata["start_time"].as_str().map(str::to_string)
+}
+
+/// Store a spooled import: its messages, then the session metadata, search index entry and tags
+///
+/// `source` names what was imported in the summary (e.g. "Codex session").
+/// Returns false if the session was skipped as a duplicate of a stored one.
+#[allow(clippy::too_many_arguments)]
+fn store_import(
+    config: &Config,
+    writer: &dyn WriterBackend,
+    args: &ImportArgs,
+    spool: &ImportSpool,
+    session_id: &str,
+    assistant: &str,
+    start_time: &str,
+    skills: &[String],
+    project: Option<String>,
+    source: &str,
+) -> Result<bool> {
+    let date = writer.session_date(session_id, assistant, Some(start_time));
+
+    // Only the new tail is written if this session was imported before. Done
+    // before write_session so a duplicate of another stored session leaves nothing behind
+    let Some(outcome) = import_or_skip(config, writer, session_id, assistant, &date, spool, Some(start_time), args.force)? else {
+        return Ok(false);
+    };
 
-    // Write session
-    writer.write_session(
+    let session_dir = writer.write_session(
         session_id,
-        "claude-code",
-        Some(&timestamp),
+        assistant,
+        Some(start_time),
         None,
         "closed",
-        message_count,
-        &skills,
+        spool.len(),
+        skills,
+        None,
+        None,
     )?;
+    if let Some(project) = project {
+        writer.update_session_metadata(session_id, assistant, &date, serde_json::json!({ "project": project }))?;
+    }
+
+    update_search_index(config, writer, session_id, assistant, &date, spool);
+    apply_tags(writer, session_id, assistant, &date, &args.tags)?;
+
+    println!("✓ {} from {}: {}", describe_import(writer, outcome), source, session_id);
+    println!("  Location: {}", session_dir.display());
+
+    Ok(true)
+}
+
+/// Import a session's messages, turning a duplicate-content refusal into a notice
+/// Returns None if the session was skipped
+#[allow(clippy::too_many_arguments)]
+fn import_or_skip(
+    config: &Config,
+    writer: &dyn WriterBackend,
+    session_id: &str,
+    assistant: &str,
+    date: &str,
+    spool: &ImportSpool,
+    timestamp: Option<&str>,
+    force: bool,
+) -> Result<Option<ImportOutcome>> {
+    // An archived session is restored first so the import appends to it
+    restore_archived(config, writer, session_id, Some(assistant));
+
+    match writer.import_spool(session_id, assistant, date, spool, timestamp, force) {
+        Ok(outcome) => Ok(Some(outcome)),
+        Err(e) => match e.downcast_ref::<DuplicateSession>() {
+            Some(duplicate) => {
+                println!(
+                    "⏭ Skipped {}: same content is already stored as {} session {} (use --force to import anyway)",
+                    session_id, duplicate.assistant, duplicate.existing_id
+                );
+                Ok(None)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Bring back a session that `continuum archive` moved out of the log directory
+/// Restored sessions are re-indexed, since archiving dropped them from the search index
+fn restore_archived(config: &Config, writer: &dyn WriterBackend, session_id: &str, assistant: Option<&str>) {
+    let manifest = match ArchiveManifest::load(&writer.archive_dir()) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("⚠ Warning: Failed to read archive manifest: {}", e);
+            return;
+        }
+    };
 
-    // Write messages
-    for (idx, (role, content)) in compressed.iter().enumerate() {
-        writer.append_message(
-            session_id,
-            "claude-code",
-            &date,
-            idx + 1,
-            role,
-            content,
-            Some(&timestamp),
-        )?;
+    let archived = manifest
+        .sessions
+        .iter()
+        .filter(|s| s.id == session_id && assistant.is_none_or(|a| a.eq_ignore_ascii_case(&s.assistant)));
+
+    for entry in archived {
+        match writer.restore_if_archived(&entry.id, &entry.assistant) {
+            Ok(Some(session_dir)) => {
+                eprintln!("↺ Restored {} session {} from {}", entry.assistant, entry.id, entry.archive);
+                if let (true, Ok(messages)) = (config.search_index, store::load_messages(&session_dir)) {
+                    let messages: Vec<(String, String)> = messages.into_iter().map(|m| (m.role, m.content)).collect();
+                    if let Err(e) = writer.index_session(&entry.id, &entry.assistant, &entry.date, &messages) {
+                        eprintln!("⚠ Warning: Failed to update search index: {}", e);
+                    }
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("⚠ Warning: Failed to restore {} from {}: {}", entry.id, entry.archive, e),
+        }
     }
+}
 
-    println!("✓ Imported {} messages from Claude Code session: {}", message_count, session_id);
-    println!("  Location: {}", writer.base_dir().join("claude-code").join(&date).join(session_id).display());
+/// Summarize the native session before anything is written, unless --quiet
+/// A source whose metadata can't be read is still imported; the reader reports the real error.
+fn preview_session(args: &ImportArgs, adapter: &dyn LogAdapter, path: &Path) {
+    if args.quiet {
+        return;
+    }
+    let Ok(metadata) = adapter.session_metadata(path) else {
+        return;
+    };
+    eprintln!(
+        "  {} entries, {} → {}, model {}",
+        metadata.message_count,
+        metadata.start_time.as_deref().unwrap_or("?"),
+        metadata.end_time.as_deref().unwrap_or("?"),
+        metadata.model.as_deref().unwrap_or("unknown"),
+    );
+}
 
-    Ok(())
+/// Print how many tokens and messages the noise filter saved, unless --quiet
+fn report_compression(args: &ImportArgs, stats: CompressionStats) {
+    if !args.quiet {
+        println!("{}", stats);
+    }
 }
 
-fn handle_stats() -> Result<()> {
-    println!("\n📊 Continuum Statistics\n");
-    println!("To view detailed statistics, use the Nushell function:");
-    println!("  continuum-stats\n");
-    println!("To search conversations:");
-    println!("  continuum-search \"your query\"\n");
-    println!("To view timeline:");
-    println!("  continuum-timeline 2025-11-09\n");
-    println!("📍 Log location: ~/Assistants/continuum-logs/\n");
-    Ok(())
+fn describe_import(writer: &dyn WriterBackend, outcome: ImportOutcome) -> String {
+    match (outcome, writer.is_dry_run()) {
+        (ImportOutcome::Unchanged, _) => "No new messages".to_string(),
+        (ImportOutcome::Appended(n), false) => format!("Appended {} new messages", n),
+        (ImportOutcome::Appended(n), true) => format!("Would append {} new messages", n),
+        (ImportOutcome::Rewritten(n), false) => format!("Imported {} messages", n),
+        (ImportOutcome::Rewritten(n), true) => format!("Would import {} messages", n),
+    }
+}
+
+/// Keep the search index in step with a freshly written session
+/// Index failures are reported but never fail the import itself
+fn update_search_index(
+    config: &Config,
+    writer: &dyn WriterBackend,
+    session_id: &str,
+    assistant: &str,
+    date: &str,
+    spool: &ImportSpool,
+) {
+    if !config.search_index {
+        return;
+    }
+
+    let result = writer.index_spool(session_id, assistant, date, spool);
+
+    if let Err(e) = result {
+        eprintln!("⚠ Warning: Failed to update search index: {}", e);
+    }
+}
+
+fn handle_search(args: &SearchArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    if args.rebuild {
+        let count = SearchIndex::open_at(writer.base_dir())?.rebuild_from_logs()?;
+        eprintln!("✓ Indexed {} sessions", count);
+    }
+
+    let query = args.query.join(" ");
+    if query.trim().is_empty() {
+        return Ok(());
+    }
+
+    if let Some(ref session) = args.session {
+        restore_archived(config, &writer, session, args.assistant.as_deref());
+    }
+
+    let (since, until) = date_bounds(args.date.as_deref(), args.since.as_deref(), args.until.as_deref())?;
+    let filter = ScanFilter {
+        assistant: args.assistant.clone(),
+        role: args.role.clone(),
+        session_id: args.session.clone(),
+        since,
+        until,
+    };
+
+    let hits: Vec<ContextualHit> = if args.index {
+        SearchIndex::open_at(writer.base_dir())?
+            .search_with_context(&query, args.context)?
+            .into_iter()
+            .filter(|contextual| filter.allows(&contextual.hit))
+            .collect()
+    } else {
+        let matcher = QueryMatcher::new(&query, args.regex, args.case_sensitive)?;
+        search::with_context(writer.base_dir(), search::scan_logs(writer.base_dir(), &matcher, &filter)?, args.context)?
+    };
+
+    if args.context > 0 {
+        print_with_context(&hits);
+    } else {
+        for ContextualHit { hit, .. } in &hits {
+            println!("── {}/{}/{} [{}] {}", hit.assistant, hit.date, hit.session_id, hit.message_id, hit.role);
+            println!("{}\n", hit.snippet);
+        }
+    }
+
+    eprintln!("{} matches", hits.len());
+
+    // Non-zero exit when nothing matched, so scripts can branch on it
+    if hits.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Print hits grep -C style, in session order
+///
+/// Each session gets one `──` header. Overlapping or adjacent context windows
+/// are merged, and `---` separates windows with a gap between them. Matching
+/// messages are marked with `>`.
+fn print_with_context(hits: &[ContextualHit]) {
+    let mut hits: Vec<&ContextualHit> = hits.iter().collect();
+    let location = |hit: &SearchHit| (hit.assistant.clone(), hit.date.clone(), hit.session_id.clone());
+    hits.sort_by_key(|contextual| (location(&contextual.hit), contextual.hit.message_id));
+    let matched: std::collections::HashSet<_> = hits.iter().map(|contextual| (location(&contextual.hit), contextual.hit.message_id)).collect();
+
+    // Session of the previous window, and the last message id printed from it
+    let mut previous: Option<((String, String, String), usize)> = None;
+
+    for contextual in hits {
+        let hit = &contextual.hit;
+        let session = location(hit);
+        let first_id = contextual.context_before.first().map_or(hit.message_id, |m| m.id);
+
+        let mut last_printed = match previous {
+            Some((ref prev_session, last_printed)) if *prev_session == session => {
+                if first_id > last_printed + 1 {
+                    println!("---");
+                }
+                last_printed
+            }
+            ref other => {
+                if other.is_some() {
+                    println!("---");
+                }
+                println!("── {}/{}/{}", hit.assistant, hit.date, hit.session_id);
+                0
+            }
+        };
+
+        let flatten = |m: &StoredMessage| (m.id, m.role.clone(), truncate_id(&m.content.split_whitespace().collect::<Vec<_>>().join(" "), 120));
+        let window = contextual
+            .context_before
+            .iter()
+            .map(flatten)
+            .chain(std::iter::once((hit.message_id, hit.role.clone(), hit.snippet.clone())))
+            .chain(contextual.context_after.iter().map(flatten));
+
+        for (id, role, text) in window {
+            if id <= last_printed {
+                continue;
+            }
+            let marker = if matched.contains(&(session.clone(), id)) { ">" } else { " " };
+            println!("{} [{}] {}: {}", marker, id, role, text);
+            last_printed = id;
+        }
+
+        previous = Some((session, last_printed));
+    }
+    println!();
+}
+
+fn handle_stats(args: &StatsArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let json = args.json || args.format == StatsFormat::Json;
+    let (since, until) = date_bounds(None, args.since.as_deref(), args.until.as_deref())?;
+    let filter = args.filters.apply(SessionFilter { since, until, ..Default::default() })?;
+
+    let skipped = if let Some(ref assistant) = args.assistant {
+        print_session_stats(&writer, &filter.assistant(assistant.to_lowercase()), json)?
+    } else {
+        let summary = writer.summary_matching(&filter)?;
+        if json {
+            let totals: std::collections::BTreeMap<&str, AssistantSummary> = summary
+                .assistants
+                .iter()
+                .map(|(assistant, stats)| (assistant.as_str(), AssistantSummary::from(stats)))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&totals)?);
+        } else {
+            print_summary(&summary);
+            print_model_usage(&summary);
+        }
+        summary.skipped_sessions
+    };
+
+    if skipped > 0 {
+        eprintln!("\n⚠ Skipped {} sessions with a missing or corrupt session.json", skipped);
+    }
+    if !json {
+        eprintln!("\n📍 Log location: {}", writer.base_dir().display());
+    }
+    Ok(())
+}
+
+/// Per-assistant and per-day tables for the whole store
+fn print_summary(summary: &StatsSummary) {
+    println!(
+        "{:<14} {:>8} {:>8} {:>10} {:>9} {:>9} {:>9}  {:<25} LAST",
+        "ASSISTANT", "SESSIONS", "MESSAGES", "CHARS", "~TOKENS", "TOKENS", "COST", "FIRST"
+    );
+    for stats in summary.assistants.values() {
+        println!(
+            "{:<14} {:>8} {:>8} {:>10} {:>9} {:>9} {:>9}  {:<25} {}",
+            stats.assistant,
+            stats.session_count,
+            stats.message_count,
+            stats.total_chars,
+            stats.approx_tokens,
+            stats.tokens,
+            format!("${:.2}", stats.total_cost_usd),
+            stats.first_timestamp.as_deref().unwrap_or("-"),
+            stats.last_timestamp.as_deref().unwrap_or("-"),
+        );
+    }
+
+    println!("\n{:<10} {:>8} {:>8} {:>9}", "DATE", "SESSIONS", "MESSAGES", "COST");
+    for (date, day) in &summary.days {
+        println!(
+            "{:<10} {:>8} {:>8} {:>9}",
+            date,
+            day.sessions,
+            day.messages,
+            format!("${:.2}", day.total_cost_usd),
+        );
+    }
+
+    // Only assistants that report cost (e.g. Claude Code) show up here
+    if !summary.monthly_costs.is_empty() {
+        println!("\n{:<8} {:<14} {:>9}", "MONTH", "ASSISTANT", "COST");
+        for (month, by_assistant) in &summary.monthly_costs {
+            for (assistant, cost) in by_assistant {
+                println!("{:<8} {:<14} {:>9}", month, assistant, format!("${:.2}", cost));
+            }
+        }
+    }
+}
+
+/// Messages per model for each assistant that records models, busiest model first
+fn print_model_usage(summary: &StatsSummary) {
+    let mut header = false;
+    for (assistant, stats) in &summary.assistants {
+        for (model, count) in models_by_usage(&stats.models) {
+            if !header {
+                println!("\n{:<14} {:<32} {:>8}", "ASSISTANT", "MODEL", "MESSAGES");
+                header = true;
+            }
+            println!("{:<14} {:<32} {:>8}", assistant, model, count);
+        }
+    }
+}
+
+/// Model usage sorted by message count, then name
+fn models_by_usage(usage: &std::collections::BTreeMap<String, usize>) -> Vec<(&str, usize)> {
+    let mut models: Vec<(&str, usize)> = usage.iter().map(|(model, count)| (model.as_str(), *count)).collect();
+    models.sort_by(|(a_model, a_count), (b_model, b_count)| b_count.cmp(a_count).then_with(|| a_model.cmp(b_model)));
+    models
+}
+
+/// Per-session table (or JSON array) for the assistant `filter` names; returns the number of skipped sessions
+fn print_session_stats(writer: &PlainTextWriter, filter: &SessionFilter, json: bool) -> Result<usize> {
+    let assistant = filter.assistant.as_deref().unwrap_or_default();
+    let mut totals = AssistantStats { assistant: assistant.to_string(), ..Default::default() };
+    let mut sessions = Vec::new();
+
+    for stats in writer.stats_matching(filter)? {
+        match stats {
+            Ok(stats) => {
+                totals.add(&stats);
+                sessions.push(stats);
+            }
+            Err(_) => totals.skipped_sessions += 1,
+        }
+    }
+
+    let models = models_by_usage(&totals.models);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "sessions": sessions, "totals": totals, "models": totals.models }))?);
+        return Ok(totals.skipped_sessions);
+    }
+
+    println!(
+        "{:<38} {:<10} {:<8} {:>8} {:>10} {:>9} {:>9} {:>9}  {:<25} LAST",
+        "SESSION", "DATE", "STATUS", "MESSAGES", "CHARS", "~TOKENS", "TOKENS", "COST", "FIRST"
+    );
+    for stats in &sessions {
+        println!(
+            "{:<38} {:<10} {:<8} {:>8} {:>10} {:>9} {:>9} {:>9}  {:<25} {}",
+            stats.session_id,
+            stats.date,
+            stats.status.as_deref().unwrap_or("-"),
+            stats.message_count,
+            stats.total_chars,
+            stats.approx_tokens,
+            stats.tokens,
+            stats.total_cost_usd.map(|c| format!("${:.2}", c)).unwrap_or_else(|| "-".to_string()),
+            stats.first_timestamp.as_deref().unwrap_or("-"),
+            stats.last_timestamp.as_deref().unwrap_or("-"),
+        );
+    }
+    println!(
+        "{:<38} {:<10} {:<8} {:>8} {:>10} {:>9} {:>9} {:>9}",
+        format!("TOTAL ({} sessions)", totals.session_count),
+        "",
+        "",
+        totals.message_count,
+        totals.total_chars,
+        totals.approx_tokens,
+        totals.tokens,
+        format!("${:.2}", totals.total_cost_usd),
+    );
+
+    if !models.is_empty() {
+        println!("\n{:<32} {:>8}", "MODEL", "MESSAGES");
+        for (model, count) in models {
+            println!("{:<32} {:>8}", model, count);
+        }
+    }
+
+    Ok(totals.skipped_sessions)
+}
+
+fn handle_tail(args: &TailArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let session_dir = store::find_latest_session(writer.base_dir(), &args.assistant.to_lowercase())?.path;
+    let messages = store::load_messages(&session_dir)?;
+
+    eprintln!("📜 {}\n", session_dir.display());
+    for message in store::tail_messages(&messages, args.lines) {
+        print_message(message);
+    }
+
+    Ok(())
+}
+
+fn handle_timeline(args: &TimelineArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let Some(ref date) = args.date else {
+        return print_heatmap(args, &writer);
+    };
+    let date = match parse_date_filter(date)? {
+        DateFilter::Day(date) => date,
+        DateFilter::Range(_) => color_eyre::eyre::bail!("'{}' spans several days; timeline shows a single day", date),
+    };
+    let entries = continuum_core::timeline::day_timeline(writer.base_dir(), date, args.assistant.as_deref())?;
+
+    if entries.is_empty() {
+        println!("No messages on {}", date);
+        return Ok(());
+    }
+
+    for entry in &entries {
+        let time = entry
+            .time
+            .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+            .unwrap_or_else(|| "--:--".to_string());
+        let source = format!("{}/{}", entry.assistant, entry.session_id);
+        if args.full {
+            println!("{} {} {}:", time, source, entry.message.role);
+            println!("{}\n", entry.message.content);
+        } else {
+            println!("{} {} {}: {}", time, source, entry.message.role, entry.snippet(100));
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_browse(args: &BrowseArgs, config: &Config) -> Result<()> {
+    let reader = if let Some(ref output) = args.output {
+        PlainTextReader::with_base_dir(output.clone())
+    } else {
+        PlainTextReader::from_config(config)
+    };
+
+    if !std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+        color_eyre::eyre::bail!("continuum browse needs a terminal");
+    }
+    Browser::new(reader, args.assistant.as_deref())?.run()
+}
+
+/// Daily message and session counts for the last `--days` days
+fn print_heatmap(args: &TimelineArgs, writer: &PlainTextWriter) -> Result<()> {
+    let to = chrono::Local::now().date_naive();
+    let from = to
+        .checked_sub_days(chrono::Days::new(u64::from(args.days.saturating_sub(1))))
+        .ok_or_else(|| color_eyre::eyre::eyre!("--days {} reaches further back than any supported date", args.days))?;
+    let days = heatmap::daily_activity(writer.base_dir(), from, to, args.assistant.as_deref())?;
+
+    match args.format {
+        HeatmapFormat::Ascii => print!("{}", heatmap::render_ascii(&days)),
+        HeatmapFormat::Json => println!("{}", heatmap::render_json(&days)?),
+    }
+    Ok(())
+}
+
+fn handle_export(args: &ExportArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref log_dir) = args.log_dir {
+        PlainTextWriter::with_base_dir(log_dir.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    if !args.latest && !args.filters.is_empty() {
+        color_eyre::eyre::bail!("Filter flags select a session only with --latest");
+    }
+
+    let assistant = args.assistant.as_deref().map(str::to_lowercase);
+    let session = match (args.session.as_ref().or(args.session_flag.as_ref()), assistant) {
+        (Some(session_id), assistant) => store::find_session(writer.base_dir(), session_id, assistant.as_deref())?,
+        // clap guarantees --latest comes with --assistant
+        (None, Some(assistant)) if !args.filters.is_empty() => {
+            let filter = args.filters.apply(SessionFilter::new().assistant(assistant.as_str()).limit(1))?;
+            let record = writer
+                .query(&filter)?
+                .pop()
+                .ok_or_else(|| color_eyre::eyre::eyre!("No stored {} sessions match the filters", assistant))?;
+            store::find_session(writer.base_dir(), &record.id, Some(&assistant))?
+        }
+        (None, Some(assistant)) => store::find_latest_session(writer.base_dir(), &assistant)?,
+        (None, None) => unreachable!("--latest requires --assistant"),
+    };
+
+    // Read straight from messages.jsonl so export works without a search index
+    let messages = store::load_messages(&session.path)?;
+    let rendered = args.format.writer().render(&session, &messages)?;
+
+    match args.output {
+        Some(ref path) => {
+            std::fs::write(path, rendered)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("✓ Exported {} messages to {}", messages.len(), path.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+fn handle_merge(args: &MergeArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    if args.sessions.len() < 2 {
+        color_eyre::eyre::bail!("Give at least two --session IDs to merge");
+    }
+    let assistant = args.assistant.as_deref().map(str::to_lowercase);
+    let sources = args
+        .sessions
+        .iter()
+        .map(|id| store::find_session(writer.base_dir(), id, assistant.as_deref()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let compressor = MessageCompressor::from_config(&config.noise_filter)?;
+    let merged = session_ops::merge_all(&sources, &args.output_id, &compressor, &writer)?;
+
+    let messages: Vec<(String, String)> = store::load_messages(&merged.path)?
+        .into_iter()
+        .map(|m| (m.role, m.content))
+        .collect();
+    if config.search_index {
+        if let Err(e) = writer.index_session(&merged.session_id, &merged.assistant, &merged.date, &messages) {
+            eprintln!("⚠ Warning: Failed to update search index: {}", e);
+        }
+    }
+
+    let ids: Vec<&str> = sources.iter().map(|s| s.session_id.as_str()).collect();
+    println!("✓ Merged {} into {} ({} messages)", ids.join(", "), merged.session_id, messages.len());
+    println!("  Location: {}", merged.path.display());
+
+    if args.remove_sources {
+        for original in &sources {
+            let deleted = writer.delete_session_confirmed(&original.session_id, &original.assistant, &original.date)?;
+            println!("  Deleted {}", deleted.path.display());
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_archive(args: &ArchiveArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let assistant = args.assistant.to_lowercase();
+    let before = match args.before.as_deref() {
+        Some(before) => parse_date_filter(before)?.start(),
+        None => retention_cutoff(args.older_than.unwrap_or_else(|| config.retention_days()))?,
+    };
+
+    let filter = args.filters.apply(SessionFilter::new())?;
+    if archive_before(config, &writer, &assistant, before, &filter)? == 0 {
+        println!("No {} sessions dated before {}", assistant, before);
+    }
+
+    Ok(())
+}
+
+/// First day that is kept when sessions older than `days` are archived
+fn retention_cutoff(days: u32) -> Result<chrono::NaiveDate> {
+    chrono::Utc::now()
+        .date_naive()
+        .checked_sub_days(chrono::Days::new(u64::from(days)))
+        .ok_or_else(|| color_eyre::eyre::eyre!("An age of {} days reaches further back than any supported date", days))
+}
+
+/// Archive an assistant's sessions dated before `before` that `filter` selects, and drop them from the search index
+/// Returns how many sessions were archived
+fn archive_before(config: &Config, writer: &PlainTextWriter, assistant: &str, before: chrono::NaiveDate, filter: &SessionFilter) -> Result<usize> {
+    let archived = writer.archive_sessions_matching(assistant, before, filter)?;
+    let Some(first) = archived.first() else {
+        return Ok(0);
+    };
+
+    if config.search_index {
+        for session in &archived {
+            let removed = SearchIndex::open_at(writer.base_dir())
+                .and_then(|index| index.remove_session(&session.id, &session.assistant));
+            if let Err(e) = removed {
+                eprintln!("⚠ Warning: Failed to update search index: {}", e);
+            }
+        }
+    }
+
+    println!("✓ Archived {} {} sessions dated before {}", archived.len(), assistant, before);
+    println!("  Archive: {}", writer.archive_dir().join(&first.archive).display());
+
+    Ok(archived.len())
+}
+
+/// First and last day a `--date` (or `--since`/`--until`) selects, each resolved through [`parse_date_filter`]
+fn date_bounds(date: Option<&str>, since: Option<&str>, until: Option<&str>) -> Result<(Option<chrono::NaiveDate>, Option<chrono::NaiveDate>)> {
+    Ok(match date {
+        Some(date) => {
+            let filter = parse_date_filter(date)?;
+            (Some(filter.start()), Some(filter.end()))
+        }
+        None => (
+            since.map(parse_date_filter).transpose()?.map(|f| f.start()),
+            until.map(parse_date_filter).transpose()?.map(|f| f.end()),
+        ),
+    })
+}
+
+fn handle_list(args: &ListArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let (since, until) = date_bounds(args.date.as_deref(), args.since.as_deref(), args.until.as_deref())?;
+
+    let filter = args.filters.apply(SessionFilter {
+        assistant: args.assistant.clone(),
+        since,
+        until,
+        sort: args.sort.into(),
+        offset: args.offset,
+        limit: Some(args.limit),
+        ..Default::default()
+    })?;
+    let records = writer.query(&filter)?;
+
+    let format = if args.json { ListFormat::Json } else { args.format };
+    let status = |record: &SessionRecord| format!("{:?}", record.status).to_lowercase();
+    let cost = |record: &SessionRecord| record.cost_usd.map(|c| format!("${:.2}", c));
+
+    match format {
+        ListFormat::Json => println!("{}", serde_json::to_string_pretty(&records)?),
+        ListFormat::Tsv => {
+            println!("session_id\tassistant\tdate\tmessages\tstatus\tcost_usd\ttags\tnote");
+            for record in &records {
+                println!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                    record.id,
+                    record.assistant,
+                    record.date.as_deref().unwrap_or(""),
+                    record.message_count.unwrap_or(0),
+                    status(record),
+                    record.cost_usd.map(|c| c.to_string()).unwrap_or_default(),
+                    record.tags.join(","),
+                    record.note.as_deref().unwrap_or(""),
+                );
+            }
+        }
+        ListFormat::Table => {
+            println!(
+                "{:<14} {:<12} {:<10} {:>8}  {:<9} {:>8}  {:<40}  TAGS",
+                "SESSION", "ASSISTANT", "DATE", "MESSAGES", "STATUS", "COST", "NOTE"
+            );
+            for record in &records {
+                println!(
+                    "{:<14} {:<12} {:<10} {:>8}  {:<9} {:>8}  {:<40}  {}",
+                    truncate_id(&record.id, 14),
+                    record.assistant,
+                    record.date.as_deref().unwrap_or("-"),
+                    record.message_count.unwrap_or(0),
+                    status(record),
+                    cost(record).unwrap_or_else(|| "-".to_string()),
+                    record.note.as_deref().map_or_else(|| "-".to_string(), |note| truncate_id(note, 40)),
+                    record.tags.join(", "),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Shorten a session ID (or note) for table output, marking the cut with an ellipsis
+fn truncate_id(id: &str, width: usize) -> String {
+    if id.chars().count() <= width {
+        return id.to_string();
+    }
+    let kept: String = id.chars().take(width - 1).collect();
+    format!("{}…", kept)
+}
+
+fn handle_diff(args: &DiffArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    }
+    .with_redactor((args.redact || config.redact).then(|| config.redactor.clone()));
+
+    let assistant = args.assistant.to_lowercase();
+    restore_archived(config, &writer, &args.session, Some(&assistant));
+    let location = store::find_session(writer.base_dir(), &args.session, Some(&assistant))?;
+    let stored: Vec<(String, String)> = store::load_messages(&location.path)?
+        .into_iter()
+        .map(|m| (m.role, m.content))
+        .collect();
+
+    let tools = args.include_tools.then(|| ToolCapture::new(args.max_tool_bytes));
+    let mut messages: Vec<(String, String)> = Vec::new();
+    sources::read_stored_source(
+        config,
+        &assistant,
+        &args.session,
+        args.source.as_deref(),
+        args.profile.clone(),
+        tools.as_ref(),
+        args.include_thinking,
+        &mut messages,
+    )?;
+
+    if args.no_system {
+        messages.retain(|(role, _)| role != "system");
+    }
+
+    // As on import, each message is filtered before Codex's split turns are joined
+    let mut noise_filter = config.noise_filter.clone();
+    if let Some(level) = args.noise {
+        noise_filter.level = level;
+    }
+    let compressor = MessageCompressor::from_config(&noise_filter)?.with_max_message_bytes(args.max_message_bytes);
+    let mut current = compressor.compress_batch(&messages);
+    if assistant == "codex" && !args.no_coalesce {
+        current = coalesce_consecutive(&current, args.coalesce_max_chars);
+    }
+    // Stored messages had their secrets masked as they were written
+    if let Some(redactor) = writer.redactor() {
+        for (_, content) in &mut current {
+            *content = redactor.redact(content).0;
+        }
+    }
+    let diff = session_ops::diff_sessions(&stored, &current);
+
+    println!(
+        "{} session {}: {} added, {} removed, {} unchanged",
+        assistant,
+        args.session,
+        diff.added.len(),
+        diff.removed.len(),
+        diff.unchanged_count
+    );
+
+    for (role, content) in &diff.removed {
+        println!("\n- [{}]\n{}", role, content);
+    }
+    for (role, content) in &diff.added {
+        println!("\n+ [{}]\n{}", role, content);
+    }
+
+    if args.verbose {
+        let dropped: Vec<_> = messages
+            .iter()
+            .filter_map(|(role, content)| compressor.drop_reason(content).map(|rule| (role, content, rule)))
+            .collect();
+
+        println!("\nFiltered out {} of {} source messages", dropped.len(), messages.len());
+        for (role, content, rule) in dropped {
+            println!("\n~ [{}] {}\n{}", role, rule, content);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_delete(args: &DeleteArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let assistant = args.assistant.as_deref().map(str::to_lowercase);
+    if let Some(ref assistant) = assistant {
+        session_ops::check_path_component("assistant", assistant)?;
+    }
+
+    let all_before = args.all_before.as_deref().map(parse_date_filter).transpose()?.map(|f| f.start());
+    let targets: Vec<store::SessionLocation> = match (&args.session, all_before) {
+        (Some(session_id), _) => {
+            session_ops::check_path_component("session ID", session_id)?;
+            let (since, until) = date_bounds(args.date.as_deref(), None, None)?;
+            let dates = SessionFilter { since, until, ..Default::default() };
+            let matches: Vec<_> = store::list_sessions(writer.base_dir())?
+                .into_iter()
+                .filter(|s| s.session_id == *session_id)
+                .filter(|s| assistant.as_deref().is_none_or(|a| a == s.assistant))
+                .filter(|s| dates.in_range(&s.date))
+                .collect();
+            if matches.len() > 1 {
+                let found: Vec<String> = matches.iter().map(|s| format!("{}/{}", s.assistant, s.date)).collect();
+                color_eyre::eyre::bail!(
+                    "Session '{}' is stored more than once ({}); narrow it down with --assistant and --date",
+                    session_id,
+                    found.join(", ")
+                );
+            }
+            if matches.is_empty() {
+                color_eyre::eyre::bail!("No stored session '{}' in {}", session_id, writer.base_dir().display());
+            }
+            matches
+        }
+        (None, Some(before)) => store::list_sessions(writer.base_dir())?
+            .into_iter()
+            .filter(|s| assistant.as_deref().is_none_or(|a| a == s.assistant))
+            .filter(|s| chrono::NaiveDate::parse_from_str(&s.date, "%Y-%m-%d").is_ok_and(|date| date < before))
+            .collect(),
+        (None, None) => unreachable!("clap requires --session or --all-before"),
+    };
+
+    if targets.is_empty() {
+        println!("No sessions to delete");
+        return Ok(());
+    }
+
+    // Refuses bad names and directories outside the log tree before anything is asked
+    for target in &targets {
+        let Err(e) = writer.delete_session(&target.session_id, &target.assistant, &target.date) else {
+            continue;
+        };
+        if e.downcast_ref::<DeleteError>() != Some(&DeleteError::ConfirmationRequired) {
+            return Err(e);
+        }
+    }
+
+    if !args.yes {
+        let question = match targets.as_slice() {
+            [target] => {
+                let record = SessionRecord::from_dir(&target.path).ok();
+                eprintln!(
+                    "  {}/{}/{}: {} messages{}",
+                    target.assistant,
+                    target.date,
+                    target.session_id,
+                    record.as_ref().and_then(|r| r.message_count).map_or("?".to_string(), |n| n.to_string()),
+                    record.and_then(|r| r.start_time).map(|t| format!(", started {}", t)).unwrap_or_default(),
+                );
+                format!("Delete session {}?", target.session_id)
+            }
+            _ => {
+                for target in &targets {
+                    eprintln!("  {}/{}/{}", target.assistant, target.date, target.session_id);
+                }
+                format!("Delete {} sessions?", targets.len())
+            }
+        };
+        if !confirm(&question)? {
+            eprintln!("Aborted");
+            return Ok(());
+        }
+    }
+
+    let mut reclaimed = 0;
+    for target in &targets {
+        let deleted = writer.delete_session_confirmed(&target.session_id, &target.assistant, &target.date)?;
+        reclaimed += deleted.size;
+        println!("  Deleted {} ({} messages)", deleted.path.display(), deleted.message_count);
+    }
+
+    println!("✓ Deleted {} sessions, reclaimed {} bytes", targets.len(), reclaimed);
+    Ok(())
+}
+
+fn handle_dedupe(args: &DedupeArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let groups = writer.duplicate_sessions(args.assistant.as_deref().map(str::to_lowercase).as_deref())?;
+    if groups.is_empty() {
+        println!("✓ No duplicate sessions");
+        return Ok(());
+    }
+
+    let describe = |record: &SessionRecord| format!("{}/{}/{}", record.assistant, record.date.as_deref().unwrap_or("?"), record.id);
+    for group in &groups {
+        println!("  keep    {}", describe(&group[0]));
+        for copy in &group[1..] {
+            println!("  copy    {}", describe(copy));
+        }
+    }
+    let copies: Vec<&SessionRecord> = groups.iter().flat_map(|group| &group[1..]).collect();
+    println!("{} duplicate sessions in {} groups", copies.len(), groups.len());
+
+    if !args.remove {
+        println!("Run with --remove to delete the copies");
+        return Ok(());
+    }
+    if !args.yes && !confirm(&format!("Delete {} duplicate sessions?", copies.len()))? {
+        eprintln!("Aborted");
+        return Ok(());
+    }
+
+    let mut deleted_count = 0;
+    let mut reclaimed = 0;
+    for copy in copies.iter() {
+        let Some(date) = copy.date.as_deref() else {
+            eprintln!("⚠ Warning: Skipping {}: no date directory", describe(copy));
+            continue;
+        };
+        let deleted = writer.delete_session_confirmed(&copy.id, &copy.assistant, date)?;
+        deleted_count += 1;
+        reclaimed += deleted.size;
+        println!("  Deleted {}", deleted.path.display());
+    }
+    println!("✓ Deleted {} sessions, reclaimed {} bytes", deleted_count, reclaimed);
+    Ok(())
+}
+
+fn handle_validate(args: &ValidateArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let assistant = args.assistant.as_deref().map(str::to_lowercase);
+    let sessions = match args.session {
+        Some(ref session_id) => vec![store::find_session(writer.base_dir(), session_id, assistant.as_deref())?],
+        None => store::list_sessions(writer.base_dir())?
+            .into_iter()
+            .filter(|s| assistant.as_deref().is_none_or(|a| a == s.assistant))
+            .collect(),
+    };
+
+    let mut invalid = 0;
+    let mut fixed = 0;
+    for session in &sessions {
+        let mut report = SessionValidator::validate(&session.path);
+        if args.fix && !report.is_valid && report.is_fixable() {
+            SessionValidator::fix(&session.path)?;
+            println!("✓ Fixed {}", session.path.display());
+            fixed += 1;
+            report = SessionValidator::validate(&session.path);
+        }
+
+        for error in &report.errors {
+            println!("{}: {}", session.path.display(), error);
+        }
+        for warning in &report.warnings {
+            println!("⚠ {}: {}", session.path.display(), warning);
+        }
+        if !report.is_valid {
+            invalid += 1;
+        }
+    }
+
+    if invalid == 0 {
+        if fixed > 0 {
+            println!("✓ {} sessions valid ({} fixed)", sessions.len(), fixed);
+        } else {
+            println!("✓ {} sessions valid", sessions.len());
+        }
+        return Ok(());
+    }
+
+    if args.fix {
+        eprintln!("✗ {} of {} sessions invalid; the rest of their problems need fixing by hand", invalid, sessions.len());
+    } else {
+        eprintln!("✗ {} of {} sessions invalid", invalid, sessions.len());
+    }
+    std::process::exit(1);
+}
+
+fn handle_reindex(args: &ReindexArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let indexed = session_index::rebuild(writer.base_dir())?;
+    println!("✓ Indexed {} sessions", indexed);
+
+    if config.search_index {
+        let mut index = match SearchIndex::open_at(writer.base_dir()) {
+            Ok(index) => index,
+            // Nothing in an unreadable index is worth keeping when every entry is rebuilt
+            Err(e) if args.assistant.is_none() => {
+                eprintln!("⚠ Search index unreadable, starting it afresh: {}", e);
+                std::fs::remove_file(writer.base_dir().join(search::INDEX_FILE))?;
+                SearchIndex::open_at(writer.base_dir())?
+            }
+            Err(e) => return Err(e.wrap_err("Search index unreadable; run continuum reindex without --assistant")),
+        };
+
+        let messages = writer.reindex(&mut index, args.assistant.as_deref(), |done, total, session| {
+            if args.verbose {
+                eprintln!("  [{}/{}] {}/{}/{}", done, total, session.assistant, session.date, session.session_id);
+            } else {
+                eprint!("\r  Search index: {}/{} sessions", done, total);
+                if done == total {
+                    eprintln!();
+                }
+            }
+        })?;
+        println!("✓ Indexed {} messages for search", messages);
+    }
+
+    println!("  Location: {}", writer.base_dir().display());
+    Ok(())
+}
+
+fn handle_filter(args: &FilterArgs, config: &Config) -> Result<()> {
+    let text = match args.input {
+        Some(ref path) if path.as_os_str() != "-" => {
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?
+        }
+        _ => std::io::read_to_string(std::io::stdin())?,
+    };
+
+    let mut filter_config = match args.config {
+        Some(ref path) => FilterConfig::load(path)?,
+        None => config.noise_filter.clone(),
+    };
+    if let Some(level) = args.noise {
+        filter_config.level = level;
+    }
+    let compressor = MessageCompressor::from_config(&filter_config)?;
+
+    let transcript = filter::parse_transcript(&text);
+    let (cleaned, timestamps) = compressor.compress_batch_timed(&transcript.messages, &transcript.timestamps);
+    print!("{}", filter::render(args.format, &cleaned, &timestamps)?);
+    eprintln!("{}", compressor.compression_stats(&transcript.messages, &cleaned));
+    Ok(())
+}
+
+fn handle_filter_test(args: &FilterTestArgs, config: &Config) -> Result<()> {
+    let mut filter_config = match args.config {
+        Some(ref path) => FilterConfig::load(path)?,
+        None => config.noise_filter.clone(),
+    };
+    if let Some(level) = args.noise {
+        filter_config.level = level;
+    }
+    let filter = NoiseFilter::from_config(&filter_config)?;
+
+    let text = if args.text == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        args.text.clone()
+    };
+
+    let explanation = filter.explain(&text);
+    println!("{} {}", if explanation.is_kept() { "✓" } else { "✗" }, explanation);
+    if let FilterExplanation::StrippedBoilerplate { ref stripped, ref remaining } = explanation {
+        println!("\nStripped:\n{}\n\nRemaining:\n{}", stripped, remaining);
+    }
+    Ok(())
+}
+
+fn handle_compact(args: &CompactArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let assistant = args.assistant.as_deref().map(str::to_lowercase);
+    let session = store::find_session(writer.base_dir(), &args.session, assistant.as_deref())?;
+    let stored = store::load_messages(&session.path)?.len();
+
+    if stored > args.keep_last && !args.yes {
+        let question = format!(
+            "Replace {} of the {} messages in {}/{}/{} with a summary marker?",
+            stored - args.keep_last,
+            stored,
+            session.assistant,
+            session.date,
+            session.session_id
+        );
+        if !confirm(&question)? {
+            eprintln!("Aborted");
+            return Ok(());
+        }
+    }
+
+    let result = Compactor::compact(&session.path, args.keep_last)?;
+    if result.is_unchanged() {
+        println!("Nothing to compact: {} has no more than {} messages to keep", session.session_id, args.keep_last);
+        return Ok(());
+    }
+
+    reindex_session(&writer, &session, config)?;
+
+    println!(
+        "✓ Compacted {}: {} → {} messages ({} summarized), saved {} bytes",
+        session.session_id,
+        result.lines_before,
+        result.lines_after,
+        result.summarized_messages,
+        result.bytes_saved()
+    );
+    Ok(())
+}
+
+fn handle_redact(args: &RedactArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let patterns: Vec<&str> = args.pattern.iter().map(String::as_str).collect();
+    let redactor = Redactor::from_patterns(&patterns)?.with_builtins();
+
+    let assistant = args.assistant.as_deref().map(str::to_lowercase);
+    let session = store::find_session(writer.base_dir(), &args.session, assistant.as_deref())?;
+
+    // Count first so the question can say what will change
+    let matches: usize = store::load_messages(&session.path)?
+        .iter()
+        .map(|m| redactor.redact(&m.content).1.len())
+        .sum();
+    if matches == 0 {
+        println!("Nothing to redact in {}", session.session_id);
+        return Ok(());
+    }
+
+    if !args.yes {
+        let question = format!(
+            "Redact {} match{} in {}/{}/{}?",
+            matches,
+            if matches == 1 { "" } else { "es" },
+            session.assistant,
+            session.date,
+            session.session_id
+        );
+        if !confirm(&question)? {
+            eprintln!("Aborted");
+            return Ok(());
+        }
+    }
+
+    let changed = redactor.redact_session(&session.path)?;
+    reindex_session(&writer, &session, config)?;
+
+    let mut by_pattern: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+    for event in changed.iter().flat_map(|(_, events)| events) {
+        *by_pattern.entry(event.pattern.as_str()).or_default() += 1;
+    }
+    let total: usize = by_pattern.values().sum();
+    let breakdown: Vec<String> = by_pattern.iter().map(|(name, count)| format!("{}: {}", name, count)).collect();
+    println!(
+        "✓ Redacted {} match{} in {} message{} of {} ({})",
+        total,
+        if total == 1 { "" } else { "es" },
+        changed.len(),
+        if changed.len() == 1 { "" } else { "s" },
+        session.session_id,
+        breakdown.join(", ")
+    );
+    Ok(())
+}
+
+fn handle_replay(args: &ReplayArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone()).with_zone(config.timezone)
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let from = args.from.as_deref().map(str::to_lowercase);
+    let source = store::find_session(writer.base_dir(), &args.session, from.as_deref())?;
+    let reader = PlainTextReader::with_base_dir(writer.base_dir().to_path_buf());
+    let (_, messages) = reader.read_session(&source.assistant, &source.date, &source.session_id)?;
+
+    ReplayEngine::new(writer, &source.session_id)
+        .dry_run(args.dry_run)
+        .interactive(args.interactive)
+        .with_binary(args.binary.clone())
+        .with_search_index(config.search_index)
+        .run(&messages, &args.assistant)
+}
+
+/// Refresh a rewritten session in the search index, when the index is enabled
+fn reindex_session(writer: &PlainTextWriter, session: &store::SessionLocation, config: &Config) -> Result<()> {
+    if !config.search_index {
+        return Ok(());
+    }
+
+    let messages: Vec<(String, String)> = store::load_messages(&session.path)?
+        .into_iter()
+        .map(|m| (m.role, m.content))
+        .collect();
+    if let Err(e) = writer.index_session(&session.session_id, &session.assistant, &session.date, &messages) {
+        eprintln!("⚠ Warning: Failed to update search index: {}", e);
+    }
+    Ok(())
+}
+
+/// Ask a yes/no question on stderr; anything but y/yes counts as no
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::{self, Write};
+
+    eprint!("{} [y/N] ", question);
+    io::stderr().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn handle_watch(args: &WatchArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let assistant = args.assistant.as_deref().map(str::to_lowercase);
+    let location = store::find_session(writer.base_dir(), &args.session, assistant.as_deref())?;
+
+    let mut watch = WatchMode::new(location.path.clone(), args.format);
+    watch.skip_existing()?;
+
+    eprintln!("👀 Watching {} (Ctrl-C to stop)\n", location.path.display());
+    watch.run(&mut std::io::stdout().lock())
+}
+
+fn handle_tag(args: &TagArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+    let base_dir = writer.base_dir();
+
+    match &args.command {
+        TagCommand::Add(edit) | TagCommand::Remove(edit) => {
+            let assistant = edit.assistant.as_deref().map(str::to_lowercase);
+            let location = store::find_session(base_dir, &edit.session, assistant.as_deref())?;
+            let adding = matches!(args.command, TagCommand::Add(_));
+
+            for tag in &edit.tags {
+                if adding {
+                    writer.add_tag(&location.session_id, &location.assistant, &location.date, tag)?;
+                } else {
+                    writer.remove_tag(&location.session_id, &location.assistant, &location.date, tag)?;
+                }
+            }
+
+            let tags = writer.session_tags(&location.session_id, &location.assistant, &location.date)?;
+            println!("{}/{}: {}", location.assistant, location.session_id, tags.join(", "));
+        }
+        TagCommand::List { session: Some(session), assistant, .. } => {
+            let assistant = assistant.as_deref().map(str::to_lowercase);
+            let location = store::find_session(base_dir, session, assistant.as_deref())?;
+            for tag in writer.session_tags(&location.session_id, &location.assistant, &location.date)? {
+                println!("{}", tag);
+            }
+        }
+        TagCommand::List { tag: Some(tag), .. } => {
+            for record in writer.find_sessions_with_tag(tag)? {
+                println!("{}/{} {}", record.assistant, record.id, record.start_time.as_deref().unwrap_or(""));
+            }
+        }
+        TagCommand::List { .. } => {
+            // Every tag in use, with the number of sessions carrying it
+            let mut counts: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+            for location in store::list_sessions(base_dir)? {
+                if let Ok(tags) = writer.session_tags(&location.session_id, &location.assistant, &location.date) {
+                    for tag in tags {
+                        *counts.entry(tag).or_default() += 1;
+                    }
+                }
+            }
+            for (tag, count) in counts {
+                println!("{:>5}  {}", count, tag);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_note(args: &NoteArgs, config: &Config) -> Result<()> {
+    let writer = if let Some(ref output) = args.output {
+        PlainTextWriter::with_base_dir(output.clone())
+    } else {
+        PlainTextWriter::from_config(config)
+    };
+
+    let (NoteCommand::Set { target, .. } | NoteCommand::Get(target) | NoteCommand::Clear(target)) = &args.command;
+    let assistant = target.assistant.as_deref().map(str::to_lowercase);
+    let location = store::find_session(writer.base_dir(), &target.session, assistant.as_deref())?;
+    let (id, assistant, date) = (&location.session_id, &location.assistant, &location.date);
+
+    let indexed = match &args.command {
+        NoteCommand::Get(_) => {
+            match writer.session_note(id, assistant, date)? {
+                Some(note) => println!("{}", note),
+                None => eprintln!("{}/{} has no note", assistant, id),
+            }
+            return Ok(());
+        }
+        NoteCommand::Set { text, .. } => {
+            writer.set_note(id, assistant, date, &text.join(" "))?;
+            let note = writer.session_note(id, assistant, date)?;
+            println!("✓ Noted {}/{}: {}", assistant, id, note.as_deref().unwrap_or_default());
+            note
+        }
+        NoteCommand::Clear(_) => {
+            writer.clear_note(id, assistant, date)?;
+            println!("✓ Cleared the note on {}/{}", assistant, id);
+            None
+        }
+    };
+
+    if config.search_index {
+        let updated = SearchIndex::open_at(writer.base_dir()).and_then(|index| index.index_note(id, assistant, date, indexed.as_deref()));
+        if let Err(e) = updated {
+            eprintln!("⚠ Warning: Failed to update search index: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a stored message with a role header
+fn print_message(message: &StoredMessage) {
+    let timestamp = message.timestamp.as_deref().unwrap_or("");
+    println!("── [{}] {} {}", message.id, message.role, timestamp);
+    println!("{}\n", message.content);
 }
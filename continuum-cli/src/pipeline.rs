@@ -0,0 +1,187 @@
+// Streaming import pipeline
+// Carries a source's messages through the noise filter, coalescing and loop detection into a spool
+
+use color_eyre::Result;
+use continuum_core::{Coalescer, CompressionStats, CompressionStream, ImportSpool, LoopDetection, LoopDetector, MessageCompressor};
+
+use crate::sources::MessageSink;
+
+/// Filters messages as a source reader produces them, so only the spool grows with the session
+///
+/// Each message goes through the noise filter on its own before runs of
+/// same-role messages are joined, so a polite opener can't take the answer
+/// after it down with it. Loop detection sees the joined runs as they were
+/// before the noise filter, counting over the detector's rolling window.
+pub struct ImportPipeline<'a> {
+    system: bool,
+    coalesce: bool,
+    /// Run of source messages for the loop detector, when coalescing
+    source_run: Coalescer,
+    /// Run of cleaned messages for the spool, when coalescing
+    kept_run: Coalescer,
+    /// Model of the run held in `kept_run`: the first its messages name
+    run_model: Option<String>,
+    compression: CompressionStream<'a>,
+    detector: Option<LoopDetector>,
+    spool: ImportSpool,
+}
+
+/// What an [`ImportPipeline`] left once the source was read
+pub struct Spooled {
+    pub spool: ImportSpool,
+    pub stats: CompressionStats,
+    pub detections: Vec<LoopDetection>,
+}
+
+impl<'a> ImportPipeline<'a> {
+    pub fn new(compressor: &'a MessageCompressor) -> Result<Self> {
+        Ok(ImportPipeline {
+            system: true,
+            coalesce: false,
+            source_run: Coalescer::default(),
+            kept_run: Coalescer::default(),
+            run_model: None,
+            compression: compressor.stream(),
+            detector: None,
+            spool: ImportSpool::new()?,
+        })
+    }
+
+    /// Keep `system` messages (the default); without them they are dropped before anything else
+    pub fn system(mut self, system: bool) -> Self {
+        self.system = system;
+        self
+    }
+
+    /// Join runs of same-role messages that survive the noise filter (see [`continuum_core::coalesce_consecutive`])
+    pub fn coalesce(mut self, coalesce: bool) -> Self {
+        self.coalesce = coalesce;
+        self
+    }
+
+    /// Keep joined runs to at most `max_chars` characters (see [`Coalescer::max_chars`])
+    pub fn coalesce_max_chars(mut self, max_chars: Option<usize>) -> Self {
+        self.source_run = Coalescer::default().max_chars(max_chars);
+        self.kept_run = Coalescer::default().max_chars(max_chars);
+        self
+    }
+
+    /// Check the messages for loops with this detector
+    pub fn detect_loops(mut self, detector: LoopDetector) -> Self {
+        self.detector = Some(detector);
+        self
+    }
+
+    /// Flush any held-back runs and hand over the spool
+    pub fn finish(mut self) -> Result<Spooled> {
+        if let (Some(detector), Some((role, content, _))) = (self.detector.as_mut(), std::mem::take(&mut self.source_run).finish()) {
+            detector.push(&role, &content);
+        }
+        if let Some((role, content, timestamp)) = std::mem::take(&mut self.kept_run).finish() {
+            self.spool.push(&role, &content, timestamp.as_deref(), self.run_model.as_deref())?;
+        }
+        Ok(Spooled {
+            stats: self.compression.stats(),
+            detections: self.detector.map(|detector| detector.detections()).unwrap_or_default(),
+            spool: self.spool,
+        })
+    }
+
+    fn detect(&mut self, role: &str, content: &str) {
+        let Some(ref mut detector) = self.detector else {
+            return;
+        };
+        if !self.coalesce {
+            detector.push(role, content);
+        } else if let Some((role, content, _)) = self.source_run.push(role.to_string(), content.to_string(), None) {
+            detector.push(&role, &content);
+        }
+    }
+
+    fn keep(&mut self, role: String, content: String, timestamp: Option<String>, model: Option<String>) -> Result<()> {
+        if !self.coalesce {
+            return self.spool.push(&role, &content, timestamp.as_deref(), model.as_deref());
+        }
+        match self.kept_run.push(role, content, timestamp) {
+            Some((role, content, timestamp)) => {
+                let run_model = std::mem::replace(&mut self.run_model, model);
+                self.spool.push(&role, &content, timestamp.as_deref(), run_model.as_deref())
+            }
+            None => {
+                self.run_model = self.run_model.take().or(model);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl MessageSink for ImportPipeline<'_> {
+    fn push(&mut self, role: String, content: String, timestamp: Option<String>) -> Result<()> {
+        self.push_model(role, content, timestamp, None)
+    }
+
+    fn push_model(&mut self, role: String, content: String, timestamp: Option<String>, model: Option<String>) -> Result<()> {
+        if !self.system && role == "system" {
+            return Ok(());
+        }
+        self.detect(&role, &content);
+        match self.compression.compress(&role, &content) {
+            Some(cleaned) => self.keep(role, cleaned, timestamp, model),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_each_message_before_coalescing() -> Result<()> {
+        let compressor = MessageCompressor::new();
+        let mut pipeline = ImportPipeline::new(&compressor)?.coalesce(true);
+        for (role, content) in [
+            ("user", "Why does the parser reject trailing commas in arrays?"),
+            ("assistant", "Let me look at parser.rs."),
+            ("assistant", "The array rule requires a value after every comma."),
+            ("assistant", "Make the trailing value optional in parse_array."),
+        ] {
+            pipeline.push(role.to_string(), content.to_string(), None)?;
+        }
+
+        let Spooled { spool, stats, .. } = pipeline.finish()?;
+        let messages = spool.messages()?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            messages,
+            vec![
+                ("user".to_string(), "Why does the parser reject trailing commas in arrays?".to_string()),
+                (
+                    "assistant".to_string(),
+                    "The array rule requires a value after every comma.\nMake the trailing value optional in parse_array.".to_string()
+                ),
+            ]
+        );
+        assert_eq!(stats.messages_removed, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_coalesced_runs_keep_the_first_model_named() -> Result<()> {
+        let compressor = MessageCompressor::new();
+        let mut pipeline = ImportPipeline::new(&compressor)?.coalesce(true);
+        for (role, content, model) in [
+            ("user", "Which lifetime does the iterator borrow?", None),
+            ("assistant", "It borrows the slice for 'a.", None),
+            ("assistant", "So the slice must outlive the iterator.", Some("qwen2.5-coder-7b")),
+            ("user", "And if the slice is a Vec?", None),
+            ("assistant", "Then the Vec must outlive it instead.", Some("llama3.1:8b")),
+        ] {
+            pipeline.push_model(role.to_string(), content.to_string(), None, model.map(str::to_string))?;
+        }
+
+        let Spooled { spool, .. } = pipeline.finish()?;
+        let models = spool.records()?.map(|record| Ok(record?["model"].as_str().map(str::to_string))).collect::<Result<Vec<_>>>()?;
+        assert_eq!(models, vec![None, Some("qwen2.5-coder-7b".to_string()), None, Some("llama3.1:8b".to_string())]);
+        Ok(())
+    }
+}
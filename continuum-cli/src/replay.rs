@@ -0,0 +1,276 @@
+// Replaying a stored session's prompts
+// Sends each user message to a fresh assistant process and stores the answers as a new session
+
+use color_eyre::{eyre::{bail, Context}, Result};
+use continuum_core::{Message, PlainTextWriter, Role};
+use serde_json::json;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Reruns the user prompts of a stored session against an assistant
+///
+/// Every prompt goes to its own non-interactive process of the assistant
+/// (e.g. `claude --print`) on stdin, so the assistant sees each prompt fresh,
+/// without the answers to the earlier ones. Prompts and answers are stored as
+/// a new session whose session.json names the source under `replayed_from`.
+pub struct ReplayEngine {
+    writer: PlainTextWriter,
+    source_id: String,
+    dry_run: bool,
+    interactive: bool,
+    binary: Option<PathBuf>,
+    search_index: bool,
+}
+
+impl ReplayEngine {
+    /// Engine replaying the session `source_id`, storing replays through `writer`
+    pub fn new(writer: PlainTextWriter, source_id: &str) -> Self {
+        ReplayEngine {
+            writer,
+            source_id: source_id.to_string(),
+            dry_run: false,
+            interactive: false,
+            binary: None,
+            search_index: false,
+        }
+    }
+
+    /// Only print the prompts that would be sent
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Wait for Enter before sending each prompt
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Run this executable instead of the assistant's usual binary on PATH
+    pub fn with_binary(mut self, binary: Option<PathBuf>) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    /// Add the replayed session to the search index
+    pub fn with_search_index(mut self, search_index: bool) -> Self {
+        self.search_index = search_index;
+        self
+    }
+
+    pub fn run(&self, messages: &[Message], target_assistant: &str) -> Result<()> {
+        let prompts: Vec<&str> = messages
+            .iter()
+            .filter(|m| m.role == Role::User)
+            .map(|m| m.content.as_str())
+            .collect();
+        if prompts.is_empty() {
+            bail!("Session '{}' has no user messages to replay", self.source_id);
+        }
+
+        if self.dry_run {
+            for (idx, prompt) in prompts.iter().enumerate() {
+                println!("[{}/{}] {}", idx + 1, prompts.len(), prompt);
+            }
+            eprintln!("Would replay {} prompts against {}", prompts.len(), target_assistant);
+            return Ok(());
+        }
+
+        let (assistant, program, args) = invocation(target_assistant)?;
+        let program = self.binary.clone().unwrap_or_else(|| PathBuf::from(program));
+
+        let started = chrono::Utc::now();
+        let start_time = started.to_rfc3339();
+        let session_id = format!("replay-{}-{}", self.source_id, started.format("%Y%m%d%H%M%S"));
+        let date = self.writer.date_of(Some(&start_time));
+        self.record(&session_id, assistant, &start_time, "active", 0)?;
+
+        let mut stored: Vec<(String, String)> = Vec::new();
+        let mut outcome = Ok(());
+        for (idx, prompt) in prompts.iter().enumerate() {
+            if self.interactive && !confirm_next(idx + 1, prompts.len())? {
+                eprintln!("Stopped after {} of {} prompts", idx, prompts.len());
+                break;
+            }
+            eprintln!("▶ Prompt {}/{}", idx + 1, prompts.len());
+
+            let asked_at = chrono::Utc::now().to_rfc3339();
+            let response = match ask(&program, args, prompt) {
+                Ok(response) => response,
+                Err(e) => {
+                    outcome = Err(e);
+                    break;
+                }
+            };
+            println!("{}\n", response);
+            let answered_at = chrono::Utc::now().to_rfc3339();
+
+            for (role, content, timestamp) in [("user", *prompt, &asked_at), ("assistant", response.as_str(), &answered_at)] {
+                stored.push((role.to_string(), content.to_string()));
+                self.writer.append_message(
+                    &session_id,
+                    assistant,
+                    &date,
+                    stored.len(),
+                    role,
+                    content,
+                    Some(timestamp),
+                    Some(PlainTextWriter::count_tokens(content)),
+                    None,
+                )?;
+            }
+        }
+
+        let session_dir = self.record(&session_id, assistant, &start_time, "closed", stored.len())?;
+        if self.search_index && !stored.is_empty() {
+            if let Err(e) = self.writer.index_session(&session_id, assistant, &date, &stored) {
+                eprintln!("⚠ Warning: Failed to update search index: {}", e);
+            }
+        }
+
+        outcome.with_context(|| format!("Replay stopped; {} messages kept in {}", stored.len(), session_dir.display()))?;
+        let replayed = stored.len() / 2;
+        println!(
+            "✓ Replayed {} prompt{} from {} as {} session {}",
+            replayed,
+            if replayed == 1 { "" } else { "s" },
+            self.source_id,
+            assistant,
+            session_id
+        );
+        println!("  Location: {}", session_dir.display());
+        Ok(())
+    }
+
+    /// Write session.json for the replay, keeping its `replayed_from` link
+    fn record(&self, session_id: &str, assistant: &str, start_time: &str, status: &str, count: usize) -> Result<PathBuf> {
+        let end_time = (status == "closed").then(|| chrono::Utc::now().to_rfc3339());
+        let cwd = std::env::current_dir().ok().map(|dir| dir.to_string_lossy().into_owned());
+        let session_dir = self.writer.write_session(
+            session_id,
+            assistant,
+            Some(start_time),
+            end_time.as_deref(),
+            status,
+            count,
+            &[],
+            None,
+            cwd.as_deref(),
+        )?;
+        let date = self.writer.date_of(Some(start_time));
+        self.writer
+            .update_session_metadata(session_id, assistant, &date, json!({ "replayed_from": self.source_id }))?;
+        Ok(session_dir)
+    }
+}
+
+/// Stored assistant name, binary and arguments that answer one prompt read from stdin
+fn invocation(assistant: &str) -> Result<(&'static str, &'static str, &'static [&'static str])> {
+    match assistant.to_lowercase().as_str() {
+        "claude-code" | "claude" => Ok(("claude-code", "claude", &["--print"])),
+        "codex" => Ok(("codex", "codex", &["exec", "-"])),
+        "goose" => Ok(("goose", "goose", &["run", "--instructions", "-"])),
+        "gemini" | "gemini-cli" => Ok(("gemini-cli", "gemini", &[])),
+        other => bail!("Replay supports claude-code, codex, goose and gemini, not '{}'", other),
+    }
+}
+
+/// Run the assistant once with `prompt` on stdin and return what it printed
+fn ask(program: &PathBuf, args: &[&str], prompt: &str) -> Result<String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program.display()))?;
+
+    // Written from its own thread, so a prompt larger than the pipe buffer can't
+    // block on an assistant that's waiting for its output to be read. Dropping
+    // stdin after the write closes it, so the assistant sees the end of the prompt.
+    let writer = child.stdin.take().map(|mut stdin| {
+        let prompt = prompt.to_string();
+        std::thread::spawn(move || stdin.write_all(prompt.as_bytes()))
+    });
+    let output = child.wait_with_output()?;
+    if let Some(writer) = writer {
+        match writer.join() {
+            Ok(written) => written.with_context(|| format!("Failed to send the prompt to {}", program.display()))?,
+            Err(_) => bail!("Sending the prompt to {} panicked", program.display()),
+        }
+    }
+    if !output.status.success() {
+        bail!("{} exited with {}", program.display(), output.status);
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Ask on stderr whether to send the next prompt; `q` stops the replay
+fn confirm_next(number: usize, total: usize) -> Result<bool> {
+    eprint!("Send prompt {}/{}? [Enter to send, q to stop] ", number, total);
+    std::io::stderr().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(!answer.trim().eq_ignore_ascii_case("q"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn message(role: Role, content: &str) -> Message {
+        Message { role, content: content.to_string(), timestamp: None }
+    }
+
+    #[test]
+    fn test_ask_sends_prompts_larger_than_the_pipe_buffer() -> Result<()> {
+        let prompt = "a long prompt line\n".repeat(20_000);
+        let response = ask(&PathBuf::from("cat"), &[], &prompt)?;
+        assert_eq!(response, prompt.trim());
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay_stores_prompts_and_answers() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let messages = [
+            message(Role::User, "First question"),
+            message(Role::Assistant, "Original answer"),
+            message(Role::User, "Second question"),
+        ];
+
+        // cat answers every prompt with the prompt itself
+        ReplayEngine::new(writer, "src1")
+            .with_binary(Some(PathBuf::from("cat")))
+            .run(&messages, "gemini")?;
+
+        let replays = continuum_core::store::list_sessions(temp_dir.path())?;
+        assert_eq!(replays.len(), 1);
+        assert_eq!(replays[0].assistant, "gemini-cli");
+        let stored: Vec<(String, String)> = continuum_core::store::load_messages(&replays[0].path)?
+            .into_iter()
+            .map(|m| (m.role, m.content))
+            .collect();
+        let turn = |content: &str| [("user".to_string(), content.to_string()), ("assistant".to_string(), content.to_string())];
+        assert_eq!(stored, [turn("First question"), turn("Second question")].concat());
+
+        let record = continuum_core::SessionRecord::from_dir(&replays[0].path)?;
+        assert_eq!(record.status, continuum_core::SessionStatus::Closed);
+        let metadata: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(replays[0].path.join("session.json"))?)?;
+        assert_eq!(metadata["replayed_from"], "src1");
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay_without_prompts_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let engine = ReplayEngine::new(writer, "src1").with_binary(Some(PathBuf::from("cat")));
+        assert!(engine.run(&[message(Role::Assistant, "Only an answer")], "gemini").is_err());
+    }
+}
@@ -0,0 +1,391 @@
+// Reading sessions from each assistant's native logs
+// Shared by `continuum import` and `continuum diff`, so both see the same messages
+// Readers hand messages to a MessageSink one at a time, so imports can stream them
+
+use color_eyre::{eyre::{bail, eyre}, Result};
+use continuum_core::adapters::aider::{self, AiderAdapter};
+use continuum_core::adapters::chatgpt::ChatGptAdapter;
+use continuum_core::adapters::claude_code::ClaudeCodeAdapter;
+use continuum_core::adapters::codex::CodexAdapter;
+use continuum_core::adapters::cursor::CursorAdapter;
+use continuum_core::adapters::gemini::{self, GeminiAdapter};
+use continuum_core::adapters::goose::{parse_goose_content, GooseAdapter, GooseSessionRef};
+use continuum_core::adapters::lmstudio::{self, LmStudioAdapter};
+use continuum_core::adapters::openwebui::OpenWebUiAdapter;
+use continuum_core::adapters::zed::{self, ZedAdapter};
+use continuum_core::tool_capture::TOOL_ROLE;
+use continuum_core::{CodexLogEntry, Config, LogAdapter, PlainTextWriter, ToolCapture};
+use std::path::{Path, PathBuf};
+
+/// Receives a source's messages one at a time, in log order
+pub trait MessageSink {
+    /// `timestamp` is RFC3339 UTC, `None` where the log doesn't say
+    fn push(&mut self, role: String, content: String, timestamp: Option<String>) -> Result<()>;
+
+    /// As [`MessageSink::push`], for a message the log says `model` wrote
+    fn push_model(&mut self, role: String, content: String, timestamp: Option<String>, _model: Option<String>) -> Result<()> {
+        self.push(role, content, timestamp)
+    }
+}
+
+/// Collects the messages in memory, for callers that compare whole sessions
+impl MessageSink for Vec<(String, String)> {
+    fn push(&mut self, role: String, content: String, _timestamp: Option<String>) -> Result<()> {
+        Vec::push(self, (role, content));
+        Ok(())
+    }
+}
+
+/// A session as read from an assistant's native log; its messages went to a [`MessageSink`]
+pub struct SourceSession {
+    pub id: String,
+    /// Timestamp of the first entry, for logs that record one
+    pub start_time: Option<String>,
+    /// Skills invoked during the session (claude-code only)
+    pub skills: Vec<String>,
+}
+
+impl SourceSession {
+    fn new(id: impl Into<String>) -> Self {
+        SourceSession {
+            id: id.into(),
+            start_time: None,
+            skills: Vec::new(),
+        }
+    }
+
+    /// Pass on a message logged at `timestamp` (as the source wrote it)
+    /// The first timestamp passed on becomes the session's start time.
+    fn push(&mut self, sink: &mut dyn MessageSink, role: String, content: String, timestamp: Option<&str>) -> Result<()> {
+        self.push_model(sink, role, content, timestamp, None)
+    }
+
+    /// As [`SourceSession::push`], for a message the log says `model` wrote
+    fn push_model(
+        &mut self,
+        sink: &mut dyn MessageSink,
+        role: String,
+        content: String,
+        timestamp: Option<&str>,
+        model: Option<String>,
+    ) -> Result<()> {
+        let timestamp = timestamp.and_then(PlainTextWriter::normalize_timestamp);
+        if self.start_time.is_none() {
+            self.start_time.clone_from(&timestamp);
+        }
+        sink.push_model(role, content, timestamp, model)
+    }
+}
+
+/// Pass on `(role, content, timestamp)` entries, timestamps as the source wrote them
+fn push_entries(sink: &mut dyn MessageSink, entries: Vec<(String, String, Option<String>)>) -> Result<()> {
+    for (role, content, timestamp) in entries {
+        sink.push(role, content, timestamp.as_deref().and_then(PlainTextWriter::normalize_timestamp))?;
+    }
+    Ok(())
+}
+
+/// Id of a session addressed by an adapter pseudo-path (`<file>#<id>`)
+fn pseudo_path_id(path: &Path) -> String {
+    let path_str = path.to_string_lossy();
+    path_str.rsplit_once('#').map(|(_, id)| id).unwrap_or("unknown").to_string()
+}
+
+fn file_stem_id(path: &Path) -> String {
+    path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown").to_string()
+}
+
+pub fn read_codex(
+    adapter: &CodexAdapter,
+    path: &Path,
+    tools: Option<&ToolCapture>,
+    sink: &mut dyn MessageSink,
+) -> Result<SourceSession> {
+    let mut session = SourceSession::new(file_stem_id(path));
+
+    for line_result in adapter.stream_session(path)? {
+        let line = line_result?;
+        let entry: CodexLogEntry = serde_json::from_str(&line)?;
+
+        if entry.entry_type == "response_item" {
+            if let Some(ref payload) = entry.payload {
+                if let Some(ref role) = payload.role {
+                    if let Some(ref content_array) = payload.content {
+                        let text = content_array
+                            .iter()
+                            .filter_map(|c| c.text.as_deref())
+                            .collect::<Vec<_>>()
+                            .join("");
+
+                        session.push(sink, role.clone(), text, entry.timestamp.as_deref())?;
+                    }
+                } else if let Some(text) = tools.and_then(|t| t.format_codex_payload(payload)) {
+                    session.push(sink, TOOL_ROLE.to_string(), text, entry.timestamp.as_deref())?;
+                }
+            }
+        }
+    }
+
+    Ok(session)
+}
+
+/// Tool requests and responses are only kept with `tools`
+pub fn read_goose(
+    adapter: &GooseAdapter,
+    session_ref: &GooseSessionRef,
+    tools: Option<&ToolCapture>,
+    sink: &mut dyn MessageSink,
+) -> Result<SourceSession> {
+    #[derive(serde::Deserialize)]
+    struct GooseMessage {
+        role: String,
+        content_json: String,
+        timestamp: Option<String>,
+    }
+
+    let mut session = SourceSession::new(session_ref.session_id.clone());
+
+    for msg_result in adapter.stream(session_ref)? {
+        let msg: GooseMessage = serde_json::from_str(&msg_result?)?;
+        let content = parse_goose_content(&msg.content_json, tools)?;
+
+        if !content.text.is_empty() {
+            session.push(sink, msg.role, content.text, msg.timestamp.as_deref())?;
+        }
+        for text in content.tool_messages {
+            session.push(sink, TOOL_ROLE.to_string(), text, msg.timestamp.as_deref())?;
+        }
+    }
+
+    Ok(session)
+}
+
+/// With `thinking`, extended-thinking blocks are kept as `thinking` messages
+pub fn read_claude_code(
+    adapter: &ClaudeCodeAdapter,
+    path: &Path,
+    tools: Option<&ToolCapture>,
+    thinking: bool,
+    sink: &mut dyn MessageSink,
+) -> Result<SourceSession> {
+    let transcript = adapter.stream_entries(path, tools, thinking, |role, content, timestamp| {
+        sink.push(role, content, timestamp.as_deref().and_then(PlainTextWriter::normalize_timestamp))
+    })?;
+
+    Ok(SourceSession {
+        start_time: transcript.start_time,
+        skills: transcript.skills,
+        ..SourceSession::new(file_stem_id(path))
+    })
+}
+
+/// Function calls and responses are only kept with `tools`
+/// Gemini CLI saves a session as one JSON document, so it is parsed whole.
+pub fn read_gemini(path: &Path, tools: Option<&ToolCapture>, sink: &mut dyn MessageSink) -> Result<SourceSession> {
+    let parsed = gemini::parse_session(path, tools)?;
+
+    push_entries(sink, parsed.messages)?;
+    Ok(SourceSession {
+        start_time: parsed.start_time,
+        ..SourceSession::new(parsed.session_id)
+    })
+}
+
+/// Aider, ChatGPT, Cursor, Zed, LM Studio and Open WebUI adapters all stream `{role, content}` JSON lines
+#[derive(serde::Deserialize)]
+struct RoleContent {
+    role: String,
+    content: String,
+    timestamp: Option<String>,
+    /// Model that wrote the message, where the log records one
+    model: Option<String>,
+}
+
+fn read_role_content(adapter: &dyn LogAdapter, path: &Path, id: String, sink: &mut dyn MessageSink) -> Result<SourceSession> {
+    let mut session = SourceSession::new(id);
+
+    for line_result in adapter.stream_session(path)? {
+        let msg: RoleContent = serde_json::from_str(&line_result?)?;
+        session.push_model(sink, msg.role, msg.content, msg.timestamp.as_deref(), msg.model)?;
+    }
+
+    Ok(session)
+}
+
+pub fn read_aider(adapter: &AiderAdapter, path: &Path, sink: &mut dyn MessageSink) -> Result<SourceSession> {
+    read_role_content(adapter, path, aider::session_id_for(path), sink)
+}
+
+pub fn read_chatgpt(adapter: &ChatGptAdapter, path: &Path, sink: &mut dyn MessageSink) -> Result<SourceSession> {
+    read_role_content(adapter, path, pseudo_path_id(path), sink)
+}
+
+/// `path` is a chat tab pseudo-path or a JSON conversation log named by session ID
+pub fn read_cursor(adapter: &CursorAdapter, path: &Path, sink: &mut dyn MessageSink) -> Result<SourceSession> {
+    let id = if path.to_string_lossy().contains('#') { pseudo_path_id(path) } else { file_stem_id(path) };
+    read_role_content(adapter, path, id, sink)
+}
+
+pub fn read_zed(adapter: &ZedAdapter, path: &Path, sink: &mut dyn MessageSink) -> Result<SourceSession> {
+    read_role_content(adapter, path, zed::session_id_for(path), sink)
+}
+
+/// `path` is a webui.db#<chat id> pseudo-path
+pub fn read_openwebui(adapter: &OpenWebUiAdapter, path: &Path, sink: &mut dyn MessageSink) -> Result<SourceSession> {
+    read_role_content(adapter, path, pseudo_path_id(path), sink)
+}
+
+/// LM Studio records the conversation ID inside the file
+pub fn read_lmstudio(adapter: &LmStudioAdapter, path: &Path, sink: &mut dyn MessageSink) -> Result<SourceSession> {
+    read_role_content(adapter, path, lmstudio::parse_conversation(path)?.id, sink)
+}
+
+/// Session ID a source path will be stored under, without importing it
+/// Gemini CLI and LM Studio record the ID inside the session file, so that file is parsed.
+pub fn source_session_id(assistant: &str, path: &Path) -> Result<String> {
+    Ok(match assistant {
+        "codex" | "claude-code" => file_stem_id(path),
+        "gemini-cli" => gemini::parse_session(path, None)?.session_id,
+        "aider" => aider::session_id_for(path),
+        "cursor" if path.to_string_lossy().contains('#') => pseudo_path_id(path),
+        "cursor" => file_stem_id(path),
+        "zed" => zed::session_id_for(path),
+        "lmstudio" => lmstudio::parse_conversation(path)?.id,
+        "goose" | "chatgpt" | "openwebui" => pseudo_path_id(path),
+        _ => bail!("Unknown assistant '{}'", assistant),
+    })
+}
+
+/// Re-read a stored session from its assistant's native log
+///
+/// `source` overrides where the log is looked for: the session file for
+/// codex, claude-code, gemini-cli, aider, zed and lmstudio, the export file for chatgpt, webui.db for openwebui, and the
+/// workspace storage directory for cursor. Goose sessions are found in the
+/// sessions database of `profile`. Default locations follow `config`.
+/// The messages go to `sink`.
+#[allow(clippy::too_many_arguments)]
+pub fn read_stored_source(
+    config: &Config,
+    assistant: &str,
+    session_id: &str,
+    source: Option<&Path>,
+    profile: Option<String>,
+    tools: Option<&ToolCapture>,
+    thinking: bool,
+    sink: &mut dyn MessageSink,
+) -> Result<SourceSession> {
+    match assistant {
+        "codex" => {
+            let adapter = CodexAdapter::from_config(config);
+            let path = match source {
+                Some(path) => path.to_path_buf(),
+                None => find_file_named(&adapter.sessions_dir()?, &format!("{}.jsonl", session_id))?,
+            };
+            read_codex(&adapter, &path, tools, sink)
+        }
+        "claude-code" => {
+            let adapter = ClaudeCodeAdapter::from_config(config);
+            let path = match source {
+                Some(path) => path.to_path_buf(),
+                None => find_file_named(&adapter.projects_dir()?, &format!("{}.jsonl", session_id))?,
+            };
+            read_claude_code(&adapter, &path, tools, thinking, sink)
+        }
+        "gemini-cli" => {
+            let path = match source {
+                Some(path) => path.to_path_buf(),
+                None => find_gemini_session(&GeminiAdapter::from_config(config).tmp_dir()?, session_id)?,
+            };
+            read_gemini(&path, tools, sink)
+        }
+        "aider" => {
+            let adapter = AiderAdapter::from_config(config);
+            let path = match source {
+                Some(path) => path.to_path_buf(),
+                None => {
+                    let path = adapter.sessions_dir()?.join(format!("{}.md", session_id));
+                    if !path.exists() {
+                        bail!("No Aider session file for {}; pass its chat history with --source", session_id);
+                    }
+                    path
+                }
+            };
+            read_aider(&adapter, &path, sink)
+        }
+        "goose" => {
+            if source.is_some() {
+                bail!("--source is not supported for goose; use --profile to pick the sessions database");
+            }
+            let adapter = GooseAdapter::from_config(config, profile)?;
+            read_goose(&adapter, &adapter.session_ref(session_id), tools, sink)
+        }
+        "chatgpt" => {
+            let adapter = match source {
+                Some(path) => ChatGptAdapter::with_export_path(path.to_path_buf()),
+                None => ChatGptAdapter::from_config(config),
+            };
+            read_chatgpt(&adapter, &adapter.session_path(session_id)?, sink)
+        }
+        "cursor" => {
+            let adapter = match source {
+                Some(path) => CursorAdapter::with_storage_dir(path.to_path_buf()),
+                None => CursorAdapter::from_config(config),
+            };
+            read_cursor(&adapter, &adapter.session_path(session_id)?, sink)
+        }
+        "zed" => {
+            let adapter = ZedAdapter::from_config(config);
+            let path = match source {
+                Some(path) => path.to_path_buf(),
+                None => adapter.session_path(session_id)?,
+            };
+            read_zed(&adapter, &path, sink)
+        }
+        "lmstudio" => {
+            let adapter = LmStudioAdapter::from_config(config);
+            let path = match source {
+                Some(path) => path.to_path_buf(),
+                None => adapter.session_path(session_id)?,
+            };
+            read_lmstudio(&adapter, &path, sink)
+        }
+        "openwebui" => {
+            let adapter = match source {
+                Some(path) => OpenWebUiAdapter::with_db_path(path.to_path_buf()),
+                None => OpenWebUiAdapter::from_config(config),
+            };
+            read_openwebui(&adapter, &adapter.session_path(session_id)?, sink)
+        }
+        other => bail!("Unknown assistant '{}'. Supported: codex, goose, claude-code, gemini-cli, aider, chatgpt, cursor, zed, lmstudio, openwebui", other),
+    }
+}
+
+/// Search `root` recursively for a file called `name`
+fn find_file_named(root: &Path, name: &str) -> Result<PathBuf> {
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.file_name().and_then(|s| s.to_str()) == Some(name) {
+                return Ok(path);
+            }
+        }
+    }
+
+    Err(eyre!("No {} found under {}; pass the session file with --source", name, root.display()))
+}
+
+/// Gemini CLI names files by start time, so find the session by the ID inside
+fn find_gemini_session(tmp_dir: &Path, session_id: &str) -> Result<PathBuf> {
+    gemini::session_files(tmp_dir)
+        .into_iter()
+        .map(|(path, _)| path)
+        .find(|path| gemini::parse_session(path, None).is_ok_and(|s| s.session_id == session_id))
+        .ok_or_else(|| eyre!("No Gemini CLI session {} under {}; pass the session file with --source", session_id, tmp_dir.display()))
+}
@@ -0,0 +1,204 @@
+// Live tailing of a stored session
+// Follows a session's messages file via filesystem events, polling when they're unavailable
+
+use color_eyre::{eyre::Context, Result};
+use continuum_core::store::{self, StoredMessage};
+use notify::{RecursiveMode, Watcher};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// How often the session is re-read when no filesystem event arrives
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Output format for watched messages
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchFormat {
+    /// Role header followed by the message content
+    Text,
+    /// Raw JSONL lines, for piping into other tools
+    Json,
+}
+
+/// Follows one stored session and renders messages as they are appended
+///
+/// Plain messages.jsonl files are read from the last consumed offset, holding
+/// back a trailing line until it is complete. Compressed sessions can't be
+/// resumed mid-stream, so they are re-read and the already printed messages
+/// skipped. A file that shrinks (e.g. `import --force` rewrote it) is read
+/// again from the start.
+pub struct WatchMode {
+    session_dir: PathBuf,
+    format: WatchFormat,
+    offset: u64,
+    partial: Vec<u8>,
+    printed: usize,
+}
+
+impl WatchMode {
+    pub fn new(session_dir: PathBuf, format: WatchFormat) -> Self {
+        WatchMode {
+            session_dir,
+            format,
+            offset: 0,
+            partial: Vec::new(),
+            printed: 0,
+        }
+    }
+
+    /// Mark everything stored so far as seen, so only new messages are shown
+    pub fn skip_existing(&mut self) -> Result<()> {
+        self.read_new().map(|_| ())
+    }
+
+    /// Messages appended since the last call, rendered in the output format
+    pub fn read_new(&mut self) -> Result<Vec<String>> {
+        let Some(path) = store::messages_file(&self.session_dir) else {
+            // Nothing written yet
+            return Ok(Vec::new());
+        };
+
+        if path.ends_with(store::COMPRESSED_MESSAGES_FILE) {
+            let messages = store::load_messages(&self.session_dir)?;
+            if messages.len() < self.printed {
+                self.printed = 0;
+            }
+            let new = messages[self.printed..]
+                .iter()
+                .map(|message| self.render(message))
+                .collect::<Result<Vec<_>>>()?;
+            self.printed = messages.len();
+            return Ok(new);
+        }
+
+        let mut file = File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let read = file.read_to_end(&mut self.partial)?;
+        self.offset += read as u64;
+
+        // Keep an unterminated last line for the next read
+        let complete = match self.partial.iter().rposition(|b| *b == b'\n') {
+            Some(end) => self.partial.drain(..=end).collect::<Vec<u8>>(),
+            None => return Ok(Vec::new()),
+        };
+
+        let mut new = Vec::new();
+        for line in String::from_utf8_lossy(&complete).lines().filter(|l| !l.trim().is_empty()) {
+            match self.format {
+                WatchFormat::Json => new.push(format!("{}\n", line)),
+                WatchFormat::Text => {
+                    // A malformed line shouldn't stop the watch
+                    if let Ok(message) = serde_json::from_str::<StoredMessage>(line) {
+                        new.push(self.render(&message)?);
+                    }
+                }
+            }
+        }
+        Ok(new)
+    }
+
+    /// Print new messages to `out` until interrupted
+    ///
+    /// Filesystem events (inotify, kqueue, ...) wake the loop early; without
+    /// them the session is simply re-read every [`POLL_INTERVAL`].
+    pub fn run(&mut self, out: &mut impl Write) -> Result<()> {
+        let (tx, rx) = mpsc::channel();
+        let watcher = notify::recommended_watcher(move |event| {
+            let _ = tx.send(event);
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(&self.session_dir, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+        // Dropping the watcher would stop the events, so hold on to it
+        let _watcher = match watcher {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                eprintln!("⚠ Filesystem events unavailable ({}); polling every {}ms", e, POLL_INTERVAL.as_millis());
+                None
+            }
+        };
+
+        loop {
+            for rendered in self.read_new()? {
+                out.write_all(rendered.as_bytes())?;
+            }
+            out.flush()?;
+
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => std::thread::sleep(POLL_INTERVAL),
+            }
+            // One read covers any burst of events
+            while rx.try_recv().is_ok() {}
+        }
+    }
+
+    fn render(&self, message: &StoredMessage) -> Result<String> {
+        Ok(match self.format {
+            WatchFormat::Json => format!("{}\n", serde_json::to_string(message)?),
+            WatchFormat::Text => format!(
+                "── [{}] {} {}\n{}\n\n",
+                message.id,
+                message.role,
+                message.timestamp.as_deref().unwrap_or(""),
+                message.content
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use continuum_core::PlainTextWriter;
+    use std::fs::OpenOptions;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_new_emits_appended_messages() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let session_dir = temp_dir.path().join("codex/2025-11-09/s1");
+        let mut watch = WatchMode::new(session_dir.clone(), WatchFormat::Text);
+        assert!(watch.read_new()?.is_empty());
+
+        writer.append_message("s1", "codex", "2025-11-09", 1, "user", "Already there", None, None, None)?;
+        watch.skip_existing()?;
+        writer.append_message("s1", "codex", "2025-11-09", 2, "assistant", "Fresh reply", None, None, None)?;
+        assert_eq!(watch.read_new()?, vec!["── [2] assistant \nFresh reply\n\n".to_string()]);
+        assert!(watch.read_new()?.is_empty());
+
+        // A half-written line waits until it is complete
+        let line = r#"{"id":3,"role":"user","content":"Thanks"}"#;
+        let mut file = OpenOptions::new().append(true).open(session_dir.join("messages.jsonl"))?;
+        file.write_all(&line.as_bytes()[..10])?;
+        assert!(watch.read_new()?.is_empty());
+        file.write_all(&line.as_bytes()[10..])?;
+        file.write_all(b"\n")?;
+        assert_eq!(watch.read_new()?, vec!["── [3] user \nThanks\n\n".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewritten_session_is_read_again() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let session_dir = temp_dir.path().join("s1");
+        std::fs::create_dir_all(&session_dir)?;
+        let messages = session_dir.join("messages.jsonl");
+        std::fs::write(&messages, "{\"id\":1,\"role\":\"user\",\"content\":\"A long first question\"}\n")?;
+
+        let mut watch = WatchMode::new(session_dir, WatchFormat::Json);
+        assert_eq!(watch.read_new()?.len(), 1);
+
+        std::fs::write(&messages, "{\"id\":1,\"role\":\"user\",\"content\":\"Short\"}\n")?;
+        assert_eq!(watch.read_new()?, vec!["{\"id\":1,\"role\":\"user\",\"content\":\"Short\"}\n".to_string()]);
+        Ok(())
+    }
+}
@@ -3,12 +3,30 @@
 
 use std::process::{Command, Stdio};
 use color_eyre::{eyre::Context, Result};
+use continuum_core::{DuplicateSession, ImportOutcome};
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
+    // A broken config file shouldn't stop the assistant from starting
+    let mut config = continuum_core::Config::load().unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring continuum config: {:#}", e);
+        continuum_core::Config::default()
+    });
+
+    // The directory codex runs in, recorded as the session's cwd
+    let cwd = std::env::current_dir().ok().map(|dir| dir.to_string_lossy().into_owned());
+
     // Get all arguments passed to continuum-codex
-    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Our own flags; codex would reject them
+    if let Some(level) = continuum_core::take_noise_flag(&mut args)? {
+        config.noise_filter.level = level;
+    }
+    if continuum_core::take_redact_flag(&mut args) {
+        config.redact = true;
+    }
 
     // Find the real codex binary
     let codex_path = which::which("codex")
@@ -48,21 +66,15 @@ fn main() -> Result<()> {
         codex_path_str
     };
 
-    // Check for no-save marker file
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    let marker_path = std::path::Path::new(&home).join(".continuum-nosave");
-    let skip_saving = marker_path.exists();
+    // Skipped via $CONTINUUM_NOSAVE or the single-use ~/.continuum-nosave marker
+    let skip_saving = continuum_core::config::should_skip_saving();
 
     if skip_saving {
-        // Delete marker file immediately
-        let _ = std::fs::remove_file(&marker_path);
         eprintln!("⚠ This conversation will NOT be saved to continuum logs");
     }
 
-    // Get the most recently modified session file BEFORE running codex
-    let sessions_dir = std::path::PathBuf::from(&home).join(".codex/sessions");
-
-    let before_session = find_latest_session_file(&sessions_dir);
+    // Get the most recently modified session file (and its size) BEFORE running codex
+    let before_session = find_latest_session(&config);
 
     // Spawn codex as a child process
     let status = Command::new(&real_codex)
@@ -75,31 +87,33 @@ fn main() -> Result<()> {
         .wait()?;
 
     // After codex exits, find the session that was just modified
-    let after_session = find_latest_session_file(&sessions_dir);
+    let after_session = find_latest_session(&config);
 
-    // Import the session if it's different from before (and we're not skipping)
+    // Import a new session file, or one resumed during this run (newer mtime
+    // or more lines); a resumed session only gets its new messages appended
     let mut session_dir: Option<std::path::PathBuf> = None;
     if !skip_saving {
-        if let Some(session_path) = after_session {
-            if before_session.as_ref() != Some(&session_path) {
-                eprintln!("\n📝 Importing session to continuum logs...");
-                match import_session_to_continuum(&session_path) {
-                    Ok(dir) => {
-                        session_dir = Some(dir);
-                    }
-                    Err(e) => {
-                        eprintln!("⚠ Warning: Failed to import session: {}", e);
-                    }
+        if let Some(after) = after_session.as_ref().filter(|after| before_session.as_ref() != Some(*after)) {
+            eprintln!("\n📝 Importing session to continuum logs...");
+            match import_session_to_continuum(&after.path, &config, cwd.as_deref()) {
+                Ok((dir, true)) => {
+                    session_dir = Some(dir);
                 }
+                // The session was saved on a previous run, so there is nothing to discard
+                Ok((_, false)) => {}
+                Err(e) => match e.downcast_ref::<DuplicateSession>() {
+                    Some(duplicate) => eprintln!("⏭ Not saved again: {}", duplicate),
+                    None => eprintln!("⚠ Warning: Failed to import session: {}", e),
+                },
             }
         }
     }
 
-    // Post-conversation review prompt (if session was saved)
+    // Post-conversation review prompt (if a new session was saved)
     if let Some(ref dir) = session_dir {
         if !prompt_save_conversation()? {
             // User chose to discard - delete the session directory
-            let _ = std::fs::remove_dir_all(dir);
+            discard_session(dir, "codex", &config);
             eprintln!("✗ Conversation discarded");
         } else {
             eprintln!("✓ Conversation saved");
@@ -109,78 +123,55 @@ fn main() -> Result<()> {
     std::process::exit(status.code().unwrap_or(1))
 }
 
-fn find_latest_session_file(sessions_dir: &std::path::Path) -> Option<std::path::PathBuf> {
-    use std::time::SystemTime;
-
-    if !sessions_dir.exists() {
-        return None;
-    }
-
-    let mut latest: Option<(std::path::PathBuf, SystemTime)> = None;
-
-    // Walk through YYYY/MM/DD directory structure
-    if let Ok(year_entries) = std::fs::read_dir(sessions_dir) {
-        for year_entry in year_entries.flatten() {
-            let year_dir = year_entry.path();
-            if !year_dir.is_dir() {
-                continue;
-            }
+/// A session file as it was at one point in time
+#[derive(Debug, PartialEq)]
+struct SessionSnapshot {
+    path: std::path::PathBuf,
+    modified: std::time::SystemTime,
+    lines: usize,
+}
 
-            if let Ok(month_entries) = std::fs::read_dir(&year_dir) {
-                for month_entry in month_entries.flatten() {
-                    let month_dir = month_entry.path();
-                    if !month_dir.is_dir() {
-                        continue;
-                    }
+/// The most recently modified session file, with its mtime and line count
+fn find_latest_session(config: &continuum_core::Config) -> Option<SessionSnapshot> {
+    use std::io::{BufRead, BufReader};
 
-                    if let Ok(day_entries) = std::fs::read_dir(&month_dir) {
-                        for day_entry in day_entries.flatten() {
-                            let day_dir = day_entry.path();
-                            if !day_dir.is_dir() {
-                                continue;
-                            }
-
-                            if let Ok(files) = std::fs::read_dir(&day_dir) {
-                                for file_entry in files.flatten() {
-                                    let file_path = file_entry.path();
-
-                                    if file_path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                                        if let Ok(metadata) = std::fs::metadata(&file_path) {
-                                            if let Ok(modified) = metadata.modified() {
-                                                if latest.is_none() || modified > latest.as_ref().unwrap().1 {
-                                                    latest = Some((file_path, modified));
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+    let sessions_dir = continuum_core::adapters::codex::CodexAdapter::from_config(config).sessions_dir().ok()?;
+    let scan = continuum_core::adapters::codex::latest_session_file(&sessions_dir).ok()?;
+    for unreadable in &scan.skipped {
+        eprintln!("⚠ Warning: Skipping unreadable Codex session entry {}", unreadable);
     }
-
-    latest.map(|(path, _)| path)
+    let (path, modified) = scan.latest?;
+    let lines = BufReader::new(std::fs::File::open(&path).ok()?).lines().count();
+    Some(SessionSnapshot { path, modified, lines })
 }
 
-fn import_session_to_continuum(session_path: &std::path::Path) -> Result<std::path::PathBuf> {
-    use continuum_core::{CodexLogEntry, MessageCompressor, PlainTextWriter, LoopDetector, LoopSeverity};
+/// Import a session, returning its directory and whether this run created it
+fn import_session_to_continuum(
+    session_path: &std::path::Path,
+    config: &continuum_core::Config,
+    cwd: Option<&str>,
+) -> Result<(std::path::PathBuf, bool)> {
+    use continuum_core::{CodexLogEntry, ImportSpool, MessageCompressor, PlainTextWriter, LoopSeverity};
     use std::io::{BufRead, BufReader};
 
-    let writer = PlainTextWriter::new()?;
+    let writer = PlainTextWriter::from_config(config);
 
     let session_id = session_path
         .file_stem()
         .and_then(|s| s.to_str())
         .unwrap_or("unknown");
 
-    let compressor = MessageCompressor::new();
-    let mut messages: Vec<(String, String)> = Vec::new();
-    let start_time = chrono::Utc::now().to_rfc3339();
-
-    // Read all messages from the session file
+    let compressor = MessageCompressor::from_config(&config.noise_filter).unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring noise config: {:#}", e);
+        MessageCompressor::new()
+    });
+    let mut stream = compressor.stream();
+    let mut detector = config.loop_detector.build();
+    let mut spool = ImportSpool::new()?;
+    let mut first_timestamp: Option<String> = None;
+
+    // Read the session file a message at a time: loop detection sees every
+    // message, the spool only those the noise filter keeps
     let file = std::fs::File::open(session_path)
         .with_context(|| format!("Failed to open {}", session_path.display()))?;
     let reader = BufReader::new(file);
@@ -199,24 +190,29 @@ fn import_session_to_continuum(session_path: &std::path::Path) -> Result<std::pa
                             .collect::<Vec<_>>()
                             .join("");
 
-                        messages.push((role.clone(), text));
+                        let timestamp = entry.timestamp.as_deref().and_then(PlainTextWriter::normalize_timestamp);
+                        if first_timestamp.is_none() {
+                            first_timestamp.clone_from(&timestamp);
+                        }
+                        detector.push(role, &text);
+                        if let Some(cleaned) = stream.compress(role, &text) {
+                            spool.push(role, &cleaned, timestamp.as_deref(), None)?;
+                        }
                     }
                 }
             }
         }
     }
 
-    // Compress messages
-    let compressed = compressor.compress_batch(&messages);
-    let message_count = compressed.len();
+    // File the session under its first message's day
+    let start_time = first_timestamp.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let message_count = spool.len();
 
     if message_count == 0 {
         return Err(color_eyre::eyre::eyre!("No messages to import"));
     }
 
-    // Loop detection - analyze messages before writing
-    let detector = LoopDetector::new();
-    let detections = detector.analyze(&messages);
+    let detections = detector.detections();
 
     // Report any detected loops
     if !detections.is_empty() {
@@ -233,7 +229,15 @@ fn import_session_to_continuum(session_path: &std::path::Path) -> Result<std::pa
         eprintln!("This may indicate an automation failure or runaway process.\n");
     }
 
-    let date = PlainTextWriter::extract_date(Some(&start_time));
+    let date = writer.session_date(session_id, "codex", Some(&start_time));
+
+    // Only a session this run creates may be discarded afterwards; a rewrite
+    // of one saved earlier (shorter source, new noise level) must not be
+    let existed = continuum_core::store::find_session(writer.base_dir(), session_id, Some("codex")).is_ok();
+
+    // Write messages first (a resumed session only appends what's new); this
+    // refuses content already stored under another session before anything is created
+    let outcome = writer.import_spool(session_id, "codex", &date, &spool, Some(&start_time), false)?;
 
     // Write session
     let session_dir = writer.write_session(
@@ -244,24 +248,24 @@ fn import_session_to_continuum(session_path: &std::path::Path) -> Result<std::pa
         "closed",
         message_count,
         &[],
+        None,
+        cwd,
     )?;
 
-    // Write messages
-    for (idx, (role, content)) in compressed.iter().enumerate() {
-        writer.append_message(
-            session_id,
-            "codex",
-            &date,
-            idx + 1,
-            role,
-            content,
-            Some(&start_time),
-        )?;
+    // Keep the search index fresh; a failure here shouldn't lose the import
+    if config.search_index {
+        if let Err(e) = writer.index_spool(session_id, "codex", &date, &spool) {
+            eprintln!("⚠ Warning: Failed to update search index: {}", e);
+        }
     }
 
-    eprintln!("✓ Saved {} messages to continuum logs", message_count);
+    match outcome {
+        ImportOutcome::Unchanged => eprintln!("✓ Continuum logs already up to date"),
+        ImportOutcome::Appended(count) => eprintln!("✓ Appended {} new messages to continuum logs", count),
+        ImportOutcome::Rewritten(_) => eprintln!("✓ Saved {} messages to continuum logs", message_count),
+    }
 
-    Ok(session_dir)
+    Ok((session_dir, !existed))
 }
 
 /// Prompt user whether to save the conversation
@@ -281,3 +285,14 @@ fn prompt_save_conversation() -> Result<bool> {
         _ => Ok(true), // Default to save (Y or Enter)
     }
 }
+
+/// Delete a discarded session, dropping it from the search and session indexes
+fn discard_session(session_dir: &std::path::Path, assistant: &str, config: &continuum_core::Config) {
+    let writer = continuum_core::PlainTextWriter::from_config(config);
+    let result = continuum_core::SessionRecord::from_dir(session_dir).and_then(|record| {
+        writer.delete_session_confirmed(&record.id, assistant, record.date.as_deref().unwrap_or_default())
+    });
+    if let Err(e) = result {
+        eprintln!("⚠ Warning: Failed to discard session: {}", e);
+    }
+}
@@ -0,0 +1,66 @@
+// Throughput comparison of PlainTextWriter vs AsyncPlainTextWriter
+// Run with: cargo bench -p continuum-core --features async
+
+use std::time::{Duration, Instant};
+
+use color_eyre::Result;
+use continuum_core::{AsyncPlainTextWriter, PlainTextWriter};
+
+const MESSAGES: usize = 1000;
+const RUNS: usize = 5;
+
+fn content(i: usize) -> String {
+    format!("Message {} with enough text to resemble a typical assistant reply. {}", i, "lorem ipsum ".repeat(20))
+}
+
+fn bench_sync(run: usize) -> Result<Duration> {
+    let temp_dir = tempfile::tempdir()?;
+    let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+    let session_id = format!("sync-{}", run);
+
+    let start = Instant::now();
+    writer.write_session(&session_id, "bench", Some("2025-11-09T00:00:00Z"), None, "active", 0, &[], None, None)?;
+    for i in 1..=MESSAGES {
+        writer.append_message(&session_id, "bench", "2025-11-09", i, "assistant", &content(i), None, None, None)?;
+    }
+    Ok(start.elapsed())
+}
+
+async fn bench_async(run: usize) -> Result<Duration> {
+    let temp_dir = tempfile::tempdir()?;
+    let writer = AsyncPlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+    let session_id = format!("async-{}", run);
+
+    let start = Instant::now();
+    writer
+        .write_session(&session_id, "bench", Some("2025-11-09T00:00:00Z"), None, "active", 0, &[], None, None)
+        .await?;
+    for i in 1..=MESSAGES {
+        writer
+            .append_message(&session_id, "bench", "2025-11-09", i, "assistant", &content(i), None, None, None)
+            .await?;
+    }
+    Ok(start.elapsed())
+}
+
+fn report(label: &str, timings: &[Duration]) {
+    let total: Duration = timings.iter().sum();
+    let mean = total / timings.len() as u32;
+    let rate = MESSAGES as f64 / mean.as_secs_f64();
+    println!("{:<6} mean {:>8.2?} per {}-message session ({:.0} msg/s)", label, mean, MESSAGES, rate);
+}
+
+fn main() -> Result<()> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+
+    let mut sync_timings = Vec::new();
+    let mut async_timings = Vec::new();
+    for run in 0..RUNS {
+        sync_timings.push(bench_sync(run)?);
+        async_timings.push(runtime.block_on(bench_async(run))?);
+    }
+
+    report("sync", &sync_timings);
+    report("async", &async_timings);
+    Ok(())
+}
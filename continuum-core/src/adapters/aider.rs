@@ -0,0 +1,368 @@
+// Aider log adapter
+// Reads .aider.chat.history.md from the project tree, or ~/.aider/sessions/<name>.md
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::Context, Result};
+use serde_json::json;
+
+use super::claude_code::ClaudeCodeAdapter;
+use super::{newest_first, LogAdapter};
+use crate::config::Config;
+
+/// File name Aider uses for its per-project chat history
+pub const HISTORY_FILE: &str = ".aider.chat.history.md";
+
+/// Directories never worth descending into when looking for history files
+const SKIP_DIRS: &[&str] = &[".git", "node_modules", "target", ".venv", "venv", "__pycache__"];
+
+/// How many directories deep [`find_history_file`] looks below its root
+const MAX_SEARCH_DEPTH: usize = 6;
+
+pub struct AiderAdapter {
+    sessions_dir: Option<PathBuf>,
+}
+
+impl AiderAdapter {
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Fall back to `assistant_paths.aider`, or ~/.aider/sessions, when the
+    /// current project has no chat history
+    pub fn from_config(config: &Config) -> Self {
+        AiderAdapter { sessions_dir: config.assistant_path("aider") }
+    }
+
+    /// Flat directory of exported `<session>.md` files
+    pub fn sessions_dir(&self) -> Result<PathBuf> {
+        self.sessions_dir.clone().ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))
+    }
+}
+
+impl Default for AiderAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAdapter for AiderAdapter {
+    fn name(&self) -> &'static str {
+        "aider"
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        // Prefer the chat history Aider writes into the current project tree
+        let cwd = std::env::current_dir().context("Failed to read current directory")?;
+        if let Some((path, _)) = find_history_file(&cwd) {
+            return Ok(path);
+        }
+
+        let sessions_dir = self.sessions_dir()?;
+
+        if !sessions_dir.exists() {
+            return Err(color_eyre::eyre::eyre!(
+                "No {} under {} and Aider sessions directory not found: {}",
+                HISTORY_FILE,
+                cwd.display(),
+                sessions_dir.display()
+            ));
+        }
+
+        // Aider keeps sessions in a flat directory, so a single pass is enough
+        let mut latest: Option<(PathBuf, std::time::SystemTime)> = None;
+
+        for file_entry in std::fs::read_dir(&sessions_dir)? {
+            let file_path = file_entry?.path();
+            if file_path.extension().and_then(|s| s.to_str()) == Some("md") {
+                let metadata = std::fs::metadata(&file_path)?;
+                let modified = metadata.modified()?;
+
+                if latest.is_none() || modified > latest.as_ref().unwrap().1 {
+                    latest = Some((file_path, modified));
+                }
+            }
+        }
+
+        latest
+            .map(|(path, _)| path)
+            .ok_or_else(|| color_eyre::eyre::eyre!("No Aider session files found"))
+    }
+
+    /// The sessions directory's `.md` files, and the current project's chat history if there is one
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let cwd = std::env::current_dir().context("Failed to read current directory")?;
+        files.extend(find_history_file(&cwd));
+
+        let sessions_dir = self.sessions_dir()?;
+        if let Ok(entries) = std::fs::read_dir(&sessions_dir) {
+            for file_entry in entries {
+                let file_path = file_entry?.path();
+                if file_path.extension().and_then(|s| s.to_str()) == Some("md") {
+                    let modified = std::fs::metadata(&file_path)?.modified()?;
+                    files.push((file_path, modified));
+                }
+            }
+        }
+
+        Ok(newest_first(files))
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let messages = parse_aider_session(path)?;
+
+        // Emit one JSON object per message (compatible with LogAdapter interface)
+        let json_messages: Vec<Result<String>> = messages
+            .into_iter()
+            .map(|(role, content)| Ok(json!({ "role": role, "content": content }).to_string()))
+            .collect();
+
+        Ok(Box::new(json_messages.into_iter()))
+    }
+}
+
+/// Find the most recently modified `.aider.chat.history.md` under `root`
+/// Symlinked directories aren't followed, and the search stops [`MAX_SEARCH_DEPTH`] levels down.
+pub fn find_history_file(root: &Path) -> Option<(PathBuf, std::time::SystemTime)> {
+    let mut latest: Option<(PathBuf, std::time::SystemTime)> = None;
+    let mut pending = vec![(root.to_path_buf(), 0)];
+
+    while let Some((dir, depth)) = pending.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                if depth < MAX_SEARCH_DEPTH && !SKIP_DIRS.contains(&name.as_ref()) {
+                    pending.push((path, depth + 1));
+                }
+            } else if name == HISTORY_FILE {
+                if let Ok(modified) = entry.metadata().and_then(|m| m.modified()) {
+                    if latest.is_none() || modified > latest.as_ref().unwrap().1 {
+                        latest = Some((path, modified));
+                    }
+                }
+            }
+        }
+    }
+
+    latest
+}
+
+/// Derive a session ID from an Aider history path
+///
+/// Project history files are named after their project's full path, encoded
+/// as Claude Code encodes project directories (`/home/me/api` -> `-home-me-api`),
+/// so same-named projects in different places don't share a session.
+pub fn session_id_for(path: &Path) -> String {
+    let is_history = path.file_name().and_then(|s| s.to_str()) == Some(HISTORY_FILE);
+    if is_history {
+        if let Some(project) = path.parent() {
+            let project = project.canonicalize().unwrap_or_else(|_| project.to_path_buf());
+            return ClaudeCodeAdapter::encode_project_path(&project.to_string_lossy());
+        }
+    }
+
+    path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown").to_string()
+}
+
+/// Parse an Aider markdown session into (role, content) pairs
+///
+/// - `#### ` lines are user prompts (consecutive lines form one prompt)
+/// - `> ` lines are Aider's own tool output and are dropped
+/// - `# aider chat started at ...` headers are dropped
+/// - Everything else is assistant response text, minus ```diff fenced hunks
+pub fn parse_aider_session(path: &Path) -> Result<Vec<(String, String)>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(parse_aider_markdown(&raw))
+}
+
+/// Parse Aider markdown content (see [`parse_aider_session`])
+pub fn parse_aider_markdown(raw: &str) -> Vec<(String, String)> {
+    let mut messages: Vec<(String, String)> = Vec::new();
+    let mut user_lines: Vec<&str> = Vec::new();
+    let mut assistant_lines: Vec<&str> = Vec::new();
+    let mut fence: Option<bool> = None; // Some(is_diff) while inside a fenced block
+
+    fn flush(messages: &mut Vec<(String, String)>, role: &str, lines: &mut Vec<&str>) {
+        let text = lines.join("\n").trim().to_string();
+        if !text.is_empty() {
+            messages.push((role.to_string(), text));
+        }
+        lines.clear();
+    }
+
+    for line in raw.lines() {
+        let trimmed = line.trim_start();
+
+        // Inside a fenced block: nothing is a header, diff hunks are dropped
+        if let Some(is_diff) = fence {
+            if trimmed.starts_with("```") {
+                fence = None;
+            }
+            if !is_diff {
+                assistant_lines.push(line);
+            }
+            continue;
+        }
+
+        if let Some(prompt) = line.strip_prefix("#### ") {
+            flush(&mut messages, "assistant", &mut assistant_lines);
+            user_lines.push(prompt);
+            continue;
+        }
+
+        // Any non-prompt line ends the current user prompt
+        flush(&mut messages, "user", &mut user_lines);
+
+        if line.starts_with("# aider chat started at") || line.starts_with("> ") || line == ">" {
+            continue;
+        }
+
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            let is_diff = lang.trim() == "diff";
+            fence = Some(is_diff);
+            if !is_diff {
+                assistant_lines.push(line);
+            }
+            continue;
+        }
+
+        assistant_lines.push(line);
+    }
+
+    flush(&mut messages, "user", &mut user_lines);
+    flush(&mut messages, "assistant", &mut assistant_lines);
+
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_aider_markdown() {
+        let raw = "# aider chat started at 2025-11-09 14:00:00\n\
+\n\
+> Add src/main.rs to the chat? y\n\
+\n\
+#### Rename the main function\n\
+#### and add a doc comment\n\
+\n\
+Sure, here is the change:\n\
+\n\
+```diff\n\
+-fn main() {}\n\
++fn start() {}\n\
+```\n\
+\n\
+```rust\n\
+/// Entry point\n\
+fn start() {}\n\
+```\n\
+\n\
+> Applied edit to src/main.rs\n\
+\n\
+#### thanks, now run the tests\n\
+\n\
+All tests pass.\n";
+
+        let messages = parse_aider_markdown(raw);
+        assert_eq!(messages.len(), 4);
+        assert_eq!(messages[0], ("user".to_string(), "Rename the main function\nand add a doc comment".to_string()));
+        assert_eq!(messages[1].0, "assistant");
+        assert!(messages[1].1.starts_with("Sure, here is the change:"));
+        assert!(messages[1].1.contains("/// Entry point"));
+        assert!(!messages[1].1.contains("-fn main"));
+        assert!(!messages[1].1.contains("Applied edit"));
+        assert_eq!(messages[2], ("user".to_string(), "thanks, now run the tests".to_string()));
+        assert_eq!(messages[3], ("assistant".to_string(), "All tests pass.".to_string()));
+    }
+
+    #[test]
+    fn test_fenced_headers_are_not_prompts() {
+        let raw = "#### show me a markdown example\n\nHere you go:\n\n```markdown\n#### Not a prompt\n```\n";
+
+        let messages = parse_aider_markdown(raw);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].0, "user");
+        assert_eq!(messages[1].0, "assistant");
+        assert!(messages[1].1.contains("#### Not a prompt"));
+    }
+
+    #[test]
+    fn test_empty_history_file() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join(HISTORY_FILE);
+        std::fs::write(&path, "")?;
+
+        let adapter = AiderAdapter::new();
+        assert_eq!(adapter.stream_session(&path)?.count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_history_file_in_tree() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let project = temp_dir.path().join("myproject");
+        std::fs::create_dir_all(project.join("src"))?;
+        std::fs::create_dir_all(temp_dir.path().join("node_modules/dep"))?;
+        std::fs::write(project.join(HISTORY_FILE), "#### hi\n")?;
+        std::fs::write(temp_dir.path().join("node_modules/dep").join(HISTORY_FILE), "#### skip\n")?;
+
+        let (found, _) = find_history_file(temp_dir.path()).unwrap();
+        assert_eq!(found, project.join(HISTORY_FILE));
+        let project_id = ClaudeCodeAdapter::encode_project_path(&project.canonicalize()?.to_string_lossy());
+        assert_eq!(session_id_for(&found), project_id);
+        assert!(project_id.ends_with("-myproject"));
+
+        let lines: Vec<String> = AiderAdapter::new()
+            .stream_session(&found)?
+            .collect::<Result<Vec<_>>>()?;
+        let value: serde_json::Value = serde_json::from_str(&lines[0])?;
+        assert_eq!(value["role"], "user");
+        assert_eq!(value["content"], "hi");
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_named_projects_get_different_ids() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let work = temp_dir.path().join("work/api");
+        let personal = temp_dir.path().join("personal/api");
+        std::fs::create_dir_all(&work)?;
+        std::fs::create_dir_all(&personal)?;
+
+        assert_ne!(session_id_for(&work.join(HISTORY_FILE)), session_id_for(&personal.join(HISTORY_FILE)));
+        assert_eq!(session_id_for(&temp_dir.path().join("refactor.md")), "refactor");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_history_file_skips_symlinked_dirs_and_deep_trees() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let project = temp_dir.path().join("project");
+        std::fs::create_dir_all(&project)?;
+        // A link back to an ancestor would otherwise be walked forever
+        std::os::unix::fs::symlink(temp_dir.path(), project.join("loop"))?;
+        let deep = (0..=MAX_SEARCH_DEPTH).fold(temp_dir.path().to_path_buf(), |dir, level| dir.join(level.to_string()));
+        std::fs::create_dir_all(&deep)?;
+        std::fs::write(deep.join(HISTORY_FILE), "#### too deep\n")?;
+
+        assert!(find_history_file(temp_dir.path()).is_none());
+        std::fs::write(project.join(HISTORY_FILE), "#### hi\n")?;
+        assert_eq!(find_history_file(temp_dir.path()).map(|(path, _)| path), Some(project.join(HISTORY_FILE)));
+        Ok(())
+    }
+}
@@ -0,0 +1,396 @@
+// ChatGPT export adapter
+// Reads the conversations.json produced by ChatGPT's "Export data" feature
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::LogAdapter;
+use crate::config::Config;
+
+/// File name of the conversation dump inside a ChatGPT export
+pub const EXPORT_FILE: &str = "conversations.json";
+
+/// Environment variable pointing at a conversations.json (or the folder holding it)
+pub const EXPORT_PATH_ENV: &str = "CONTINUUM_CHATGPT_EXPORT";
+
+pub struct ChatGptAdapter {
+    export_path: Option<PathBuf>,
+}
+
+impl ChatGptAdapter {
+    /// Use $CONTINUUM_CHATGPT_EXPORT if set, otherwise look in ~/Downloads
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Use $CONTINUUM_CHATGPT_EXPORT if set, then `assistant_paths.chatgpt`,
+    /// otherwise look in ~/Downloads
+    pub fn from_config(config: &Config) -> Self {
+        let export_path = std::env::var_os(EXPORT_PATH_ENV)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| config.assistant_path("chatgpt"));
+        ChatGptAdapter { export_path }
+    }
+
+    /// Read conversations from a specific export file or directory
+    pub fn with_export_path(path: PathBuf) -> Self {
+        ChatGptAdapter { export_path: Some(path) }
+    }
+
+    /// Locate the conversations.json to read
+    pub fn export_file(&self) -> Result<PathBuf> {
+        if let Some(ref path) = self.export_path {
+            let file = if path.is_dir() { path.join(EXPORT_FILE) } else { path.clone() };
+            if !file.exists() {
+                return Err(color_eyre::eyre::eyre!("ChatGPT export not found: {}", file.display()));
+            }
+            return Ok(file);
+        }
+
+        let home = crate::config::home_dir().ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))?;
+        let downloads = home.join("Downloads");
+
+        // Exports are zip files, so also look one level down for an unpacked folder
+        let mut candidates = vec![downloads.join(EXPORT_FILE)];
+        if let Ok(entries) = std::fs::read_dir(&downloads) {
+            candidates.extend(entries.flatten().map(|e| e.path().join(EXPORT_FILE)));
+        }
+
+        candidates
+            .into_iter()
+            .filter_map(|path| {
+                let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+                Some((path, modified))
+            })
+            .max_by_key(|(_, modified)| *modified)
+            .map(|(path, _)| path)
+            .ok_or_else(|| {
+                color_eyre::eyre::eyre!(
+                    "No {} found in {} (set {} to point at your export)",
+                    EXPORT_FILE,
+                    downloads.display(),
+                    EXPORT_PATH_ENV
+                )
+            })
+    }
+
+    /// Pseudo-path addressing one conversation: /path/to/conversations.json#<id>
+    pub fn session_path(&self, conversation_id: &str) -> Result<PathBuf> {
+        Ok(PathBuf::from(format!("{}#{}", self.export_file()?.display(), conversation_id)))
+    }
+}
+
+impl Default for ChatGptAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAdapter for ChatGptAdapter {
+    fn name(&self) -> &'static str {
+        "chatgpt"
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        let file = self.export_file()?;
+        let conversations = load_conversations(&file)?;
+
+        let latest = conversations
+            .iter()
+            .max_by(|a, b| a.last_activity().total_cmp(&b.last_activity()))
+            .ok_or_else(|| color_eyre::eyre::eyre!("No conversations in {}", file.display()))?;
+
+        Ok(PathBuf::from(format!("{}#{}", file.display(), latest.id())))
+    }
+
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        let file = self.export_file()?;
+        let mut conversations = load_conversations(&file)?;
+        conversations.sort_by(|a, b| b.last_activity().total_cmp(&a.last_activity()));
+
+        Ok(conversations.iter().map(|c| PathBuf::from(format!("{}#{}", file.display(), c.id()))).collect())
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let path_str = path.to_string_lossy();
+        let Some((file, conversation_id)) = path_str.rsplit_once('#') else {
+            return Err(color_eyre::eyre::eyre!("Invalid ChatGPT session path (expected conversations.json#<id>)"));
+        };
+
+        let conversations = load_conversations(Path::new(file))?;
+        let conversation = conversations
+            .iter()
+            .find(|c| c.id() == conversation_id)
+            .ok_or_else(|| color_eyre::eyre::eyre!("Conversation '{}' not found in {}", conversation_id, file))?;
+
+        // Emit one JSON object per turn (compatible with LogAdapter interface)
+        let json_messages: Vec<Result<String>> = linearize(conversation)
+            .into_iter()
+            .map(|turn| Ok(json!({ "role": turn.role, "content": turn.content, "timestamp": turn.timestamp }).to_string()))
+            .collect();
+
+        Ok(Box::new(json_messages.into_iter()))
+    }
+}
+
+/// List every conversation in an export as (id, title), most recently updated first
+pub fn list_sessions(path: &Path) -> Result<Vec<(String, String)>> {
+    let mut conversations = load_conversations(path)?;
+    conversations.sort_by(|a, b| b.last_activity().total_cmp(&a.last_activity()));
+
+    Ok(conversations
+        .iter()
+        .map(|c| (c.id().to_string(), c.title.clone().unwrap_or_else(|| "(untitled)".to_string())))
+        .collect())
+}
+
+/// A conversation from an export, flattened to its visible branch
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedConversation {
+    pub id: String,
+    pub title: Option<String>,
+    /// When the conversation was started (RFC 3339), if the export records it
+    pub create_time: Option<String>,
+    pub turns: Vec<Turn>,
+}
+
+/// Read every conversation in an export, most recently updated first
+/// The file is parsed once, so this suits bulk imports of large exports.
+pub fn read_export(path: &Path) -> Result<Vec<ExportedConversation>> {
+    let mut conversations = load_conversations(path)?;
+    conversations.sort_by(|a, b| b.last_activity().total_cmp(&a.last_activity()));
+
+    Ok(conversations
+        .iter()
+        .map(|c| ExportedConversation {
+            id: c.id().to_string(),
+            title: c.title.clone(),
+            create_time: c.create_time.and_then(rfc3339),
+            turns: linearize(c),
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct Conversation {
+    id: Option<String>,
+    conversation_id: Option<String>,
+    title: Option<String>,
+    create_time: Option<f64>,
+    update_time: Option<f64>,
+    current_node: Option<String>,
+    #[serde(default)]
+    mapping: HashMap<String, Node>,
+}
+
+impl Conversation {
+    fn id(&self) -> &str {
+        self.id.as_deref().or(self.conversation_id.as_deref()).unwrap_or_default()
+    }
+
+    fn last_activity(&self) -> f64 {
+        self.update_time.or(self.create_time).unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Node {
+    message: Option<NodeMessage>,
+    parent: Option<String>,
+    #[serde(default)]
+    children: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeMessage {
+    author: Author,
+    content: Option<NodeContent>,
+    create_time: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Author {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct NodeContent {
+    #[serde(default)]
+    parts: Vec<serde_json::Value>,
+}
+
+/// One user or assistant turn from a conversation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    pub role: String,
+    pub content: String,
+    pub timestamp: Option<String>,
+}
+
+fn load_conversations(path: &Path) -> Result<Vec<Conversation>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("Invalid ChatGPT export: {}", path.display()))
+}
+
+/// Flatten the message tree into the branch the user last saw
+///
+/// The export keeps every edited/regenerated branch. `current_node` marks the
+/// leaf of the visible branch, so walk its `parent` links back to the root.
+/// Without it, descend from the root following the newest child.
+fn linearize(conversation: &Conversation) -> Vec<Turn> {
+    let mapping = &conversation.mapping;
+    let mut path: Vec<&Node> = Vec::new();
+
+    if let Some(mut node) = conversation.current_node.as_deref().and_then(|id| mapping.get(id)) {
+        path.push(node);
+        // Bounded by the node count in case of a malformed cycle
+        while let Some(parent) = node.parent.as_deref().and_then(|id| mapping.get(id)) {
+            if path.len() > mapping.len() {
+                break;
+            }
+            path.push(parent);
+            node = parent;
+        }
+        path.reverse();
+    } else if let Some(mut node) = mapping.values().find(|n| n.parent.is_none()) {
+        path.push(node);
+        while let Some(child) = node.children.last().and_then(|id| mapping.get(id)) {
+            if path.len() > mapping.len() {
+                break;
+            }
+            path.push(child);
+            node = child;
+        }
+    }
+
+    path.into_iter()
+        .filter_map(|node| node.message.as_ref())
+        .filter(|message| matches!(message.author.role.as_str(), "user" | "assistant" | "system"))
+        .filter_map(|message| {
+            let content = message
+                .content
+                .as_ref()?
+                .parts
+                .iter()
+                .filter_map(|part| part.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let content = content.trim();
+            if content.is_empty() {
+                return None;
+            }
+
+            let timestamp = message.create_time.and_then(rfc3339);
+
+            Some(Turn {
+                role: message.author.role.clone(),
+                content: content.to_string(),
+                timestamp,
+            })
+        })
+        .collect()
+}
+
+/// Export timestamps are fractional Unix seconds
+fn rfc3339(secs: f64) -> Option<String> {
+    chrono::DateTime::from_timestamp_millis((secs * 1000.0) as i64).map(|dt| dt.to_rfc3339())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const EXPORT: &str = r#"[
+      {
+        "id": "conv-old", "title": "Old chat", "create_time": 1700000000.0, "update_time": 1700000100.0,
+        "current_node": "a",
+        "mapping": {
+          "root": {"message": null, "parent": null, "children": ["a"]},
+          "a": {"message": {"author": {"role": "user"}, "content": {"content_type": "text", "parts": ["hello"]}, "create_time": 1700000000.5},
+                "parent": "root", "children": []}
+        }
+      },
+      {
+        "id": "conv-new", "title": "Rust help", "create_time": 1731160000.0, "update_time": 1731160500.0,
+        "current_node": "a2",
+        "mapping": {
+          "root": {"message": null, "parent": null, "children": ["sys"]},
+          "sys": {"message": {"author": {"role": "system"}, "content": {"content_type": "text", "parts": [""]}},
+                  "parent": "root", "children": ["u1"]},
+          "u1": {"message": {"author": {"role": "user"}, "content": {"content_type": "text", "parts": ["How do I borrow?"]}, "create_time": 1731160000.0},
+                 "parent": "sys", "children": ["a1", "a2"]},
+          "a1": {"message": {"author": {"role": "assistant"}, "content": {"content_type": "text", "parts": ["Discarded draft"]}},
+                 "parent": "u1", "children": []},
+          "a2": {"message": {"author": {"role": "assistant"}, "content": {"content_type": "text", "parts": ["Use &value."]}},
+                 "parent": "u1", "children": []}
+        }
+      }
+    ]"#;
+
+    #[test]
+    fn test_list_and_latest() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let file = temp_dir.path().join(EXPORT_FILE);
+        std::fs::write(&file, EXPORT)?;
+
+        let sessions = list_sessions(&file)?;
+        assert_eq!(sessions[0], ("conv-new".to_string(), "Rust help".to_string()));
+        assert_eq!(sessions.len(), 2);
+
+        let adapter = ChatGptAdapter::with_export_path(temp_dir.path().to_path_buf());
+        let latest = adapter.find_latest_session()?;
+        assert!(latest.to_string_lossy().ends_with("conversations.json#conv-new"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_export() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let file = temp_dir.path().join(EXPORT_FILE);
+        std::fs::write(&file, EXPORT)?;
+
+        let conversations = read_export(&file)?;
+        let ids: Vec<&str> = conversations.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["conv-new", "conv-old"]);
+
+        let old = &conversations[1];
+        assert_eq!(old.title.as_deref(), Some("Old chat"));
+        assert_eq!(old.create_time.as_deref(), Some("2023-11-14T22:13:20+00:00"));
+        assert_eq!(old.turns.len(), 1);
+        assert_eq!(old.turns[0].content, "hello");
+
+        // Only the current branch is kept
+        let contents: Vec<&str> = conversations[0].turns.iter().map(|t| t.content.as_str()).collect();
+        assert_eq!(contents, vec!["How do I borrow?", "Use &value."]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_follows_current_branch() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let file = temp_dir.path().join(EXPORT_FILE);
+        std::fs::write(&file, EXPORT)?;
+
+        let adapter = ChatGptAdapter::with_export_path(file);
+        let lines: Vec<serde_json::Value> = adapter
+            .stream_session(&adapter.session_path("conv-new")?)?
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<_>>()?;
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["role"], "user");
+        assert_eq!(lines[0]["content"], "How do I borrow?");
+        assert_eq!(lines[0]["timestamp"], "2024-11-09T13:46:40+00:00");
+        assert_eq!(lines[1]["content"], "Use &value.");
+
+        assert!(adapter.stream_session(&adapter.session_path("missing")?).is_err());
+        Ok(())
+    }
+}
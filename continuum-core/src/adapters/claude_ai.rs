@@ -0,0 +1,311 @@
+// Claude.ai export adapter
+// Reads the conversations.json produced by Claude.ai's "Export data" feature
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::LogAdapter;
+use crate::config::Config;
+
+/// Environment variable pointing at a Claude.ai conversations.json
+pub const EXPORT_PATH_ENV: &str = "CLAUDE_AI_EXPORT_PATH";
+
+/// Name Claude.ai conversations are stored under
+pub const ASSISTANT: &str = "claude-ai";
+
+pub struct ClaudeAiAdapter {
+    export_path: Option<PathBuf>,
+}
+
+impl ClaudeAiAdapter {
+    /// Use $CLAUDE_AI_EXPORT_PATH if set
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Use $CLAUDE_AI_EXPORT_PATH if set, then `assistant_paths.claude-ai`
+    pub fn from_config(config: &Config) -> Self {
+        let export_path = std::env::var_os(EXPORT_PATH_ENV)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| config.assistant_path(ASSISTANT));
+        ClaudeAiAdapter { export_path }
+    }
+
+    /// Read conversations from a specific export file
+    pub fn with_export_path(path: PathBuf) -> Self {
+        ClaudeAiAdapter { export_path: Some(path) }
+    }
+
+    /// Locate the export to read
+    ///
+    /// Claude.ai and ChatGPT both name their export conversations.json, so
+    /// unlike the ChatGPT adapter this one doesn't go looking in ~/Downloads.
+    pub fn export_file(&self) -> Result<PathBuf> {
+        let Some(ref file) = self.export_path else {
+            return Err(color_eyre::eyre::eyre!(
+                "No Claude.ai export configured (set {} or assistant_paths.{} to your conversations.json)",
+                EXPORT_PATH_ENV,
+                ASSISTANT
+            ));
+        };
+        if !file.is_file() {
+            return Err(color_eyre::eyre::eyre!("Claude.ai export not found: {}", file.display()));
+        }
+        Ok(file.clone())
+    }
+
+    /// Pseudo-path addressing one conversation: /path/to/conversations.json#<uuid>
+    pub fn session_path(&self, conversation_uuid: &str) -> Result<PathBuf> {
+        Ok(PathBuf::from(format!("{}#{}", self.export_file()?.display(), conversation_uuid)))
+    }
+}
+
+impl Default for ClaudeAiAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAdapter for ClaudeAiAdapter {
+    fn name(&self) -> &'static str {
+        ASSISTANT
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        let file = self.export_file()?;
+        let conversations = load_conversations(&file)?;
+
+        // RFC 3339 timestamps in one zone compare correctly as strings
+        let latest = conversations
+            .iter()
+            .max_by(|a, b| a.last_activity().cmp(b.last_activity()))
+            .ok_or_else(|| color_eyre::eyre::eyre!("No conversations in {}", file.display()))?;
+
+        Ok(PathBuf::from(format!("{}#{}", file.display(), latest.uuid)))
+    }
+
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        let file = self.export_file()?;
+        let mut conversations = load_conversations(&file)?;
+        conversations.sort_by(|a, b| b.last_activity().cmp(a.last_activity()));
+
+        Ok(conversations.iter().map(|c| PathBuf::from(format!("{}#{}", file.display(), c.uuid))).collect())
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let path_str = path.to_string_lossy();
+        let Some((file, conversation_uuid)) = path_str.rsplit_once('#') else {
+            return Err(color_eyre::eyre::eyre!("Invalid Claude.ai session path (expected conversations.json#<uuid>)"));
+        };
+
+        let conversation = load_conversations(Path::new(file))?
+            .into_iter()
+            .find(|c| c.uuid == conversation_uuid)
+            .ok_or_else(|| color_eyre::eyre::eyre!("Conversation '{}' not found in {}", conversation_uuid, file))?;
+
+        // Emit one JSON object per message (compatible with LogAdapter interface)
+        // Attachment warnings need read_export; this interface has nowhere to put them
+        let json_messages: Vec<Result<String>> = conversation
+            .messages(&mut Vec::new())
+            .into_iter()
+            .map(|(role, content, timestamp)| Ok(json!({ "role": role, "content": content, "timestamp": timestamp }).to_string()))
+            .collect();
+
+        Ok(Box::new(json_messages.into_iter()))
+    }
+}
+
+/// A conversation from an export, with what couldn't be carried over
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedConversation {
+    pub uuid: String,
+    pub name: Option<String>,
+    /// When the conversation was started (RFC 3339), if the export records it
+    pub created_at: Option<String>,
+    /// Role, content and timestamp of each non-empty message, in order
+    pub messages: Vec<(String, String, Option<String>)>,
+    /// Attachments and files the export leaves out or that can't be read
+    pub warnings: Vec<String>,
+}
+
+/// Read every conversation in an export, most recently active first
+/// The file is parsed once, so this suits bulk imports of large exports.
+pub fn read_export(path: &Path) -> Result<Vec<ExportedConversation>> {
+    let mut conversations = load_conversations(path)?;
+    conversations.sort_by(|a, b| b.last_activity().cmp(a.last_activity()));
+
+    Ok(conversations
+        .into_iter()
+        .map(|conversation| {
+            let mut warnings = Vec::new();
+            let messages = conversation.messages(&mut warnings);
+            ExportedConversation {
+                messages,
+                warnings,
+                uuid: conversation.uuid,
+                name: conversation.name,
+                created_at: conversation.created_at,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct Conversation {
+    uuid: String,
+    #[serde(default)]
+    name: Option<String>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    #[serde(default)]
+    chat_messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    sender: String,
+    #[serde(default)]
+    text: String,
+    created_at: Option<String>,
+    #[serde(default)]
+    attachments: Vec<Value>,
+    #[serde(default)]
+    files: Vec<Value>,
+}
+
+impl Conversation {
+    fn last_activity(&self) -> &str {
+        self.updated_at.as_deref().or(self.created_at.as_deref()).unwrap_or_default()
+    }
+
+    /// Role, content and timestamp of each non-empty message, in order
+    /// `human` becomes `user`; attachments are folded into the message text.
+    fn messages(&self, warnings: &mut Vec<String>) -> Vec<(String, String, Option<String>)> {
+        self.chat_messages
+            .iter()
+            .filter_map(|message| {
+                let role = match message.sender.as_str() {
+                    "human" => "user",
+                    other => other,
+                };
+                let content = self.with_attachments(message, warnings);
+                (!content.trim().is_empty()).then(|| (role.to_string(), content, message.created_at.clone()))
+            })
+            .collect()
+    }
+
+    /// The message text followed by its attachments
+    ///
+    /// Pasted or uploaded text the export carries (`extracted_content`) is
+    /// kept under an `[attachment: name]` marker. Files the export only names
+    /// get a `[file: name]` marker and a warning, as does any attachment
+    /// reference that can't be read.
+    fn with_attachments(&self, message: &ChatMessage, warnings: &mut Vec<String>) -> String {
+        let mut content = message.text.clone();
+        let title = self.name.as_deref().unwrap_or(&self.uuid);
+
+        for attachment in &message.attachments {
+            let name = attachment.get("file_name").and_then(Value::as_str);
+            match (name, attachment.get("extracted_content").and_then(Value::as_str)) {
+                (name, Some(extracted)) => {
+                    push_block(&mut content, &format!("[attachment: {}]\n{}", name.unwrap_or("unnamed"), extracted));
+                }
+                (Some(name), None) => {
+                    warnings.push(format!("Attachment {} in '{}' has no content in the export", name, title));
+                    push_block(&mut content, &format!("[attachment: {}]", name));
+                }
+                (None, None) => warnings.push(format!("Skipping unreadable attachment in '{}'", title)),
+            }
+        }
+
+        for file in &message.files {
+            match file.get("file_name").and_then(Value::as_str) {
+                Some(name) => {
+                    warnings.push(format!("File {} in '{}' is not included in the export", name, title));
+                    push_block(&mut content, &format!("[file: {}]", name));
+                }
+                None => warnings.push(format!("Skipping unreadable file reference in '{}'", title)),
+            }
+        }
+
+        content
+    }
+}
+
+fn push_block(content: &mut String, block: &str) {
+    if !content.is_empty() {
+        content.push_str("\n\n");
+    }
+    content.push_str(block);
+}
+
+fn load_conversations(path: &Path) -> Result<Vec<Conversation>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&raw).with_context(|| format!("Invalid Claude.ai export: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const EXPORT: &str = r#"[
+        {"uuid": "c-old", "name": "Borrowing", "created_at": "2025-11-08T09:00:00Z", "updated_at": "2025-11-08T09:10:00Z", "account": {"uuid": "a"},
+         "chat_messages": [
+            {"uuid": "m1", "sender": "human", "text": "Why won't this borrow?", "created_at": "2025-11-08T09:00:00Z",
+             "attachments": [{"file_name": "main.rs", "extracted_content": "fn main() {}"}, "bogus"], "files": [{"file_name": "diagram.png"}]},
+            {"uuid": "m2", "sender": "assistant", "text": "Use &value.", "created_at": "2025-11-08T09:00:05Z", "attachments": [], "files": []}
+         ]},
+        {"uuid": "c-new", "name": "Parser", "created_at": "2025-11-09T10:00:00Z", "updated_at": "2025-11-09T10:30:00Z", "account": {"uuid": "a"},
+         "chat_messages": [
+            {"uuid": "m3", "sender": "human", "text": "Fix the parser", "created_at": "2025-11-09T10:00:00Z"},
+            {"uuid": "m4", "sender": "assistant", "text": "", "created_at": "2025-11-09T10:00:05Z"}
+         ]}
+    ]"#;
+
+    #[test]
+    fn test_stream_and_read_export() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let export = temp_dir.path().join("conversations.json");
+        std::fs::write(&export, EXPORT)?;
+        let adapter = ClaudeAiAdapter::with_export_path(export.clone());
+
+        let latest = adapter.find_latest_session()?;
+        assert_eq!(latest, adapter.session_path("c-new")?);
+
+        let lines: Vec<Value> = adapter
+            .stream_session(&adapter.session_path("c-old")?)?
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<_>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["role"], "user");
+        assert_eq!(
+            lines[0]["content"],
+            "Why won't this borrow?\n\n[attachment: main.rs]\nfn main() {}\n\n[file: diagram.png]"
+        );
+        assert_eq!(lines[1]["timestamp"], "2025-11-08T09:00:05Z");
+        assert!(adapter.stream_session(&adapter.session_path("missing")?).is_err());
+
+        let conversations = read_export(&export)?;
+        let uuids: Vec<&str> = conversations.iter().map(|c| c.uuid.as_str()).collect();
+        assert_eq!(uuids, vec!["c-new", "c-old"]);
+        assert_eq!(conversations[0].messages.len(), 1);
+        assert!(conversations[0].warnings.is_empty());
+        assert_eq!(
+            conversations[1].warnings,
+            vec![
+                "Skipping unreadable attachment in 'Borrowing'".to_string(),
+                "File diagram.png in 'Borrowing' is not included in the export".to_string(),
+            ]
+        );
+
+        // Without a configured export there's nothing to read, and no prompt
+        assert!(ClaudeAiAdapter { export_path: None }.export_file().is_err());
+        Ok(())
+    }
+}
@@ -1,75 +1,315 @@
 // Claude Code log adapter
 // Reads from ~/.claude/projects/<project>/<sessionId>.jsonl files
 
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use color_eyre::{eyre::Context, Result};
+use serde::Deserialize;
 
-use super::LogAdapter;
+use super::{entry_model, newest_first, LogAdapter, SessionMetadata};
+use crate::config::Config;
+use crate::session_ops::check_path_component;
+use crate::thinking::{claude_thinking_text, THINKING_ROLE};
+use crate::tool_capture::TOOL_ROLE;
+use crate::ToolCapture;
 
-pub struct ClaudeCodeAdapter;
+/// One line of a Claude Code session file
+#[derive(Deserialize)]
+struct ClaudeCodeEntry {
+    #[serde(rename = "type")]
+    entry_type: String,
+    /// ID of the event; a replayed event keeps the ID it was first logged with
+    uuid: Option<String>,
+    message: Option<serde_json::Value>,
+    /// Text of a `system` entry
+    content: Option<serde_json::Value>,
+    timestamp: Option<String>,
+}
+
+/// Messages parsed from a Claude Code session file (see [`ClaudeCodeAdapter::parse_entries`])
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClaudeCodeTranscript {
+    /// Timestamp of the first entry that carries one
+    pub start_time: Option<String>,
+    /// Role, content and entry timestamp of each message, in file order
+    pub entries: Vec<(String, String, Option<String>)>,
+    /// Skills invoked through the Skill tool, in order of first use
+    pub skills: Vec<String>,
+}
+
+impl ClaudeCodeTranscript {
+    /// Role/content pairs, as the noise filter and writer take them
+    pub fn messages(&self) -> Vec<(String, String)> {
+        self.entries
+            .iter()
+            .map(|(role, content, _)| (role.clone(), content.clone()))
+            .collect()
+    }
+}
+
+pub struct ClaudeCodeAdapter {
+    projects_dir: Option<PathBuf>,
+}
 
 impl ClaudeCodeAdapter {
     pub fn new() -> Self {
-        ClaudeCodeAdapter
+        Self::from_config(&Config::default())
     }
-}
 
-impl LogAdapter for ClaudeCodeAdapter {
-    fn name(&self) -> &'static str {
-        "claude-code"
+    /// Read sessions from `assistant_paths.claude-code`, or ~/.claude/projects
+    pub fn from_config(config: &Config) -> Self {
+        ClaudeCodeAdapter { projects_dir: config.assistant_path("claude-code") }
     }
 
-    fn find_latest_session(&self) -> Result<PathBuf> {
-        let home = std::env::var("HOME").context("HOME not set")?;
-        let claude_dir = PathBuf::from(home).join(".claude/projects");
-
-        if !claude_dir.exists() {
-            return Err(color_eyre::eyre::eyre!(
-                "Claude Code projects directory not found: {}",
-                claude_dir.display()
-            ));
+    /// Directory holding one subdirectory of <sessionId>.jsonl files per project
+    pub fn projects_dir(&self) -> Result<PathBuf> {
+        self.projects_dir.clone().ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))
+    }
+
+    /// Project directory names under the projects directory, sorted
+    pub fn list_projects(&self) -> Result<Vec<String>> {
+        let claude_dir = self.projects_dir()?;
+        let mut projects = Vec::new();
+        for entry in std::fs::read_dir(&claude_dir)
+            .with_context(|| format!("Failed to read {}", claude_dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
+                    projects.push(name.to_string());
+                }
+            }
         }
+        projects.sort();
+        Ok(projects)
+    }
 
-        // Find latest session file across all project directories
-        let mut latest: Option<(PathBuf, std::time::SystemTime)> = None;
+    /// Directory of a project, given its directory name or the working directory it encodes
+    pub fn project_dir(&self, project: &str) -> Result<PathBuf> {
+        let claude_dir = self.projects_dir()?;
+        for name in [project.to_string(), Self::encode_project_path(project)] {
+            if check_path_component("project", &name).is_err() {
+                continue;
+            }
+            let dir = claude_dir.join(&name);
+            if dir.is_dir() {
+                return Ok(dir);
+            }
+        }
+        Err(color_eyre::eyre::eyre!("Claude Code project not found: {}", project))
+    }
 
-        for project_entry in std::fs::read_dir(&claude_dir)? {
-            let project_dir = project_entry?.path();
-            if !project_dir.is_dir() {
+    /// (session_id, mtime) for each session in a project, most recently modified first
+    pub fn list_sessions_for_project(&self, project: &str) -> Result<Vec<(String, std::time::SystemTime)>> {
+        let mut sessions: Vec<(String, std::time::SystemTime)> = session_files(&self.project_dir(project)?)?
+            .into_iter()
+            .filter_map(|(path, modified)| {
+                path.file_stem().and_then(|s| s.to_str()).map(|id| (id.to_string(), modified))
+            })
+            .collect();
+        sessions.sort_by_key(|&(_, modified)| std::cmp::Reverse(modified));
+        Ok(sessions)
+    }
+
+    /// Project directory name of a session file stored directly in a project under the projects directory
+    pub fn project_of(&self, session_path: &Path) -> Option<String> {
+        let project_dir = session_path.parent()?;
+        if project_dir.parent()? != self.projects_dir.as_deref()? {
+            return None;
+        }
+        project_dir.file_name()?.to_str().map(str::to_string)
+    }
+
+    /// Project directory name Claude Code uses for a working directory
+    /// (`/home/me/my.app` -> `-home-me-my-app`)
+    pub fn encode_project_path(path: &str) -> String {
+        path.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' }).collect()
+    }
+
+    /// Working directory a project directory name stands for, for display
+    ///
+    /// The encoding is lossy: dashes and dots in the original path also
+    /// became `-`, so they come back as `/`.
+    pub fn decode_project_path(encoded: &str) -> String {
+        encoded.replace('-', "/")
+    }
+
+    /// Parse the user, assistant and system messages out of a session file
+    ///
+    /// Assistant text blocks in one entry are joined into a single message.
+    /// Tool calls and results are only kept with `tools`, and thinking blocks
+    /// only with `thinking`. An entry whose `uuid` was already read is skipped,
+    /// since resuming a session replays earlier events into the file; a repeated
+    /// message logged as a new event (a second "yes") is kept.
+    pub fn parse_entries(&self, path: &Path, tools: Option<&ToolCapture>, thinking: bool) -> Result<ClaudeCodeTranscript> {
+        let mut entries = Vec::new();
+        let transcript = self.stream_entries(path, tools, thinking, |role, content, timestamp| {
+            entries.push((role, content, timestamp));
+            Ok(())
+        })?;
+        Ok(ClaudeCodeTranscript { entries, ..transcript })
+    }
+
+    /// [`Self::parse_entries`], handing each message to `on_message` as it is read
+    /// The returned transcript has the start time and skills but no entries.
+    pub fn stream_entries(
+        &self,
+        path: &Path,
+        tools: Option<&ToolCapture>,
+        thinking: bool,
+        mut on_message: impl FnMut(String, String, Option<String>) -> Result<()>,
+    ) -> Result<ClaudeCodeTranscript> {
+        let mut transcript = ClaudeCodeTranscript::default();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut emit = |role: &str, content: String, timestamp: &Option<String>| on_message(role.to_string(), content, timestamp.clone());
+
+        for line_result in self.stream_session(path)? {
+            let entry: ClaudeCodeEntry = serde_json::from_str(&line_result?)
+                .with_context(|| format!("Invalid entry in {}", path.display()))?;
+
+            if transcript.start_time.is_none() {
+                transcript.start_time = entry.timestamp.clone();
+            }
+            if entry.uuid.as_ref().is_some_and(|uuid| !seen.insert(uuid.clone())) {
                 continue;
             }
 
-            for file_entry in std::fs::read_dir(&project_dir)? {
-                let file_path = file_entry?.path();
+            if entry.entry_type == "system" {
+                if let Some(content) = entry.content.as_ref().and_then(|c| c.as_str()).filter(|c| !c.trim().is_empty()) {
+                    emit("system", content.to_string(), &entry.timestamp)?;
+                }
+                continue;
+            }
+            if entry.entry_type != "user" && entry.entry_type != "assistant" {
+                continue;
+            }
+            let Some(msg) = entry.message else {
+                continue;
+            };
+            let timestamp = &entry.timestamp;
 
-                // Skip files that start with "agent-" (those are agent-specific logs)
-                if let Some(filename) = file_path.file_name().and_then(|s| s.to_str()) {
-                    if filename.starts_with("agent-") {
-                        continue;
+            match msg["role"].as_str().unwrap_or("") {
+                "user" => {
+                    // User messages have content as a string
+                    if let Some(content) = msg["content"].as_str() {
+                        emit("user", content.to_string(), timestamp)?;
+                    } else if let (Some(tools), Some(blocks)) = (tools, msg["content"].as_array()) {
+                        // Tool results come back as user messages with block content
+                        for block in blocks {
+                            if let Some(text) = tools.format_claude_block(block) {
+                                emit(TOOL_ROLE, text, timestamp)?;
+                            }
+                        }
                     }
                 }
+                "assistant" => {
+                    // Assistant messages have content as an array
+                    let Some(blocks) = msg["content"].as_array() else {
+                        continue;
+                    };
 
-                // Only process UUID.jsonl files (session files)
-                if file_path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                    let metadata = std::fs::metadata(&file_path)?;
-                    let modified = metadata.modified()?;
+                    for block in blocks {
+                        if block.get("type").and_then(|v| v.as_str()) == Some("tool_use")
+                            && block.get("name").and_then(|v| v.as_str()) == Some("Skill")
+                        {
+                            if let Some(skill) = block.pointer("/input/skill").and_then(|v| v.as_str()) {
+                                if !transcript.skills.iter().any(|s| s == skill) {
+                                    transcript.skills.push(skill.to_string());
+                                }
+                            }
+                        }
+                    }
 
-                    if latest.is_none() || modified > latest.as_ref().unwrap().1 {
-                        latest = Some((file_path, modified));
+                    if thinking {
+                        for text in blocks.iter().filter_map(claude_thinking_text) {
+                            emit(THINKING_ROLE, text.to_string(), timestamp)?;
+                        }
+                    }
+
+                    let text = blocks
+                        .iter()
+                        .filter(|block| block["type"].as_str() == Some("text"))
+                        .filter_map(|block| block["text"].as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    if !text.is_empty() {
+                        emit("assistant", text, timestamp)?;
+                    }
+
+                    if let Some(tools) = tools {
+                        for text in blocks.iter().filter_map(|block| tools.format_claude_block(block)) {
+                            emit(TOOL_ROLE, text, timestamp)?;
+                        }
                     }
                 }
+                _ => {}
+            }
+        }
+
+        Ok(transcript)
+    }
+}
+
+/// Session files (<sessionId>.jsonl, skipping agent-* logs) in one project directory, with their mtimes
+fn session_files(project_dir: &Path) -> Result<Vec<(PathBuf, std::time::SystemTime)>> {
+    let mut files = Vec::new();
+    for file_entry in std::fs::read_dir(project_dir)? {
+        let file_path = file_entry?.path();
+
+        // Skip files that start with "agent-" (those are agent-specific logs)
+        if let Some(filename) = file_path.file_name().and_then(|s| s.to_str()) {
+            if filename.starts_with("agent-") {
+                continue;
             }
         }
 
-        latest
-            .map(|(path, _)| path)
+        // Only process UUID.jsonl files (session files)
+        if file_path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
+            let modified = std::fs::metadata(&file_path)?.modified()?;
+            files.push((file_path, modified));
+        }
+    }
+    Ok(files)
+}
+
+impl Default for ClaudeCodeAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAdapter for ClaudeCodeAdapter {
+    fn name(&self) -> &'static str {
+        "claude-code"
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        self.list_sessions()?
+            .into_iter()
+            .next()
             .ok_or_else(|| color_eyre::eyre::eyre!("No Claude Code session files found"))
     }
 
-    fn stream_session(&self, path: &PathBuf) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        let claude_dir = self.projects_dir()?;
+        let mut files = Vec::new();
+
+        for project_entry in std::fs::read_dir(&claude_dir)
+            .with_context(|| format!("Claude Code projects directory not found: {}", claude_dir.display()))?
+        {
+            let project_dir = project_entry?.path();
+            if project_dir.is_dir() {
+                files.extend(session_files(&project_dir)?);
+            }
+        }
+
+        Ok(newest_first(files))
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
         let file = File::open(path)
             .with_context(|| format!("Failed to open {}", path.display()))?;
         let reader = BufReader::new(file);
@@ -78,4 +318,140 @@ impl LogAdapter for ClaudeCodeAdapter {
             line.map_err(|e| color_eyre::eyre::eyre!("Failed to read line: {}", e))
         })))
     }
+
+    /// Parses only the first and last lines; the rest are just counted
+    /// The model comes from the last line (an assistant reply, usually), else the first.
+    fn session_metadata(&self, path: &Path) -> Result<SessionMetadata> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+
+        let mut message_count = 0;
+        let mut first = None;
+        let mut last = None;
+        for line in BufReader::new(file).lines() {
+            let line = line.with_context(|| format!("Failed to read {}", path.display()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            message_count += 1;
+            if first.is_none() {
+                first = Some(line);
+            } else {
+                last = Some(line);
+            }
+        }
+
+        let parse = |line: Option<String>| line.and_then(|line| serde_json::from_str::<serde_json::Value>(&line).ok());
+        let first = parse(first);
+        let last = parse(last).or_else(|| first.clone());
+        let timestamp = |entry: &Option<serde_json::Value>| {
+            entry.as_ref()?.get("timestamp")?.as_str().map(str::to_string)
+        };
+
+        let model = [&last, &first].into_iter().flatten().find_map(entry_model);
+
+        Ok(SessionMetadata {
+            message_count,
+            start_time: timestamp(&first),
+            end_time: timestamp(&last),
+            model,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_entries() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("session.jsonl");
+        let lines = [
+            r#"{"type":"summary","timestamp":"2025-11-08T09:00:00Z"}"#,
+            r#"{"type":"system","timestamp":"2025-11-08T09:00:00Z","content":"Working directory is /home/me/app","level":"info"}"#,
+            r#"{"type":"user","uuid":"u1","timestamp":"2025-11-08T09:00:01Z","message":{"role":"user","content":"Fix the build"}}"#,
+            r#"{"type":"assistant","timestamp":"2025-11-08T09:00:02Z","message":{"role":"assistant","content":[{"type":"thinking","thinking":"Check Cargo.toml"},{"type":"text","text":"Looking"},{"type":"tool_use","name":"Skill","input":{"skill":"rust"}},{"type":"text","text":"now."}]}}"#,
+            r#"{"type":"user","timestamp":"2025-11-08T09:00:03Z","message":{"role":"user","content":[{"type":"tool_result","content":"ok"}]}}"#,
+            // A resumed session replays earlier events under their original IDs
+            r#"{"type":"user","uuid":"u1","timestamp":"2025-11-08T09:00:01Z","message":{"role":"user","content":"Fix the build"}}"#,
+            // Saying the same thing again is a new event
+            r#"{"type":"user","uuid":"u2","timestamp":"2025-11-08T09:05:00Z","message":{"role":"user","content":"Fix the build"}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n"))?;
+        let adapter = ClaudeCodeAdapter::new();
+
+        let plain = adapter.parse_entries(&path, None, false)?;
+        assert_eq!(plain.start_time.as_deref(), Some("2025-11-08T09:00:00Z"));
+        assert_eq!(plain.skills, vec!["rust".to_string()]);
+        assert_eq!(
+            plain.entries,
+            vec![
+                ("system".to_string(), "Working directory is /home/me/app".to_string(), Some("2025-11-08T09:00:00Z".to_string())),
+                ("user".to_string(), "Fix the build".to_string(), Some("2025-11-08T09:00:01Z".to_string())),
+                ("assistant".to_string(), "Looking\nnow.".to_string(), Some("2025-11-08T09:00:02Z".to_string())),
+                ("user".to_string(), "Fix the build".to_string(), Some("2025-11-08T09:05:00Z".to_string())),
+            ]
+        );
+
+        let tools = ToolCapture::new(None);
+        let full = adapter.parse_entries(&path, Some(&tools), true)?;
+        let roles: Vec<&str> = full.entries.iter().map(|(role, _, _)| role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "user", THINKING_ROLE, "assistant", TOOL_ROLE, TOOL_ROLE, "user"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_metadata_from_first_and_last_lines() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("session.jsonl");
+        let lines = [
+            r#"{"type":"user","timestamp":"2025-11-08T09:00:01Z","message":{"role":"user","content":"Fix the build"}}"#,
+            "",
+            r#"{"type":"assistant","timestamp":"2025-11-08T09:00:02Z","message":{"role":"assistant","content":"Done"}}"#,
+            r#"{"type":"assistant","timestamp":"2025-11-08T09:04:00Z","message":{"role":"assistant","model":"claude-sonnet-4-5","content":"Built"}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n"))?;
+
+        let metadata = ClaudeCodeAdapter::new().session_metadata(&path)?;
+        assert_eq!(
+            metadata,
+            SessionMetadata {
+                message_count: 3,
+                start_time: Some("2025-11-08T09:00:01Z".to_string()),
+                end_time: Some("2025-11-08T09:04:00Z".to_string()),
+                model: Some("claude-sonnet-4-5".to_string()),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_projects_and_sessions() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let mut config = Config::default();
+        config.assistant_paths.insert("claude-code".to_string(), temp_dir.path().to_path_buf());
+        let adapter = ClaudeCodeAdapter::from_config(&config);
+
+        let project = temp_dir.path().join("-home-me-app");
+        std::fs::create_dir_all(&project)?;
+        std::fs::create_dir_all(temp_dir.path().join("-tmp"))?;
+        let old = std::fs::File::create(project.join("older.jsonl"))?;
+        old.set_modified(std::time::SystemTime::UNIX_EPOCH)?;
+        std::fs::write(project.join("newer.jsonl"), "")?;
+        std::fs::write(project.join("agent-1.jsonl"), "")?;
+        std::fs::write(project.join("notes.txt"), "")?;
+
+        assert_eq!(adapter.list_projects()?, vec!["-home-me-app".to_string(), "-tmp".to_string()]);
+        let ids: Vec<String> = adapter.list_sessions_for_project("/home/me/app")?.into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, vec!["newer".to_string(), "older".to_string()]);
+        assert!(adapter.list_sessions_for_project("../etc").is_err());
+        assert_eq!(adapter.project_of(&project.join("newer.jsonl")).as_deref(), Some("-home-me-app"));
+        assert_eq!(adapter.project_of(Path::new("/elsewhere/-home-me-app/newer.jsonl")), None);
+
+        assert_eq!(ClaudeCodeAdapter::encode_project_path("/home/me/my.app"), "-home-me-my-app");
+        assert_eq!(ClaudeCodeAdapter::decode_project_path("-home-me-app"), "/home/me/app");
+        Ok(())
+    }
 }
@@ -2,17 +2,38 @@
 
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use color_eyre::{eyre::Context, Result};
+use rayon::prelude::*;
 
-use super::LogAdapter;
+use super::{newest_first, LogAdapter};
+use crate::config::Config;
 
-pub struct CodexAdapter;
+pub struct CodexAdapter {
+    sessions_dir: Option<PathBuf>,
+}
 
 impl CodexAdapter {
     pub fn new() -> Self {
-        CodexAdapter
+        Self::from_config(&Config::default())
+    }
+
+    /// Read sessions from `assistant_paths.codex`, or ~/.codex/sessions
+    pub fn from_config(config: &Config) -> Self {
+        CodexAdapter { sessions_dir: config.assistant_path("codex") }
+    }
+
+    /// Directory holding the <YYYY>/<MM>/<DD>/*.jsonl session tree
+    pub fn sessions_dir(&self) -> Result<PathBuf> {
+        self.sessions_dir.clone().ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))
+    }
+}
+
+impl Default for CodexAdapter {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -22,8 +43,7 @@ impl LogAdapter for CodexAdapter {
     }
 
     fn find_latest_session(&self) -> Result<PathBuf> {
-        let home = std::env::var("HOME").context("HOME not set")?;
-        let sessions_dir = PathBuf::from(home).join(".codex/sessions");
+        let sessions_dir = self.sessions_dir()?;
 
         if !sessions_dir.exists() {
             return Err(color_eyre::eyre::eyre!(
@@ -32,54 +52,225 @@ impl LogAdapter for CodexAdapter {
             ));
         }
 
-        // Find latest session file by scanning date directories
-        let mut latest: Option<(PathBuf, std::time::SystemTime)> = None;
+        latest_session_file(&sessions_dir)?
+            .latest
+            .map(|(path, _)| path)
+            .ok_or_else(|| color_eyre::eyre::eyre!("No Codex session files found"))
+    }
+
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        let sessions_dir = self.sessions_dir()?;
 
-        for year_entry in std::fs::read_dir(&sessions_dir)? {
-            let year_dir = year_entry?.path();
-            if !year_dir.is_dir() {
-                continue;
-            }
+        if !sessions_dir.exists() {
+            return Err(color_eyre::eyre::eyre!(
+                "Codex sessions directory not found: {}",
+                sessions_dir.display()
+            ));
+        }
+
+        Ok(newest_first(session_files(&sessions_dir)?))
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let reader = BufReader::new(file);
+
+        Ok(Box::new(reader.lines().map(|line| {
+            line.map_err(|e| color_eyre::eyre::eyre!("Failed to read line: {}", e))
+        })))
+    }
+}
 
-            for month_entry in std::fs::read_dir(&year_dir)? {
-                let month_dir = month_entry?.path();
-                if !month_dir.is_dir() {
+/// Find the most recently modified session file under a Codex sessions tree
+/// Layout: <sessions_dir>/<YYYY>/<MM>/<DD>/*.jsonl
+///
+/// Year and month directories are listed serially (there are few of them);
+/// the day directories, which hold the bulk of the files, are scanned in
+/// parallel. Ties on mtime resolve to the greater path so the result doesn't
+/// depend on scan order. Directories and files below `sessions_dir` that
+/// can't be read are skipped and listed in [`LatestSession::skipped`].
+pub fn latest_session_file(sessions_dir: &Path) -> Result<LatestSession> {
+    let mut skipped = Vec::new();
+    let per_day: Vec<LatestSession> = day_dirs(sessions_dir, &mut skipped)?
+        .par_iter()
+        .map(|day_dir| {
+            let mut day = LatestSession::default();
+            let entries = match std::fs::read_dir(day_dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    day.skipped.push(format!("{}: {}", day_dir.display(), e));
+                    return day;
+                }
+            };
+            for file_entry in entries {
+                let file_path = match file_entry {
+                    Ok(entry) => entry.path(),
+                    Err(e) => {
+                        day.skipped.push(format!("{}: {}", day_dir.display(), e));
+                        continue;
+                    }
+                };
+                if file_path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
                     continue;
                 }
-
-                for day_entry in std::fs::read_dir(&month_dir)? {
-                    let day_dir = day_entry?.path();
-                    if !day_dir.is_dir() {
+                let modified = match std::fs::metadata(&file_path).and_then(|m| m.modified()) {
+                    Ok(modified) => modified,
+                    Err(e) => {
+                        day.skipped.push(format!("{}: {}", file_path.display(), e));
                         continue;
                     }
+                };
+                if day.latest.as_ref().is_none_or(|(p, m)| (modified, &file_path) > (*m, p)) {
+                    day.latest = Some((file_path, modified));
+                }
+            }
+            day
+        })
+        .collect();
 
-                    for file_entry in std::fs::read_dir(&day_dir)? {
-                        let file_path = file_entry?.path();
-                        if file_path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-                            let metadata = std::fs::metadata(&file_path)?;
-                            let modified = metadata.modified()?;
+    let mut latest = LatestSession { latest: None, skipped };
+    for day in per_day {
+        latest.skipped.extend(day.skipped);
+        if let Some((path, modified)) = day.latest {
+            if latest.latest.as_ref().is_none_or(|(p, m)| (modified, &path) > (*m, p)) {
+                latest.latest = Some((path, modified));
+            }
+        }
+    }
+    Ok(latest)
+}
 
-                            if latest.is_none() || modified > latest.as_ref().unwrap().1 {
-                                latest = Some((file_path, modified));
-                            }
-                        }
-                    }
+/// What [`latest_session_file`] found
+#[derive(Debug, Default)]
+pub struct LatestSession {
+    /// The newest session file and its mtime, if there is one
+    pub latest: Option<(PathBuf, SystemTime)>,
+    /// Entries that couldn't be read, each as "<path>: <error>"
+    pub skipped: Vec<String>,
+}
+
+/// Every session file under a Codex sessions tree, with its mtime, in no particular order
+pub fn session_files(sessions_dir: &Path) -> Result<Vec<(PathBuf, SystemTime)>> {
+    let mut skipped = Vec::new();
+    let day_dirs = day_dirs(sessions_dir, &mut skipped)?;
+    if let Some(unreadable) = skipped.first() {
+        return Err(color_eyre::eyre::eyre!("Failed to read {}", unreadable));
+    }
+    let per_day = day_dirs
+        .par_iter()
+        .map(|day_dir| -> Result<Vec<(PathBuf, SystemTime)>> {
+            let mut files = Vec::new();
+            for file_entry in std::fs::read_dir(day_dir)? {
+                let file_path = file_entry?.path();
+                if file_path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
+                    let modified = std::fs::metadata(&file_path)?.modified()?;
+                    files.push((file_path, modified));
                 }
             }
+            Ok(files)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(per_day.into_iter().flatten().collect())
+}
+
+/// The <YYYY>/<MM>/<DD> directories of a Codex sessions tree
+/// Year and month directories that can't be listed are added to `skipped`.
+fn day_dirs(sessions_dir: &Path, skipped: &mut Vec<String>) -> Result<Vec<PathBuf>> {
+    fn subdirs(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+        let mut dirs = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            }
         }
+        Ok(dirs)
+    }
+    let mut listed = |dir: &Path| {
+        subdirs(dir).unwrap_or_else(|e| {
+            skipped.push(format!("{}: {}", dir.display(), e));
+            Vec::new()
+        })
+    };
 
-        latest
-            .map(|(path, _)| path)
-            .ok_or_else(|| color_eyre::eyre::eyre!("No Codex session files found"))
+    let mut day_dirs = Vec::new();
+    let year_dirs = subdirs(sessions_dir).with_context(|| format!("Failed to read {}", sessions_dir.display()))?;
+    for year_dir in year_dirs {
+        for month_dir in listed(&year_dir) {
+            day_dirs.extend(listed(&month_dir));
+        }
     }
+    Ok(day_dirs)
+}
 
-    fn stream_session(&self, path: &PathBuf) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
-        let file = File::open(path)
-            .with_context(|| format!("Failed to open {}", path.display()))?;
-        let reader = BufReader::new(file);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::tempdir;
 
-        Ok(Box::new(reader.lines().map(|line| {
-            line.map_err(|e| color_eyre::eyre::eyre!("Failed to read line: {}", e))
-        })))
+    fn touch(path: &Path, modified: SystemTime) -> Result<()> {
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, "{}\n")?;
+        File::options().write(true).open(path)?.set_modified(modified)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_latest_session_file_in_nested_tree() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let root = temp_dir.path();
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        // Newest by mtime lives in an older day directory (a resumed session)
+        let newest = root.join("2024/12/30/rollout-resumed.jsonl");
+        for (i, day) in (1..=28).enumerate() {
+            touch(&root.join(format!("2025/02/{:02}/rollout-{}.jsonl", day, i)), base + Duration::from_secs(i as u64))?;
+        }
+        touch(&root.join("2025/03/01/rollout-newest-day.jsonl"), base + Duration::from_secs(100))?;
+        touch(&newest, base + Duration::from_secs(500))?;
+        fs::write(root.join("2025/03/01/notes.txt"), "ignored")?;
+        File::options().write(true).open(root.join("2025/03/01/notes.txt"))?.set_modified(base + Duration::from_secs(900))?;
+
+        let (found, modified) = latest_session_file(root)?.latest.unwrap();
+        assert_eq!(found, newest);
+        assert_eq!(modified, base + Duration::from_secs(500));
+
+        let mut config = Config::default();
+        config.assistant_paths.insert("codex".to_string(), root.to_path_buf());
+        let listed = CodexAdapter::from_config(&config).list_sessions()?;
+        assert_eq!(listed.len(), 30);
+        assert_eq!(listed[0], newest);
+        assert_eq!(listed.last(), Some(&root.join("2025/02/01/rollout-0.jsonl")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_latest_session_file_empty_tree() -> Result<()> {
+        let temp_dir = tempdir()?;
+        fs::create_dir_all(temp_dir.path().join("2025/01/01"))?;
+        assert!(latest_session_file(temp_dir.path())?.latest.is_none());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_latest_session_file_skips_unreadable_entries() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let root = temp_dir.path();
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        touch(&root.join("2025/03/01/rollout-ok.jsonl"), base)?;
+        let dangling = root.join("2025/03/02/rollout-dangling.jsonl");
+        fs::create_dir_all(dangling.parent().unwrap())?;
+        std::os::unix::fs::symlink(root.join("missing.jsonl"), &dangling)?;
+
+        let scan = latest_session_file(root)?;
+        assert_eq!(scan.latest.map(|(path, _)| path), Some(root.join("2025/03/01/rollout-ok.jsonl")));
+        assert_eq!(scan.skipped.len(), 1);
+        assert!(scan.skipped[0].starts_with(&dangling.display().to_string()));
+        Ok(())
     }
 }
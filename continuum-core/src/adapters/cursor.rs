@@ -0,0 +1,494 @@
+// Cursor adapter - reads AI chat tabs from Cursor's per-workspace SQLite state DBs,
+// and conversation logs saved as JSON under ~/.cursor/logs
+// The schema is undocumented, so this is best effort: rows and bubbles that can't be decoded are skipped
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::Context, Result};
+use rusqlite::{types::ValueRef, Connection, OpenFlags};
+use serde_json::{json, Value};
+
+use super::{newest_first, LogAdapter};
+use crate::config::Config;
+use crate::{Message, Role};
+
+/// File name of the state DB inside each workspace storage directory
+pub const STATE_DB_FILE: &str = "state.vscdb";
+
+/// Environment variable overriding the workspaceStorage directory to scan
+pub const STORAGE_DIR_ENV: &str = "CONTINUUM_CURSOR_STORAGE";
+
+/// ItemTable keys holding chat tabs end with this suffix
+/// (e.g. `workbench.panel.aichat.view.aichat.chatdata`)
+const CHAT_DATA_SUFFIX: &str = "chatdata";
+
+pub struct CursorAdapter {
+    storage_dir: Option<PathBuf>,
+    logs_dir: Option<PathBuf>,
+}
+
+impl CursorAdapter {
+    /// Use $CONTINUUM_CURSOR_STORAGE if set, otherwise Cursor's default location
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Use $CONTINUUM_CURSOR_STORAGE if set, then `assistant_paths.cursor`,
+    /// otherwise Cursor's default location
+    pub fn from_config(config: &Config) -> Self {
+        let storage_dir = std::env::var_os(STORAGE_DIR_ENV)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| config.assistant_path("cursor"));
+        CursorAdapter { storage_dir, logs_dir: config.assistant_path("cursor-logs") }
+    }
+
+    /// Scan a specific workspaceStorage directory
+    pub fn with_storage_dir(path: PathBuf) -> Self {
+        CursorAdapter { storage_dir: Some(path), logs_dir: None }
+    }
+
+    /// Also look for `<session>.json` conversation logs in `path`
+    pub fn with_logs_dir(mut self, path: PathBuf) -> Self {
+        self.logs_dir = Some(path);
+        self
+    }
+
+    /// The most recently modified `.json` conversation log, if any
+    pub fn latest_log(&self) -> Option<(PathBuf, std::time::SystemTime)> {
+        let entries = std::fs::read_dir(self.logs_dir.as_ref()?).ok()?;
+
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| is_json_log(path))
+            .filter_map(|path| Some((path.clone(), std::fs::metadata(&path).and_then(|m| m.modified()).ok()?)))
+            .max_by(|(a_path, a_mod), (b_path, b_mod)| (a_mod, a_path).cmp(&(b_mod, b_path)))
+    }
+
+    /// Directory holding one subdirectory (with a state.vscdb) per workspace
+    pub fn storage_dir(&self) -> Result<PathBuf> {
+        self.storage_dir.clone().ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))
+    }
+
+    /// Every workspace state DB, most recently updated first
+    pub fn workspace_dbs(&self) -> Result<Vec<PathBuf>> {
+        let dir = self.storage_dir()?;
+        let entries = std::fs::read_dir(&dir).with_context(|| {
+            format!("Cursor workspace storage not found: {} (set {} to override)", dir.display(), STORAGE_DIR_ENV)
+        })?;
+
+        let mut dbs: Vec<(PathBuf, std::time::SystemTime)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let db = entry.path().join(STATE_DB_FILE);
+                let modified = std::fs::metadata(&db).and_then(|m| m.modified()).ok()?;
+                Some((db, modified))
+            })
+            .collect();
+        dbs.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+
+        Ok(dbs.into_iter().map(|(db, _)| db).collect())
+    }
+
+    /// Resolve `--session` to a JSON log or a pseudo-path: /path/to/state.vscdb#<tab id>
+    ///
+    /// Accepts a JSON log, a full pseudo-path, a state DB (its latest tab is
+    /// used), or a bare ID, which is looked up as `<logs dir>/<id>.json` and
+    /// then as a tab ID across all workspace DBs.
+    pub fn session_path(&self, session: &str) -> Result<PathBuf> {
+        if session.contains('#') {
+            return Ok(PathBuf::from(session));
+        }
+
+        let path = Path::new(session);
+        if is_json_log(path) && path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+        if let Some(log) = self.logs_dir.as_ref().map(|dir| dir.join(format!("{}.json", session))) {
+            if log.is_file() {
+                return Ok(log);
+            }
+        }
+        if path.is_file() {
+            let tab = latest_tab(path)?
+                .ok_or_else(|| color_eyre::eyre::eyre!("No chat tabs found in {}", path.display()))?;
+            return Ok(tab_path(path, &tab.id));
+        }
+
+        for db in self.workspace_dbs()? {
+            if read_tabs(&db).unwrap_or_default().iter().any(|tab| tab.id == session) {
+                return Ok(tab_path(&db, session));
+            }
+        }
+
+        Err(color_eyre::eyre::eyre!("Cursor chat tab '{}' not found in any workspace DB", session))
+    }
+}
+
+impl Default for CursorAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAdapter for CursorAdapter {
+    fn name(&self) -> &'static str {
+        "cursor"
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        let latest_log = self.latest_log();
+        let dbs = match self.workspace_dbs() {
+            Ok(dbs) => dbs,
+            Err(e) if latest_log.is_none() => return Err(e),
+            Err(_) => Vec::new(),
+        };
+
+        // The newest DB may belong to a workspace that never used chat, so keep looking
+        for db in dbs {
+            if let Ok(Some(tab)) = latest_tab(&db) {
+                let db_modified = std::fs::metadata(&db).and_then(|m| m.modified()).ok();
+                return Ok(match latest_log {
+                    Some((log, log_modified)) if Some(log_modified) > db_modified => log,
+                    _ => tab_path(&db, &tab.id),
+                });
+            }
+        }
+
+        if let Some((log, _)) = latest_log {
+            return Ok(log);
+        }
+        Err(color_eyre::eyre::eyre!("No Cursor chat tabs found in {}", self.storage_dir()?.display()))
+    }
+
+    /// JSON conversation logs by mtime and chat tabs by their last prompt, newest first
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        let mut sessions = Vec::new();
+
+        if let Some(entries) = self.logs_dir.as_ref().and_then(|dir| std::fs::read_dir(dir).ok()) {
+            sessions.extend(
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| is_json_log(path))
+                    .filter_map(|path| Some((path.clone(), std::fs::metadata(&path).and_then(|m| m.modified()).ok()?))),
+            );
+        }
+
+        let dbs = match self.workspace_dbs() {
+            Ok(dbs) => dbs,
+            Err(e) if sessions.is_empty() => return Err(e),
+            Err(_) => Vec::new(),
+        };
+        for db in dbs {
+            for tab in read_tabs(&db).unwrap_or_default() {
+                let sent = tab
+                    .last_send_time
+                    .and_then(|ms| u64::try_from(ms).ok())
+                    .map(|ms| std::time::UNIX_EPOCH + std::time::Duration::from_millis(ms))
+                    .unwrap_or(std::time::UNIX_EPOCH);
+                sessions.push((tab_path(&db, &tab.id), sent));
+            }
+        }
+
+        Ok(newest_first(sessions))
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        if is_json_log(path) {
+            let json_messages: Vec<Result<String>> = parse_cursor_session(path)?
+                .into_iter()
+                .map(|message| Ok(serde_json::to_string(&message)?))
+                .collect();
+            return Ok(Box::new(json_messages.into_iter()));
+        }
+
+        let path_str = path.to_string_lossy();
+        let Some((db, tab_id)) = path_str.rsplit_once('#') else {
+            return Err(color_eyre::eyre::eyre!("Invalid Cursor session path (expected state.vscdb#<tab id>)"));
+        };
+
+        let tab = read_tabs(Path::new(db))?
+            .into_iter()
+            .find(|tab| tab.id == tab_id)
+            .ok_or_else(|| color_eyre::eyre::eyre!("Chat tab '{}' not found in {}", tab_id, db))?;
+
+        // Emit one JSON object per bubble (compatible with LogAdapter interface)
+        let json_messages: Vec<Result<String>> = tab
+            .messages
+            .into_iter()
+            .map(|(role, content)| Ok(json!({ "role": role, "content": content }).to_string()))
+            .collect();
+
+        Ok(Box::new(json_messages.into_iter()))
+    }
+}
+
+/// Whether `path` names a JSON conversation log rather than a state DB or tab
+fn is_json_log(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("json") && !path.to_string_lossy().contains('#')
+}
+
+/// Read a conversation log: a JSON array of `{role, content, timestamp}` objects
+///
+/// Roles are `user`, `assistant` (or `ai`) and `system`; other entries and
+/// empty messages are skipped. Timestamps may be strings or epoch milliseconds.
+pub fn parse_cursor_session(path: &Path) -> Result<Vec<Message>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let entries: Vec<Value> = serde_json::from_str(&raw)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    Ok(entries
+        .iter()
+        .filter_map(|entry| {
+            let role = match entry.get("role").and_then(Value::as_str)? {
+                "user" => Role::User,
+                "assistant" | "ai" => Role::Assistant,
+                "system" => Role::System,
+                _ => return None,
+            };
+            let content = entry.get("content").and_then(Value::as_str).map(str::trim)?;
+            if content.is_empty() {
+                return None;
+            }
+
+            let timestamp = match entry.get("timestamp") {
+                Some(Value::String(ts)) => Some(ts.clone()),
+                Some(Value::Number(ms)) => ms
+                    .as_i64()
+                    .and_then(chrono::DateTime::from_timestamp_millis)
+                    .map(|ts| ts.to_rfc3339()),
+                _ => None,
+            };
+
+            Some(Message { role, content: content.to_string(), timestamp })
+        })
+        .collect())
+}
+
+/// One AI chat tab from a workspace DB
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorTab {
+    pub id: String,
+    pub title: Option<String>,
+    /// Milliseconds since the epoch of the last prompt sent, when recorded
+    pub last_send_time: Option<i64>,
+    /// (role, content) pairs in conversation order
+    pub messages: Vec<(String, String)>,
+}
+
+/// Pseudo-path addressing one chat tab: /path/to/state.vscdb#<tab id>
+pub fn tab_path(db: &Path, tab_id: &str) -> PathBuf {
+    PathBuf::from(format!("{}#{}", db.display(), tab_id))
+}
+
+/// The tab with the most recent prompt in a state DB
+pub fn latest_tab(db: &Path) -> Result<Option<CursorTab>> {
+    // Tabs without a send time sort first, so the last of those wins over none at all
+    Ok(read_tabs(db)?.into_iter().max_by_key(|tab| tab.last_send_time))
+}
+
+/// Read every chat tab with at least one decodable message from a state DB
+pub fn read_tabs(db: &Path) -> Result<Vec<CursorTab>> {
+    // Read-only, since Cursor may have the DB open
+    let conn = Connection::open_with_flags(db, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {}", db.display()))?;
+
+    let mut stmt = conn.prepare("SELECT value FROM ItemTable WHERE key LIKE ?1")?;
+    let mut rows = stmt.query([format!("%{}", CHAT_DATA_SUFFIX)])?;
+
+    let mut tabs = Vec::new();
+    while let Some(row) = rows.next()? {
+        // Values are usually TEXT but some versions store BLOBs
+        let raw = match row.get_ref(0)? {
+            ValueRef::Text(bytes) | ValueRef::Blob(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+            _ => continue,
+        };
+        let Ok(data) = serde_json::from_str::<Value>(&raw) else {
+            continue;
+        };
+
+        tabs.extend(
+            data.get("tabs")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(parse_tab),
+        );
+    }
+
+    Ok(tabs)
+}
+
+/// Decode one entry of a chatdata `tabs` array; `None` if it has no ID or messages
+pub fn parse_tab(tab: &Value) -> Option<CursorTab> {
+    let id = tab.get("tabId").and_then(Value::as_str)?.to_string();
+    let messages: Vec<(String, String)> = tab
+        .get("bubbles")
+        .and_then(Value::as_array)?
+        .iter()
+        .filter_map(parse_bubble)
+        .collect();
+
+    if messages.is_empty() {
+        return None;
+    }
+
+    Some(CursorTab {
+        id,
+        title: tab.get("chatTitle").and_then(Value::as_str).map(str::to_string),
+        last_send_time: tab.get("lastSendTime").and_then(Value::as_i64),
+        messages,
+    })
+}
+
+/// (role, content) of a chat bubble
+/// Bubble types are `"user"`/`"ai"` in the chat panel and `1`/`2` in newer builds
+fn parse_bubble(bubble: &Value) -> Option<(String, String)> {
+    let role = match bubble.get("type")? {
+        Value::String(t) if t == "user" => "user",
+        Value::String(t) if t == "ai" => "assistant",
+        Value::Number(n) if n.as_i64() == Some(1) => "user",
+        Value::Number(n) if n.as_i64() == Some(2) => "assistant",
+        _ => return None,
+    };
+
+    // `text` is the rendered message; `rawText` is all some AI bubbles carry
+    let content = ["text", "rawText"]
+        .iter()
+        .filter_map(|key| bubble.get(*key).and_then(Value::as_str))
+        .map(str::trim)
+        .find(|text| !text.is_empty())?;
+
+    Some((role.to_string(), content.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn create_state_db(path: &Path, chatdata: &str) -> Result<()> {
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        let conn = Connection::open(path)?;
+        conn.execute("CREATE TABLE ItemTable (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB)", [])?;
+        conn.execute(
+            "INSERT INTO ItemTable (key, value) VALUES ('workbench.panel.aichat.view.aichat.chatdata', ?1)",
+            [chatdata],
+        )?;
+        conn.execute("INSERT INTO ItemTable (key, value) VALUES ('other.chatdata', 'not json')", [])?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tab_skips_undecodable_bubbles() {
+        let tab = json!({
+            "tabId": "tab-1",
+            "chatTitle": "Fix build",
+            "lastSendTime": 1731150000000_i64,
+            "bubbles": [
+                {"type": "user", "text": "Why does the build fail?"},
+                {"type": "ai", "text": "", "rawText": "A missing import."},
+                {"type": "ai", "text": "   "},
+                {"type": "system", "text": "ignored"},
+                {"type": 1, "text": "Thanks"},
+                {"text": "no type"}
+            ]
+        });
+
+        let parsed = parse_tab(&tab).unwrap();
+        assert_eq!(parsed.title.as_deref(), Some("Fix build"));
+        assert_eq!(parsed.last_send_time, Some(1731150000000));
+        assert_eq!(
+            parsed.messages,
+            vec![
+                ("user".to_string(), "Why does the build fail?".to_string()),
+                ("assistant".to_string(), "A missing import.".to_string()),
+                ("user".to_string(), "Thanks".to_string()),
+            ]
+        );
+
+        assert!(parse_tab(&json!({"tabId": "empty", "bubbles": []})).is_none());
+        assert!(parse_tab(&json!({"bubbles": [{"type": "user", "text": "hi"}]})).is_none());
+    }
+
+    #[test]
+    fn test_cursor_adapter_with_mock_db() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let chatdata = json!({
+            "tabs": [
+                {"tabId": "old", "lastSendTime": 1, "bubbles": [{"type": "user", "text": "first"}]},
+                {"tabId": "new", "lastSendTime": 2, "bubbles": [
+                    {"type": "user", "text": "Hello Cursor"},
+                    {"type": "ai", "text": "Hello! How can I help?"}
+                ]}
+            ]
+        });
+        let db = temp_dir.path().join("abc123").join(STATE_DB_FILE);
+        create_state_db(&db, &chatdata.to_string())?;
+
+        // A workspace without any chat history is passed over
+        let empty_db = temp_dir.path().join("def456").join(STATE_DB_FILE);
+        create_state_db(&empty_db, r#"{"tabs": []}"#)?;
+
+        let adapter = CursorAdapter::with_storage_dir(temp_dir.path().to_path_buf());
+        let session_path = adapter.find_latest_session()?;
+        assert_eq!(session_path, tab_path(&db, "new"));
+
+        let messages: Vec<Value> = adapter
+            .stream_session(&session_path)?
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<_>>()?;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["role"], "user");
+        assert_eq!(messages[0]["content"], "Hello Cursor");
+        assert_eq!(messages[1]["role"], "assistant");
+
+        // A bare tab ID is found across workspaces
+        assert_eq!(adapter.session_path("old")?, tab_path(&db, "old"));
+        assert_eq!(adapter.session_path(&db.to_string_lossy())?, tab_path(&db, "new"));
+        assert!(adapter.session_path("missing").is_err());
+        assert!(adapter.stream_session(&tab_path(&db, "missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_conversation_logs() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let logs_dir = temp_dir.path().join("logs");
+        std::fs::create_dir_all(&logs_dir)?;
+        let log = logs_dir.join("9f1c2d3e.json");
+        std::fs::write(
+            &log,
+            json!([
+                {"role": "user", "content": "Rename this function", "timestamp": "2025-11-09T10:00:00Z"},
+                {"role": "ai", "content": "Renamed it to parse_config.", "timestamp": 1762682460000_i64},
+                {"role": "tool", "content": "ignored"},
+                {"role": "assistant", "content": "  "}
+            ])
+            .to_string(),
+        )?;
+
+        let messages = parse_cursor_session(&log)?;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, Role::User);
+        assert_eq!(messages[1].role, Role::Assistant);
+        assert_eq!(messages[1].timestamp.as_deref(), Some("2025-11-09T10:01:00+00:00"));
+
+        // With no workspace DBs, the newest log is the latest session
+        let adapter = CursorAdapter::with_storage_dir(temp_dir.path().join("missing")).with_logs_dir(logs_dir);
+        assert_eq!(adapter.find_latest_session()?, log);
+        assert_eq!(adapter.session_path("9f1c2d3e")?, log);
+
+        let lines: Vec<Value> = adapter
+            .stream_session(&log)?
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<_>>()?;
+        assert_eq!(lines[0]["role"], "user");
+        assert_eq!(lines[0]["content"], "Rename this function");
+        assert_eq!(lines[0]["timestamp"], "2025-11-09T10:00:00Z");
+        Ok(())
+    }
+}
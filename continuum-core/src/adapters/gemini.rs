@@ -0,0 +1,295 @@
+// Gemini CLI log adapter
+// Reads ~/.gemini/tmp/<project-hash>/chats/session-*.json files
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use color_eyre::{eyre::Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{newest_first, LogAdapter};
+use crate::config::Config;
+use crate::tool_capture::TOOL_ROLE;
+use crate::ToolCapture;
+
+pub struct GeminiAdapter {
+    tmp_dir: Option<PathBuf>,
+}
+
+impl GeminiAdapter {
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Read sessions from `assistant_paths.gemini-cli`, or ~/.gemini/tmp
+    pub fn from_config(config: &Config) -> Self {
+        GeminiAdapter { tmp_dir: config.assistant_path("gemini-cli") }
+    }
+
+    /// Directory holding one `<project-hash>/chats/` directory per project
+    pub fn tmp_dir(&self) -> Result<PathBuf> {
+        self.tmp_dir.clone().ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))
+    }
+}
+
+impl Default for GeminiAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAdapter for GeminiAdapter {
+    fn name(&self) -> &'static str {
+        "gemini-cli"
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        let tmp_dir = self.tmp_dir()?;
+
+        if !tmp_dir.exists() {
+            return Err(color_eyre::eyre::eyre!(
+                "Gemini CLI directory not found: {}",
+                tmp_dir.display()
+            ));
+        }
+
+        session_files(&tmp_dir)
+            .into_iter()
+            .max_by(|(a_path, a_mod), (b_path, b_mod)| (a_mod, a_path).cmp(&(b_mod, b_path)))
+            .map(|(path, _)| path)
+            .ok_or_else(|| color_eyre::eyre::eyre!("No Gemini CLI session files found"))
+    }
+
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        let tmp_dir = self.tmp_dir()?;
+
+        if !tmp_dir.exists() {
+            return Err(color_eyre::eyre::eyre!(
+                "Gemini CLI directory not found: {}",
+                tmp_dir.display()
+            ));
+        }
+
+        Ok(newest_first(session_files(&tmp_dir)))
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let session = parse_session(path, None)?;
+
+        // Emit one JSON object per message (compatible with LogAdapter interface)
+        let json_messages: Vec<Result<String>> = session
+            .messages
+            .into_iter()
+            .map(|(role, content, timestamp)| {
+                Ok(json!({ "role": role, "content": content, "timestamp": timestamp }).to_string())
+            })
+            .collect();
+
+        Ok(Box::new(json_messages.into_iter()))
+    }
+}
+
+/// Every session file under `tmp_dir`, with its modification time
+pub fn session_files(tmp_dir: &Path) -> Vec<(PathBuf, SystemTime)> {
+    let mut sessions = Vec::new();
+
+    let Ok(projects) = std::fs::read_dir(tmp_dir) else {
+        return sessions;
+    };
+
+    for project_entry in projects.flatten() {
+        let Ok(files) = std::fs::read_dir(project_entry.path().join("chats")) else {
+            continue;
+        };
+
+        for file_entry in files.flatten() {
+            let path = file_entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            if let Ok(modified) = file_entry.metadata().and_then(|m| m.modified()) {
+                sessions.push((path, modified));
+            }
+        }
+    }
+
+    sessions
+}
+
+/// A Gemini CLI session file, reduced to its user and model messages
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeminiSession {
+    pub session_id: String,
+    pub start_time: Option<String>,
+    /// `lastUpdated` from the session file
+    pub end_time: Option<String>,
+    /// Role, content and timestamp of each message, in order
+    pub messages: Vec<(String, String, Option<String>)>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionFile {
+    session_id: String,
+    start_time: Option<String>,
+    last_updated: Option<String>,
+    #[serde(default)]
+    messages: Vec<SessionMessage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionMessage {
+    timestamp: Option<String>,
+    #[serde(rename = "type")]
+    msg_type: String,
+    /// A plain string, or an array of parts (`text`, `functionCall`, `functionResponse`)
+    #[serde(default)]
+    content: Value,
+    /// Tool calls the model made in this turn, with their results
+    #[serde(default)]
+    tool_calls: Vec<Value>,
+}
+
+/// Parse a session file into `user` and `assistant` messages
+///
+/// Only text parts are kept unless `tools` is given, in which case function
+/// calls and responses become `tool` messages. `info` and `error` entries are
+/// skipped. The start time falls back to the first message's timestamp.
+pub fn parse_session(path: &Path, tools: Option<&ToolCapture>) -> Result<GeminiSession> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: SessionFile = serde_json::from_str(&raw)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let mut session = GeminiSession {
+        start_time: file
+            .start_time
+            .or_else(|| file.messages.iter().find_map(|m| m.timestamp.clone())),
+        session_id: file.session_id,
+        end_time: file.last_updated,
+        messages: Vec::new(),
+    };
+
+    for msg in file.messages {
+        let role = match msg.msg_type.as_str() {
+            "user" => "user",
+            "gemini" => "assistant",
+            _ => continue,
+        };
+
+        let text = match &msg.content {
+            Value::String(text) => text.clone(),
+            Value::Array(parts) => parts
+                .iter()
+                .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        };
+        if !text.trim().is_empty() {
+            session.messages.push((role.to_string(), text, msg.timestamp.clone()));
+        }
+
+        let Some(tools) = tools else {
+            continue;
+        };
+        let parts = msg.content.as_array().into_iter().flatten();
+        for text in parts.filter_map(|part| tools.format_gemini_part(part)) {
+            session.messages.push((TOOL_ROLE.to_string(), text, msg.timestamp.clone()));
+        }
+        for call in &msg.tool_calls {
+            for text in tools.format_gemini_tool_call(call) {
+                session.messages.push((TOOL_ROLE.to_string(), text, msg.timestamp.clone()));
+            }
+        }
+    }
+
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const SESSION: &str = r#"{
+        "sessionId": "abc-123",
+        "lastUpdated": "2025-11-09T10:05:00Z",
+        "messages": [
+            {"type": "info", "content": "Logged in", "timestamp": "2025-11-09T10:00:00Z"},
+            {"type": "user", "content": [{"text": "List the files"}], "timestamp": "2025-11-09T10:00:01Z"},
+            {"type": "gemini", "content": [
+                {"text": "Listing them now."},
+                {"functionCall": {"name": "list_directory", "args": {"path": "."}}}
+            ], "timestamp": "2025-11-09T10:00:02Z"},
+            {"type": "user", "content": [
+                {"functionResponse": {"name": "list_directory", "response": {"output": "Cargo.toml\nsrc"}}}
+            ], "timestamp": "2025-11-09T10:00:03Z"},
+            {"type": "gemini", "content": "Two entries.", "timestamp": "2025-11-09T10:00:04Z",
+             "toolCalls": [{"name": "read_file", "args": {"path": "Cargo.toml"}, "result": [
+                {"functionResponse": {"name": "read_file", "response": {"output": "[package]"}}}
+             ]}]}
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_session_text_only() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("session.json");
+        std::fs::write(&path, SESSION)?;
+
+        let session = parse_session(&path, None)?;
+        assert_eq!(session.session_id, "abc-123");
+        assert_eq!(session.start_time.as_deref(), Some("2025-11-09T10:00:00Z"));
+        assert_eq!(session.end_time.as_deref(), Some("2025-11-09T10:05:00Z"));
+
+        let messages: Vec<(&str, &str)> = session.messages.iter().map(|(r, c, _)| (r.as_str(), c.as_str())).collect();
+        assert_eq!(
+            messages,
+            vec![("user", "List the files"), ("assistant", "Listing them now."), ("assistant", "Two entries.")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_session_with_tools() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let path = temp_dir.path().join("session.json");
+        std::fs::write(&path, SESSION)?;
+
+        let session = parse_session(&path, Some(&ToolCapture::default()))?;
+        let tool_messages: Vec<&str> = session
+            .messages
+            .iter()
+            .filter(|(role, _, _)| role == TOOL_ROLE)
+            .map(|(_, content, _)| content.as_str())
+            .collect();
+        assert_eq!(
+            tool_messages,
+            vec![
+                r#"TOOL_USE: list_directory -> {"path":"."}"#,
+                "TOOL_RESULT: Cargo.toml\nsrc",
+                r#"TOOL_USE: read_file -> {"path":"Cargo.toml"}"#,
+                "TOOL_RESULT: [package]",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_files() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let chats = temp_dir.path().join("0a1b2c/chats");
+        std::fs::create_dir_all(&chats)?;
+        std::fs::write(chats.join("session-1.json"), SESSION)?;
+        std::fs::write(chats.join("notes.txt"), "ignored")?;
+        std::fs::write(temp_dir.path().join("0a1b2c/logs.json"), "[]")?;
+
+        let files = session_files(temp_dir.path());
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, chats.join("session-1.json"));
+        Ok(())
+    }
+}
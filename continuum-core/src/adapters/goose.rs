@@ -1,40 +1,137 @@
 // Goose adapter - reads from Goose's SQLite database
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use color_eyre::{eyre::Context, Result};
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use super::LogAdapter;
+use super::{LogAdapter, SessionMetadata};
+use crate::config::Config;
+use crate::ToolCapture;
+
+/// File name of a Goose session database
+pub const SESSIONS_DB_FILE: &str = "sessions.db";
 
 pub struct GooseAdapter {
     db_path: PathBuf,
+    profile: Option<String>,
+}
+
+/// One Goose session: the database it lives in and its ID there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GooseSessionRef {
+    pub db_path: PathBuf,
+    pub session_id: String,
+    /// Profile the database belongs to (`None` for the default one)
+    pub profile: Option<String>,
+    /// When the session was created, as Goose recorded it (from [`GooseAdapter::list_all_sessions`])
+    pub created_at: Option<String>,
+    /// Messages Goose holds for the session (from [`GooseAdapter::list_all_sessions`])
+    pub message_count: Option<usize>,
+}
+
+impl GooseSessionRef {
+    /// Pseudo-path form used by the [`LogAdapter`] interface: /path/to/sessions.db#session_id
+    pub fn to_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}#{}", self.db_path.display(), self.session_id))
+    }
 }
 
 impl GooseAdapter {
-    pub fn new() -> Result<Self> {
-        let home = std::env::var("HOME").context("HOME not set")?;
-        let db_path = PathBuf::from(home).join(".local/share/goose/sessions/sessions.db");
+    /// Open the default sessions database, or a named profile's
+    pub fn new(profile: Option<String>) -> Result<Self> {
+        Self::from_config(&Config::default(), profile)
+    }
+
+    /// Like [`GooseAdapter::new`], looking under `assistant_paths.goose` when configured
+    pub fn from_config(config: &Config, profile: Option<String>) -> Result<Self> {
+        let sessions_dir = sessions_dir(config)?;
+        let db_path = match profile {
+            Some(ref name) => {
+                if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+                    return Err(color_eyre::eyre::eyre!("Invalid Goose profile name: '{}'", name));
+                }
+                sessions_dir.join(name).join(SESSIONS_DB_FILE)
+            }
+            None => sessions_dir.join(SESSIONS_DB_FILE),
+        };
 
         if !db_path.exists() {
+            let hint = match profile {
+                Some(_) => {
+                    let profiles = list_profiles_in(&sessions_dir).unwrap_or_default();
+                    if profiles.is_empty() {
+                        " (no profiles found)".to_string()
+                    } else {
+                        format!(" (available profiles: {})", profiles.join(", "))
+                    }
+                }
+                None => String::new(),
+            };
             return Err(color_eyre::eyre::eyre!(
-                "Goose database not found: {}",
-                db_path.display()
+                "Goose database not found: {}{}",
+                db_path.display(),
+                hint
             ));
         }
 
-        Ok(GooseAdapter { db_path })
+        Ok(GooseAdapter { db_path, profile })
     }
-}
 
-impl LogAdapter for GooseAdapter {
-    fn name(&self) -> &'static str {
-        "goose"
+    /// Names of the profiles that have their own sessions database
+    pub fn list_profiles() -> Result<Vec<String>> {
+        list_profiles_in(&sessions_dir(&Config::default())?)
     }
 
-    fn find_latest_session(&self) -> Result<PathBuf> {
-        // For Goose, we return the database path and session ID as a pseudo-path
-        // The session ID will be extracted later
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    pub fn db_path(&self) -> &Path {
+        &self.db_path
+    }
+
+    /// Reference a session in this adapter's database by ID
+    pub fn session_ref(&self, session_id: &str) -> GooseSessionRef {
+        GooseSessionRef {
+            db_path: self.db_path.clone(),
+            session_id: session_id.to_string(),
+            profile: self.profile.clone(),
+            created_at: None,
+            message_count: None,
+        }
+    }
+
+    /// Every session in this adapter's database, oldest first, with its
+    /// creation time and message count
+    pub fn list_all_sessions(&self) -> Result<Vec<GooseSessionRef>> {
+        let conn = Connection::open(&self.db_path)?;
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT s.id, s.created_at, COUNT(m.id) FROM sessions s
+                 LEFT JOIN messages m ON m.session_id = s.id
+                 GROUP BY s.id
+                 ORDER BY s.created_at ASC, s.id ASC",
+            )
+            .with_context(|| format!("Failed to list sessions in {}", self.db_path.display()))?;
+
+        let sessions = stmt
+            .query_map([], |row| {
+                Ok(GooseSessionRef {
+                    created_at: row.get(1)?,
+                    message_count: Some(row.get::<_, i64>(2)? as usize),
+                    ..self.session_ref(&row.get::<_, String>(0)?)
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(sessions)
+    }
+
+    /// The most recently updated session in this adapter's database
+    pub fn latest_session(&self) -> Result<GooseSessionRef> {
         let conn = Connection::open(&self.db_path)?;
 
         let session_id: String = conn.query_row(
@@ -43,22 +140,13 @@ impl LogAdapter for GooseAdapter {
             |row| row.get(0),
         )?;
 
-        // Return a pseudo-path that encodes both db and session
-        // Format: /path/to/sessions.db#session_id
-        let pseudo_path = format!("{}#{}", self.db_path.display(), session_id);
-        Ok(PathBuf::from(pseudo_path))
+        Ok(self.session_ref(&session_id))
     }
 
-    fn stream_session(&self, path: &PathBuf) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
-        // Parse the pseudo-path to get session ID
-        let path_str = path.to_string_lossy();
-        let session_id = if let Some(hash_pos) = path_str.rfind('#') {
-            &path_str[hash_pos + 1..]
-        } else {
-            return Err(color_eyre::eyre::eyre!("Invalid Goose session path"));
-        };
-
-        let conn = Connection::open(&self.db_path)?;
+    /// Stream a session's messages as JSON lines (role, content_json, timestamp)
+    /// Messages come in source (row id) order, which also settles equal timestamps.
+    pub fn stream(&self, session: &GooseSessionRef) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let conn = Connection::open(&session.db_path)?;
 
         let mut stmt = conn.prepare(
             "SELECT role, content_json, timestamp FROM messages
@@ -66,7 +154,7 @@ impl LogAdapter for GooseAdapter {
              ORDER BY id ASC"
         )?;
 
-        let messages: Vec<GooseMessage> = stmt.query_map([session_id], |row| {
+        let messages: Vec<GooseMessage> = stmt.query_map([&session.session_id], |row| {
             Ok(GooseMessage {
                 role: row.get(0)?,
                 content_json: row.get(1)?,
@@ -87,6 +175,87 @@ impl LogAdapter for GooseAdapter {
     }
 }
 
+impl LogAdapter for GooseAdapter {
+    fn name(&self) -> &'static str {
+        "goose"
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        Ok(self.latest_session()?.to_path())
+    }
+
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        Ok(self.list_all_sessions()?.iter().rev().map(GooseSessionRef::to_path).collect())
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let path_str = path.to_string_lossy();
+        let Some((db_path, session_id)) = path_str.rsplit_once('#') else {
+            return Err(color_eyre::eyre::eyre!("Invalid Goose session path (expected sessions.db#<id>)"));
+        };
+
+        self.stream(&GooseSessionRef {
+            db_path: PathBuf::from(db_path),
+            ..self.session_ref(session_id)
+        })
+    }
+
+    /// Read straight from the `sessions` table instead of streaming the messages
+    /// Start and end are Goose's `created_at` and `updated_at`, in its own format.
+    fn session_metadata(&self, path: &Path) -> Result<SessionMetadata> {
+        let path_str = path.to_string_lossy();
+        let Some((db_path, session_id)) = path_str.rsplit_once('#') else {
+            return Err(color_eyre::eyre::eyre!("Invalid Goose session path (expected sessions.db#<id>)"));
+        };
+        let conn = Connection::open(db_path)?;
+
+        let (start_time, end_time, message_count) = conn
+            .query_row(
+                "SELECT s.created_at, s.updated_at, COUNT(m.id) FROM sessions s
+                 LEFT JOIN messages m ON m.session_id = s.id
+                 WHERE s.id = ?1
+                 GROUP BY s.id",
+                [session_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)? as usize)),
+            )
+            .with_context(|| format!("No Goose session {} in {}", session_id, db_path))?;
+
+        // Older databases have no model_config_json column
+        let model = conn
+            .query_row("SELECT model_config_json FROM sessions WHERE id = ?1", [session_id], |row| {
+                row.get::<_, Option<String>>(0)
+            })
+            .ok()
+            .flatten()
+            .and_then(|config| serde_json::from_str::<Value>(&config).ok())
+            .and_then(|config| config.get("model_name")?.as_str().map(str::to_string));
+
+        Ok(SessionMetadata { message_count, start_time, end_time, model })
+    }
+}
+
+/// Directory holding the default sessions.db and one subdirectory per profile
+fn sessions_dir(config: &Config) -> Result<PathBuf> {
+    config
+        .assistant_path("goose")
+        .ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))
+}
+
+/// Profiles under a sessions directory: subdirectories containing a sessions.db, sorted by name
+pub fn list_profiles_in(sessions_dir: &Path) -> Result<Vec<String>> {
+    let entries = std::fs::read_dir(sessions_dir)
+        .with_context(|| format!("Failed to read {}", sessions_dir.display()))?;
+
+    let mut profiles: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().join(SESSIONS_DB_FILE).is_file())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .collect();
+    profiles.sort();
+
+    Ok(profiles)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct GooseMessage {
     role: String,
@@ -94,27 +263,79 @@ struct GooseMessage {
     timestamp: Option<String>,
 }
 
+/// A Goose message's text, and its tool activity as separate `tool` messages
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GooseContent {
+    pub text: String,
+    /// Tool calls and results formatted by the [`ToolCapture`] given (none without one)
+    pub tool_messages: Vec<String>,
+}
+
 /// Parse Goose content_json to extract text
-/// Goose stores content as JSON array with various content types
-pub fn parse_goose_content(content_json: &str) -> Result<String> {
-    #[derive(Deserialize)]
-    struct ContentItem {
-        #[serde(rename = "type")]
-        _content_type: Option<String>,
-        text: Option<String>,
+/// Goose stores content as JSON array with various content types. Text items
+/// are kept as-is and images become summaries such as `[image: image/png]`.
+/// Tool requests and responses are only kept with `tools`, which formats and
+/// caps them like the other adapters' tool calls.
+pub fn parse_goose_content(content_json: &str, tools: Option<&ToolCapture>) -> Result<GooseContent> {
+    let items: Vec<Value> = serde_json::from_str(content_json)
+        .unwrap_or_else(|_| vec![]);
+
+    let mut parts = Vec::new();
+    let mut tool_messages = Vec::new();
+    for item in &items {
+        match item.get("type").and_then(Value::as_str) {
+            Some("toolRequest" | "toolResponse") => tool_messages.extend(tools.and_then(|tools| tool_message(item, tools))),
+            _ => parts.extend(item_text(item)),
+        }
     }
 
-    let items: Vec<ContentItem> = serde_json::from_str(content_json)
-        .unwrap_or_else(|_| vec![]);
+    Ok(GooseContent { text: parts.join("\n"), tool_messages })
+}
 
-    let text = items
-        .iter()
-        .filter_map(|item| item.text.as_ref())
-        .map(|s| s.as_str())
-        .collect::<Vec<_>>()
-        .join("\n");
+/// Text of a text or image content item (`None` for other types)
+fn item_text(item: &Value) -> Option<String> {
+    if let Some(text) = item.get("text").and_then(Value::as_str) {
+        return Some(text.to_string());
+    }
+    match item.get("type").and_then(Value::as_str)? {
+        "image" => {
+            let mime_type = item.get("mimeType").and_then(Value::as_str).unwrap_or("unknown");
+            Some(format!("[image: {}]", mime_type))
+        }
+        _ => None,
+    }
+}
+
+/// Message text for a tool request or response item; `None` for an empty result
+fn tool_message(item: &Value, tools: &ToolCapture) -> Option<String> {
+    let (key, is_call) = match item.get("type").and_then(Value::as_str)? {
+        "toolRequest" => ("toolCall", true),
+        _ => ("toolResult", false),
+    };
+    let status = item.get(key)?;
+    let Some(value) = status.get("value") else {
+        return tools.format_result(&format!("error: {}", error_text(status)));
+    };
+
+    if is_call {
+        let name = value.get("name").and_then(Value::as_str).unwrap_or("unknown");
+        let arguments = value.get("arguments").map(Value::to_string).unwrap_or_default();
+        return Some(tools.format_call(name, &arguments));
+    }
+    let output = match value {
+        Value::Array(contents) => contents.iter().filter_map(item_text).collect::<Vec<_>>().join("\n"),
+        other => other.to_string(),
+    };
+    tools.format_result(&output)
+}
 
-    Ok(text)
+/// Error message of a failed tool call or result
+fn error_text(status: &Value) -> String {
+    match status.get("error") {
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+        None => "unknown error".to_string(),
+    }
 }
 
 #[cfg(test)]
@@ -125,12 +346,44 @@ mod tests {
     #[test]
     fn test_parse_goose_content() {
         let json = r#"[{"type":"text","text":"Hello world"}]"#;
-        let result = parse_goose_content(json).unwrap();
-        assert_eq!(result, "Hello world");
+        let result = parse_goose_content(json, None).unwrap();
+        assert_eq!(result.text, "Hello world");
 
         let json_multi = r#"[{"type":"text","text":"Line 1"},{"type":"text","text":"Line 2"}]"#;
-        let result_multi = parse_goose_content(json_multi).unwrap();
-        assert_eq!(result_multi, "Line 1\nLine 2");
+        let result_multi = parse_goose_content(json_multi, None).unwrap();
+        assert_eq!(result_multi.text, "Line 1\nLine 2");
+    }
+
+    #[test]
+    fn test_parse_goose_content_with_tools() {
+        let json = r#"[
+            {"type":"text","text":"Listing the directory"},
+            {"type":"toolRequest","id":"call_1","toolCall":{"status":"success","value":{"name":"developer__shell","arguments":{"command":"ls -la"}}}},
+            {"type":"image","data":"iVBORw0KGgo=","mimeType":"image/png"}
+        ]"#;
+
+        // Without capture, tool activity is left out
+        let result = parse_goose_content(json, None).unwrap();
+        assert_eq!(result, GooseContent { text: "Listing the directory\n[image: image/png]".to_string(), tool_messages: vec![] });
+
+        let tools = ToolCapture::new(Some(8));
+        let result = parse_goose_content(json, Some(&tools)).unwrap();
+        assert_eq!(result.tool_messages, vec![r#"TOOL_USE: developer__shell -> {"command":"ls -la"}"#.to_string()]);
+
+        let json = r#"[
+            {"type":"toolResponse","id":"call_1","toolResult":{"status":"success","value":[{"type":"text","text":"Cargo.toml\nsrc"}]}},
+            {"type":"toolResponse","id":"call_2","toolResult":{"status":"error","error":"not found"}},
+            {"type":"toolResponse","id":"call_3","toolResult":{"status":"success","value":[]}}
+        ]"#;
+        let result = parse_goose_content(json, Some(&tools)).unwrap();
+        assert_eq!(result.text, "");
+        assert_eq!(
+            result.tool_messages,
+            vec![
+                "TOOL_RESULT: Cargo.to... [truncated 6 bytes]".to_string(),
+                "TOOL_RESULT: error: n... [truncated 8 bytes]".to_string(),
+            ]
+        );
     }
 
     #[test]
@@ -143,6 +396,7 @@ mod tests {
         conn.execute(
             "CREATE TABLE sessions (
                 id TEXT PRIMARY KEY,
+                created_at TEXT,
                 updated_at TEXT
             )",
             [],
@@ -158,9 +412,13 @@ mod tests {
             [],
         )?;
 
-        // Insert test session
+        // Insert test sessions
+        conn.execute(
+            "INSERT INTO sessions (id, created_at, updated_at) VALUES ('test_session', '2025-11-09 11:00:00', '2025-11-09 12:00:00')",
+            [],
+        )?;
         conn.execute(
-            "INSERT INTO sessions (id, updated_at) VALUES ('test_session', '2025-11-09 12:00:00')",
+            "INSERT INTO sessions (id, created_at, updated_at) VALUES ('empty_session', '2025-11-08 09:00:00', '2025-11-08 09:00:00')",
             [],
         )?;
 
@@ -181,6 +439,7 @@ mod tests {
         // Create adapter pointing to test DB
         let adapter = GooseAdapter {
             db_path: db_path.clone(),
+            profile: None,
         };
 
         // Test find_latest_session
@@ -199,15 +458,105 @@ mod tests {
         // Parse first message
         let msg1: GooseMessage = serde_json::from_str(&messages[0])?;
         assert_eq!(msg1.role, "user");
-        let text1 = parse_goose_content(&msg1.content_json)?;
+        let text1 = parse_goose_content(&msg1.content_json, None)?.text;
         assert_eq!(text1, "Hello Goose");
 
         // Parse second message
         let msg2: GooseMessage = serde_json::from_str(&messages[1])?;
         assert_eq!(msg2.role, "assistant");
-        let text2 = parse_goose_content(&msg2.content_json)?;
+        let text2 = parse_goose_content(&msg2.content_json, None)?.text;
         assert_eq!(text2, "Hello! How can I help you?");
 
+        // Test list_all_sessions
+        let listed: Vec<(String, Option<String>, Option<usize>)> = adapter
+            .list_all_sessions()?
+            .into_iter()
+            .map(|s| (s.session_id, s.created_at, s.message_count))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("empty_session".to_string(), Some("2025-11-08 09:00:00".to_string()), Some(0)),
+                ("test_session".to_string(), Some("2025-11-09 11:00:00".to_string()), Some(2)),
+            ]
+        );
+
+        // Test session_metadata (this schema has no model_config_json)
+        let metadata = adapter.session_metadata(&session_path)?;
+        assert_eq!(
+            metadata,
+            SessionMetadata {
+                message_count: 2,
+                start_time: Some("2025-11-09 11:00:00".to_string()),
+                end_time: Some("2025-11-09 12:00:00".to_string()),
+                model: None,
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_equal_timestamps_keep_source_order() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let db_path = temp_dir.path().join(SESSIONS_DB_FILE);
+        let conn = Connection::open(&db_path)?;
+        conn.execute_batch(
+            "CREATE TABLE messages (id INTEGER PRIMARY KEY, session_id TEXT, role TEXT, content_json TEXT, timestamp TEXT);
+             INSERT INTO messages VALUES (2, 's1', 'assistant', '[{\"type\":\"text\",\"text\":\"second\"}]', '2025-11-09 12:00:01');
+             INSERT INTO messages VALUES (1, 's1', 'user', '[{\"type\":\"text\",\"text\":\"first\"}]', '2025-11-09 12:00:01');
+             INSERT INTO messages VALUES (3, 's1', 'user', '[{\"type\":\"text\",\"text\":\"third\"}]', '2025-11-09 12:00:05');",
+        )?;
+        drop(conn);
+
+        let adapter = GooseAdapter { db_path, profile: None };
+        let (messages, timestamps): (Vec<(String, String)>, Vec<Option<String>>) = adapter
+            .stream(&adapter.session_ref("s1"))?
+            .map(|line| {
+                let msg: GooseMessage = serde_json::from_str(&line?)?;
+                let timestamp = msg.timestamp.as_deref().and_then(crate::PlainTextWriter::normalize_timestamp);
+                Ok(((msg.role, parse_goose_content(&msg.content_json, None)?.text), timestamp))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+
+        let writer = crate::PlainTextWriter::with_base_dir(temp_dir.path().join("logs"));
+        writer.import_messages("s1", "goose", "2025-11-09", &messages, &timestamps, None, false)?;
+
+        // Goose's own times are stored, and sorting by them keeps the tie in source order
+        let stored = crate::store::load_messages(&temp_dir.path().join("logs/goose/2025-11-09/s1"))?;
+        assert_eq!(stored[0].timestamp.as_deref(), Some("2025-11-09T12:00:01Z"));
+        assert_eq!(stored[1].timestamp, stored[0].timestamp);
+        let day = chrono::NaiveDate::from_ymd_opt(2025, 11, 9).unwrap();
+        let order: Vec<String> = crate::timeline::day_timeline(&temp_dir.path().join("logs"), day, None)?
+            .into_iter()
+            .map(|entry| entry.message.content)
+            .collect();
+        assert_eq!(order, vec!["first", "second", "third"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_profiles_and_session_ref() -> Result<()> {
+        let temp_dir = tempdir()?;
+        std::fs::write(temp_dir.path().join(SESSIONS_DB_FILE), "")?;
+        for profile in ["work", "personal"] {
+            std::fs::create_dir(temp_dir.path().join(profile))?;
+            std::fs::write(temp_dir.path().join(profile).join(SESSIONS_DB_FILE), "")?;
+        }
+        // A directory without a sessions.db isn't a profile
+        std::fs::create_dir(temp_dir.path().join("cache"))?;
+
+        assert_eq!(list_profiles_in(temp_dir.path())?, vec!["personal", "work"]);
+
+        let adapter = GooseAdapter {
+            db_path: temp_dir.path().join("work").join(SESSIONS_DB_FILE),
+            profile: Some("work".to_string()),
+        };
+        let session = adapter.session_ref("abc");
+        assert_eq!(session.profile.as_deref(), Some("work"));
+        assert_eq!(session.to_path(), PathBuf::from(format!("{}#abc", adapter.db_path().display())));
         Ok(())
     }
 }
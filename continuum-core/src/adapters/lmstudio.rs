@@ -0,0 +1,249 @@
+// LM Studio adapter
+// Reads the chat history LM Studio keeps as JSON files under its data directory
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{newest_first, LogAdapter};
+use crate::config::Config;
+
+/// Name LM Studio conversations are stored under
+pub const ASSISTANT: &str = "lmstudio";
+
+/// Subdirectory of the data directory holding conversation files
+pub const CONVERSATIONS_DIR: &str = "conversations";
+
+/// LM Studio's data directory, relative to the home directory
+#[cfg(target_os = "macos")]
+const PLATFORM_DIR: &str = "Library/Application Support/LM Studio";
+#[cfg(not(target_os = "macos"))]
+const PLATFORM_DIR: &str = ".lmstudio";
+
+/// A saved LM Studio conversation; only the fields continuum reads
+#[derive(Debug, Deserialize)]
+struct ConversationFile {
+    id: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    messages: Vec<ConversationMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConversationMessage {
+    role: String,
+    #[serde(default)]
+    content: String,
+    timestamp: Option<Value>,
+}
+
+/// One LM Studio conversation file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LmStudioConversation {
+    /// The conversation's `id`, or the file name when it has none
+    pub id: String,
+    pub title: Option<String>,
+    /// Model the conversation was held with, if recorded
+    pub model: Option<String>,
+    /// Role, content and timestamp of each non-empty message, in order
+    pub messages: Vec<(String, String, Option<String>)>,
+}
+
+pub struct LmStudioAdapter {
+    conversations_dir: Option<PathBuf>,
+}
+
+impl LmStudioAdapter {
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Use `assistant_paths.lmstudio`, or the `conversations` directory under [`Self::detect_platform_path`]
+    pub fn from_config(config: &Config) -> Self {
+        LmStudioAdapter { conversations_dir: config.assistant_path(ASSISTANT) }
+    }
+
+    /// Read conversations from a specific directory
+    pub fn with_conversations_dir(path: PathBuf) -> Self {
+        LmStudioAdapter { conversations_dir: Some(path) }
+    }
+
+    /// LM Studio's data directory on this platform: ~/Library/Application Support/LM Studio
+    /// on macOS, ~/.lmstudio elsewhere
+    pub fn detect_platform_path() -> Result<PathBuf> {
+        crate::config::home_dir()
+            .map(|home| home.join(PLATFORM_DIR))
+            .ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))
+    }
+
+    /// Directory searched (including subfolders) for `.json` conversation files
+    pub fn conversations_dir(&self) -> Result<PathBuf> {
+        self.conversations_dir.clone().ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))
+    }
+
+    /// Resolve `--session`: a conversation file, or a conversation ID looked up in the conversations directory
+    pub fn session_path(&self, session: &str) -> Result<PathBuf> {
+        let path = Path::new(session);
+        if path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+
+        let dir = self.conversations_dir()?;
+        conversation_files(&dir)?
+            .into_iter()
+            .map(|(path, _)| path)
+            .find(|path| parse_conversation(path).is_ok_and(|conversation| conversation.id == session))
+            .ok_or_else(|| color_eyre::eyre::eyre!("No LM Studio conversation {} in {}", session, dir.display()))
+    }
+}
+
+impl Default for LmStudioAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAdapter for LmStudioAdapter {
+    fn name(&self) -> &'static str {
+        ASSISTANT
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        let dir = self.conversations_dir()?;
+        conversation_files(&dir)?
+            .into_iter()
+            .max_by(|(a_path, a_mod), (b_path, b_mod)| (a_mod, a_path).cmp(&(b_mod, b_path)))
+            .map(|(path, _)| path)
+            .ok_or_else(|| color_eyre::eyre::eyre!("No LM Studio conversations found in {}", dir.display()))
+    }
+
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        Ok(newest_first(conversation_files(&self.conversations_dir()?)?))
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let conversation = parse_conversation(path)?;
+
+        // Emit one JSON object per message (compatible with LogAdapter interface);
+        // the conversation's model is credited with its assistant messages
+        let model = conversation.model;
+        Ok(Box::new(conversation.messages.into_iter().map(move |(role, content, timestamp)| {
+            let model = if role == "assistant" { model.as_deref() } else { None };
+            Ok(json!({ "role": role, "content": content, "timestamp": timestamp, "model": model }).to_string())
+        })))
+    }
+}
+
+/// `.json` files anywhere under the conversations directory, with their mtimes
+/// LM Studio keeps chats the user filed into folders in subdirectories.
+fn conversation_files(dir: &Path) -> Result<Vec<(PathBuf, std::time::SystemTime)>> {
+    let entries = std::fs::read_dir(dir).with_context(|| format!("LM Studio conversations directory not found: {}", dir.display()))?;
+
+    let mut files = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            files.extend(conversation_files(&path)?);
+        } else if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            if let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) {
+                files.push((path, modified));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Parse an LM Studio conversation file
+///
+/// Timestamps may be RFC 3339 strings or Unix milliseconds; blank messages are skipped.
+pub fn parse_conversation(path: &Path) -> Result<LmStudioConversation> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: ConversationFile =
+        serde_json::from_str(&text).with_context(|| format!("Invalid LM Studio conversation {}", path.display()))?;
+
+    let messages = file
+        .messages
+        .into_iter()
+        .filter(|message| !message.content.trim().is_empty())
+        .map(|message| {
+            let timestamp = match message.timestamp {
+                Some(Value::String(ts)) => Some(ts),
+                Some(Value::Number(ms)) => ms
+                    .as_i64()
+                    .and_then(chrono::DateTime::from_timestamp_millis)
+                    .map(|ts| ts.to_rfc3339()),
+                _ => None,
+            };
+            (message.role, message.content, timestamp)
+        })
+        .collect();
+
+    Ok(LmStudioConversation {
+        id: file.id.filter(|id| !id.is_empty()).unwrap_or_else(|| file_stem_id(path)),
+        title: file.title,
+        model: file.model,
+        messages,
+    })
+}
+
+/// File name without `.json` (or `.conversation.json`)
+fn file_stem_id(path: &Path) -> String {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("unknown");
+    let stem = name.strip_suffix(".json").unwrap_or(name);
+    stem.strip_suffix(".conversation").unwrap_or(stem).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_and_find_conversations() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let dir = temp_dir.path();
+        std::fs::create_dir_all(dir.join("Work"))?;
+        let older = dir.join("1731000000000.conversation.json");
+        let newer = dir.join("Work/parser.json");
+        std::fs::write(
+            &older,
+            r#"{"id": "chat-1", "title": "Borrowing", "model": "qwen2.5-coder-7b",
+                "messages": [{"role": "user", "content": "Why won't this borrow?", "timestamp": 1731060000000},
+                             {"role": "assistant", "content": "Use &value.", "timestamp": "2024-11-08T10:00:05Z"},
+                             {"role": "assistant", "content": " "}]}"#,
+        )?;
+        std::fs::write(&newer, r#"{"title": "Parser", "messages": [{"role": "user", "content": "Fix the parser"}]}"#)?;
+        std::fs::write(dir.join("notes.txt"), "ignored")?;
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        std::fs::File::options().write(true).open(&older)?.set_modified(base)?;
+        std::fs::File::options().write(true).open(&newer)?.set_modified(base + Duration::from_secs(60))?;
+
+        let adapter = LmStudioAdapter::with_conversations_dir(dir.to_path_buf());
+        assert_eq!(adapter.find_latest_session()?, newer);
+        assert_eq!(adapter.list_sessions()?, vec![newer.clone(), older.clone()]);
+        assert_eq!(adapter.session_path("chat-1")?, older);
+        assert!(adapter.session_path("missing").is_err());
+
+        let lines: Vec<Value> = adapter
+            .stream_session(&older)?
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<_>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["timestamp"], "2024-11-08T10:00:00+00:00");
+        assert_eq!(lines[0]["model"], Value::Null);
+        assert_eq!(lines[1]["model"], "qwen2.5-coder-7b");
+
+        // Without an `id`, the file name stands in
+        let conversation = parse_conversation(&newer)?;
+        assert_eq!(conversation.id, "parser");
+        assert_eq!(conversation.messages, vec![("user".to_string(), "Fix the parser".to_string(), None)]);
+
+        assert!(LmStudioAdapter::detect_platform_path()?.ends_with(PLATFORM_DIR));
+        Ok(())
+    }
+}
@@ -1,11 +1,21 @@
 // Adapter traits and implementations for different assistant log formats
 
 use color_eyre::Result;
-use std::path::PathBuf;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+pub mod aider;
+pub mod chatgpt;
+pub mod claude_ai;
 pub mod claude_code;
 pub mod codex;
+pub mod cursor;
+pub mod gemini;
 pub mod goose;
+pub mod lmstudio;
+pub mod openwebui;
+pub mod zed;
 
 /// Trait for adapting different assistant log formats into Continuum's format
 pub trait LogAdapter {
@@ -15,7 +25,63 @@ pub trait LogAdapter {
     /// Find the latest active session for this assistant
     fn find_latest_session(&self) -> Result<PathBuf>;
 
+    /// Every session this adapter can read, newest first, as paths
+    /// [`LogAdapter::stream_session`] accepts
+    fn list_sessions(&self) -> Result<Vec<PathBuf>>;
+
     /// Stream messages from a session file
     /// Returns an iterator of parsed log entries
-    fn stream_session(&self, path: &PathBuf) -> Result<Box<dyn Iterator<Item = Result<String>>>>;
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>>;
+
+    /// Summarize a session without importing it
+    ///
+    /// The default streams the whole session: every entry counts towards
+    /// `message_count`, and the first and last `timestamp` fields give the
+    /// start and end. Adapters with a cheaper source override it.
+    fn session_metadata(&self, path: &Path) -> Result<SessionMetadata> {
+        let mut metadata = SessionMetadata::default();
+
+        for line in self.stream_session(path)? {
+            let line = line?;
+            metadata.message_count += 1;
+            let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            if let Some(timestamp) = entry.get("timestamp").and_then(Value::as_str) {
+                metadata.start_time.get_or_insert_with(|| timestamp.to_string());
+                metadata.end_time = Some(timestamp.to_string());
+            }
+            if metadata.model.is_none() {
+                metadata.model = entry_model(&entry);
+            }
+        }
+
+        Ok(metadata)
+    }
+}
+
+/// What a native session holds, read without importing it (see [`LogAdapter::session_metadata`])
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionMetadata {
+    /// Entries in the session; for line-based logs this includes non-message events
+    pub message_count: usize,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    /// Model the session was held with, if the log records one
+    pub model: Option<String>,
+}
+
+/// Paths ordered by modification time, newest first (ties by path, greatest first)
+pub(crate) fn newest_first(mut files: Vec<(PathBuf, SystemTime)>) -> Vec<PathBuf> {
+    files.sort_by(|(a_path, a_mod), (b_path, b_mod)| (b_mod, b_path).cmp(&(a_mod, a_path)));
+    files.into_iter().map(|(path, _)| path).collect()
+}
+
+/// A log entry's `model`, top level or inside its `message` or `payload`
+pub(crate) fn entry_model(entry: &Value) -> Option<String> {
+    [Some(entry), entry.get("message"), entry.get("payload")]
+        .into_iter()
+        .flatten()
+        .find_map(|value| value.get("model").and_then(Value::as_str))
+        .map(str::to_string)
 }
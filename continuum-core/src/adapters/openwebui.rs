@@ -0,0 +1,203 @@
+// Open WebUI adapter
+// Reads chats from Open WebUI's webui.db, where each row of the `chat` table holds the conversation as JSON
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::Context, Result};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use serde_json::{json, Value};
+
+use super::LogAdapter;
+use crate::config::Config;
+
+/// Name Open WebUI chats are stored under
+pub const ASSISTANT: &str = "openwebui";
+
+/// File name of Open WebUI's database inside its data directory
+pub const DB_FILE: &str = "webui.db";
+
+/// Open WebUI's own setting for where it keeps webui.db
+pub const DATA_DIR_ENV: &str = "DATA_DIR";
+
+pub struct OpenWebUiAdapter {
+    db_path: Option<PathBuf>,
+}
+
+impl OpenWebUiAdapter {
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Use `assistant_paths.openwebui`, else webui.db in $DATA_DIR
+    pub fn from_config(config: &Config) -> Self {
+        let db_path = config.assistant_path(ASSISTANT).or_else(|| {
+            std::env::var_os(DATA_DIR_ENV)
+                .filter(|v| !v.is_empty())
+                .map(|dir| PathBuf::from(dir).join(DB_FILE))
+        });
+        OpenWebUiAdapter { db_path }
+    }
+
+    /// Read chats from a specific webui.db
+    pub fn with_db_path(path: PathBuf) -> Self {
+        OpenWebUiAdapter { db_path: Some(path) }
+    }
+
+    /// The database to read; Open WebUI has no fixed location, so it must be configured
+    pub fn db_path(&self) -> Result<PathBuf> {
+        let Some(ref path) = self.db_path else {
+            return Err(color_eyre::eyre::eyre!(
+                "No Open WebUI database configured (set assistant_paths.openwebui to your {}, or {})",
+                DB_FILE,
+                DATA_DIR_ENV
+            ));
+        };
+        if !path.is_file() {
+            return Err(color_eyre::eyre::eyre!("Open WebUI database not found: {}", path.display()));
+        }
+        Ok(path.clone())
+    }
+
+    /// Pseudo-path addressing one chat: /path/to/webui.db#<chat id>
+    pub fn session_path(&self, chat_id: &str) -> Result<PathBuf> {
+        Ok(PathBuf::from(format!("{}#{}", self.db_path()?.display(), chat_id)))
+    }
+
+    /// Chat IDs, most recently updated first
+    fn chat_ids(&self, limit: Option<usize>) -> Result<Vec<String>> {
+        let db_path = self.db_path()?;
+        let conn = open_read_only(&db_path)?;
+        let mut stmt = conn
+            .prepare("SELECT id FROM chat ORDER BY updated_at DESC, id DESC LIMIT ?1")
+            .with_context(|| format!("Failed to list chats in {}", db_path.display()))?;
+
+        let limit = limit.map_or(-1, |n| n as i64);
+        let ids = stmt.query_map([limit], |row| row.get(0))?.collect::<Result<Vec<String>, _>>()?;
+        Ok(ids)
+    }
+}
+
+/// Read-only, since Open WebUI may have the DB open; a mistyped path must
+/// not leave an empty database behind either
+fn open_read_only(db_path: &Path) -> Result<Connection> {
+    Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .with_context(|| format!("Failed to open {}", db_path.display()))
+}
+
+impl Default for OpenWebUiAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAdapter for OpenWebUiAdapter {
+    fn name(&self) -> &'static str {
+        ASSISTANT
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        let id = self.chat_ids(Some(1))?.pop().ok_or_else(|| color_eyre::eyre::eyre!("No chats in the Open WebUI database"))?;
+        self.session_path(&id)
+    }
+
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        self.chat_ids(None)?.iter().map(|id| self.session_path(id)).collect()
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let path_str = path.to_string_lossy();
+        let Some((db_path, chat_id)) = path_str.rsplit_once('#') else {
+            return Err(color_eyre::eyre::eyre!("Invalid Open WebUI session path (expected webui.db#<chat id>)"));
+        };
+
+        let conn = open_read_only(Path::new(db_path))?;
+        let chat: String = conn
+            .query_row("SELECT chat FROM chat WHERE id = ?1", [chat_id], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| color_eyre::eyre::eyre!("Chat '{}' not found in {}", chat_id, db_path))?;
+        let chat: Value = serde_json::from_str(&chat).with_context(|| format!("Invalid chat JSON for '{}'", chat_id))?;
+
+        // Emit one JSON object per message (compatible with LogAdapter interface)
+        let json_messages: Vec<Result<String>> = chat_messages(&chat)
+            .into_iter()
+            .map(|(role, content, timestamp, model)| {
+                Ok(json!({ "role": role, "content": content, "timestamp": timestamp, "model": model }).to_string())
+            })
+            .collect();
+
+        Ok(Box::new(json_messages.into_iter()))
+    }
+}
+
+/// Role, content, timestamp and model of each non-empty message in a chat document
+///
+/// `messages` is the branch the chat currently shows. Attached files are
+/// ignored; timestamps are Unix seconds.
+fn chat_messages(chat: &Value) -> Vec<(String, String, Option<String>, Option<String>)> {
+    let Some(messages) = chat.get("messages").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+
+    messages
+        .iter()
+        .filter_map(|message| {
+            let role = message.get("role").and_then(Value::as_str)?;
+            let content = message.get("content").and_then(Value::as_str)?;
+            if content.trim().is_empty() {
+                return None;
+            }
+            let timestamp = message
+                .get("timestamp")
+                .and_then(Value::as_i64)
+                .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+                .map(|ts| ts.to_rfc3339());
+            let model = message.get("model").and_then(Value::as_str).map(str::to_string);
+            Some((role.to_string(), content.to_string(), timestamp, model))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_openwebui_adapter_with_mock_db() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let db_path = temp_dir.path().join(DB_FILE);
+        let conn = Connection::open(&db_path)?;
+        conn.execute_batch(
+            r#"CREATE TABLE chat (id TEXT PRIMARY KEY, user_id TEXT, title TEXT, chat TEXT, created_at INTEGER, updated_at INTEGER);
+               INSERT INTO chat VALUES ('c-old', 'u1', 'Borrowing', '{"messages": [
+                   {"id": "m1", "role": "user", "content": "Why won''t this borrow?", "timestamp": 1731060000,
+                    "files": [{"type": "file", "name": "main.rs"}]},
+                   {"id": "m2", "role": "assistant", "content": "Use `&value`.", "model": "llama3.1:8b", "timestamp": 1731060005},
+                   {"id": "m3", "role": "assistant", "content": ""}
+               ]}', 1731060000, 1731060005);
+               INSERT INTO chat VALUES ('c-new', 'u1', 'Parser', '{"messages": [{"role": "user", "content": "Fix the parser"}]}', 1731100000, 1731100000);"#,
+        )?;
+
+        let adapter = OpenWebUiAdapter::with_db_path(db_path);
+        assert_eq!(adapter.find_latest_session()?, adapter.session_path("c-new")?);
+        assert_eq!(adapter.list_sessions()?, vec![adapter.session_path("c-new")?, adapter.session_path("c-old")?]);
+
+        let lines: Vec<Value> = adapter
+            .stream_session(&adapter.session_path("c-old")?)?
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<_>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["content"], "Why won't this borrow?");
+        assert_eq!(lines[0]["timestamp"], "2024-11-08T10:00:00+00:00");
+        assert_eq!(lines[1]["model"], "llama3.1:8b");
+        assert!(adapter.stream_session(&adapter.session_path("missing")?).is_err());
+
+        assert!(OpenWebUiAdapter::with_db_path(temp_dir.path().join("none.db")).find_latest_session().is_err());
+
+        // A mistyped database path errors without creating the file
+        let typo = temp_dir.path().join("typo.db");
+        assert!(adapter.stream_session(Path::new(&format!("{}#c-old", typo.display()))).is_err());
+        assert!(!typo.exists());
+        Ok(())
+    }
+}
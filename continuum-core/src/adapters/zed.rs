@@ -0,0 +1,220 @@
+// Zed adapter
+// Reads AI assistant conversations Zed saves as JSON in ~/.config/zed/conversations
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{newest_first, LogAdapter};
+use crate::config::Config;
+
+/// A saved Zed conversation; only the fields continuum reads
+#[derive(Debug, Deserialize)]
+struct ConversationFile {
+    #[serde(default)]
+    model: Option<Value>,
+    #[serde(default)]
+    messages: Vec<ConversationMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConversationMessage {
+    role: String,
+    #[serde(default)]
+    content: String,
+    timestamp: Option<String>,
+}
+
+/// Messages and model of one Zed conversation file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZedConversation {
+    /// Model the conversation was held with, if recorded
+    pub model: Option<String>,
+    /// Role, content and timestamp of each non-empty message, in order
+    pub messages: Vec<(String, String, Option<String>)>,
+}
+
+pub struct ZedAdapter {
+    conversations_dir: Option<PathBuf>,
+}
+
+impl ZedAdapter {
+    pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    /// Use `assistant_paths.zed`, or ~/.config/zed/conversations
+    pub fn from_config(config: &Config) -> Self {
+        ZedAdapter { conversations_dir: config.assistant_path("zed") }
+    }
+
+    /// Read conversations from a specific directory
+    pub fn with_conversations_dir(path: PathBuf) -> Self {
+        ZedAdapter { conversations_dir: Some(path) }
+    }
+
+    /// Flat directory of `.json` conversation files
+    pub fn conversations_dir(&self) -> Result<PathBuf> {
+        self.conversations_dir.clone().ok_or_else(|| color_eyre::eyre::eyre!("HOME not set"))
+    }
+
+    /// Resolve `--session`: a conversation file, or a session ID looked up in the conversations directory
+    pub fn session_path(&self, session: &str) -> Result<PathBuf> {
+        let path = Path::new(session);
+        if path.is_file() {
+            return Ok(path.to_path_buf());
+        }
+
+        let dir = self.conversations_dir()?;
+        conversation_files(&dir)?
+            .into_iter()
+            .map(|(path, _)| path)
+            .find(|path| session_id_for(path) == session)
+            .ok_or_else(|| color_eyre::eyre::eyre!("No Zed conversation {} in {}", session, dir.display()))
+    }
+
+    /// Distinct models across all saved conversations, sorted
+    /// Files that can't be parsed are skipped.
+    pub fn list_models_used(&self) -> Result<Vec<String>> {
+        let mut models: Vec<String> = conversation_files(&self.conversations_dir()?)?
+            .into_iter()
+            .filter_map(|(path, _)| parse_conversation(&path).ok()?.model)
+            .collect();
+        models.sort();
+        models.dedup();
+        Ok(models)
+    }
+}
+
+impl Default for ZedAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogAdapter for ZedAdapter {
+    fn name(&self) -> &'static str {
+        "zed"
+    }
+
+    fn find_latest_session(&self) -> Result<PathBuf> {
+        let dir = self.conversations_dir()?;
+        conversation_files(&dir)?
+            .into_iter()
+            .max_by(|(a_path, a_mod), (b_path, b_mod)| (a_mod, a_path).cmp(&(b_mod, b_path)))
+            .map(|(path, _)| path)
+            .ok_or_else(|| color_eyre::eyre::eyre!("No Zed conversations found in {}", dir.display()))
+    }
+
+    fn list_sessions(&self) -> Result<Vec<PathBuf>> {
+        Ok(newest_first(conversation_files(&self.conversations_dir()?)?))
+    }
+
+    fn stream_session(&self, path: &Path) -> Result<Box<dyn Iterator<Item = Result<String>>>> {
+        let conversation = parse_conversation(path)?;
+
+        // Emit one JSON object per message (compatible with LogAdapter interface);
+        // the conversation's model is credited with its assistant messages
+        let model = conversation.model;
+        Ok(Box::new(conversation.messages.into_iter().map(move |(role, content, timestamp)| {
+            let model = if role == "assistant" { model.as_deref() } else { None };
+            Ok(json!({ "role": role, "content": content, "timestamp": timestamp, "model": model }).to_string())
+        })))
+    }
+}
+
+/// `.json` files in the conversations directory, with their mtimes
+fn conversation_files(dir: &Path) -> Result<Vec<(PathBuf, std::time::SystemTime)>> {
+    let entries = std::fs::read_dir(dir).with_context(|| format!("Zed conversations directory not found: {}", dir.display()))?;
+
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|path| Some((path.clone(), std::fs::metadata(&path).and_then(|m| m.modified()).ok()?)))
+        .collect())
+}
+
+/// Parse a Zed conversation file
+///
+/// `model` may be a plain name or an object with a `model` field, as newer
+/// Zed versions record the provider alongside it.
+pub fn parse_conversation(path: &Path) -> Result<ZedConversation> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: ConversationFile =
+        serde_json::from_str(&text).with_context(|| format!("Invalid Zed conversation {}", path.display()))?;
+
+    let model = match file.model {
+        Some(Value::String(name)) => Some(name),
+        Some(Value::Object(model)) => model.get("model").and_then(Value::as_str).map(str::to_string),
+        _ => None,
+    };
+    let messages = file
+        .messages
+        .into_iter()
+        .filter(|message| !message.content.trim().is_empty())
+        .map(|message| (message.role, message.content, message.timestamp))
+        .collect();
+
+    Ok(ZedConversation { model, messages })
+}
+
+/// Session ID of a conversation file: its name without `.json` (or `.zed.json`)
+pub fn session_id_for(path: &Path) -> String {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("unknown");
+    let stem = name.strip_suffix(".json").unwrap_or(name);
+    stem.strip_suffix(".zed").unwrap_or(stem).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_and_find_conversations() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let dir = temp_dir.path();
+        let older = dir.join("Borrow checker - 1.zed.json");
+        let newer = dir.join("Parser fix.json");
+        std::fs::write(
+            &older,
+            r#"{"model": {"provider": "anthropic", "model": "claude-3-5-sonnet"},
+                "messages": [{"role": "user", "content": "Why won't this borrow?"}, {"role": "assistant", "content": "Use &value."}]}"#,
+        )?;
+        std::fs::write(
+            &newer,
+            r#"{"model": "gpt-4o", "messages": [{"role": "system", "content": "Be brief."}, {"role": "assistant", "content": "  "}]}"#,
+        )?;
+        std::fs::write(dir.join("notes.txt"), "ignored")?;
+        std::fs::write(dir.join("broken.json"), "{")?;
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        std::fs::File::options().write(true).open(&older)?.set_modified(base)?;
+        std::fs::File::options().write(true).open(&newer)?.set_modified(base + Duration::from_secs(60))?;
+        std::fs::File::options().write(true).open(dir.join("broken.json"))?.set_modified(base)?;
+
+        let adapter = ZedAdapter::with_conversations_dir(dir.to_path_buf());
+        assert_eq!(adapter.find_latest_session()?, newer);
+        assert_eq!(adapter.list_sessions()?, vec![newer.clone(), dir.join("broken.json"), older.clone()]);
+        assert_eq!(adapter.session_path("Borrow checker - 1")?, older);
+        assert_eq!(adapter.list_models_used()?, vec!["claude-3-5-sonnet".to_string(), "gpt-4o".to_string()]);
+
+        let lines: Vec<Value> = adapter
+            .stream_session(&older)?
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect::<Result<_>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["role"], "user");
+        assert_eq!(lines[1]["content"], "Use &value.");
+        assert_eq!(lines[0]["model"], Value::Null);
+        assert_eq!(lines[1]["model"], "claude-3-5-sonnet");
+
+        // Blank messages are skipped
+        let conversation = parse_conversation(&newer)?;
+        assert_eq!(conversation.messages, vec![("system".to_string(), "Be brief.".to_string(), None)]);
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+// Session archiving
+// Bundles old sessions into <assistant>-<date>.tar.zst files and restores them on demand
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::NaiveDate;
+use color_eyre::{eyre::{eyre, Context}, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{plaintext, session_ops, store, PlainTextWriter, SessionFilter, SessionRecord};
+
+/// Manifest of archived sessions, kept in the archive directory and inside each archive
+pub const ARCHIVE_MANIFEST_FILE: &str = "archived.json";
+
+/// Archive directory name, created next to the log directory
+/// (~/Assistants/continuum-logs archives to ~/Assistants/continuum-archives)
+pub const ARCHIVE_DIR_NAME: &str = "continuum-archives";
+
+/// Sessions older than this many days are archived unless a date is given
+pub const DEFAULT_ARCHIVE_AGE_DAYS: u32 = 90;
+
+/// One session stored in an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedSession {
+    pub id: String,
+    pub assistant: String,
+    pub date: String,
+    /// File name of the archive holding the session, relative to the archive directory
+    pub archive: String,
+}
+
+/// Every archived session, as listed in archived.json
+///
+/// Each archive also embeds a manifest of its own sessions, so an archive
+/// file that has been moved elsewhere can still be extracted with
+/// [`extract_archive`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub sessions: Vec<ArchivedSession>,
+}
+
+impl ArchiveManifest {
+    /// Load the manifest in `dir`; a missing manifest is an empty one
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(ARCHIVE_MANIFEST_FILE);
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Write the manifest to `dir`, replacing it atomically
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+        plaintext::write_json_atomic(&dir.join(ARCHIVE_MANIFEST_FILE), self)
+    }
+
+    /// Look up an archived session, optionally restricted to one assistant
+    pub fn find(&self, session_id: &str, assistant: Option<&str>) -> Option<&ArchivedSession> {
+        self.sessions
+            .iter()
+            .find(|s| s.id == session_id && assistant.is_none_or(|a| a.eq_ignore_ascii_case(&s.assistant)))
+    }
+}
+
+impl PlainTextWriter {
+    /// Directory holding archives and their manifest (a sibling of the log directory)
+    pub fn archive_dir(&self) -> PathBuf {
+        self.base_dir().with_file_name(ARCHIVE_DIR_NAME)
+    }
+
+    /// Bundle an assistant's sessions dated before `before` into one .tar.zst archive
+    ///
+    /// The archive is written in full before the manifest is updated and the
+    /// session directories are removed, so an interrupted run loses nothing.
+    /// Returns the archived sessions (empty if none matched, in which case no
+    /// archive is written).
+    pub fn archive_sessions(&self, assistant: &str, before: NaiveDate) -> Result<Vec<ArchivedSession>> {
+        self.archive_sessions_matching(assistant, before, &SessionFilter::new())
+    }
+
+    /// [`Self::archive_sessions`] for only the old sessions `filter` also selects
+    /// Sessions whose session.json the filter needs but can't read are left alone.
+    pub fn archive_sessions_matching(&self, assistant: &str, before: NaiveDate, filter: &SessionFilter) -> Result<Vec<ArchivedSession>> {
+        let locations: Vec<store::SessionLocation> = store::list_sessions(self.base_dir())?
+            .into_iter()
+            .filter(|s| s.assistant.eq_ignore_ascii_case(assistant))
+            .filter(|s| NaiveDate::parse_from_str(&s.date, "%Y-%m-%d").is_ok_and(|date| date < before))
+            .filter(|s| filter.matches_location(s).unwrap_or(false))
+            .collect();
+
+        if locations.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let archive_dir = self.archive_dir();
+        fs::create_dir_all(&archive_dir)
+            .with_context(|| format!("Failed to create directory: {}", archive_dir.display()))?;
+        let archive_name = unused_archive_name(&archive_dir, &format!("{}-{}", assistant, before));
+
+        let archived: Vec<ArchivedSession> = locations
+            .iter()
+            .map(|s| ArchivedSession {
+                id: s.session_id.clone(),
+                assistant: s.assistant.clone(),
+                date: s.date.clone(),
+                archive: archive_name.clone(),
+            })
+            .collect();
+
+        let archive_path = archive_dir.join(&archive_name);
+        let tmp = archive_dir.join(format!("{}.tmp", archive_name));
+        write_archive(&tmp, &archived, &locations)
+            .with_context(|| format!("Failed to write {}", archive_path.display()))?;
+        fs::rename(&tmp, &archive_path)?;
+
+        // Re-archiving a restored session replaces its old entry
+        let mut manifest = ArchiveManifest::load(&archive_dir)?;
+        manifest
+            .sessions
+            .retain(|old| !archived.iter().any(|new| new.id == old.id && new.assistant == old.assistant));
+        manifest.sessions.extend(archived.iter().cloned());
+        manifest.save(&archive_dir)?;
+
+        for location in &locations {
+            session_ops::delete_session(location)?;
+            // A custom layout can leave more than the date directory empty
+            if let Some(parent) = location.path.parent() {
+                crate::layout::remove_empty_dirs(self.base_dir(), parent);
+            }
+        }
+
+        Ok(archived)
+    }
+
+    /// Whether the archive manifest lists this session
+    pub fn is_archived(&self, session_id: &str, assistant: &str) -> bool {
+        ArchiveManifest::load(&self.archive_dir())
+            .map(|manifest| manifest.find(session_id, Some(assistant)).is_some())
+            .unwrap_or(false)
+    }
+
+    /// Extract one archived session back into the log directory
+    /// Returns the restored session directory
+    pub fn unarchive_session(&self, session_id: &str, assistant: &str) -> Result<PathBuf> {
+        let archive_dir = self.archive_dir();
+        let manifest = ArchiveManifest::load(&archive_dir)?;
+        let entry = manifest
+            .find(session_id, Some(assistant))
+            .ok_or_else(|| eyre!("Session '{}' for '{}' is not archived", session_id, assistant))?;
+
+        let archive_path = archive_dir.join(&entry.archive);
+        let prefix = Path::new(&entry.assistant).join(&entry.date).join(&entry.id);
+        let target = self.session_dir(&entry.assistant, &entry.date, &entry.id);
+
+        // Archives always hold <assistant>/<date>/<id>; unpack into wherever the layout puts it
+        let mut archive = open_archive(&archive_path)?;
+        let mut found = false;
+        for item in archive.entries()? {
+            let mut item = item?;
+            let path = item.path()?.into_owned();
+            let Ok(relative) = path.strip_prefix(&prefix) else {
+                continue;
+            };
+            if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+                continue;
+            }
+            let dest = target.join(relative);
+            self.create_session_dir(dest.parent().unwrap_or(&target))?;
+            item.unpack(&dest)?;
+            found = true;
+        }
+
+        if !found {
+            return Err(eyre!("Session '{}' is missing from {}", session_id, archive_path.display()));
+        }
+        if self.layout().has_project() {
+            let project = SessionRecord::from_dir(&target).ok().and_then(|record| record.project);
+            self.file_under_project(&target, &entry.assistant, &entry.date, &entry.id, project.as_deref())?;
+            return Ok(self.session_dir(&entry.assistant, &entry.date, &entry.id));
+        }
+        Ok(target)
+    }
+
+    /// Restore a session from its archive if it is archived and no longer on disk
+    /// Returns the restored directory, or `None` if nothing needed restoring
+    pub fn restore_if_archived(&self, session_id: &str, assistant: &str) -> Result<Option<PathBuf>> {
+        let on_disk = self.session_exists(session_id, assistant)?;
+        if on_disk || !self.is_archived(session_id, assistant) {
+            return Ok(None);
+        }
+        self.unarchive_session(session_id, assistant).map(Some)
+    }
+
+    fn session_exists(&self, session_id: &str, assistant: &str) -> Result<bool> {
+        Ok(store::list_sessions(self.base_dir())?
+            .iter()
+            .any(|s| s.session_id == session_id && s.assistant.eq_ignore_ascii_case(assistant)))
+    }
+}
+
+/// Extract every session in an archive into `base_dir`
+/// Needs only the archive file itself; returns the sessions its embedded manifest lists
+pub fn extract_archive(archive_path: &Path, base_dir: &Path) -> Result<Vec<ArchivedSession>> {
+    let mut archive = open_archive(archive_path)?;
+    let mut manifest = None;
+
+    fs::create_dir_all(base_dir)
+        .with_context(|| format!("Failed to create directory: {}", base_dir.display()))?;
+
+    for item in archive.entries()? {
+        let mut item = item?;
+        if item.path()?.as_ref() == Path::new(ARCHIVE_MANIFEST_FILE) {
+            let mut raw = String::new();
+            item.read_to_string(&mut raw)?;
+            manifest = Some(serde_json::from_str::<ArchiveManifest>(&raw)?);
+        } else {
+            item.unpack_in(base_dir)?;
+        }
+    }
+
+    manifest
+        .map(|m| m.sessions)
+        .ok_or_else(|| eyre!("{} has no {}", archive_path.display(), ARCHIVE_MANIFEST_FILE))
+}
+
+/// `<stem>.tar.zst`, or `<stem>-2.tar.zst` etc. if an archive by that name already exists
+fn unused_archive_name(dir: &Path, stem: &str) -> String {
+    let mut name = format!("{}.tar.zst", stem);
+    let mut n = 2;
+    while dir.join(&name).exists() {
+        name = format!("{}-{}.tar.zst", stem, n);
+        n += 1;
+    }
+    name
+}
+
+/// Write the embedded manifest followed by each session's directory
+/// Entries are stored as <assistant>/<date>/<session_id>/..., whatever the log directory's layout
+fn write_archive(path: &Path, sessions: &[ArchivedSession], locations: &[store::SessionLocation]) -> Result<()> {
+    let encoder = zstd::Encoder::new(File::create(path)?, 0)?;
+    let mut builder = tar::Builder::new(encoder);
+
+    let embedded = serde_json::to_vec_pretty(&ArchiveManifest { sessions: sessions.to_vec() })?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(embedded.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, ARCHIVE_MANIFEST_FILE, embedded.as_slice())?;
+
+    for location in locations {
+        let name = Path::new(&location.assistant).join(&location.date).join(&location.session_id);
+        builder.append_dir_all(&name, &location.path)?;
+    }
+
+    builder.into_inner()?.finish()?.sync_all()?;
+    Ok(())
+}
+
+fn open_archive(path: &Path) -> Result<tar::Archive<zstd::Decoder<'static, std::io::BufReader<File>>>> {
+    let file = File::open(path).with_context(|| format!("Failed to open archive {}", path.display()))?;
+    Ok(tar::Archive::new(zstd::Decoder::new(file)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_session(writer: &PlainTextWriter, id: &str, date: &str, text: &str) -> Result<()> {
+        let messages = vec![("user".to_string(), text.to_string())];
+        writer.import_messages(id, "codex", date, &messages, &[], None, false)?;
+        writer.write_session(id, "codex", Some(&format!("{}T10:00:00Z", date)), None, "closed", 1, &[], None, None)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_and_unarchive() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().join("continuum-logs"));
+        write_session(&writer, "old", "2025-01-10", "first")?;
+        write_session(&writer, "older", "2025-01-02", "second")?;
+        write_session(&writer, "recent", "2025-06-01", "third")?;
+
+        let before = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let archived = writer.archive_sessions("codex", before)?;
+        assert_eq!(archived.len(), 2);
+        assert_eq!(archived[0].archive, "codex-2025-03-01.tar.zst");
+        assert_eq!(writer.archive_dir(), temp_dir.path().join(ARCHIVE_DIR_NAME));
+        assert!(writer.archive_dir().join("codex-2025-03-01.tar.zst").exists());
+
+        // Originals (and their now-empty date directories) are gone
+        assert!(!writer.base_dir().join("codex/2025-01-10").exists());
+        assert!(writer.base_dir().join("codex/2025-06-01/recent").exists());
+        assert!(writer.is_archived("old", "codex"));
+        assert!(!writer.is_archived("recent", "codex"));
+
+        // Nothing left to archive
+        assert!(writer.archive_sessions("codex", before)?.is_empty());
+
+        let restored = writer.restore_if_archived("old", "codex")?.expect("restored");
+        assert_eq!(restored, writer.base_dir().join("codex/2025-01-10/old"));
+        assert_eq!(store::load_messages(&restored)?[0].content, "first");
+        assert!(!writer.base_dir().join("codex/2025-01-02/older").exists());
+
+        // Already on disk: nothing to do
+        assert!(writer.restore_if_archived("old", "codex")?.is_none());
+        assert!(writer.unarchive_session("recent", "codex").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_under_custom_layout_leaves_no_empty_dirs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().join("continuum-logs")).with_layout("{date}/{assistant}/{session}")?;
+        write_session(&writer, "old", "2025-01-10", "first")?;
+
+        writer.archive_sessions("codex", NaiveDate::from_ymd_opt(2025, 3, 1).unwrap())?;
+        assert!(!writer.base_dir().join("2025-01-10").exists());
+
+        let restored = writer.restore_if_archived("old", "codex")?.expect("restored");
+        assert_eq!(restored, writer.base_dir().join("2025-01-10/codex/old"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_moved_archive_is_self_contained() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().join("continuum-logs"));
+        write_session(&writer, "s1", "2025-01-10", "hello")?;
+
+        let before = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        writer.archive_sessions("codex", before)?;
+
+        let moved = temp_dir.path().join("moved.tar.zst");
+        fs::rename(writer.archive_dir().join("codex-2025-03-01.tar.zst"), &moved)?;
+
+        let target = temp_dir.path().join("elsewhere");
+        let sessions = extract_archive(&moved, &target)?;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, "s1");
+        assert_eq!(store::load_messages(&target.join("codex/2025-01-10/s1"))?[0].content, "hello");
+        assert!(target.join("codex/2025-01-10/s1/session.json").exists());
+        Ok(())
+    }
+}
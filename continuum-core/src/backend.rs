@@ -0,0 +1,472 @@
+// Writer backends
+// The write side of the log tree, so imports can run for real or as a dry run
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::Result;
+use serde_json::Value;
+
+use crate::loop_detection::LoopDetector;
+use crate::plaintext::{DateZone, ImportOutcome, PlainTextWriter};
+use crate::spool::ImportSpool;
+use crate::types::SessionCost;
+
+/// Operations an import performs on the log tree
+///
+/// [`PlainTextWriter`] carries them out; [`DryRunWriter`] only reports what
+/// they would do.
+pub trait WriterBackend {
+    /// Root of the log tree
+    fn base_dir(&self) -> &Path;
+
+    /// Whether writes are only reported, never performed
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+
+    /// Date directory `write_session` files a session starting at `timestamp` under
+    fn date_of(&self, timestamp: Option<&str>) -> String;
+
+    /// Date directory for a session, reusing the one it's stored under (see [`PlainTextWriter::session_date`])
+    fn session_date(&self, session_id: &str, assistant: &str, timestamp: Option<&str>) -> String {
+        match crate::store::find_session(self.base_dir(), session_id, Some(assistant)) {
+            Ok(location) => location.date,
+            Err(_) => self.date_of(timestamp),
+        }
+    }
+
+    /// Create or refresh a session's session.json, returning its directory
+    #[allow(clippy::too_many_arguments)]
+    fn write_session(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        start_time: Option<&str>,
+        end_time: Option<&str>,
+        status: &str,
+        message_count: usize,
+        skills: &[String],
+        cost: Option<&SessionCost>,
+        cwd: Option<&str>,
+    ) -> Result<PathBuf>;
+
+    /// Append one message to a session's messages file, recording the model that wrote it if known
+    #[allow(clippy::too_many_arguments)]
+    fn append_message(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        message_id: usize,
+        role: &str,
+        content: &str,
+        timestamp: Option<&str>,
+        tokens: Option<usize>,
+        model: Option<&str>,
+    ) -> Result<()>;
+
+    /// Store a session's messages (see [`PlainTextWriter::import_messages`])
+    #[allow(clippy::too_many_arguments)]
+    fn import_messages(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        messages: &[(String, String)],
+        timestamps: &[Option<String>],
+        timestamp: Option<&str>,
+        force: bool,
+    ) -> Result<ImportOutcome> {
+        let spool = ImportSpool::from_messages(messages, timestamps)?;
+        self.import_spool(session_id, assistant, date, &spool, timestamp, force)
+    }
+
+    /// Store a session's spooled messages (see [`PlainTextWriter::import_spool`])
+    #[allow(clippy::too_many_arguments)]
+    fn import_spool(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        spool: &ImportSpool,
+        timestamp: Option<&str>,
+        force: bool,
+    ) -> Result<ImportOutcome>;
+
+    /// Merge fields into a session's session.json
+    fn update_session_metadata(&self, session_id: &str, assistant: &str, date: &str, updates: Value) -> Result<()>;
+
+    /// Attach a tag to a stored session
+    fn add_tag(&self, session_id: &str, assistant: &str, date: &str, tag: &str) -> Result<()>;
+
+    /// Replace a session's entries in the search index
+    fn index_session(&self, session_id: &str, assistant: &str, date: &str, messages: &[(String, String)]) -> Result<()>;
+
+    /// Replace a session's entries in the search index with the spooled messages
+    fn index_spool(&self, session_id: &str, assistant: &str, date: &str, spool: &ImportSpool) -> Result<()>;
+
+    /// Bring an archived session back into the log tree (see [`PlainTextWriter::restore_if_archived`])
+    fn restore_if_archived(&self, session_id: &str, assistant: &str) -> Result<Option<PathBuf>>;
+
+    /// Directory holding archives and their manifest
+    fn archive_dir(&self) -> PathBuf;
+}
+
+impl WriterBackend for PlainTextWriter {
+    fn base_dir(&self) -> &Path {
+        PlainTextWriter::base_dir(self)
+    }
+
+    fn date_of(&self, timestamp: Option<&str>) -> String {
+        PlainTextWriter::date_of(self, timestamp)
+    }
+
+    fn session_date(&self, session_id: &str, assistant: &str, timestamp: Option<&str>) -> String {
+        PlainTextWriter::session_date(self, session_id, assistant, timestamp)
+    }
+
+    fn write_session(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        start_time: Option<&str>,
+        end_time: Option<&str>,
+        status: &str,
+        message_count: usize,
+        skills: &[String],
+        cost: Option<&SessionCost>,
+        cwd: Option<&str>,
+    ) -> Result<PathBuf> {
+        PlainTextWriter::write_session(self, session_id, assistant, start_time, end_time, status, message_count, skills, cost, cwd)
+    }
+
+    fn append_message(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        message_id: usize,
+        role: &str,
+        content: &str,
+        timestamp: Option<&str>,
+        tokens: Option<usize>,
+        model: Option<&str>,
+    ) -> Result<()> {
+        PlainTextWriter::append_message(self, session_id, assistant, date, message_id, role, content, timestamp, tokens, model)
+    }
+
+    fn import_spool(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        spool: &ImportSpool,
+        timestamp: Option<&str>,
+        force: bool,
+    ) -> Result<ImportOutcome> {
+        PlainTextWriter::import_spool(self, session_id, assistant, date, spool, timestamp, force)
+    }
+
+    fn update_session_metadata(&self, session_id: &str, assistant: &str, date: &str, updates: Value) -> Result<()> {
+        PlainTextWriter::update_session_metadata(self, session_id, assistant, date, updates)
+    }
+
+    fn add_tag(&self, session_id: &str, assistant: &str, date: &str, tag: &str) -> Result<()> {
+        PlainTextWriter::add_tag(self, session_id, assistant, date, tag)
+    }
+
+    fn index_session(&self, session_id: &str, assistant: &str, date: &str, messages: &[(String, String)]) -> Result<()> {
+        PlainTextWriter::index_session(self, session_id, assistant, date, messages)
+    }
+
+    fn index_spool(&self, session_id: &str, assistant: &str, date: &str, spool: &ImportSpool) -> Result<()> {
+        PlainTextWriter::index_spool(self, session_id, assistant, date, spool)
+    }
+
+    fn restore_if_archived(&self, session_id: &str, assistant: &str) -> Result<Option<PathBuf>> {
+        PlainTextWriter::restore_if_archived(self, session_id, assistant)
+    }
+
+    fn archive_dir(&self) -> PathBuf {
+        PlainTextWriter::archive_dir(self)
+    }
+}
+
+/// Prefix of every line a [`DryRunWriter`] prints
+pub const DRY_RUN_PREFIX: &str = "[DRY RUN]";
+
+/// Backend that prints each write to stderr instead of performing it
+///
+/// The log tree is only read, to report whether an import would append to or
+/// rewrite a stored session and whether it duplicates another one. With a
+/// loop detector set, the messages of each import are also checked for loops.
+pub struct DryRunWriter {
+    /// Used for reads only
+    store: PlainTextWriter,
+    detector: Option<LoopDetector>,
+}
+
+impl DryRunWriter {
+    pub fn with_base_dir(base_dir: PathBuf) -> Self {
+        DryRunWriter { store: PlainTextWriter::with_base_dir(base_dir), detector: None }
+    }
+
+    /// Report paths as a compressing writer would create them
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.store = self.store.with_compression(compress);
+        self
+    }
+
+    /// Report paths dated in `zone` (see [`PlainTextWriter::with_zone`])
+    pub fn with_zone(mut self, zone: DateZone) -> Self {
+        self.store = self.store.with_zone(zone);
+        self
+    }
+
+    /// Report the loops this detector finds in each imported session
+    pub fn with_loop_detector(mut self, detector: LoopDetector) -> Self {
+        self.detector = Some(detector);
+        self
+    }
+
+    fn report(&self, line: String) {
+        eprintln!("{} {}", DRY_RUN_PREFIX, line);
+    }
+
+    /// The first and last message of a batch, as a reminder of what would be stored
+    fn sample(messages: &[(String, String)]) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some((role, content)) = messages.first() {
+            lines.push(format!("  first: [{}] {}", role, LoopDetector::snippet(content)));
+        }
+        if let (true, Some((role, content))) = (messages.len() > 1, messages.last()) {
+            lines.push(format!("  last:  [{}] {}", role, LoopDetector::snippet(content)));
+        }
+        lines
+    }
+
+    /// Token count and first and last message of the spooled messages after the first `skip`
+    fn spooled_sample(spool: &ImportSpool, skip: usize) -> Result<(usize, Vec<String>)> {
+        let mut tokens = 0;
+        let mut ends: Vec<(String, String)> = Vec::new();
+        for message in spool.messages()?.skip(skip) {
+            let message = message?;
+            tokens += PlainTextWriter::count_tokens(&message.1);
+            if ends.len() == 2 {
+                ends.pop();
+            }
+            ends.push(message);
+        }
+        Ok((tokens, Self::sample(&ends)))
+    }
+}
+
+impl WriterBackend for DryRunWriter {
+    fn base_dir(&self) -> &Path {
+        self.store.base_dir()
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+
+    fn date_of(&self, timestamp: Option<&str>) -> String {
+        self.store.date_of(timestamp)
+    }
+
+    fn write_session(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        start_time: Option<&str>,
+        _end_time: Option<&str>,
+        status: &str,
+        message_count: usize,
+        _skills: &[String],
+        _cost: Option<&SessionCost>,
+        _cwd: Option<&str>,
+    ) -> Result<PathBuf> {
+        let date = self.store.session_date(session_id, assistant, start_time);
+        let session_dir = self.store.session_dir(assistant, &date, session_id);
+        self.report(format!(
+            "Would write {} ({} messages, status {})",
+            session_dir.join("session.json").display(),
+            message_count,
+            status
+        ));
+        Ok(session_dir)
+    }
+
+    fn append_message(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        message_id: usize,
+        role: &str,
+        content: &str,
+        _timestamp: Option<&str>,
+        tokens: Option<usize>,
+        _model: Option<&str>,
+    ) -> Result<()> {
+        let (messages_path, _) = self.store.messages_target(&self.store.session_dir(assistant, date, session_id));
+        let tokens = tokens.unwrap_or_else(|| PlainTextWriter::count_tokens(content));
+        self.report(format!(
+            "Would append message {} ({}, {} tokens) to {}",
+            message_id,
+            role,
+            tokens,
+            messages_path.display()
+        ));
+        Ok(())
+    }
+
+    fn import_spool(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        spool: &ImportSpool,
+        _timestamp: Option<&str>,
+        force: bool,
+    ) -> Result<ImportOutcome> {
+        if let Some(ref detector) = self.detector {
+            let detections = detector.analyze_stream(spool.messages()?.map_while(Result::ok));
+            if detections.is_empty() {
+                self.report("Loop detection: no loops found".to_string());
+            }
+            for detection in detections {
+                self.report(format!("Loop detection ({:?}): {}", detection.severity, detection.message));
+            }
+        }
+
+        if !force && !spool.is_empty() {
+            if let Some(duplicate) = self.store.duplicate_of(session_id, assistant, &spool.content_hash())? {
+                return Err(duplicate.into());
+            }
+        }
+
+        let session_dir = self.store.session_dir(assistant, date, session_id);
+        let (messages_path, _) = self.store.messages_target(&session_dir);
+
+        let outcome = match self.store.appendable_prefix(&session_dir, spool, force)? {
+            Some(stored) if stored == spool.len() => {
+                self.report(format!("No new messages for {}", messages_path.display()));
+                ImportOutcome::Unchanged
+            }
+            Some(stored) => {
+                let (tokens, sample) = Self::spooled_sample(spool, stored)?;
+                self.report(format!(
+                    "Would append {} messages (~{} tokens) to {}",
+                    spool.len() - stored,
+                    tokens,
+                    messages_path.display()
+                ));
+                sample.into_iter().for_each(|line| self.report(line));
+                ImportOutcome::Appended(spool.len() - stored)
+            }
+            None => {
+                let (tokens, sample) = Self::spooled_sample(spool, 0)?;
+                self.report(format!(
+                    "Would write {} messages (~{} tokens) to {}",
+                    spool.len(),
+                    tokens,
+                    messages_path.display()
+                ));
+                sample.into_iter().for_each(|line| self.report(line));
+                ImportOutcome::Rewritten(spool.len())
+            }
+        };
+        Ok(outcome)
+    }
+
+    fn update_session_metadata(&self, session_id: &str, assistant: &str, date: &str, updates: Value) -> Result<()> {
+        let path = self.store.session_dir(assistant, date, session_id).join("session.json");
+        self.report(format!("Would update {} with {}", path.display(), updates));
+        Ok(())
+    }
+
+    fn add_tag(&self, session_id: &str, _assistant: &str, _date: &str, tag: &str) -> Result<()> {
+        let tag = PlainTextWriter::normalize_tag(tag)?;
+        self.report(format!("Would tag {} with '{}'", session_id, tag));
+        Ok(())
+    }
+
+    fn index_session(&self, session_id: &str, _assistant: &str, _date: &str, messages: &[(String, String)]) -> Result<()> {
+        self.report(format!("Would index {} messages of {} for search", messages.len(), session_id));
+        Ok(())
+    }
+
+    fn index_spool(&self, session_id: &str, _assistant: &str, _date: &str, spool: &ImportSpool) -> Result<()> {
+        self.report(format!("Would index {} messages of {} for search", spool.len(), session_id));
+        Ok(())
+    }
+
+    fn restore_if_archived(&self, session_id: &str, assistant: &str) -> Result<Option<PathBuf>> {
+        let on_disk = crate::store::find_session(self.base_dir(), session_id, Some(assistant)).is_ok();
+        if !on_disk && self.store.is_archived(session_id, assistant) {
+            self.report(format!("Would restore {} session {} from its archive", assistant, session_id));
+        }
+        Ok(None)
+    }
+
+    fn archive_dir(&self) -> PathBuf {
+        self.store.archive_dir()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn conversation(n: usize) -> Vec<(String, String)> {
+        (1..=n).map(|i| ("user".to_string(), format!("message {}", i))).collect()
+    }
+
+    #[test]
+    fn test_dry_run_writes_nothing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let dry_run = DryRunWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let backend: &dyn WriterBackend = &dry_run;
+
+        let outcome = backend.import_messages("s1", "codex", "2025-11-09", &conversation(3), &[], None, false)?;
+        assert_eq!(outcome, ImportOutcome::Rewritten(3));
+        let session_dir = backend.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 3, &[], None, None)?;
+        assert!(session_dir.ends_with("codex/2025-11-09/s1"));
+        backend.add_tag("s1", "codex", "2025-11-09", "project:continuum")?;
+        backend.index_session("s1", "codex", "2025-11-09", &conversation(3))?;
+
+        assert!(backend.is_dry_run());
+        assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run_matches_real_outcomes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        writer.import_messages("s1", "codex", "2025-11-09", &conversation(2), &[], None, false)?;
+        writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 2, &[], None, None)?;
+
+        let dry_run = DryRunWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let plan = |messages: &[(String, String)], force| dry_run.import_messages("s1", "codex", "2025-11-09", messages, &[], None, force);
+
+        assert_eq!(plan(&conversation(2), false)?, ImportOutcome::Unchanged);
+        assert_eq!(plan(&conversation(5), false)?, ImportOutcome::Appended(3));
+        assert_eq!(plan(&conversation(5), true)?, ImportOutcome::Rewritten(5));
+        assert_eq!(
+            DryRunWriter::sample(&conversation(5)[2..]),
+            vec!["  first: [user] message 3", "  last:  [user] message 5"]
+        );
+        assert_eq!(DryRunWriter::sample(&conversation(1)), vec!["  first: [user] message 1"]);
+
+        // Same content under another ID is refused, as a real import would be
+        let duplicate = dry_run.import_messages("s2", "codex", "2025-11-09", &conversation(2), &[], None, false);
+        assert!(duplicate.unwrap_err().downcast_ref::<crate::DuplicateSession>().is_some());
+
+        // The stored session is untouched
+        assert_eq!(crate::store::load_messages(&temp_dir.path().join("codex/2025-11-09/s1"))?.len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,159 @@
+// Batch imports
+// Writes many sessions in one go, pacing disk writes so a large import doesn't swamp other processes
+
+use std::time::Duration;
+
+use color_eyre::Result;
+
+use crate::{DuplicateSession, ImportOutcome, PlainTextWriter};
+
+/// How a batch import paces itself and reports progress
+pub struct ImportOptions {
+    /// Pause after every `batch_size` sessions; `None` writes as fast as possible
+    pub rate_limit: Option<Duration>,
+    /// Sessions written between pauses (0 counts as 1)
+    pub batch_size: usize,
+    /// Called with `(completed, total)` after each session, whether it was written or not
+    pub progress_callback: Option<Box<dyn Fn(usize, usize)>>,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions { rate_limit: None, batch_size: 1, progress_callback: None }
+    }
+}
+
+impl ImportOptions {
+    /// Report progress, then pause if a batch just finished and sessions remain
+    pub fn session_done(&self, completed: usize, total: usize) {
+        if let Some(ref callback) = self.progress_callback {
+            callback(completed, total);
+        }
+        if let Some(pause) = self.rate_limit {
+            if completed < total && completed.is_multiple_of(self.batch_size.max(1)) {
+                std::thread::sleep(pause);
+            }
+        }
+    }
+}
+
+/// One session for [`PlainTextWriter::write_session_batch`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchSession {
+    pub session_id: String,
+    pub assistant: String,
+    /// RFC3339 start time; also stamps messages without a timestamp of their own
+    pub start_time: Option<String>,
+    pub messages: Vec<(String, String)>,
+    /// Per-message timestamps, parallel to `messages` (may be empty)
+    pub timestamps: Vec<Option<String>>,
+}
+
+/// What a batch import did, session by session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchImportResult {
+    /// Sessions written or extended
+    pub succeeded: usize,
+    /// Sessions already stored unchanged, or duplicating another stored session
+    pub skipped: usize,
+    /// Sessions that failed to import
+    pub failed: usize,
+}
+
+impl PlainTextWriter {
+    /// Import `sessions` one after another, paced by `options`
+    ///
+    /// Each session goes through [`Self::import_messages`] and then
+    /// [`Self::write_session`], so re-importing appends only what's new. A
+    /// session that fails is counted and the batch carries on.
+    pub fn write_session_batch(&self, sessions: &[BatchSession], options: &ImportOptions) -> Result<BatchImportResult> {
+        let mut result = BatchImportResult::default();
+
+        for (index, session) in sessions.iter().enumerate() {
+            match self.write_batch_session(session) {
+                Ok(ImportOutcome::Unchanged) => result.skipped += 1,
+                Ok(_) => result.succeeded += 1,
+                Err(e) if e.downcast_ref::<DuplicateSession>().is_some() => result.skipped += 1,
+                Err(_) => result.failed += 1,
+            }
+            options.session_done(index + 1, sessions.len());
+        }
+
+        Ok(result)
+    }
+
+    fn write_batch_session(&self, session: &BatchSession) -> Result<ImportOutcome> {
+        crate::session_ops::check_path_component("session ID", &session.session_id)?;
+        let start_time = session.start_time.as_deref();
+        let date = self.session_date(&session.session_id, &session.assistant, start_time);
+
+        let outcome = self.import_messages(
+            &session.session_id,
+            &session.assistant,
+            &date,
+            &session.messages,
+            &session.timestamps,
+            start_time,
+            false,
+        )?;
+        if outcome != ImportOutcome::Unchanged {
+            self.write_session(
+                &session.session_id,
+                &session.assistant,
+                start_time,
+                None,
+                "closed",
+                session.messages.len(),
+                &[],
+                None,
+                None,
+            )?;
+        }
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_session_batch_counts_and_progress() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let session = |id: &str, text: &str| BatchSession {
+            session_id: id.to_string(),
+            assistant: "goose".to_string(),
+            start_time: Some("2025-11-09T10:00:00Z".to_string()),
+            messages: vec![("user".to_string(), text.to_string())],
+            timestamps: Vec::new(),
+        };
+        let sessions = vec![
+            session("first", "Set up the project"),
+            session("copy", "Set up the project"),
+            session("../escape", "Read the config"),
+            session("second", "Read the config"),
+        ];
+
+        let progress = Rc::new(RefCell::new(Vec::new()));
+        let seen = Rc::clone(&progress);
+        let options = ImportOptions {
+            rate_limit: Some(Duration::from_millis(1)),
+            batch_size: 2,
+            progress_callback: Some(Box::new(move |done, total| seen.borrow_mut().push((done, total)))),
+        };
+
+        let result = writer.write_session_batch(&sessions, &options)?;
+        assert_eq!(result, BatchImportResult { succeeded: 2, skipped: 1, failed: 1 });
+        assert_eq!(*progress.borrow(), vec![(1, 4), (2, 4), (3, 4), (4, 4)]);
+        assert!(temp_dir.path().join("goose/2025-11-09/second/session.json").exists());
+
+        // Nothing new the second time round
+        let again = writer.write_session_batch(&sessions[..1], &ImportOptions::default())?;
+        assert_eq!(again, BatchImportResult { succeeded: 0, skipped: 1, failed: 0 });
+        Ok(())
+    }
+}
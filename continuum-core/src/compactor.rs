@@ -0,0 +1,212 @@
+// Session compaction
+// Replaces the older messages of a stored session with a single summary marker
+
+use color_eyre::{eyre::{bail, Context}, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::BufRead;
+use std::path::Path;
+
+use crate::plaintext::{temp_path, write_json_atomic};
+use crate::store::{self, open_messages};
+use crate::PlainTextWriter;
+
+/// What [`Compactor::compact`] changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionResult {
+    /// Messages the session held before compaction, counting those earlier compactions summarized
+    pub original_messages: usize,
+    /// Messages replaced by the summary marker, in this and earlier compactions
+    pub summarized_messages: usize,
+    /// Lines in the messages file before and after
+    pub lines_before: usize,
+    pub lines_after: usize,
+    /// Size of the messages file before and after
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl CompactionResult {
+    pub fn bytes_saved(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+
+    /// How many fewer lines the messages file has
+    pub fn messages_removed(&self) -> usize {
+        self.lines_before.saturating_sub(self.lines_after)
+    }
+
+    /// Whether the messages file was left as it was
+    pub fn is_unchanged(&self) -> bool {
+        self.lines_before == self.lines_after && self.bytes_before == self.bytes_after
+    }
+}
+
+/// Shrinks stored sessions in place
+pub struct Compactor;
+
+impl Compactor {
+    /// Keep the last `keep_last_n` messages of a session and replace everything
+    /// before them with one `system` message marked `"compacted": true`
+    ///
+    /// Messages are renumbered from 1 and the file keeps its format (plain or
+    /// gzip). session.json gets `"status": "compacted"`, the new `message_count`
+    /// and `original_message_count`. A session already within the limit is
+    /// left untouched. Compacting again folds the earlier marker into the new one.
+    pub fn compact(session_dir: &Path, keep_last_n: usize) -> Result<CompactionResult> {
+        let Some(messages_path) = store::messages_file(session_dir) else {
+            bail!("No messages file in {}", session_dir.display());
+        };
+        let bytes_before = fs::metadata(&messages_path)?.len();
+
+        let mut records = Vec::new();
+        for line in open_messages(&messages_path)?.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Value = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid message in {}", messages_path.display()))?;
+            records.push(record);
+        }
+
+        let metadata_path = session_dir.join("session.json");
+        let metadata: Value = match fs::read_to_string(&metadata_path) {
+            Ok(text) => serde_json::from_str(&text)
+                .with_context(|| format!("Invalid JSON in {}", metadata_path.display()))?,
+            Err(_) => json!({}),
+        };
+
+        // An earlier marker stands for the messages it summarized
+        let marker_count = records.first().filter(|r| Self::is_marker(r)).map(|_| 1).unwrap_or(0);
+        let previously_summarized = if marker_count == 1 {
+            let original = metadata["original_message_count"].as_u64().map(|n| n as usize);
+            original.map_or(1, |n| n.saturating_sub(records.len() - 1))
+        } else {
+            0
+        };
+        let original_messages = previously_summarized + records.len() - marker_count;
+
+        let kept_from = records.len().saturating_sub(keep_last_n).max(marker_count);
+        let unchanged = CompactionResult {
+            original_messages,
+            summarized_messages: previously_summarized,
+            lines_before: records.len(),
+            lines_after: records.len(),
+            bytes_before,
+            bytes_after: bytes_before,
+        };
+        if kept_from == marker_count {
+            return Ok(unchanged);
+        }
+
+        let summarized_messages = previously_summarized + kept_from - marker_count;
+        let last_summarized = &records[kept_from - 1];
+        let mut marker = PlainTextWriter::message_record(
+            1,
+            "system",
+            &format!("[Compacted: {} messages summarized]", summarized_messages),
+            last_summarized["timestamp"].as_str(),
+            None,
+            None,
+        );
+        marker["compacted"] = json!(true);
+
+        let mut compacted = vec![marker];
+        for (offset, record) in records[kept_from..].iter().enumerate() {
+            let mut record = record.clone();
+            record["id"] = json!(offset + 2);
+            compacted.push(record);
+        }
+
+        let gzip = messages_path.ends_with(store::COMPRESSED_MESSAGES_FILE);
+        let encoded = PlainTextWriter::encode_records(&compacted, gzip)?;
+        let tmp = temp_path(&messages_path);
+        let written = fs::write(&tmp, &encoded).and_then(|_| fs::rename(&tmp, &messages_path));
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        written.with_context(|| format!("Failed to write {}", messages_path.display()))?;
+
+        let updates = json!({
+            "status": "compacted",
+            "message_count": compacted.len(),
+            "original_message_count": original_messages,
+        });
+        write_json_atomic(&metadata_path, &PlainTextWriter::merge_metadata(metadata, &updates))?;
+
+        Ok(CompactionResult {
+            summarized_messages,
+            lines_after: compacted.len(),
+            bytes_after: encoded.len() as u64,
+            ..unchanged
+        })
+    }
+
+    /// Whether a messages.jsonl record is a compaction marker
+    pub fn is_marker(record: &Value) -> bool {
+        record["compacted"].as_bool() == Some(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::SessionValidator;
+    use tempfile::TempDir;
+
+    fn stored_session(writer: &PlainTextWriter, count: usize) -> Result<std::path::PathBuf> {
+        let messages: Vec<(String, String)> =
+            (1..=count).map(|i| ("user".to_string(), format!("message {}", i))).collect();
+        writer.import_messages("s1", "codex", "2025-11-09", &messages, &[], Some("2025-11-09T10:00:00Z"), false)?;
+        writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", count, &[], None, None)
+    }
+
+    #[test]
+    fn test_compact_keeps_tail() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let session_dir = stored_session(&writer, 10)?;
+
+        let result = Compactor::compact(&session_dir, 3)?;
+        assert_eq!((result.original_messages, result.summarized_messages), (10, 7));
+        assert_eq!(result.messages_removed(), 6);
+        assert!(result.bytes_saved() > 0);
+
+        // Still valid JSONL in the stored format
+        assert!(SessionValidator::validate(&session_dir).errors.is_empty());
+        let messages = store::load_messages(&session_dir)?;
+        let contents: Vec<&str> = messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(
+            contents,
+            vec!["[Compacted: 7 messages summarized]", "message 8", "message 9", "message 10"]
+        );
+        assert_eq!(messages.iter().map(|m| m.id).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+        let metadata: Value = serde_json::from_str(&fs::read_to_string(session_dir.join("session.json"))?)?;
+        assert_eq!(metadata["status"], "compacted");
+        assert_eq!(metadata["original_message_count"], 10);
+        assert_eq!(metadata["message_count"], 4);
+
+        // Compacting again folds the old marker into the new one
+        let again = Compactor::compact(&session_dir, 1)?;
+        assert_eq!((again.original_messages, again.summarized_messages), (10, 9));
+        assert_eq!(store::load_messages(&session_dir)?[0].content, "[Compacted: 9 messages summarized]");
+        assert!(Compactor::compact(&session_dir, 5)?.is_unchanged());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_short_session_is_unchanged() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf()).with_compression(true);
+        let session_dir = stored_session(&writer, 3)?;
+
+        assert!(Compactor::compact(&session_dir, 3)?.is_unchanged());
+        let result = Compactor::compact(&session_dir, 0)?;
+        assert_eq!(result.lines_after, 1);
+        assert!(session_dir.join(store::COMPRESSED_MESSAGES_FILE).exists());
+        assert!(SessionValidator::validate(&session_dir).errors.is_empty());
+        Ok(())
+    }
+}
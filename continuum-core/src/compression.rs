@@ -1,7 +1,253 @@
 // Compression and noise filtering for messages
 // Removes boilerplate, pleasantries, and redundant content
 
+use std::collections::HashSet;
+use std::path::Path;
+
+use color_eyre::{eyre::Context, Result};
 use regex::Regex;
+use serde::Deserialize;
+
+use crate::tool_capture::{TOOL_RESULT_PREFIX, TOOL_ROLE, TOOL_USE_PREFIX};
+
+/// Default minimum length for a message to be kept after filtering
+const DEFAULT_MIN_CONTENT_LENGTH: usize = 3;
+
+/// Tool outputs that only say the tool produced nothing
+const EMPTY_TOOL_OUTPUTS: [&str; 3] = ["(No content)", "(no output)", "Tool ran without output or errors"];
+
+/// File name of the user's noise filter config inside the config directory
+pub const NOISE_CONFIG_FILE: &str = "noise.toml";
+
+/// Pattern categories whose built-ins can be replaced via `replace_defaults`
+const CATEGORIES: [&str; 3] = ["pleasantries", "boilerplate", "acknowledgments"];
+
+/// User-supplied noise patterns, loadable from TOML
+///
+/// Patterns extend the built-in set unless their category is listed in
+/// `replace_defaults`, in which case they replace it:
+///
+/// ```toml
+/// boilerplate = ['\[JIRA-\d+\]']
+/// acknowledgments = []                   # keep short acknowledgments
+/// replace_defaults = ["acknowledgments"]
+/// min_content_length = 5
+/// level = "light"                        # off, light, standard or aggressive
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FilterConfig {
+    /// Whole-message pleasantries to drop
+    #[serde(alias = "pleasantries")]
+    pub pleasantry_patterns: Vec<String>,
+    /// Blocks to strip from within messages
+    #[serde(alias = "boilerplate")]
+    pub boilerplate_patterns: Vec<String>,
+    /// Whole-message acknowledgments to drop
+    #[serde(alias = "acknowledgments")]
+    pub acknowledgment_patterns: Vec<String>,
+    /// Categories whose built-in patterns are replaced rather than extended
+    pub replace_defaults: Vec<String>,
+    /// Messages shorter than this (after cleaning) are dropped
+    pub min_content_length: usize,
+    /// Which pattern groups are applied
+    pub level: NoiseLevel,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            pleasantry_patterns: Vec::new(),
+            boilerplate_patterns: Vec::new(),
+            acknowledgment_patterns: Vec::new(),
+            replace_defaults: Vec::new(),
+            min_content_length: DEFAULT_MIN_CONTENT_LENGTH,
+            level: NoiseLevel::default(),
+        }
+    }
+}
+
+/// How aggressively [`NoiseFilter`] drops content
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoiseLevel {
+    /// Keep every message unchanged
+    Off,
+    /// Only strip boilerplate blocks such as `<system-reminder>`
+    Light,
+    /// Also drop pleasantries, acknowledgments and near-empty messages
+    #[default]
+    Standard,
+    /// Also drop short questions and one-sentence confirmations
+    Aggressive,
+}
+
+impl std::fmt::Display for NoiseLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            NoiseLevel::Off => "off",
+            NoiseLevel::Light => "light",
+            NoiseLevel::Standard => "standard",
+            NoiseLevel::Aggressive => "aggressive",
+        })
+    }
+}
+
+impl std::str::FromStr for NoiseLevel {
+    type Err = color_eyre::Report;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Ok(NoiseLevel::Off),
+            "light" => Ok(NoiseLevel::Light),
+            "standard" => Ok(NoiseLevel::Standard),
+            "aggressive" => Ok(NoiseLevel::Aggressive),
+            _ => Err(color_eyre::eyre::eyre!(
+                "Invalid noise level '{}': use off, light, standard or aggressive",
+                value
+            )),
+        }
+    }
+}
+
+/// Pull `--noise <level>` (or `--noise=<level>`) out of a wrapper's arguments
+///
+/// The wrappers pass everything else through to the assistant untouched.
+pub fn take_noise_flag(args: &mut Vec<String>) -> Result<Option<NoiseLevel>> {
+    let Some(idx) = args.iter().position(|arg| arg == "--noise" || arg.starts_with("--noise=")) else {
+        return Ok(None);
+    };
+
+    let arg = args.remove(idx);
+    let value = match arg.strip_prefix("--noise=") {
+        Some(value) => value.to_string(),
+        None if idx < args.len() => args.remove(idx),
+        None => return Err(color_eyre::eyre::eyre!("--noise needs a level: off, light, standard or aggressive")),
+    };
+    value.parse().map(Some)
+}
+
+impl FilterConfig {
+    /// Read and validate a noise filter config from a TOML file
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: FilterConfig = toml::from_str(&raw)
+            .with_context(|| format!("Invalid noise filter config: {}", path.display()))?;
+
+        config.validate().with_context(|| format!("In {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Check that every pattern compiles, naming the first one that doesn't
+    pub fn validate(&self) -> Result<()> {
+        for category in &self.replace_defaults {
+            if !CATEGORIES.contains(&category.as_str()) {
+                return Err(color_eyre::eyre::eyre!(
+                    "Unknown category in replace_defaults: {} (expected one of {})",
+                    category,
+                    CATEGORIES.join(", ")
+                ));
+            }
+        }
+
+        let groups = [
+            ("pleasantry_patterns", &self.pleasantry_patterns),
+            ("boilerplate_patterns", &self.boilerplate_patterns),
+            ("acknowledgment_patterns", &self.acknowledgment_patterns),
+        ];
+
+        for (field, patterns) in groups {
+            for pattern in patterns {
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid regex in {}: {}", field, pattern))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn replaces(&self, category: &str) -> bool {
+        self.replace_defaults.iter().any(|c| c == category)
+    }
+
+    fn compile(patterns: &[String]) -> Result<Vec<Regex>> {
+        patterns
+            .iter()
+            .map(|p| Regex::new(p).with_context(|| format!("Invalid regex: {}", p)))
+            .collect()
+    }
+}
+
+/// The noise filter rule that dropped a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoiseRule {
+    /// Matched a whole-message pleasantry pattern
+    Pleasantry(String),
+    /// Matched a whole-message acknowledgment pattern
+    Acknowledgment(String),
+    /// Shorter than the minimum length once boilerplate was stripped
+    TooShort { min_length: usize },
+    /// Matched a short question or confirmation pattern (aggressive level only)
+    ShortExchange(String),
+}
+
+impl std::fmt::Display for NoiseRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoiseRule::Pleasantry(pattern) => write!(f, "pleasantry /{}/", pattern),
+            NoiseRule::Acknowledgment(pattern) => write!(f, "acknowledgment /{}/", pattern),
+            NoiseRule::TooShort { min_length } => {
+                write!(f, "shorter than {} bytes after removing boilerplate", min_length)
+            }
+            NoiseRule::ShortExchange(pattern) => write!(f, "short exchange /{}/", pattern),
+        }
+    }
+}
+
+/// Why [`NoiseFilter::explain`] kept or dropped a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterExplanation {
+    /// Kept as written
+    Kept,
+    /// The whole message matched a pleasantry pattern
+    DroppedPleasantry { pattern: String },
+    /// Nothing was left once this boilerplate pattern (and any others) were stripped
+    DroppedBoilerplate { pattern: String },
+    /// The whole message matched an acknowledgment pattern
+    DroppedAcknowledgment { pattern: String },
+    /// The whole message matched a short question or confirmation pattern (aggressive level only)
+    DroppedShortExchange { pattern: String },
+    /// Empty, or shorter than the minimum content length
+    DroppedEmpty,
+    /// Kept once boilerplate blocks were removed
+    StrippedBoilerplate { stripped: String, remaining: String },
+}
+
+impl FilterExplanation {
+    /// Whether the message survives the filter
+    pub fn is_kept(&self) -> bool {
+        matches!(self, FilterExplanation::Kept | FilterExplanation::StrippedBoilerplate { .. })
+    }
+}
+
+impl std::fmt::Display for FilterExplanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterExplanation::Kept => write!(f, "kept"),
+            FilterExplanation::DroppedPleasantry { pattern } => write!(f, "dropped: pleasantry /{}/", pattern),
+            FilterExplanation::DroppedBoilerplate { pattern } => {
+                write!(f, "dropped: nothing left after removing boilerplate /{}/", pattern)
+            }
+            FilterExplanation::DroppedAcknowledgment { pattern } => write!(f, "dropped: acknowledgment /{}/", pattern),
+            FilterExplanation::DroppedShortExchange { pattern } => write!(f, "dropped: short exchange /{}/", pattern),
+            FilterExplanation::DroppedEmpty => write!(f, "dropped: empty or shorter than the minimum length"),
+            FilterExplanation::StrippedBoilerplate { stripped, .. } => {
+                write!(f, "kept after removing {} bytes of boilerplate", stripped.len())
+            }
+        }
+    }
+}
 
 /// Noise filter for cleaning messages before storage or context emission
 pub struct NoiseFilter {
@@ -11,9 +257,15 @@ pub struct NoiseFilter {
     boilerplate: Vec<Regex>,
     // Empty acknowledgments
     acknowledgments: Vec<Regex>,
+    // Messages shorter than this after cleaning are noise
+    min_content_length: usize,
+    // Short questions and confirmations, only dropped at the aggressive level
+    short_exchanges: Vec<Regex>,
+    level: NoiseLevel,
 }
 
 impl NoiseFilter {
+    /// The built-in patterns at [`NoiseLevel::Standard`]
     pub fn new() -> Self {
         Self {
             pleasantries: vec![
@@ -38,12 +290,110 @@ impl NoiseFilter {
                 // Empty acknowledgments that just confirm
                 Regex::new(r"(?i)^(i understand|i see|i got it|understood|noted|will do|on it|done)\s*[.!]?\s*$").unwrap(),
             ],
+            min_content_length: DEFAULT_MIN_CONTENT_LENGTH,
+            short_exchanges: vec![
+                // Brief follow-up questions
+                Regex::new(r"^[^.!?\n]{1,40}\?$").unwrap(),
+                // One-sentence confirmations
+                Regex::new(r"(?i)^(yes|yep|yeah|correct|exactly|agreed|absolutely|of course|sounds good|that works|looks good|lgtm|go ahead)\b[^.!?\n]{0,40}[.!]?$").unwrap(),
+            ],
+            level: NoiseLevel::Standard,
+        }
+    }
+
+    /// The same filter at a different level
+    pub fn with_level(mut self, level: NoiseLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Level this filter runs at
+    pub fn level(&self) -> NoiseLevel {
+        self.level
+    }
+
+    /// Build a filter from the built-in patterns extended (or replaced) by `config`
+    pub fn from_config(config: &FilterConfig) -> Result<Self> {
+        config.validate()?;
+
+        let mut filter = Self::new();
+        let groups = [
+            ("pleasantries", &mut filter.pleasantries, &config.pleasantry_patterns),
+            ("boilerplate", &mut filter.boilerplate, &config.boilerplate_patterns),
+            ("acknowledgments", &mut filter.acknowledgments, &config.acknowledgment_patterns),
+        ];
+
+        for (category, patterns, extra) in groups {
+            if config.replaces(category) {
+                patterns.clear();
+            }
+            patterns.extend(FilterConfig::compile(extra)?);
         }
+        filter.min_content_length = config.min_content_length;
+
+        Ok(filter.with_level(config.level))
+    }
+
+    /// Use the `[noise_filter]` table of config.toml, or noise.toml, otherwise the built-ins
+    pub fn from_user_config() -> Result<Self> {
+        Self::from_config(&crate::config::Config::load()?.noise_filter)
+    }
+
+    /// Load a [`FilterConfig`] from a TOML file and build a filter from it
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_config(&FilterConfig::load(path)?)
     }
 
     /// Filter out noise from message content
     /// Returns cleaned content, or None if message is entirely noise
     pub fn filter(&self, content: &str) -> Option<String> {
+        self.evaluate(content).ok()
+    }
+
+    /// The rule that makes the filter drop a message, or `None` if it is kept
+    pub fn drop_reason(&self, content: &str) -> Option<NoiseRule> {
+        self.evaluate(content).err()
+    }
+
+    /// Run the filter over `content` and say which step kept or dropped it
+    ///
+    /// Steps run in the filter's order: boilerplate stripping, pleasantries,
+    /// acknowledgments, short exchanges (aggressive only), then the length check.
+    pub fn explain(&self, content: &str) -> FilterExplanation {
+        if self.level == NoiseLevel::Off {
+            return FilterExplanation::Kept;
+        }
+
+        let mut stripped = Vec::new();
+        let mut first_pattern = None;
+        let mut remaining = content.to_string();
+        for pattern in &self.boilerplate {
+            let matches: Vec<String> = pattern.find_iter(&remaining).map(|m| m.as_str().to_string()).collect();
+            if !matches.is_empty() {
+                first_pattern.get_or_insert_with(|| pattern.as_str().to_string());
+                stripped.extend(matches);
+                remaining = pattern.replace_all(&remaining, "").to_string();
+            }
+        }
+
+        match (self.evaluate(content), first_pattern) {
+            (Ok(_), None) => FilterExplanation::Kept,
+            (Ok(remaining), Some(_)) => FilterExplanation::StrippedBoilerplate { stripped: stripped.join("\n"), remaining },
+            (Err(NoiseRule::Pleasantry(pattern)), _) => FilterExplanation::DroppedPleasantry { pattern },
+            (Err(NoiseRule::Acknowledgment(pattern)), _) => FilterExplanation::DroppedAcknowledgment { pattern },
+            (Err(NoiseRule::ShortExchange(pattern)), _) => FilterExplanation::DroppedShortExchange { pattern },
+            (Err(NoiseRule::TooShort { .. }), Some(pattern)) if remaining.trim().is_empty() => {
+                FilterExplanation::DroppedBoilerplate { pattern }
+            }
+            (Err(NoiseRule::TooShort { .. }), _) => FilterExplanation::DroppedEmpty,
+        }
+    }
+
+    /// Clean a message, or name the rule that drops it
+    fn evaluate(&self, content: &str) -> std::result::Result<String, NoiseRule> {
+        if self.level == NoiseLevel::Off {
+            return Ok(content.to_string());
+        }
         let mut cleaned = content.to_string();
 
         // Remove boilerplate blocks first
@@ -54,26 +404,72 @@ impl NoiseFilter {
         // Trim whitespace
         cleaned = cleaned.trim().to_string();
 
+        if self.level == NoiseLevel::Light {
+            if cleaned.is_empty() {
+                return Err(NoiseRule::TooShort { min_length: 1 });
+            }
+            return Ok(cleaned);
+        }
+
         // Check if entire message is just a pleasantry
         for pattern in &self.pleasantries {
             if pattern.is_match(&cleaned) {
-                return None; // Entirely noise
+                return Err(NoiseRule::Pleasantry(pattern.as_str().to_string()));
             }
         }
 
         // Check if entire message is just an acknowledgment
         for pattern in &self.acknowledgments {
             if pattern.is_match(&cleaned) {
-                return None; // Entirely noise
+                return Err(NoiseRule::Acknowledgment(pattern.as_str().to_string()));
+            }
+        }
+
+        if self.level == NoiseLevel::Aggressive {
+            for pattern in &self.short_exchanges {
+                if pattern.is_match(&cleaned) {
+                    return Err(NoiseRule::ShortExchange(pattern.as_str().to_string()));
+                }
             }
         }
 
         // If nothing left after filtering, consider it noise
-        if cleaned.is_empty() || cleaned.len() < 3 {
+        if cleaned.is_empty() || cleaned.len() < self.min_content_length {
+            return Err(NoiseRule::TooShort { min_length: self.min_content_length });
+        }
+
+        Ok(cleaned)
+    }
+
+    /// Clean a tool result, or `None` if it is empty or stock "no output" boilerplate
+    ///
+    /// Boilerplate blocks are stripped as for messages. Pleasantry and length
+    /// rules don't apply, since a short result like `0` or `ok` can matter.
+    /// `tool_name` may be empty when the call isn't known.
+    pub fn filter_tool_result(&self, content: &str, tool_name: &str) -> Option<String> {
+        if self.level == NoiseLevel::Off {
+            return Some(content.to_string());
+        }
+        let mut cleaned = content.to_string();
+        for pattern in &self.boilerplate {
+            cleaned = pattern.replace_all(&cleaned, "").to_string();
+        }
+        let cleaned = cleaned.trim();
+
+        if cleaned.is_empty() || EMPTY_TOOL_OUTPUTS.iter().any(|stock| cleaned.eq_ignore_ascii_case(stock)) {
+            return None;
+        }
+
+        // Reading an empty file yields only line-number gutters ("     1→")
+        let empty_read = tool_name.eq_ignore_ascii_case("read")
+            && cleaned
+                .lines()
+                .all(|line| line.split_once('→').map_or(line, |(_, text)| text).trim().is_empty());
+        if empty_read {
             return None;
         }
 
-        Some(cleaned)
+        Some(cleaned.to_string())
     }
 
     /// Check if a message is likely just noise
@@ -84,8 +480,8 @@ impl NoiseFilter {
     /// Get approximate token savings from filtering
     /// Rough estimate: 1 token ~= 4 characters
     pub fn token_savings(&self, original: &str, filtered: Option<&str>) -> usize {
-        let original_tokens = (original.len() + 3) / 4;
-        let filtered_tokens = filtered.map(|s| (s.len() + 3) / 4).unwrap_or(0);
+        let original_tokens = original.len().div_ceil(4);
+        let filtered_tokens = filtered.map(|s| s.len().div_ceil(4)).unwrap_or(0);
         original_tokens.saturating_sub(filtered_tokens)
     }
 }
@@ -96,38 +492,336 @@ impl Default for NoiseFilter {
     }
 }
 
+/// Join runs of consecutive messages with the same role into one message
+///
+/// Some assistants split a single turn across several log entries. Contents
+/// are joined with a newline. Tool messages are left alone, since each one is
+/// a separate call or result. With `max_chars`, a run stops growing before it
+/// would pass that many characters (see [`Coalescer::max_chars`]).
+pub fn coalesce_consecutive(messages: &[(String, String)], max_chars: Option<usize>) -> Vec<(String, String)> {
+    coalesce_consecutive_timed(messages, &[], max_chars).0
+}
+
+/// [`coalesce_consecutive`], carrying each message's timestamp along
+/// A joined message keeps the timestamp of the first message in its run.
+pub fn coalesce_consecutive_timed(
+    messages: &[(String, String)],
+    timestamps: &[Option<String>],
+    max_chars: Option<usize>,
+) -> (Vec<(String, String)>, Vec<Option<String>>) {
+    let mut coalesced: Vec<(String, String)> = Vec::with_capacity(messages.len());
+    let mut coalesced_timestamps = Vec::with_capacity(messages.len());
+
+    let mut coalescer = Coalescer::default().max_chars(max_chars);
+    let mut keep = |(role, content, timestamp): (String, String, Option<String>)| {
+        coalesced.push((role, content));
+        coalesced_timestamps.push(timestamp);
+    };
+    for (idx, (role, content)) in messages.iter().enumerate() {
+        if let Some(run) = coalescer.push(role.clone(), content.clone(), timestamps.get(idx).cloned().flatten()) {
+            keep(run);
+        }
+    }
+    if let Some(run) = coalescer.finish() {
+        keep(run);
+    }
+
+    (coalesced, coalesced_timestamps)
+}
+
+/// Streaming form of [`coalesce_consecutive_timed`]
+///
+/// Holds back the current run of same-role messages and hands it out, joined,
+/// once a message with another role (or a tool message) arrives. Call
+/// [`Coalescer::finish`] after the last message for the final run.
+#[derive(Debug, Default)]
+pub struct Coalescer {
+    pending: Option<(String, String, Option<String>)>,
+    /// Characters in the pending run
+    pending_chars: usize,
+    max_chars: Option<usize>,
+}
+
+impl Coalescer {
+    /// Keep runs to at most `max_chars` characters; a message that would take
+    /// a run past it starts the next run instead (`None`, the default, has no limit)
+    pub fn max_chars(mut self, max_chars: Option<usize>) -> Self {
+        self.max_chars = max_chars;
+        self
+    }
+
+    /// Add a message; returns the previous run if this message ends it
+    pub fn push(&mut self, role: String, content: String, timestamp: Option<String>) -> Option<(String, String, Option<String>)> {
+        let chars = content.chars().count();
+        if let Some((last_role, last_content, _)) = self.pending.as_mut() {
+            let fits = self.max_chars.is_none_or(|max| self.pending_chars + 1 + chars <= max);
+            if *last_role == role && role != TOOL_ROLE && fits {
+                last_content.push('\n');
+                last_content.push_str(&content);
+                self.pending_chars += 1 + chars;
+                return None;
+            }
+        }
+        self.pending_chars = chars;
+        self.pending.replace((role, content, timestamp))
+    }
+
+    /// The run still held back, if any
+    pub fn finish(self) -> Option<(String, String, Option<String>)> {
+        self.pending
+    }
+}
+
+/// Batch settings for a [`MessageCompressor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressorConfig {
+    /// Characters per shingle (n-gram) in [`MessageCompressor::compress_batch_dedup`]
+    pub ngram_size: usize,
+    /// How many earlier same-role messages each message is compared against
+    pub window: usize,
+    /// Drop a message identical (role and cleaned content) to the message kept just before it
+    pub dedupe_adjacent: bool,
+}
+
+impl Default for CompressorConfig {
+    fn default() -> Self {
+        CompressorConfig { ngram_size: 3, window: 1, dedupe_adjacent: false }
+    }
+}
+
+/// Token and message counts before and after compressing a batch
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CompressionStats {
+    /// Estimated tokens in the source messages
+    pub original_tokens: usize,
+    /// Estimated tokens left after compression
+    pub compressed_tokens: usize,
+    /// Messages dropped (or folded into another) as noise
+    pub messages_removed: usize,
+    /// Percentage of tokens saved (0.0 when there was nothing to compress)
+    pub reduction_percent: f64,
+}
+
+impl CompressionStats {
+    /// Count one more source message and what compression left of it
+    /// Tokens are estimated as in [`MessageCompressor::estimate_tokens`].
+    pub fn add(&mut self, original: &str, compressed: Option<&str>) {
+        self.original_tokens += 5 + original.len().div_ceil(4);
+        match compressed {
+            Some(compressed) => self.compressed_tokens += 5 + compressed.len().div_ceil(4),
+            None => self.messages_removed += 1,
+        }
+        self.reduction_percent = if self.original_tokens == 0 {
+            0.0
+        } else {
+            (1.0 - (self.compressed_tokens as f64 / self.original_tokens as f64)) * 100.0
+        };
+    }
+}
+
+impl std::fmt::Display for CompressionStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Compressed {} → {} tokens ({:.1}% reduction, {} noise messages removed)",
+            self.original_tokens, self.compressed_tokens, self.reduction_percent, self.messages_removed
+        )
+    }
+}
+
+/// Cut `content` to at most `max_bytes` bytes (on a char boundary), marking what was dropped
+///
+/// Longer content becomes `<head>…[truncated N bytes]`; shorter content is returned unchanged.
+pub fn truncate_content(content: &str, max_bytes: usize) -> String {
+    if content.len() <= max_bytes {
+        return content.to_string();
+    }
+    let end = floor_char_boundary(content, max_bytes);
+    format!("{}…[truncated {} bytes]", &content[..end], content.len() - end)
+}
+
+/// Largest char boundary of `text` at or before `index`
+pub(crate) fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut end = index.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    end
+}
+
 /// Message compressor that combines filtering and batching
 pub struct MessageCompressor {
     filter: NoiseFilter,
+    config: CompressorConfig,
+    /// Longest message content to keep, in bytes (see [`truncate_content`])
+    max_message_bytes: Option<usize>,
 }
 
 impl MessageCompressor {
     pub fn new() -> Self {
-        Self {
-            filter: NoiseFilter::new(),
-        }
+        Self::with_filter(NoiseFilter::new())
+    }
+
+    /// Create a compressor using a custom noise filter
+    pub fn with_filter(filter: NoiseFilter) -> Self {
+        Self { filter, config: CompressorConfig::default(), max_message_bytes: None }
+    }
+
+    /// Truncate each message of a batch to this many bytes; `None` keeps messages whole
+    pub fn with_max_message_bytes(mut self, max_bytes: Option<usize>) -> Self {
+        self.max_message_bytes = max_bytes;
+        self
+    }
+
+    /// Use these shingling settings for near-duplicate removal
+    pub fn with_config(mut self, config: CompressorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Drop repeats of the message kept just before, leaving the other settings as they are
+    pub fn with_dedupe_adjacent(mut self, dedupe_adjacent: bool) -> Self {
+        self.config.dedupe_adjacent = dedupe_adjacent;
+        self
+    }
+
+    /// Create a compressor from noise filter settings (e.g. `Config::noise_filter`)
+    pub fn from_config(config: &FilterConfig) -> Result<Self> {
+        Ok(Self::with_filter(NoiseFilter::from_config(config)?))
+    }
+
+    /// Create a compressor using the user's noise config, if any
+    pub fn from_user_config() -> Result<Self> {
+        Ok(Self::with_filter(NoiseFilter::from_user_config()?))
+    }
+
+    /// Filter noise from a single message's content
+    /// Returns None if the whole message is noise
+    pub fn compress(&self, content: &str) -> Option<String> {
+        self.filter.filter(content)
+    }
+
+    /// The noise rule that drops a message, if any (see [`NoiseFilter::drop_reason`])
+    pub fn drop_reason(&self, content: &str) -> Option<NoiseRule> {
+        self.filter.drop_reason(content)
     }
 
     /// Compress a batch of messages by filtering noise
     /// Returns vector of (role, cleaned_content) tuples
+    ///
+    /// `tool` messages go through [`NoiseFilter::filter_tool_result`] instead,
+    /// using the name from the closest preceding tool call. Messages left
+    /// longer than the `with_max_message_bytes` limit are truncated.
     pub fn compress_batch(&self, messages: &[(String, String)]) -> Vec<(String, String)> {
-        messages
-            .iter()
-            .filter_map(|(role, content)| {
-                self.filter.filter(content).map(|cleaned| {
-                    (role.clone(), cleaned)
-                })
+        self.compress_batch_timed(messages, &[]).0
+    }
+
+    /// [`Self::compress_batch`], keeping each surviving message's timestamp
+    /// `timestamps` runs parallel to `messages` (missing entries count as `None`).
+    pub fn compress_batch_timed(
+        &self,
+        messages: &[(String, String)],
+        timestamps: &[Option<String>],
+    ) -> (Vec<(String, String)>, Vec<Option<String>>) {
+        let mut stream = self.stream();
+        let mut kept = Vec::with_capacity(messages.len());
+        let mut kept_timestamps = Vec::with_capacity(messages.len());
+
+        for (idx, (role, content)) in messages.iter().enumerate() {
+            if let Some(cleaned) = stream.compress(role, content) {
+                kept.push((role.clone(), cleaned));
+                kept_timestamps.push(timestamps.get(idx).cloned().flatten());
+            }
+        }
+
+        (kept, kept_timestamps)
+    }
+
+    /// Compress messages one at a time, for imports too large to batch
+    pub fn stream(&self) -> CompressionStream<'_> {
+        CompressionStream {
+            compressor: self,
+            last_tool: String::new(),
+            last_kept: None,
+            stats: CompressionStats::default(),
+        }
+    }
+
+    /// Filter a `tool` message as formatted by [`crate::ToolCapture`]
+    /// Calls are kept whole and remembered so the following result is filtered for that tool
+    fn compress_tool(&self, content: &str, last_tool: &mut String) -> Option<String> {
+        if let Some(call) = content.strip_prefix(TOOL_USE_PREFIX) {
+            *last_tool = call.split_once(" -> ").map_or(call, |(name, _)| name).to_string();
+            return Some(content.to_string());
+        }
+
+        match content.strip_prefix(TOOL_RESULT_PREFIX) {
+            Some(output) => self
+                .filter
+                .filter_tool_result(output, last_tool)
+                .map(|output| format!("{}{}", TOOL_RESULT_PREFIX, output)),
+            None => self.filter.filter_tool_result(content, last_tool),
+        }
+    }
+
+    /// Compress a batch, also collapsing near-duplicate messages
+    ///
+    /// A message whose character n-gram Jaccard similarity to one of the
+    /// previous `window` kept messages with the same role exceeds
+    /// `similarity_threshold` (0.0-1.0) is folded into that message, which
+    /// gets a `[repeated N times]` suffix. The first wording is kept.
+    pub fn compress_batch_dedup(&self, messages: &[(String, String)], similarity_threshold: f64) -> Vec<(String, String)> {
+        // (role, content, shingles, occurrences)
+        let mut kept: Vec<(String, String, HashSet<String>, usize)> = Vec::new();
+
+        for (role, content) in self.compress_batch(messages) {
+            let shingles = Self::shingles(&content, self.config.ngram_size);
+            let repeat_of = kept
+                .iter_mut()
+                .rev()
+                .filter(|(kept_role, ..)| *kept_role == role)
+                .take(self.config.window)
+                .find(|(_, _, kept_shingles, _)| Self::jaccard(kept_shingles, &shingles) > similarity_threshold);
+
+            match repeat_of {
+                Some((.., occurrences)) => *occurrences += 1,
+                None => kept.push((role, content, shingles, 1)),
+            }
+        }
+
+        kept.into_iter()
+            .map(|(role, content, _, occurrences)| match occurrences {
+                1 => (role, content),
+                n => (role, format!("{} [repeated {} times]", content, n)),
             })
             .collect()
     }
 
+    /// Character n-grams of `content`; shorter content is a single shingle
+    fn shingles(content: &str, n: usize) -> HashSet<String> {
+        let chars: Vec<char> = content.chars().collect();
+        if chars.len() <= n.max(1) {
+            return HashSet::from([content.to_string()]);
+        }
+        chars.windows(n.max(1)).map(|w| w.iter().collect()).collect()
+    }
+
+    /// Jaccard similarity of two shingle sets (1.0 when both are empty)
+    fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+        let union = a.union(b).count();
+        if union == 0 {
+            return 1.0;
+        }
+        a.intersection(b).count() as f64 / union as f64
+    }
+
     /// Estimate total tokens for a batch of messages
     /// Uses: ~5 tokens for role prefix + ~4 chars per token for content
     pub fn estimate_tokens(&self, messages: &[(String, String)]) -> usize {
         messages.iter()
             .map(|(_role, content)| {
                 // Role prefix adds ~5 tokens, content is ~4 chars per token
-                5 + ((content.len() + 3) / 4)
+                5 + content.len().div_ceil(4)
             })
             .sum()
     }
@@ -139,6 +833,18 @@ impl MessageCompressor {
         }
         (1.0 - (compressed_tokens as f64 / original_tokens as f64)) * 100.0
     }
+
+    /// Compare a batch with its compressed form (see [`estimate_tokens`](Self::estimate_tokens))
+    pub fn compression_stats(&self, original: &[(String, String)], compressed: &[(String, String)]) -> CompressionStats {
+        let original_tokens = self.estimate_tokens(original);
+        let compressed_tokens = self.estimate_tokens(compressed);
+        CompressionStats {
+            original_tokens,
+            compressed_tokens,
+            messages_removed: original.len().saturating_sub(compressed.len()),
+            reduction_percent: self.compression_ratio(original_tokens, compressed_tokens),
+        }
+    }
 }
 
 impl Default for MessageCompressor {
@@ -147,6 +853,48 @@ impl Default for MessageCompressor {
     }
 }
 
+/// Per-message form of [`MessageCompressor::compress_batch`] (see [`MessageCompressor::stream`])
+///
+/// Remembers the last tool call so its result is filtered for that tool, and
+/// keeps running [`CompressionStats`] for everything compressed so far.
+pub struct CompressionStream<'a> {
+    compressor: &'a MessageCompressor,
+    last_tool: String,
+    /// Role and content of the last kept message, with `dedupe_adjacent`
+    last_kept: Option<(String, String)>,
+    stats: CompressionStats,
+}
+
+impl CompressionStream<'_> {
+    /// The cleaned content of the next message, or None if it is noise
+    pub fn compress(&mut self, role: &str, content: &str) -> Option<String> {
+        let cleaned = if role == TOOL_ROLE {
+            self.compressor.compress_tool(content, &mut self.last_tool)
+        } else {
+            self.compressor.compress(content)
+        };
+        let mut cleaned = match (cleaned, self.compressor.max_message_bytes) {
+            (Some(cleaned), Some(max)) if cleaned.len() > max => Some(truncate_content(&cleaned, max)),
+            (cleaned, _) => cleaned,
+        };
+        if self.compressor.config.dedupe_adjacent {
+            if let Some(ref kept) = cleaned {
+                match self.last_kept {
+                    Some((ref last_role, ref last_content)) if last_role == role && last_content == kept => cleaned = None,
+                    _ => self.last_kept = Some((role.to_string(), kept.clone())),
+                }
+            }
+        }
+        self.stats.add(content, cleaned.as_deref());
+        cleaned
+    }
+
+    /// Savings over the messages compressed so far
+    pub fn stats(&self) -> CompressionStats {
+        self.stats
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -195,6 +943,57 @@ mod tests {
         assert_eq!(result2.unwrap(), "Actual content");
     }
 
+    #[test]
+    fn test_noise_levels() -> Result<()> {
+        let boilerplate = "<system-reminder>Some reminder</system-reminder>thanks";
+        let level = |level: NoiseLevel| NoiseFilter::new().with_level(level);
+
+        assert_eq!(NoiseFilter::new().level(), NoiseLevel::Standard);
+        assert_eq!(level(NoiseLevel::Off).filter("thanks").as_deref(), Some("thanks"));
+        assert_eq!(level(NoiseLevel::Off).filter(boilerplate).as_deref(), Some(boilerplate));
+        assert_eq!(level(NoiseLevel::Light).filter(boilerplate).as_deref(), Some("thanks"));
+        assert_eq!(level(NoiseLevel::Standard).filter("thanks"), None);
+        assert_eq!(level(NoiseLevel::Aggressive).filter("thanks"), None);
+
+        // Only the aggressive level drops short questions and confirmations
+        for message in ["What changed?", "Yes, that works."] {
+            assert!(level(NoiseLevel::Standard).filter(message).is_some());
+            assert!(matches!(level(NoiseLevel::Aggressive).drop_reason(message), Some(NoiseRule::ShortExchange(_))));
+        }
+        assert!(level(NoiseLevel::Aggressive).filter("Yes. Now rename the parser module and update its tests.").is_some());
+
+        let config: FilterConfig = toml::from_str("level = \"light\"")?;
+        assert_eq!(NoiseFilter::from_config(&config)?.filter("thanks").as_deref(), Some("thanks"));
+
+        let mut args: Vec<String> = ["--noise", "off", "-c"].iter().map(|a| a.to_string()).collect();
+        assert_eq!(take_noise_flag(&mut args)?, Some(NoiseLevel::Off));
+        assert_eq!(args, vec!["-c".to_string()]);
+        assert!(take_noise_flag(&mut vec!["--noise=loud".to_string()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain() {
+        let filter = NoiseFilter::new();
+
+        assert_eq!(filter.explain("Here's the code you requested"), FilterExplanation::Kept);
+        assert!(matches!(filter.explain("Thanks!"), FilterExplanation::DroppedPleasantry { .. }));
+        assert!(matches!(filter.explain("Noted."), FilterExplanation::DroppedAcknowledgment { .. }));
+        assert_eq!(filter.explain("  "), FilterExplanation::DroppedEmpty);
+        assert_eq!(
+            filter.explain("<system-reminder>Some reminder</system-reminder>"),
+            FilterExplanation::DroppedBoilerplate { pattern: r"<system-reminder>[\s\S]*?</system-reminder>".to_string() }
+        );
+        assert_eq!(
+            filter.explain("<system-reminder>Some reminder</system-reminder>\nActual content"),
+            FilterExplanation::StrippedBoilerplate {
+                stripped: "<system-reminder>Some reminder</system-reminder>".to_string(),
+                remaining: "Actual content".to_string(),
+            }
+        );
+        assert_eq!(filter.with_level(NoiseLevel::Off).explain("Thanks!"), FilterExplanation::Kept);
+    }
+
     #[test]
     fn test_filter_acknowledgments() {
         let filter = NoiseFilter::new();
@@ -205,6 +1004,84 @@ mod tests {
         assert_eq!(filter.filter("Done"), None);
     }
 
+    #[test]
+    fn test_drop_reason_names_rule() {
+        let filter = NoiseFilter::new();
+
+        assert!(matches!(filter.drop_reason("Thanks!"), Some(NoiseRule::Pleasantry(_))));
+        assert!(matches!(filter.drop_reason("Noted."), Some(NoiseRule::Acknowledgment(_))));
+        assert_eq!(filter.drop_reason("Fix the parser bug in line 12"), None);
+
+        let reason = filter.drop_reason("Will do!").unwrap();
+        assert!(reason.to_string().starts_with("acknowledgment /"));
+    }
+
+    #[test]
+    fn test_config_extends_builtin_patterns() {
+        let config = FilterConfig {
+            boilerplate_patterns: vec![r"\[JIRA-\d+\]\s*".to_string()],
+            acknowledgment_patterns: vec![r"(?i)^ack$".to_string()],
+            min_content_length: 5,
+            ..Default::default()
+        };
+        let filter = NoiseFilter::from_config(&config).unwrap();
+
+        assert_eq!(filter.filter("[JIRA-123] Fix the parser").unwrap(), "Fix the parser");
+        assert_eq!(filter.filter("ack"), None);
+        assert_eq!(filter.filter("abcd"), None);
+        // Built-ins still apply
+        assert_eq!(filter.filter("Thank you!"), None);
+    }
+
+    #[test]
+    fn test_config_invalid_pattern_named() {
+        let config = FilterConfig {
+            pleasantry_patterns: vec!["(unclosed".to_string()],
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("pleasantry_patterns: (unclosed"));
+        assert!(NoiseFilter::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_load_from_toml() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("noise.toml");
+        std::fs::write(&path, "boilerplate_patterns = ['<internal-tool>.*?</internal-tool>']\n")?;
+
+        let filter = NoiseFilter::load(&path)?;
+        assert_eq!(filter.filter("<internal-tool>x</internal-tool>Real content").unwrap(), "Real content");
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_can_disable_acknowledgments() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("noise.toml");
+        std::fs::write(
+            &path,
+            "acknowledgments = []\nreplace_defaults = [\"acknowledgments\"]\n",
+        )?;
+
+        assert_eq!(NoiseFilter::new().filter("Done"), None);
+
+        let filter = NoiseFilter::load(&path)?;
+        assert_eq!(filter.filter("Done").unwrap(), "Done");
+        // Other categories keep their built-ins
+        assert_eq!(filter.filter("thanks"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_unknown_category() {
+        let config = FilterConfig {
+            replace_defaults: vec!["greetings".to_string()],
+            ..Default::default()
+        };
+        assert!(config.validate().unwrap_err().to_string().contains("greetings"));
+    }
+
     #[test]
     fn test_compressor_batch() {
         let compressor = MessageCompressor::new();
@@ -221,12 +1098,164 @@ mod tests {
         // Should keep substantive message and partially filter the polite suffix
         // "Here's how it works" won't be filtered (good content)
         // "Let me know if..." will be filtered by suffix pattern
-        assert!(compressed.len() >= 1);
+        assert!(!compressed.is_empty());
         assert!(compressed.iter().any(|(role, content)|
             role == "assistant" && content.contains("step 1")
         ));
     }
 
+    #[test]
+    fn test_dedupe_adjacent_drops_retries() {
+        let a = ("assistant".to_string(), "Retrying the request to the API".to_string());
+        let b = ("user".to_string(), "Did it work this time?".to_string());
+        let messages = vec![a.clone(), a.clone(), b.clone(), a.clone()];
+
+        assert_eq!(MessageCompressor::new().compress_batch(&messages).len(), 4);
+        let deduping = MessageCompressor::new().with_config(CompressorConfig { dedupe_adjacent: true, ..Default::default() });
+        let mut stream = deduping.stream();
+        let kept: Vec<_> = messages.iter().filter_map(|(role, content)| stream.compress(role, content)).collect();
+        assert_eq!(kept, vec![a.1.clone(), b.1.clone(), a.1.clone()]);
+        assert_eq!(stream.stats().messages_removed, 1);
+        assert_eq!(deduping.compress_batch(&messages), vec![a.clone(), b, a]);
+    }
+
+    #[test]
+    fn test_with_dedupe_adjacent_keeps_other_settings() {
+        let config = CompressorConfig { ngram_size: 5, window: 4, dedupe_adjacent: false };
+        let compressor = MessageCompressor::new().with_config(config).with_dedupe_adjacent(true);
+        assert_eq!(compressor.config, CompressorConfig { dedupe_adjacent: true, ..config });
+    }
+
+    #[test]
+    fn test_compress_batch_dedup_collapses_repeats() {
+        let compressor = MessageCompressor::new();
+        let messages = vec![
+            ("assistant".to_string(), "Running the test suite now to check the fix".to_string()),
+            ("user".to_string(), "Any luck with the build?".to_string()),
+            ("assistant".to_string(), "Running the test suite now to check the fix.".to_string()),
+            ("assistant".to_string(), "Running the test suite now to check the fix!".to_string()),
+            ("assistant".to_string(), "All 42 tests pass".to_string()),
+        ];
+
+        let deduped = compressor.compress_batch_dedup(&messages, 0.8);
+        assert_eq!(deduped.len(), 3);
+        assert_eq!(deduped[0].1, "Running the test suite now to check the fix [repeated 3 times]");
+        assert_eq!(deduped[1].1, "Any luck with the build?");
+        assert_eq!(deduped[2].1, "All 42 tests pass");
+
+        // With the default window of 1, an intervening same-role message breaks the run
+        let interleaved = vec![
+            ("assistant".to_string(), "Checking the config file".to_string()),
+            ("assistant".to_string(), "All 42 tests pass".to_string()),
+            ("assistant".to_string(), "Checking the config file".to_string()),
+        ];
+        assert_eq!(compressor.compress_batch_dedup(&interleaved, 0.8).len(), 3);
+        let wide = MessageCompressor::new().with_config(CompressorConfig { window: 2, ..Default::default() });
+        assert_eq!(wide.compress_batch_dedup(&interleaved, 0.8).len(), 2);
+    }
+
+    #[test]
+    fn test_compress_batch_dedup_keeps_code_variants() {
+        let compressor = MessageCompressor::new();
+        let messages = vec![
+            ("assistant".to_string(), "```rust\nlet total = items.len();\nprintln!(\"{}\", total);\n```".to_string()),
+            ("assistant".to_string(), "```rust\nlet count = entries.len();\nprintln!(\"{}\", count);\n```".to_string()),
+        ];
+
+        let shingles: Vec<_> = messages.iter().map(|(_, c)| MessageCompressor::shingles(c, 3)).collect();
+        assert!(MessageCompressor::jaccard(&shingles[0], &shingles[1]) < 0.8);
+        assert_eq!(compressor.compress_batch_dedup(&messages, 0.8), messages);
+    }
+
+    #[test]
+    fn test_coalesce_consecutive() {
+        let messages = vec![
+            ("user".to_string(), "Fix the parser".to_string()),
+            ("assistant".to_string(), "Looking at parser.rs.".to_string()),
+            ("assistant".to_string(), "The match arm is missing.".to_string()),
+            ("assistant".to_string(), "Fixed.".to_string()),
+            ("tool".to_string(), "TOOL_USE: shell -> cargo test".to_string()),
+            ("tool".to_string(), "TOOL_RESULT: ok".to_string()),
+            ("user".to_string(), "Thanks".to_string()),
+        ];
+
+        let coalesced = coalesce_consecutive(&messages, None);
+        assert_eq!(coalesced.len(), 5);
+        assert_eq!(coalesced[1].0, "assistant");
+        assert_eq!(coalesced[1].1, "Looking at parser.rs.\nThe match arm is missing.\nFixed.");
+        assert_eq!(coalesced[2].1, "TOOL_USE: shell -> cargo test");
+        assert_eq!(coalesced[3].1, "TOOL_RESULT: ok");
+
+        // Runs stop short of the limit: 21 + 1 + 25 characters fit, one more line doesn't
+        let capped = coalesce_consecutive(&messages, Some(47));
+        assert_eq!(capped.len(), 6);
+        assert_eq!(capped[1].1, "Looking at parser.rs.\nThe match arm is missing.");
+        assert_eq!(capped[2].1, "Fixed.");
+        assert_eq!(coalesce_consecutive(&messages, Some(10)).len(), 7);
+    }
+
+    #[test]
+    fn test_timestamps_follow_kept_messages() {
+        let messages = vec![
+            ("user".to_string(), "Fix the parser".to_string()),
+            ("assistant".to_string(), "Thank you!".to_string()),
+            ("assistant".to_string(), "Looking at parser.rs.".to_string()),
+            ("assistant".to_string(), "Fixed.".to_string()),
+        ];
+        let timestamps: Vec<Option<String>> = ["10:00", "10:01", "10:02", "10:03"]
+            .iter()
+            .map(|t| Some(format!("2025-11-09T{}:00Z", t)))
+            .collect();
+
+        let (compressed, kept) = MessageCompressor::new().compress_batch_timed(&messages, &timestamps);
+        assert_eq!(compressed.len(), 3);
+        assert_eq!(kept, vec![timestamps[0].clone(), timestamps[2].clone(), timestamps[3].clone()]);
+
+        let (coalesced, kept) = coalesce_consecutive_timed(&compressed, &kept, None);
+        assert_eq!(coalesced[1].1, "Looking at parser.rs.\nFixed.");
+        assert_eq!(kept, vec![timestamps[0].clone(), timestamps[2].clone()]);
+
+        // Without timestamps every message gets None
+        assert_eq!(MessageCompressor::new().compress_batch_timed(&messages, &[]).1, vec![None, None, None]);
+    }
+
+    #[test]
+    fn test_filter_tool_result() {
+        let filter = NoiseFilter::new();
+
+        assert_eq!(filter.filter_tool_result("  ", "Bash"), None);
+        assert_eq!(filter.filter_tool_result("<system>Tool ran without output or errors</system>", "Bash"), None);
+        assert_eq!(filter.filter_tool_result("(No content)", "Write"), None);
+        assert_eq!(
+            filter.filter_tool_result("<system-reminder>Warning: the file exists but the contents are empty.</system-reminder>", "Read"),
+            None
+        );
+        assert_eq!(filter.filter_tool_result("     1→\n", "Read"), None);
+
+        // Short but meaningful output survives, unlike a short chat message
+        assert_eq!(filter.filter_tool_result("ok", "Bash").as_deref(), Some("ok"));
+        assert_eq!(filter.filter_tool_result("     1→fn main() {}", "Read").as_deref(), Some("1→fn main() {}"));
+    }
+
+    #[test]
+    fn test_compress_batch_tool_messages() {
+        let compressor = MessageCompressor::new();
+        let messages = vec![
+            ("assistant".to_string(), "Let me check the file".to_string()),
+            ("tool".to_string(), r#"TOOL_USE: Read -> {"file_path":"empty.txt"}"#.to_string()),
+            ("tool".to_string(), "TOOL_RESULT:      1→".to_string()),
+            ("tool".to_string(), r#"TOOL_USE: Bash -> {"command":"wc -l"}"#.to_string()),
+            ("tool".to_string(), "TOOL_RESULT: 0".to_string()),
+        ];
+
+        let compressed = compressor.compress_batch(&messages);
+        let contents: Vec<&str> = compressed.iter().map(|(_, c)| c.as_str()).collect();
+        assert_eq!(
+            contents,
+            [r#"TOOL_USE: Read -> {"file_path":"empty.txt"}"#, r#"TOOL_USE: Bash -> {"command":"wc -l"}"#, "TOOL_RESULT: 0"]
+        );
+    }
+
     #[test]
     fn test_token_estimation() {
         let compressor = MessageCompressor::new();
@@ -237,7 +1266,7 @@ mod tests {
 
         let tokens = compressor.estimate_tokens(&messages);
         // ~35 chars / 4 + 5 for role = ~14 tokens
-        assert!(tokens >= 10 && tokens <= 20);
+        assert!((10..=20).contains(&tokens));
     }
 
     #[test]
@@ -251,6 +1280,51 @@ mod tests {
         assert_eq!(ratio2, 75.0); // 75% reduction
     }
 
+    #[test]
+    fn test_truncate_content_on_char_boundary() {
+        assert_eq!(truncate_content("short", 10), "short");
+        assert_eq!(truncate_content("abcdef", 3), "abc…[truncated 3 bytes]");
+        // "é" is two bytes, so a 5-byte cut backs off to 4
+        assert_eq!(truncate_content("abcdé and more", 5), "abcd…[truncated 11 bytes]");
+
+        let compressor = MessageCompressor::new().with_max_message_bytes(Some(20));
+        let pasted = "x".repeat(48230);
+        let compressed = compressor.compress_batch(&[
+            ("user".to_string(), pasted),
+            ("assistant".to_string(), "That file looks fine.".to_string()),
+        ]);
+        assert_eq!(compressed[0].1, format!("{}…[truncated 48210 bytes]", "x".repeat(20)));
+        assert_eq!(compressed[1].1, "That file looks fine…[truncated 1 bytes]");
+        assert!(serde_json::to_string(&compressed).is_ok());
+    }
+
+    #[test]
+    fn test_compression_stats_line() {
+        let compressor = MessageCompressor::new();
+        let messages = vec![
+            ("user".to_string(), "Please refactor the config loader into its own module".to_string()),
+            ("assistant".to_string(), "Thanks!".to_string()),
+            ("assistant".to_string(), "Done".to_string()),
+        ];
+
+        let compressed = compressor.compress_batch(&messages);
+        let stats = compressor.compression_stats(&messages, &compressed);
+        assert_eq!(stats.original_tokens, 32);
+        assert_eq!(stats.compressed_tokens, 19);
+        assert_eq!(stats.messages_removed, 2);
+        assert_eq!(
+            stats.to_string(),
+            "Compressed 32 → 19 tokens (40.6% reduction, 2 noise messages removed)"
+        );
+
+        // Compressing one message at a time keeps the same tally
+        let mut stream = compressor.stream();
+        for (role, content) in &messages {
+            stream.compress(role, content);
+        }
+        assert_eq!(stream.stats(), stats);
+    }
+
     #[test]
     fn test_preserves_code_and_technical_content() {
         let filter = NoiseFilter::new();
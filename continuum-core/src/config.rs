@@ -0,0 +1,384 @@
+// User configuration
+// Config files live in $XDG_CONFIG_HOME/continuum (default ~/.config/continuum)
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::{eyre, Context}, Result};
+use serde::Deserialize;
+
+use crate::adapters::lmstudio::{self, LmStudioAdapter};
+use crate::compression::{FilterConfig, NOISE_CONFIG_FILE};
+use crate::loop_detection::{LoopDetectorConfig, LOOP_CONFIG_FILE};
+use crate::layout::Layout;
+use crate::plaintext::{DateZone, PlainTextWriter, LOGS_DIR_ENV, LOG_DIR_ENV};
+use crate::redaction::Redactor;
+
+/// File name of the main config inside the config directory
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// JSON alternative to config.toml, read only when config.toml is absent
+pub const CONFIG_JSON_FILE: &str = "config.json";
+
+/// Directory holding Continuum's user configuration files
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(xdg) = std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        return Some(PathBuf::from(xdg).join("continuum"));
+    }
+
+    home_dir().map(|home| home.join(".config").join("continuum"))
+}
+
+/// Path of a named file inside the config directory
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(name))
+}
+
+/// The user's home directory, from $HOME
+pub fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+/// Environment variable that keeps the wrappers from saving sessions ("1", "true" or "yes")
+pub const NOSAVE_ENV: &str = "CONTINUUM_NOSAVE";
+
+/// Marker file in the home directory that skips saving the next wrapped session
+pub const NOSAVE_MARKER: &str = ".continuum-nosave";
+
+/// Whether an environment variable is set to "1", "true" or "yes"
+pub fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Whether a wrapper should leave the session it is about to run out of the logs
+///
+/// True while `$CONTINUUM_NOSAVE` is set, or when `~/.continuum-nosave`
+/// exists. The marker only covers one session, so it is deleted here.
+pub fn should_skip_saving() -> bool {
+    skip_saving_in(&home_dir().unwrap_or_else(|| PathBuf::from(".")))
+}
+
+fn skip_saving_in(home: &Path) -> bool {
+    // Consume the marker even when the variable is set, so it can't leak into a later session
+    let marker_path = home.join(NOSAVE_MARKER);
+    let marker = marker_path.exists();
+    if marker {
+        let _ = std::fs::remove_file(&marker_path);
+    }
+    marker || env_flag(NOSAVE_ENV)
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &Path) -> Result<PathBuf> {
+    let Ok(rest) = path.strip_prefix("~") else {
+        return Ok(path.to_path_buf());
+    };
+    let home = home_dir().ok_or_else(|| eyre!("HOME not set"))?;
+    Ok(home.join(rest))
+}
+
+/// Where each assistant keeps its native logs when config.toml doesn't say
+///
+/// `None` for assistants without a fixed location (chatgpt exports are
+/// searched for in ~/Downloads, claude-ai exports asked for) and when HOME is unset.
+pub fn default_assistant_path(assistant: &str) -> Option<PathBuf> {
+    let home = home_dir()?;
+    let path = match assistant {
+        "codex" => home.join(".codex/sessions"),
+        "claude-code" => home.join(".claude/projects"),
+        "aider" => home.join(".aider/sessions"),
+        "goose" => home.join(".local/share/goose/sessions"),
+        "gemini-cli" => home.join(".gemini/tmp"),
+        "cursor" if cfg!(target_os = "macos") => home.join("Library/Application Support/Cursor/User/workspaceStorage"),
+        "cursor" => home.join(".config/Cursor/User/workspaceStorage"),
+        "cursor-logs" => home.join(".cursor/logs"),
+        "zed" => home.join(".config/zed/conversations"),
+        "lmstudio" => LmStudioAdapter::detect_platform_path().ok()?.join(lmstudio::CONVERSATIONS_DIR),
+        _ => return None,
+    };
+    Some(path)
+}
+
+/// Continuum settings from config.toml
+///
+/// Every field is optional; anything omitted keeps today's behavior:
+///
+/// ```toml
+/// base_dir = "~/notes/assistant-logs"
+/// retention_days = 180
+/// auto_archive = true
+/// search_index = true
+/// layout = "{assistant}/{project}/{date}/{session}"
+/// redact = true
+/// redact_patterns = ['hunter\d']
+///
+/// [assistant_paths]
+/// codex = "~/work/.codex/sessions"
+///
+/// [noise_filter]
+/// boilerplate = ['\[JIRA-\d+\]']
+///
+/// [loop_detector]
+/// max_messages_warning = 150
+/// ```
+///
+/// Without a `[noise_filter]` or `[loop_detector]` table, noise.toml and
+/// loops.toml are still read. `$CONTINUUM_LOG_DIR` (or `$CONTINUUM_LOGS_DIR`)
+/// overrides `base_dir`.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Root of the plain-text log tree
+    pub base_dir: PathBuf,
+    /// Overrides for where each assistant's native logs are read from
+    pub assistant_paths: HashMap<String, PathBuf>,
+    pub noise_filter: FilterConfig,
+    pub loop_detector: LoopDetectorConfig,
+    /// Age in days after which sessions are archived (default 90)
+    pub retention_days: Option<u32>,
+    /// Archive sessions older than `retention_days` after each import
+    pub auto_archive: bool,
+    /// Keep the SQLite search index up to date on import
+    pub search_index: bool,
+    /// Zone whose calendar date sessions are filed under (default UTC)
+    pub timezone: DateZone,
+    /// Session directory template (default: whatever the log directory records, see [`Layout`])
+    pub layout: Option<Layout>,
+    /// Mask secrets in messages before they are written
+    pub redact: bool,
+    /// What `redact` masks: `redact_patterns` as `custom_N`, then the built-in patterns
+    pub redactor: Redactor,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let base_dir = home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("Assistants")
+            .join("continuum-logs");
+
+        Config {
+            base_dir,
+            assistant_paths: HashMap::new(),
+            noise_filter: FilterConfig::default(),
+            loop_detector: LoopDetectorConfig::default(),
+            retention_days: None,
+            auto_archive: false,
+            search_index: true,
+            timezone: DateZone::Utc,
+            layout: None,
+            redact: false,
+            redactor: Redactor::new(),
+        }
+    }
+}
+
+/// config.toml as written; tables left out fall back to their own files
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    base_dir: Option<PathBuf>,
+    assistant_paths: HashMap<String, PathBuf>,
+    noise_filter: Option<FilterConfig>,
+    loop_detector: Option<LoopDetectorConfig>,
+    retention_days: Option<u32>,
+    auto_archive: Option<bool>,
+    search_index: Option<bool>,
+    /// `utc`, `local` or an offset like `-08:00`
+    timezone: Option<String>,
+    /// e.g. `{assistant}/{project}/{date}/{session}`
+    layout: Option<String>,
+    redact: Option<bool>,
+    /// Regexes masked on top of the built-in patterns
+    redact_patterns: Vec<String>,
+}
+
+impl Config {
+    /// Read config.toml (or config.json) from the config directory
+    ///
+    /// Falls back to the defaults when neither file exists.
+    pub fn load() -> Result<Config> {
+        let dir = config_dir();
+        let path = dir.as_ref().map(|d| d.join(CONFIG_FILE)).filter(|p| p.exists());
+        let path = path.or_else(|| dir.map(|d| d.join(CONFIG_JSON_FILE)).filter(|p| p.exists()));
+
+        match path {
+            Some(path) => Self::load_from(&path),
+            None => Self::from_file(ConfigFile::default())?.with_env_overrides(),
+        }
+    }
+
+    /// Read a specific config file, as TOML or (with a .json extension) JSON
+    pub fn load_from(path: &Path) -> Result<Config> {
+        Self::read_file(path)?.with_env_overrides()
+    }
+
+    fn read_file(path: &Path) -> Result<Config> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let file: ConfigFile = if path.extension().and_then(|e| e.to_str()) == Some("json") {
+            serde_json::from_str(&raw).with_context(|| format!("Invalid config: {}", path.display()))?
+        } else {
+            toml::from_str(&raw).with_context(|| format!("Invalid config: {}", path.display()))?
+        };
+
+        Self::from_file(file).with_context(|| format!("In {}", path.display()))
+    }
+
+    /// `$CONTINUUM_LOG_DIR` (or `$CONTINUUM_LOGS_DIR`) wins over `base_dir`
+    fn with_env_overrides(mut self) -> Result<Config> {
+        let dir = [LOG_DIR_ENV, LOGS_DIR_ENV]
+            .iter()
+            .find_map(|name| std::env::var_os(name).filter(|v| !v.is_empty()));
+        if let Some(dir) = dir {
+            self.base_dir = PlainTextWriter::resolve_dir(&dir.to_string_lossy())?;
+        }
+        Ok(self)
+    }
+
+    fn from_file(file: ConfigFile) -> Result<Config> {
+        let defaults = Config::default();
+        let base_dir = file.base_dir.unwrap_or(defaults.base_dir);
+
+        let assistant_paths = file
+            .assistant_paths
+            .into_iter()
+            .map(|(assistant, path)| Ok((assistant.to_lowercase(), expand_home(&path)?)))
+            .collect::<Result<_>>()?;
+
+        let noise_filter = match file.noise_filter {
+            Some(filter) => filter,
+            None => match config_file(NOISE_CONFIG_FILE).filter(|p| p.exists()) {
+                Some(path) => FilterConfig::load(&path)?,
+                None => defaults.noise_filter,
+            },
+        };
+        noise_filter.validate()?;
+
+        let loop_detector = match file.loop_detector {
+            Some(thresholds) => thresholds,
+            None => match config_file(LOOP_CONFIG_FILE).filter(|p| p.exists()) {
+                Some(path) => LoopDetectorConfig::load(&path)?,
+                None => defaults.loop_detector,
+            },
+        };
+        loop_detector.validate()?;
+
+        let patterns: Vec<&str> = file.redact_patterns.iter().map(String::as_str).collect();
+        let redactor = Redactor::from_patterns(&patterns).context("In redact_patterns")?.with_builtins();
+
+        Ok(Config {
+            base_dir: expand_home(&base_dir)?,
+            assistant_paths,
+            noise_filter,
+            loop_detector,
+            retention_days: file.retention_days,
+            auto_archive: file.auto_archive.unwrap_or(defaults.auto_archive),
+            search_index: file.search_index.unwrap_or(defaults.search_index),
+            timezone: file.timezone.as_deref().map(str::parse).transpose()?.unwrap_or(defaults.timezone),
+            layout: file.layout.as_deref().map(str::parse).transpose()?,
+            redact: file.redact.unwrap_or(defaults.redact),
+            redactor,
+        })
+    }
+
+    /// Where to read an assistant's native logs: the configured path, else the default
+    pub fn assistant_path(&self, assistant: &str) -> Option<PathBuf> {
+        self.assistant_paths
+            .get(assistant)
+            .cloned()
+            .or_else(|| default_assistant_path(assistant))
+    }
+
+    /// Age in days after which sessions are archived
+    pub fn retention_days(&self) -> u32 {
+        self.retention_days.unwrap_or(crate::archive::DEFAULT_ARCHIVE_AGE_DAYS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_toml_and_json() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let toml_path = temp_dir.path().join(CONFIG_FILE);
+        std::fs::write(
+            &toml_path,
+            "base_dir = \"/srv/logs\"\nauto_archive = true\nretention_days = 30\ntimezone = \"-08:00\"\n\n\
+             [assistant_paths]\nCodex = \"/srv/codex\"\n\n\
+             [noise_filter]\nboilerplate = ['\\[JIRA-\\d+\\]']\n\n\
+             [loop_detector]\nmin_repetitions = 4\n",
+        )?;
+        let config = Config::read_file(&toml_path)?;
+        assert_eq!(config.base_dir, PathBuf::from("/srv/logs"));
+        assert!(config.auto_archive);
+        assert!(config.search_index);
+        assert_eq!(config.retention_days(), 30);
+        assert_eq!(config.timezone, "-08:00".parse::<DateZone>()?);
+        assert_eq!(config.assistant_path("codex"), Some(PathBuf::from("/srv/codex")));
+        assert_eq!(config.noise_filter.boilerplate_patterns, vec![r"\[JIRA-\d+\]".to_string()]);
+        assert_eq!(config.loop_detector.build().analyze(&[]).len(), 0);
+
+        let json_path = temp_dir.path().join(CONFIG_JSON_FILE);
+        std::fs::write(&json_path, r#"{"search_index": false, "assistant_paths": {"aider": "/srv/aider"}}"#)?;
+        let config = Config::read_file(&json_path)?;
+        assert!(!config.search_index);
+        assert!(!config.auto_archive);
+        assert_eq!(config.timezone, DateZone::Utc);
+        assert_eq!(config.assistant_path("aider"), Some(PathBuf::from("/srv/aider")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nosave_marker_and_env() -> Result<()> {
+        let home = TempDir::new()?;
+        let marker = home.path().join(NOSAVE_MARKER);
+
+        assert!(!skip_saving_in(home.path()));
+
+        std::fs::write(&marker, "")?;
+        assert!(skip_saving_in(home.path()));
+        // The marker is single-use
+        assert!(!marker.exists());
+        assert!(!skip_saving_in(home.path()));
+
+        std::env::set_var(NOSAVE_ENV, "1");
+        let with_env = [skip_saving_in(home.path()), skip_saving_in(home.path())];
+        std::env::set_var(NOSAVE_ENV, "0");
+        let disabled = skip_saving_in(home.path());
+        std::env::remove_var(NOSAVE_ENV);
+
+        // The variable keeps applying, session after session
+        assert_eq!(with_env, [true, true]);
+        assert!(!disabled);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_config_rejected() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join(CONFIG_FILE);
+
+        std::fs::write(&path, "base_directory = \"/srv/logs\"\n")?;
+        assert!(Config::read_file(&path).is_err());
+
+        std::fs::write(&path, "[noise_filter]\nboilerplate = ['(unclosed']\n")?;
+        assert!(Config::read_file(&path).is_err());
+
+        std::fs::write(&path, "[loop_detector]\nsimilarity_threshold = 1.5\n")?;
+        assert!(Config::read_file(&path).is_err());
+
+        std::fs::write(&path, "timezone = \"Mars/Olympus\"\n")?;
+        assert!(Config::read_file(&path).is_err());
+
+        std::fs::write(&path, "redact = true\nredact_patterns = ['(unclosed']\n")?;
+        assert!(Config::read_file(&path).is_err());
+        Ok(())
+    }
+}
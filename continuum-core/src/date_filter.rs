@@ -0,0 +1,198 @@
+// Date filter parsing for --since/--until style arguments
+// Accepts absolute dates and common relative expressions
+
+use chrono::{Datelike, Days, Duration, Local, Months, NaiveDate};
+use color_eyre::Result;
+
+/// Inclusive range of calendar dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    /// Check whether a date falls inside the range (inclusive)
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        date >= self.start && date <= self.end
+    }
+}
+
+/// Result of parsing a date filter expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFilter {
+    /// A single day (e.g. `2025-11-09`, `yesterday`, `7d`)
+    Day(NaiveDate),
+    /// A span of days (e.g. `month`, `last-month`, `2025-11`)
+    Range(DateRange),
+}
+
+impl DateFilter {
+    /// Earliest date covered (use for `--since`)
+    pub fn start(&self) -> NaiveDate {
+        match self {
+            DateFilter::Day(date) => *date,
+            DateFilter::Range(range) => range.start,
+        }
+    }
+
+    /// Latest date covered (use for `--until`)
+    pub fn end(&self) -> NaiveDate {
+        match self {
+            DateFilter::Day(date) => *date,
+            DateFilter::Range(range) => range.end,
+        }
+    }
+}
+
+/// Parse a date filter relative to the local current date
+///
+/// Supported forms:
+/// - `YYYY-MM-DD` absolute date
+/// - `YYYY-MM` a whole calendar month
+/// - `today`, `yesterday`
+/// - `Nd`, `Nw`, `Nm` — N days/weeks/months ago
+/// - `week`, `month`, `year` — the current calendar period
+/// - `last-week`, `last-month`, `last-year` — the previous calendar period
+pub fn parse_date_filter(input: &str) -> Result<DateFilter> {
+    parse_date_filter_at(input, Local::now().date_naive())
+}
+
+/// Parse a date filter relative to an explicit `today`
+pub fn parse_date_filter_at(input: &str, today: NaiveDate) -> Result<DateFilter> {
+    let value = input.trim().to_lowercase();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+        return Ok(DateFilter::Day(date));
+    }
+
+    if let Ok(first) = NaiveDate::parse_from_str(&format!("{}-01", value), "%Y-%m-%d") {
+        return Ok(DateFilter::Range(month_range(first)));
+    }
+
+    match value.as_str() {
+        "today" => return Ok(DateFilter::Day(today)),
+        "yesterday" => return Ok(DateFilter::Day(today - Duration::days(1))),
+        "week" => return Ok(DateFilter::Range(week_range(today))),
+        "last-week" => return Ok(DateFilter::Range(week_range(today - Duration::weeks(1)))),
+        "month" => return Ok(DateFilter::Range(month_range(today))),
+        "last-month" => return Ok(DateFilter::Range(month_range(months_before(input, today, 1)?))),
+        "year" => return Ok(DateFilter::Range(year_range(today.year()))),
+        "last-year" => return Ok(DateFilter::Range(year_range(today.year() - 1))),
+        _ => {}
+    }
+
+    // Relative offsets: 7d, 2w, 3m
+    if value.len() >= 2 {
+        let (count, unit) = value.split_at(value.len() - 1);
+        if let Ok(n) = count.parse::<u32>() {
+            let date = match unit {
+                "d" => Some(days_before(input, today, u64::from(n))?),
+                "w" => Some(days_before(input, today, u64::from(n) * 7)?),
+                "m" => Some(months_before(input, today, n)?),
+                _ => None,
+            };
+            if let Some(date) = date {
+                return Ok(DateFilter::Day(date));
+            }
+        }
+    }
+
+    Err(color_eyre::eyre::eyre!(
+        "Invalid date filter '{}'. Expected YYYY-MM-DD, YYYY-MM, today, yesterday, Nd/Nw/Nm, week, month, year, or last-week/last-month/last-year",
+        input
+    ))
+}
+
+/// `days` before `date`, or an error naming `input` if that is before the earliest supported date
+fn days_before(input: &str, date: NaiveDate, days: u64) -> Result<NaiveDate> {
+    date.checked_sub_days(Days::new(days)).ok_or_else(|| out_of_range(input))
+}
+
+/// Move a date back by `months`, clamping the day to the target month's length
+fn months_before(input: &str, date: NaiveDate, months: u32) -> Result<NaiveDate> {
+    date.checked_sub_months(Months::new(months)).ok_or_else(|| out_of_range(input))
+}
+
+fn out_of_range(input: &str) -> color_eyre::Report {
+    color_eyre::eyre::eyre!("Date filter '{}' reaches further back than any supported date", input)
+}
+
+fn week_range(date: NaiveDate) -> DateRange {
+    let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+    DateRange { start, end: start + Duration::days(6) }
+}
+
+fn month_range(date: NaiveDate) -> DateRange {
+    let start = date.with_day(1).expect("valid day");
+    let next = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .expect("valid month");
+    DateRange { start, end: next - Duration::days(1) }
+}
+
+fn year_range(year: i32) -> DateRange {
+    DateRange {
+        start: NaiveDate::from_ymd_opt(year, 1, 1).expect("valid year"),
+        end: NaiveDate::from_ymd_opt(year, 12, 31).expect("valid year"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, 9).unwrap()
+    }
+
+    #[test]
+    fn test_absolute_date() {
+        let filter = parse_date_filter_at("2025-10-01", today()).unwrap();
+        assert_eq!(filter, DateFilter::Day(NaiveDate::from_ymd_opt(2025, 10, 1).unwrap()));
+    }
+
+    #[test]
+    fn test_relative_days() {
+        let filter = parse_date_filter_at("7d", today()).unwrap();
+        assert_eq!(filter, DateFilter::Day(NaiveDate::from_ymd_opt(2025, 11, 2).unwrap()));
+    }
+
+    #[test]
+    fn test_yesterday() {
+        let filter = parse_date_filter_at("Yesterday", today()).unwrap();
+        assert_eq!(filter, DateFilter::Day(NaiveDate::from_ymd_opt(2025, 11, 8).unwrap()));
+    }
+
+    #[test]
+    fn test_month_boundaries() {
+        let filter = parse_date_filter_at("month", today()).unwrap();
+        assert_eq!(filter.start(), NaiveDate::from_ymd_opt(2025, 11, 1).unwrap());
+        assert_eq!(filter.end(), NaiveDate::from_ymd_opt(2025, 11, 30).unwrap());
+
+        let last = parse_date_filter_at("last-month", today()).unwrap();
+        assert_eq!(last.start(), NaiveDate::from_ymd_opt(2025, 10, 1).unwrap());
+        assert_eq!(last.end(), NaiveDate::from_ymd_opt(2025, 10, 31).unwrap());
+    }
+
+    #[test]
+    fn test_offsets_out_of_range() {
+        let march_31 = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        assert_eq!(parse_date_filter_at("1m", march_31).unwrap(), DateFilter::Day(NaiveDate::from_ymd_opt(2025, 2, 28).unwrap()));
+
+        for input in ["99999999d", "4000000000w", "4000000000m"] {
+            let err = parse_date_filter_at(input, today()).unwrap_err();
+            assert!(err.to_string().contains("further back"), "{}: {}", input, err);
+        }
+    }
+
+    #[test]
+    fn test_garbage_input() {
+        let err = parse_date_filter_at("next tuesday-ish", today()).unwrap_err();
+        assert!(err.to_string().contains("Invalid date filter 'next tuesday-ish'"));
+        assert!(parse_date_filter_at("d", today()).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+// Session export
+// Renders stored sessions into shareable formats (Markdown, HTML, JSONL)
+
+use color_eyre::Result;
+
+use crate::store::SessionLocation;
+use crate::StoredMessage;
+
+/// Renders a stored session into a single document in some export format
+pub trait FormatWriter {
+    /// Render the session's metadata and messages
+    fn render(&self, session: &SessionLocation, messages: &[StoredMessage]) -> Result<String>;
+}
+
+/// Markdown: a metadata header followed by one `### Role` section per message
+pub struct MarkdownWriter;
+
+impl FormatWriter for MarkdownWriter {
+    fn render(&self, session: &SessionLocation, messages: &[StoredMessage]) -> Result<String> {
+        let mut out = format!("# Session {}\n\n", session.session_id);
+        out.push_str(&format!("- **Assistant:** {}\n", session.assistant));
+        out.push_str(&format!("- **Date:** {}\n", session.date));
+        out.push_str(&format!("- **Messages:** {}\n\n", messages.len()));
+        out.push_str(&render_markdown(messages));
+        Ok(out)
+    }
+}
+
+/// Standalone HTML page; content is escaped and shown preformatted
+pub struct HtmlWriter;
+
+impl FormatWriter for HtmlWriter {
+    fn render(&self, session: &SessionLocation, messages: &[StoredMessage]) -> Result<String> {
+        let id = escape_html(&session.session_id);
+        let mut out = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        out.push_str(&format!("<title>Session {}</title>\n</head>\n<body>\n", id));
+        out.push_str(&format!("<h1>Session {}</h1>\n<dl>\n", id));
+        out.push_str(&format!("<dt>Assistant</dt><dd>{}</dd>\n", escape_html(&session.assistant)));
+        out.push_str(&format!("<dt>Date</dt><dd>{}</dd>\n", escape_html(&session.date)));
+        out.push_str(&format!("<dt>Messages</dt><dd>{}</dd>\n</dl>\n", messages.len()));
+
+        for message in messages {
+            out.push_str(&format!("<section class=\"{}\">\n", escape_html(&message.role)));
+            out.push_str(&format!("<h3>{}</h3>\n", escape_html(&role_title(&message.role))));
+            if let Some(ref timestamp) = message.timestamp {
+                out.push_str(&format!("<p><em>{}</em></p>\n", escape_html(timestamp)));
+            }
+
+            let content = escape_html(message.content.trim_end());
+            if looks_like_code(&message.content) {
+                out.push_str(&format!("<pre><code>{}</code></pre>\n", content));
+            } else {
+                out.push_str(&format!("<p style=\"white-space: pre-wrap\">{}</p>\n", content));
+            }
+            out.push_str("</section>\n");
+        }
+
+        out.push_str("</body>\n</html>\n");
+        Ok(out)
+    }
+}
+
+/// JSONL pass-through: the stored messages, one JSON object per line
+pub struct JsonlWriter;
+
+impl FormatWriter for JsonlWriter {
+    fn render(&self, _session: &SessionLocation, messages: &[StoredMessage]) -> Result<String> {
+        let mut out = String::new();
+        for message in messages {
+            out.push_str(&serde_json::to_string(message)?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+/// Render stored messages as Markdown sections
+///
+/// Each message gets a `### Role` header with its timestamp in italics
+/// underneath. Content that already opens with a fence is kept verbatim so
+/// code blocks survive; a fence left open by a truncated message is closed
+/// so it can't swallow the messages that follow. Other content that looks
+/// like code (see [`looks_like_code`]) is wrapped in a fence of its own.
+pub fn render_markdown(messages: &[StoredMessage]) -> String {
+    let mut out = String::new();
+
+    for message in messages {
+        out.push_str(&format!("### {}\n\n", role_title(&message.role)));
+        if let Some(ref timestamp) = message.timestamp {
+            out.push_str(&format!("*{}*\n\n", timestamp));
+        }
+
+        let content = message.content.trim_end();
+        if looks_like_code(content) && !starts_with_fence(content) {
+            let fence = "`".repeat(longest_backtick_run(content).max(2) + 1);
+            out.push_str(&format!("{}\n{}\n{}\n", fence, content, fence));
+        } else {
+            out.push_str(content);
+            out.push('\n');
+            if let Some(fence) = open_fence(content) {
+                out.push_str(&fence);
+                out.push('\n');
+            }
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Heuristic for message content that should be shown as code: it opens with
+/// a fence, or has at least four indented lines
+pub fn looks_like_code(content: &str) -> bool {
+    let indented = content
+        .lines()
+        .filter(|line| line.starts_with([' ', '\t']) && !line.trim().is_empty())
+        .count();
+    starts_with_fence(content) || indented >= 4
+}
+
+fn starts_with_fence(content: &str) -> bool {
+    content.trim_start().starts_with("```")
+}
+
+/// Length of the longest run of backticks, so a wrapping fence can be made longer
+fn longest_backtick_run(content: &str) -> usize {
+    content
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Capitalise a role name for use as a header ("assistant" -> "Assistant")
+fn role_title(role: &str) -> String {
+    let mut chars = role.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Unknown".to_string(),
+    }
+}
+
+/// Return the fence needed to close a code block left open at the end of `content`
+fn open_fence(content: &str) -> Option<String> {
+    let mut open: Option<(char, usize)> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        // CommonMark allows at most three spaces of indentation before a fence
+        if line.len() - trimmed.len() > 3 {
+            continue;
+        }
+
+        let Some(marker) = trimmed.chars().next().filter(|c| *c == '`' || *c == '~') else {
+            continue;
+        };
+        let run = trimmed.chars().take_while(|c| *c == marker).count();
+        if run < 3 {
+            continue;
+        }
+
+        match open {
+            None => open = Some((marker, run)),
+            // A closing fence uses the same character, is at least as long, and has no info string
+            Some((c, len)) if c == marker && run >= len && trimmed[run..].trim().is_empty() => open = None,
+            Some(_) => {}
+        }
+    }
+
+    open.map(|(marker, len)| marker.to_string().repeat(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: usize, role: &str, content: &str, timestamp: Option<&str>) -> StoredMessage {
+        StoredMessage {
+            id,
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: timestamp.map(str::to_string),
+            tool_name: None,
+            tokens: None,
+            model: None,
+        }
+    }
+
+    fn location() -> SessionLocation {
+        SessionLocation {
+            assistant: "claude-code".to_string(),
+            date: "2025-11-09".to_string(),
+            session_id: "abc-123".to_string(),
+            path: std::path::PathBuf::from("claude-code/2025-11-09/abc-123"),
+        }
+    }
+
+    #[test]
+    fn test_render_headers_and_timestamps() {
+        let messages = vec![
+            message(1, "user", "How do I exit vim?", Some("2025-11-09T14:00:00Z")),
+            message(2, "assistant", "Press `:q`.", None),
+        ];
+
+        let markdown = render_markdown(&messages);
+        assert!(markdown.starts_with("### User\n\n*2025-11-09T14:00:00Z*\n\nHow do I exit vim?\n"));
+        assert!(markdown.contains("### Assistant\n\nPress `:q`.\n"));
+    }
+
+    #[test]
+    fn test_code_blocks_preserved_verbatim() {
+        let content = "Here:\n\n````markdown\n```rust\nfn main() {}\n```\n````\n\nDone.";
+        let markdown = render_markdown(&[message(1, "assistant", content, None)]);
+
+        assert!(markdown.contains(content));
+        assert!(open_fence(content).is_none());
+    }
+
+    #[test]
+    fn test_unterminated_fence_is_closed() {
+        let messages = vec![
+            message(1, "assistant", "Partial output:\n```python\nprint('hi')", None),
+            message(2, "user", "thanks", None),
+        ];
+
+        let markdown = render_markdown(&messages);
+        assert!(markdown.contains("print('hi')\n```\n\n### User"));
+        assert_eq!(open_fence("~~~~\ncode\n```"), Some("~~~~".to_string()));
+    }
+
+    #[test]
+    fn test_markdown_writer_metadata_header() -> Result<()> {
+        let messages = vec![message(1, "user", "hi", None), message(2, "assistant", "hello", None)];
+        let markdown = MarkdownWriter.render(&location(), &messages)?;
+
+        assert!(markdown.starts_with(
+            "# Session abc-123\n\n- **Assistant:** claude-code\n- **Date:** 2025-11-09\n- **Messages:** 2\n\n### User\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_indented_content_is_fenced() {
+        let content = "Run this:\n    let a = 1;\n    let b = 2;\n    let c = 3;\n    let d = 4;";
+        assert!(looks_like_code(content));
+        assert!(!looks_like_code("Just prose\n  with one indented line"));
+
+        let markdown = render_markdown(&[message(1, "assistant", content, None)]);
+        assert!(markdown.contains(&format!("```\n{}\n```\n", content)));
+
+        // A fence inside the content forces a longer wrapping fence
+        let nested = "    a\n    b\n    c\n    d\n```";
+        let markdown = render_markdown(&[message(1, "assistant", nested, None)]);
+        assert!(markdown.contains(&format!("````\n{}\n````\n", nested)));
+    }
+
+    #[test]
+    fn test_jsonl_writer_round_trips() -> Result<()> {
+        let messages = vec![message(1, "user", "line one\nline two", Some("2025-11-09T14:00:00Z"))];
+        let jsonl = JsonlWriter.render(&location(), &messages)?;
+
+        let parsed: StoredMessage = serde_json::from_str(jsonl.trim_end())?;
+        assert_eq!(parsed.content, "line one\nline two");
+        assert_eq!(jsonl.lines().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_html_writer_escapes_content() -> Result<()> {
+        let html = HtmlWriter.render(&location(), &[message(1, "user", "<script>alert(1)</script>", None)])?;
+
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<h3>User</h3>"));
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+// Session directory layout
+// Where each session's directory sits under the log directory, as a template like `{assistant}/{date}/{session}`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::{eyre, Context}, Result};
+
+use crate::store::{self, SessionLocation};
+
+/// The layout every log directory starts with
+pub const DEFAULT_LAYOUT: &str = "{assistant}/{date}/{session}";
+
+/// File in the log directory recording a non-default layout, so readers walk the tree the same way
+pub const LAYOUT_FILE: &str = ".layout";
+
+/// Directory `{project}` expands to for sessions without a project
+pub const NO_PROJECT: &str = "_";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Assistant,
+    Date,
+    Project,
+    Session,
+    Literal(String),
+}
+
+/// Template for a session's directory under the log directory
+///
+/// Each `/`-separated component is `{assistant}`, `{date}`, `{project}`,
+/// `{session}` or fixed text. `{session}` comes last, and `{assistant}` or
+/// `{date}` must appear; whichever is left out is read from session.json.
+/// `{project}` is the Claude Code project (see [`crate::SessionRecord::in_project`]),
+/// or [`NO_PROJECT`] until one is recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    template: String,
+    parts: Vec<Part>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            template: DEFAULT_LAYOUT.to_string(),
+            parts: vec![Part::Assistant, Part::Date, Part::Session],
+        }
+    }
+}
+
+impl std::str::FromStr for Layout {
+    type Err = color_eyre::Report;
+
+    fn from_str(template: &str) -> Result<Self> {
+        Layout::parse(template)
+    }
+}
+
+impl Layout {
+    /// Parse and validate a template such as `{assistant}/{project}/{date}/{session}`
+    pub fn parse(template: &str) -> Result<Layout> {
+        let template = template.trim().trim_matches('/');
+        let invalid = |reason: &str| eyre!("Invalid layout '{}': {}", template, reason);
+
+        let mut parts = Vec::new();
+        for component in template.split('/') {
+            let part = match component {
+                "{assistant}" => Part::Assistant,
+                "{date}" => Part::Date,
+                "{project}" => Part::Project,
+                "{session}" => Part::Session,
+                "" => return Err(invalid("empty path component")),
+                _ if component.contains(['{', '}']) => {
+                    return Err(invalid(&format!("unknown placeholder in '{}' (use {{assistant}}, {{date}}, {{project}} or {{session}}, each as a whole component)", component)))
+                }
+                _ if component.starts_with('.') || component.contains('\\') => {
+                    return Err(invalid(&format!("'{}' can't be a directory name here", component)))
+                }
+                _ => Part::Literal(component.to_string()),
+            };
+            if part != Part::Session && parts.contains(&Part::Session) {
+                return Err(invalid("{session} must be the last component"));
+            }
+            if !matches!(part, Part::Literal(_)) && parts.contains(&part) {
+                return Err(invalid(&format!("{} appears twice", component)));
+            }
+            parts.push(part);
+        }
+
+        if parts.last() != Some(&Part::Session) {
+            return Err(invalid("it must end in {session}"));
+        }
+        if !parts.iter().any(|p| matches!(p, Part::Assistant | Part::Date)) {
+            return Err(invalid("it needs {assistant} or {date}"));
+        }
+        Ok(Layout { template: template.to_string(), parts })
+    }
+
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Layout::default()
+    }
+
+    /// Whether sessions move when their project is recorded
+    pub fn has_project(&self) -> bool {
+        self.parts.contains(&Part::Project)
+    }
+
+    /// The layout recorded in `base_dir`, or the default if none is
+    pub fn load(base_dir: &Path) -> Result<Layout> {
+        let path = base_dir.join(LAYOUT_FILE);
+        match fs::read_to_string(&path) {
+            Ok(template) => Layout::parse(&template).with_context(|| format!("In {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Layout::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    /// Record this layout in `base_dir` before the first session is written in it
+    ///
+    /// Fails if `base_dir` already uses a different layout, including a tree
+    /// of sessions in the default layout, since they would no longer be found.
+    pub fn record(&self, base_dir: &Path) -> Result<()> {
+        let recorded = Layout::load(base_dir)?;
+        if recorded == *self {
+            return Ok(());
+        }
+        if !recorded.is_default() || !store::list_assistants(base_dir)?.is_empty() {
+            return Err(eyre!(
+                "{} is laid out as {}, not {}; use another log directory or move the sessions first",
+                base_dir.display(),
+                recorded.template,
+                self.template
+            ));
+        }
+
+        fs::create_dir_all(base_dir).with_context(|| format!("Failed to create directory: {}", base_dir.display()))?;
+        let path = base_dir.join(LAYOUT_FILE);
+        fs::write(&path, format!("{}\n", self.template)).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Directory for a session with `project` standing in for `{project}`
+    pub fn expand(&self, base_dir: &Path, assistant: &str, date: &str, session_id: &str, project: Option<&str>) -> PathBuf {
+        let project = project.filter(|p| is_dir_name(p)).unwrap_or(NO_PROJECT);
+        let mut path = base_dir.to_path_buf();
+        for part in &self.parts {
+            path.push(match part {
+                Part::Assistant => assistant,
+                Part::Date => date,
+                Part::Project => project,
+                Part::Session => session_id,
+                Part::Literal(name) => name,
+            });
+        }
+        path
+    }
+
+    /// Directory of a session: where it already is, whatever its project, else under [`NO_PROJECT`]
+    pub fn session_dir(&self, base_dir: &Path, assistant: &str, date: &str, session_id: &str) -> PathBuf {
+        if !self.has_project() {
+            return self.expand(base_dir, assistant, date, session_id, None);
+        }
+
+        // Walk down the template, trying every existing directory at the {project} level
+        let mut candidates = vec![base_dir.to_path_buf()];
+        for part in &self.parts {
+            candidates = match part {
+                Part::Project => candidates
+                    .iter()
+                    .flat_map(|dir| store::visible_dirs(dir).unwrap_or_default())
+                    .map(|(_, path)| path)
+                    .collect(),
+                Part::Assistant => candidates.into_iter().map(|dir| dir.join(assistant)).collect(),
+                Part::Date => candidates.into_iter().map(|dir| dir.join(date)).collect(),
+                Part::Session => candidates.into_iter().map(|dir| dir.join(session_id)).collect(),
+                Part::Literal(name) => candidates.into_iter().map(|dir| dir.join(name)).collect(),
+            };
+        }
+        candidates
+            .into_iter()
+            .find(|dir| dir.is_dir())
+            .unwrap_or_else(|| self.expand(base_dir, assistant, date, session_id, None))
+    }
+
+    /// Every session directory under `base_dir`, found by walking the template
+    ///
+    /// An assistant or date the template leaves out comes from session.json
+    /// (where [`crate::PlainTextWriter`] records it); sessions without one are skipped.
+    pub(crate) fn sessions(&self, base_dir: &Path) -> Result<Vec<SessionLocation>> {
+        let mut sessions = Vec::new();
+        if base_dir.is_dir() {
+            self.walk(base_dir, 0, &mut Fields::default(), &mut sessions)?;
+        }
+        Ok(sessions)
+    }
+
+    fn walk(&self, dir: &Path, depth: usize, fields: &mut Fields, sessions: &mut Vec<SessionLocation>) -> Result<()> {
+        let Some(part) = self.parts.get(depth) else {
+            if let Some(location) = fields.location(dir) {
+                sessions.push(location);
+            }
+            return Ok(());
+        };
+
+        if let Part::Literal(name) = part {
+            let child = dir.join(name);
+            return if child.is_dir() { self.walk(&child, depth + 1, fields, sessions) } else { Ok(()) };
+        }
+
+        for (name, child) in store::visible_dirs(dir)? {
+            match part {
+                Part::Assistant => fields.assistant = Some(name),
+                Part::Date => fields.date = Some(name),
+                Part::Session => fields.session_id = Some(name),
+                Part::Project | Part::Literal(_) => {}
+            }
+            self.walk(&child, depth + 1, fields, sessions)?;
+        }
+        Ok(())
+    }
+}
+
+/// Placeholders filled in so far while walking the tree
+#[derive(Default)]
+struct Fields {
+    assistant: Option<String>,
+    date: Option<String>,
+    session_id: Option<String>,
+}
+
+impl Fields {
+    fn location(&self, path: &Path) -> Option<SessionLocation> {
+        let recorded = || -> Option<serde_json::Value> { serde_json::from_str(&fs::read_to_string(path.join("session.json")).ok()?).ok() };
+        let from_json = |key: &str| recorded()?.get(key)?.as_str().map(str::to_string);
+
+        Some(SessionLocation {
+            assistant: self.assistant.clone().or_else(|| from_json("assistant"))?,
+            date: self.date.clone().or_else(|| from_json("date"))?,
+            session_id: self.session_id.clone()?,
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+/// Whether a recorded value can be used as one directory name
+fn is_dir_name(value: &str) -> bool {
+    !value.is_empty() && !value.starts_with('.') && !value.contains(['/', '\\'])
+}
+
+/// Remove `dir` and then its parents while they are empty, stopping at `base_dir`
+/// Directories already gone are passed over.
+pub(crate) fn remove_empty_dirs(base_dir: &Path, dir: &Path) {
+    let mut dir = Some(dir);
+    while let Some(current) = dir.filter(|d| d.starts_with(base_dir) && *d != base_dir) {
+        if let Err(e) = fs::remove_dir(current) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                break;
+            }
+        }
+        dir = current.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlainTextWriter;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_layout() {
+        assert!(Layout::parse(DEFAULT_LAYOUT).unwrap().is_default());
+        assert_eq!(Layout::parse("/{project}/{date}/{session}/").unwrap().template(), "{project}/{date}/{session}");
+        assert!(Layout::parse("logs/{assistant}/{session}").is_ok());
+
+        for template in ["{assistant}/{date}", "{session}/{date}", "{project}/{session}", "{assistant}/{date}-{session}", "{assistant}//{session}", "../{assistant}/{session}", "{date}/{date}/{session}"] {
+            assert!(Layout::parse(template).is_err(), "{} should be rejected", template);
+        }
+    }
+
+    #[test]
+    fn test_project_layout() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base = temp_dir.path();
+        let writer = PlainTextWriter::with_base_dir(base.to_path_buf()).with_layout("{assistant}/{project}/{date}/{session}")?;
+
+        writer.append_message("s1", "claude-code", "2025-11-09", 1, "user", "hello", None, None, None)?;
+        writer.write_session("s1", "claude-code", Some("2025-11-09T09:00:00Z"), None, "active", 1, &[], None, None)?;
+        assert!(base.join("claude-code/_/2025-11-09/s1/messages.jsonl").exists());
+
+        // Recording the project moves the session under it, and appends follow
+        writer.update_session_metadata("s1", "claude-code", "2025-11-09", json!({ "project": "-home-me-app" }))?;
+        writer.append_message("s1", "claude-code", "2025-11-09", 2, "assistant", "hi", None, None, None)?;
+        let moved = base.join("claude-code/-home-me-app/2025-11-09/s1");
+        assert_eq!(store::load_messages(&moved)?.len(), 2);
+        assert!(!base.join("claude-code/_").exists());
+
+        // Readers that only know the log directory find it through the recorded layout
+        assert_eq!(Layout::load(base)?, Layout::parse("{assistant}/{project}/{date}/{session}")?);
+        let location = store::find_session(base, "s1", None)?;
+        assert_eq!((location.assistant.as_str(), location.date.as_str(), location.path.as_path()), ("claude-code", "2025-11-09", moved.as_path()));
+        let records = PlainTextWriter::with_base_dir(base.to_path_buf()).list_sessions(&Default::default())?;
+        assert_eq!((records.len(), records[0].date.as_deref()), (1, Some("2025-11-09")));
+
+        // A second layout can't take over the same directory
+        assert!(PlainTextWriter::with_base_dir(base.to_path_buf())
+            .with_layout("{date}/{assistant}/{session}")?
+            .write_session("s2", "codex", None, None, "closed", 0, &[], None, None)
+            .is_err());
+        Ok(())
+    }
+}
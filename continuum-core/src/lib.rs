@@ -5,11 +5,53 @@ pub mod types;
 pub mod adapters;
 pub mod compression;
 pub mod plaintext;
+pub mod backend;
+#[cfg(feature = "async")]
+pub mod plaintext_async;
 pub mod loop_detection;
+pub mod store;
+pub mod date_filter;
+pub mod search;
+pub mod config;
+pub mod export;
+pub mod tool_capture;
+pub mod thinking;
+pub mod session_ops;
+pub mod archive;
+pub mod tokens;
+pub mod validate;
+pub mod compactor;
+pub mod timeline;
+pub mod redaction;
+pub mod reader;
+pub mod spool;
+pub mod batch;
+pub mod session_index;
+pub mod layout;
 
 // Re-export commonly used types
 pub use types::*;
-pub use adapters::LogAdapter;
-pub use compression::{NoiseFilter, MessageCompressor};
-pub use plaintext::PlainTextWriter;
-pub use loop_detection::{LoopDetector, LoopDetection, LoopSeverity};
+pub use adapters::{LogAdapter, SessionMetadata};
+pub use compression::{coalesce_consecutive, coalesce_consecutive_timed, Coalescer, CompressionStats, CompressionStream, CompressorConfig, FilterConfig, FilterExplanation, NoiseFilter, NoiseLevel, NoiseRule, MessageCompressor, take_noise_flag};
+pub use plaintext::{DateZone, DuplicateSession, ImportOutcome, PlainTextWriter};
+pub use backend::{DryRunWriter, WriterBackend};
+#[cfg(feature = "async")]
+pub use plaintext_async::AsyncPlainTextWriter;
+pub use loop_detection::{LoopDetector, LoopDetectorBuilder, LoopDetection, LoopSeverity};
+pub use store::StoredMessage;
+pub use export::{FormatWriter, HtmlWriter, JsonlWriter, MarkdownWriter};
+pub use date_filter::{parse_date_filter, DateFilter, DateRange};
+pub use search::{ContextualHit, QueryMatcher, ScanFilter, SearchHit, SearchIndex};
+pub use tool_capture::ToolCapture;
+pub use session_ops::{diff_sessions, merge_all, merge_sessions, DeleteError, DeletedSession, SessionDiff, SessionRef};
+pub use archive::{ArchiveManifest, ArchivedSession};
+pub use batch::{BatchImportResult, BatchSession, ImportOptions};
+pub use config::Config;
+pub use tokens::TokenCounter;
+pub use validate::{SessionValidator, ValidationError, ValidationReport, ValidationWarning};
+pub use compactor::{CompactionResult, Compactor};
+pub use timeline::TimelineEntry;
+pub use redaction::{take_redact_flag, RedactionEvent, Redactor};
+pub use reader::PlainTextReader;
+pub use spool::ImportSpool;
+pub use layout::Layout;
@@ -1,12 +1,31 @@
 // Loop detection for identifying runaway conversation patterns
-// Detects repeated message patterns that indicate automation failures
+// Detects repeated message patterns, runaway message growth and long autonomous stretches that indicate automation failures
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::{Hash, Hasher};
 use std::collections::hash_map::DefaultHasher;
+use std::path::Path;
 
-/// Warning levels for detected loops
-#[derive(Debug, Clone, PartialEq)]
+use color_eyre::{eyre::Context, Result};
+use serde::Deserialize;
+
+/// Name of the loop threshold file inside the config directory
+pub const LOOP_CONFIG_FILE: &str = "loops.toml";
+
+/// Messages a detector keeps counts for by default (see [`LoopDetectorBuilder::window`])
+pub const DEFAULT_WINDOW: usize = 2000;
+
+/// Latest assistant messages averaged by [`LoopDetector::detect_escalating_context`] by default
+pub const ESCALATION_WINDOW: usize = 10;
+
+/// Recent-to-overall length ratio at which escalating context warns by default
+pub const ESCALATION_RATIO: f64 = 2.0;
+
+/// Messages without a user turn before [`LoopDetector::detect_silence_pattern`] warns by default
+pub const SILENCE_TURNS: usize = 20;
+
+/// Warning levels for detected loops (ordered: `Warning < Critical`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LoopSeverity {
     /// Suspicious pattern detected but not conclusive
     Warning,
@@ -24,6 +43,12 @@ pub struct LoopDetection {
 }
 
 /// Detector for conversation loops and automation failures
+///
+/// Works either on a whole conversation ([`LoopDetector::analyze`]) or one
+/// message at a time as it streams in ([`LoopDetector::push`]). Both share the
+/// same incrementally maintained counts, so they agree on what is a loop.
+/// Repetition counts only cover the last `window` messages, which keeps memory
+/// bounded on long sessions; a loop seen within the window stays detected.
 pub struct LoopDetector {
     /// Maximum messages before warning (absolute threshold)
     max_messages_warning: usize,
@@ -33,26 +58,601 @@ pub struct LoopDetector {
     min_repetitions: usize,
     /// Maximum pattern size to check (in messages)
     max_pattern_size: usize,
+    /// Word-overlap ratio at which two messages count as the same content
+    /// (1.0 = exact matches only)
+    similarity_threshold: f64,
+    /// How many of the latest messages repetitions are counted over
+    window: usize,
+    /// Thresholds of the escalation and silence checks
+    growth_limits: GrowthLimits,
+    /// Counts for the messages pushed so far
+    state: StreamState,
 }
 
-impl LoopDetector {
-    pub fn new() -> Self {
+/// Thresholds behind [`ContextGrowth`]; a zero turns its check off
+#[derive(Debug, Clone, Copy)]
+struct GrowthLimits {
+    escalation_window: usize,
+    escalation_ratio: f64,
+    silence_turns: usize,
+}
+
+impl Default for GrowthLimits {
+    fn default() -> Self {
+        GrowthLimits { escalation_window: ESCALATION_WINDOW, escalation_ratio: ESCALATION_RATIO, silence_turns: SILENCE_TURNS }
+    }
+}
+
+/// Rolling counts behind streaming detection
+#[derive(Debug, Default)]
+struct StreamState {
+    message_count: usize,
+    /// Normalized content hash -> occurrences
+    content_counts: HashMap<u64, usize>,
+    /// Near-duplicate groups (id, representative word set, size); only used below 1.0 similarity
+    similar_groups: Vec<(u64, HashSet<String>, usize)>,
+    next_group: u64,
+    max_content_count: usize,
+    /// Role + content hash of each message in the window, for pattern windows
+    message_hashes: VecDeque<u64>,
+    /// Content hash or near-duplicate group of each message in the window
+    content_keys: VecDeque<u64>,
+    /// Per pattern size: window of message hashes -> occurrences
+    pattern_counts: HashMap<usize, HashMap<Vec<u64>, usize>>,
+    /// Per pattern size: highest window count seen
+    max_pattern_counts: HashMap<usize, usize>,
+    /// Highest severity already reported by `push`, per check
+    reported: HashMap<LoopCheck, LoopSeverity>,
+    /// Message lengths and user turns, for the escalation and silence checks
+    growth: ContextGrowth,
+}
+
+/// Running totals behind [`LoopDetector::detect_escalating_context`] and [`LoopDetector::detect_silence_pattern`]
+#[derive(Debug, Default)]
+struct ContextGrowth {
+    /// Character counts of the latest assistant messages
+    recent_lengths: VecDeque<usize>,
+    total_length: usize,
+    assistant_messages: usize,
+    /// Assistant messages since the last user message (all of them if there was none)
+    turns_since_user: usize,
+}
+
+impl ContextGrowth {
+    fn record(&mut self, role: &str, content: &str, limits: &GrowthLimits) {
+        if role.eq_ignore_ascii_case("user") {
+            self.turns_since_user = 0;
+            return;
+        }
+
+        // Tool calls and results belong to the assistant turn around them
+        if role.eq_ignore_ascii_case("assistant") {
+            self.turns_since_user += 1;
+            let length = content.chars().count();
+            self.total_length += length;
+            self.assistant_messages += 1;
+            self.recent_lengths.push_back(length);
+            if self.recent_lengths.len() > limits.escalation_window {
+                self.recent_lengths.pop_front();
+            }
+        }
+    }
+
+    /// Only ever a Warning: long replies alone don't make a runaway session
+    fn escalation(&self, limits: &GrowthLimits) -> Option<LoopDetection> {
+        // With no more messages than the window, the two averages are the same
+        if limits.escalation_window == 0 || self.assistant_messages <= limits.escalation_window || self.total_length == 0 {
+            return None;
+        }
+
+        let overall = self.total_length as f64 / self.assistant_messages as f64;
+        let recent = self.recent_lengths.iter().sum::<usize>() as f64 / self.recent_lengths.len() as f64;
+        let ratio = recent / overall;
+        if limits.escalation_ratio <= 0.0 || ratio <= limits.escalation_ratio {
+            return None;
+        }
+
+        Some(LoopDetection {
+            severity: LoopSeverity::Warning,
+            message: format!(
+                "Assistant messages growing: last {} average {:.0} characters, {:.1}x the overall {:.0} (threshold: {}x)",
+                self.recent_lengths.len(),
+                recent,
+                ratio,
+                overall,
+                limits.escalation_ratio
+            ),
+            repetition_count: 0,
+            pattern_size: 0,
+        })
+    }
+
+    fn silence(&self, limits: &GrowthLimits) -> Option<LoopDetection> {
+        (limits.silence_turns > 0 && self.turns_since_user >= limits.silence_turns).then(|| LoopDetection {
+            severity: LoopSeverity::Warning,
+            message: format!(
+                "No user message in the last {} assistant messages (threshold: {}); running autonomously",
+                self.turns_since_user, limits.silence_turns
+            ),
+            repetition_count: 0,
+            pattern_size: 0,
+        })
+    }
+}
+
+/// A message, or run of consecutive messages, that recurs in a conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RepeatedUnit {
+    /// Messages in the unit (1 for repeated content)
+    size: usize,
+    /// Index of the unit's first occurrence
+    first_index: usize,
+    count: usize,
+}
+
+/// The independent checks a detection can come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum LoopCheck {
+    MessageCount,
+    ContentRepetition,
+    PatternRepetition,
+    EscalatingContext,
+    Silence,
+}
+
+/// Loop thresholds as they appear in config files (see [`LoopDetectorBuilder`])
+pub type LoopDetectorConfig = LoopDetectorBuilder;
+
+/// Builder for a [`LoopDetector`] with custom thresholds
+///
+/// Also deserializable from TOML; omitted fields keep their defaults:
+///
+/// ```toml
+/// max_messages_warning = 150
+/// min_repetitions = 5
+/// similarity_threshold = 0.9
+/// silence_turns = 0   # 0 turns the check off
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoopDetectorBuilder {
+    max_messages_warning: usize,
+    max_messages_critical: usize,
+    min_repetitions: usize,
+    max_pattern_size: usize,
+    similarity_threshold: f64,
+    window: usize,
+    escalation_window: usize,
+    escalation_ratio: f64,
+    silence_turns: usize,
+}
+
+impl Default for LoopDetectorBuilder {
+    fn default() -> Self {
         Self {
             max_messages_warning: 100,
             max_messages_critical: 200,
             min_repetitions: 10,
             max_pattern_size: 10,
+            similarity_threshold: 1.0,
+            window: DEFAULT_WINDOW,
+            escalation_window: ESCALATION_WINDOW,
+            escalation_ratio: ESCALATION_RATIO,
+            silence_turns: SILENCE_TURNS,
+        }
+    }
+}
+
+impl LoopDetectorBuilder {
+    pub fn max_messages_warning(&mut self, count: usize) -> &mut Self {
+        self.max_messages_warning = count;
+        self
+    }
+
+    pub fn max_messages_critical(&mut self, count: usize) -> &mut Self {
+        self.max_messages_critical = count;
+        self
+    }
+
+    pub fn min_repetitions(&mut self, count: usize) -> &mut Self {
+        self.min_repetitions = count;
+        self
+    }
+
+    pub fn max_pattern_size(&mut self, size: usize) -> &mut Self {
+        self.max_pattern_size = size;
+        self
+    }
+
+    /// Set the near-duplicate threshold (clamped to 0.0..=1.0)
+    pub fn similarity_threshold(&mut self, threshold: f64) -> &mut Self {
+        self.similarity_threshold = threshold;
+        self
+    }
+
+    /// Count repetitions over only the latest `size` messages (at least one pattern's worth)
+    pub fn window(&mut self, size: usize) -> &mut Self {
+        self.window = size;
+        self
+    }
+
+    /// Compare the average of the latest `count` assistant messages with all of them (0 turns the check off)
+    pub fn escalation_window(&mut self, count: usize) -> &mut Self {
+        self.escalation_window = count;
+        self
+    }
+
+    /// Warn when the latest assistant messages average more than `ratio` times the overall length
+    pub fn escalation_ratio(&mut self, ratio: f64) -> &mut Self {
+        self.escalation_ratio = ratio;
+        self
+    }
+
+    /// Warn after `count` assistant messages without a user message (0 turns the check off)
+    pub fn silence_turns(&mut self, count: usize) -> &mut Self {
+        self.silence_turns = count;
+        self
+    }
+
+    /// Thresholds from the `[loop_detector]` table of config.toml, or loops.toml, otherwise the defaults
+    pub fn from_user_config() -> Result<Self> {
+        Ok(crate::config::Config::load()?.loop_detector)
+    }
+
+    /// Load thresholds from a TOML file; omitted fields keep their defaults
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let builder: LoopDetectorBuilder = toml::from_str(&raw)
+            .with_context(|| format!("Invalid loop detection config: {}", path.display()))?;
+
+        builder.validate().with_context(|| format!("In {}", path.display()))?;
+        Ok(builder)
+    }
+
+    /// Reject thresholds that can't be clamped into something meaningful
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.similarity_threshold) {
+            return Err(color_eyre::eyre::eyre!(
+                "similarity_threshold must be between 0.0 and 1.0 (got {})",
+                self.similarity_threshold
+            ));
         }
+        Ok(())
+    }
+
+    pub fn build(&self) -> LoopDetector {
+        LoopDetector {
+            max_messages_warning: self.max_messages_warning,
+            max_messages_critical: self.max_messages_critical,
+            min_repetitions: self.min_repetitions.max(1),
+            max_pattern_size: self.max_pattern_size,
+            similarity_threshold: self.similarity_threshold.clamp(0.0, 1.0),
+            window: self.window.max(self.max_pattern_size).max(1),
+            growth_limits: GrowthLimits {
+                escalation_window: self.escalation_window,
+                escalation_ratio: self.escalation_ratio,
+                silence_turns: self.silence_turns,
+            },
+            state: StreamState::default(),
+        }
+    }
+}
+
+impl LoopDetector {
+    pub fn new() -> Self {
+        LoopDetectorBuilder::default().build()
+    }
+
+    /// Start building a detector with custom thresholds
+    pub fn builder() -> LoopDetectorBuilder {
+        LoopDetectorBuilder::default()
+    }
+
+    /// Load thresholds from a TOML file (see [`LoopDetectorBuilder`])
+    pub fn from_config_file(path: &Path) -> Result<LoopDetector> {
+        Ok(LoopDetectorBuilder::load(path)?.build())
+    }
+
+    /// Create a detector using the user's loops.toml, if any
+    pub fn from_user_config() -> Result<LoopDetector> {
+        Ok(LoopDetectorBuilder::from_user_config()?.build())
     }
 
     /// Analyze a message batch for loop patterns
+    /// Runs a fresh detector with the same thresholds; `self` is left untouched
     pub fn analyze(&self, messages: &[(String, String)]) -> Vec<LoopDetection> {
-        let mut detections = Vec::new();
+        let mut detector = self.thresholds().build();
+        for (role, content) in messages {
+            detector.record(role, content);
+        }
+        detector.detections()
+    }
+
+    /// [`LoopDetector::analyze`] for messages read one at a time, e.g. from a large file
+    pub fn analyze_stream(&self, messages: impl IntoIterator<Item = (String, String)>) -> Vec<LoopDetection> {
+        let mut detector = self.thresholds().build();
+        for (role, content) in messages {
+            detector.record(&role, &content);
+        }
+        detector.detections()
+    }
+
+    /// Feed one message of a live conversation
+    ///
+    /// Returns a detection only when a check newly crosses a threshold (or
+    /// escalates from Warning to Critical), so each alert is raised once. If
+    /// several checks trip on the same message, the most severe is returned.
+    pub fn push(&mut self, role: &str, content: &str) -> Option<LoopDetection> {
+        self.record(role, content);
+
+        let mut escalated: Option<LoopDetection> = None;
+        for (check, detection) in self.checks() {
+            let Some(detection) = detection else { continue };
+            if self.state.reported.get(&check).is_some_and(|s| *s >= detection.severity) {
+                continue;
+            }
+            self.state.reported.insert(check, detection.severity);
+            if escalated.as_ref().is_none_or(|e| detection.severity > e.severity) {
+                escalated = Some(detection);
+            }
+        }
+        escalated
+    }
+
+    /// Everything currently detected in the messages pushed so far
+    pub fn detections(&self) -> Vec<LoopDetection> {
+        self.checks().into_iter().filter_map(|(_, detection)| detection).collect()
+    }
+
+    /// Assistant messages growing far longer than usual, as when context keeps accumulating
+    ///
+    /// Compares the average length of the last [`ESCALATION_WINDOW`] assistant
+    /// messages with the average over all of them, and warns above
+    /// [`ESCALATION_RATIO`]. Never Critical, so it can't abort a session alone.
+    pub fn detect_escalating_context(messages: &[(String, String)]) -> Option<LoopDetection> {
+        Self::context_growth(messages).escalation(&GrowthLimits::default())
+    }
+
+    /// A Warning when the last [`SILENCE_TURNS`] assistant messages came without a user message
+    /// Tool messages don't count as turns.
+    pub fn detect_silence_pattern(messages: &[(String, String)]) -> Option<LoopDetection> {
+        Self::context_growth(messages).silence(&GrowthLimits::default())
+    }
+
+    fn context_growth(messages: &[(String, String)]) -> ContextGrowth {
+        let mut growth = ContextGrowth::default();
+        for (role, content) in messages {
+            growth.record(role, content, &GrowthLimits::default());
+        }
+        growth
+    }
+
+    /// Index of the message where the first repeating unit starts
+    ///
+    /// Considers the repeated content and message patterns that reach
+    /// `min_repetitions`, as [`LoopDetector::analyze`] does, and returns the
+    /// earliest first occurrence among them. Truncating the conversation at
+    /// this index keeps everything before the loop. `None` if nothing repeats
+    /// often enough (a high message count alone has no loop boundary).
+    pub fn first_loop_index(&self, messages: &[(String, String)]) -> Option<usize> {
+        self.repeated_units(messages)
+            .iter()
+            .filter(|unit| unit.count >= self.min_repetitions)
+            .map(|unit| unit.first_index)
+            .min()
+    }
+
+    /// Markdown report explaining what [`LoopDetector::analyze`] found
+    ///
+    /// Sections cover the message count, the most repeated content (first 80
+    /// characters of each), every detection with example messages, and an
+    /// overall severity summary.
+    pub fn explain(&self, messages: &[(String, String)]) -> String {
+        let detections = self.analyze(messages);
+        let units = self.repeated_units(messages);
+        let mut report = String::from("# Loop detection report\n\n");
 
-        // Check 1: Absolute message count
-        let message_count = messages.len();
+        report.push_str("## Message count\n\n");
+        let level = if messages.len() >= self.max_messages_critical {
+            "above the critical threshold"
+        } else if messages.len() >= self.max_messages_warning {
+            "above the warning threshold"
+        } else {
+            "within the normal range"
+        };
+        report.push_str(&format!(
+            "The conversation has {} messages, {} (warning at {}, critical at {}).\n\n",
+            messages.len(),
+            level,
+            self.max_messages_warning,
+            self.max_messages_critical
+        ));
+
+        report.push_str("## Most repeated content\n\n");
+        let mut repeated: Vec<&RepeatedUnit> = units.iter().filter(|u| u.size == 1).collect();
+        repeated.sort_by(|a, b| b.count.cmp(&a.count).then(a.first_index.cmp(&b.first_index)));
+        if repeated.is_empty() {
+            report.push_str("No message content is repeated.\n\n");
+        } else {
+            for unit in repeated.iter().take(5) {
+                let (role, content) = &messages[unit.first_index];
+                report.push_str(&format!(
+                    "- {} times, first at message {} ({}): \"{}\"\n",
+                    unit.count,
+                    unit.first_index,
+                    role,
+                    Self::snippet(content)
+                ));
+            }
+            report.push('\n');
+        }
+
+        report.push_str("## Detected patterns\n\n");
+        if detections.is_empty() {
+            report.push_str("No loop patterns were detected.\n\n");
+        }
+        for detection in &detections {
+            report.push_str(&format!("- **{:?}**: {}\n", detection.severity, detection.message));
+
+            let example = units.iter().find(|u| {
+                u.size == detection.pattern_size && u.count == detection.repetition_count
+            });
+            if let Some(unit) = example {
+                for (role, content) in &messages[unit.first_index..unit.first_index + unit.size] {
+                    report.push_str(&format!("  > [{}] {}\n", role, Self::snippet(content)));
+                }
+            }
+        }
+        if !detections.is_empty() {
+            report.push('\n');
+        }
+
+        report.push_str("## Severity\n\n");
+        match detections.iter().map(|d| d.severity).max() {
+            Some(severity) => {
+                report.push_str(&format!(
+                    "Overall severity: **{:?}**, from {} of {} checks.",
+                    severity,
+                    detections.len(),
+                    self.checks().len()
+                ));
+                if let Some(index) = self.first_loop_index(messages) {
+                    report.push_str(&format!(" The first repeating unit starts at message {}.", index));
+                }
+                report.push('\n');
+            }
+            None => report.push_str("No loops detected.\n"),
+        }
+
+        report
+    }
+
+    /// Forget all pushed messages, keeping the thresholds
+    pub fn reset(&mut self) {
+        self.state = StreamState::default();
+    }
+
+    /// Builder carrying this detector's thresholds
+    fn thresholds(&self) -> LoopDetectorBuilder {
+        LoopDetectorBuilder {
+            max_messages_warning: self.max_messages_warning,
+            max_messages_critical: self.max_messages_critical,
+            min_repetitions: self.min_repetitions,
+            max_pattern_size: self.max_pattern_size,
+            similarity_threshold: self.similarity_threshold,
+            window: self.window,
+            escalation_window: self.growth_limits.escalation_window,
+            escalation_ratio: self.growth_limits.escalation_ratio,
+            silence_turns: self.growth_limits.silence_turns,
+        }
+    }
+
+    /// Update the rolling counts with one message
+    fn record(&mut self, role: &str, content: &str) {
+        self.state.message_count += 1;
+        self.state.growth.record(role, content, &self.growth_limits);
+
+        // Content repetition ignores the role; near-duplicates are grouped when configured
+        let (content_key, content_count) = if self.similarity_threshold < 1.0 {
+            let words = Self::word_set(content);
+            let threshold = self.similarity_threshold;
+            let state = &mut self.state;
+            match state.similar_groups.iter_mut().find(|(_, rep, _)| Self::jaccard(rep, &words) >= threshold) {
+                Some((id, _, count)) => {
+                    *count += 1;
+                    (*id, *count)
+                }
+                None => {
+                    let id = state.next_group;
+                    state.next_group += 1;
+                    state.similar_groups.push((id, words, 1));
+                    (id, 1)
+                }
+            }
+        } else {
+            let key = self.hash_content(content);
+            let count = self.state.content_counts.entry(key).or_insert(0);
+            *count += 1;
+            (key, *count)
+        };
+        self.state.max_content_count = self.state.max_content_count.max(content_count);
+        self.state.content_keys.push_back(content_key);
+
+        // Pattern windows ending at this message, for every pattern size
+        let mut hasher = DefaultHasher::new();
+        role.hash(&mut hasher);
+        content.hash(&mut hasher);
+        self.state.message_hashes.push_back(hasher.finish());
+
+        let hashes = &self.state.message_hashes;
+        for pattern_size in 2..=self.max_pattern_size.min(hashes.len()) {
+            let window: Vec<u64> = hashes.range(hashes.len() - pattern_size..).copied().collect();
+            let count = self.state.pattern_counts.entry(pattern_size).or_default().entry(window).or_insert(0);
+            *count += 1;
+            let max = self.state.max_pattern_counts.entry(pattern_size).or_insert(0);
+            *max = (*max).max(*count);
+        }
+
+        if self.state.message_hashes.len() > self.window {
+            self.evict_oldest();
+        }
+    }
+
+    /// Drop the oldest message in the window from the repetition counts
+    /// The highest counts seen so far are kept, so earlier detections stand.
+    fn evict_oldest(&mut self) {
+        let state = &mut self.state;
+
+        // Pattern windows starting at the oldest message
+        let hashes = &state.message_hashes;
+        for pattern_size in 2..=self.max_pattern_size.min(hashes.len()) {
+            let window: Vec<u64> = hashes.range(..pattern_size).copied().collect();
+            if let Some(counts) = state.pattern_counts.get_mut(&pattern_size) {
+                if let Some(count) = counts.get_mut(&window) {
+                    *count -= 1;
+                    if *count == 0 {
+                        counts.remove(&window);
+                    }
+                }
+            }
+        }
+        state.message_hashes.pop_front();
+
+        let Some(key) = state.content_keys.pop_front() else {
+            return;
+        };
+        if self.similarity_threshold < 1.0 {
+            if let Some(index) = state.similar_groups.iter().position(|(id, ..)| *id == key) {
+                state.similar_groups[index].2 -= 1;
+                if state.similar_groups[index].2 == 0 {
+                    state.similar_groups.remove(index);
+                }
+            }
+        } else if let Some(count) = state.content_counts.get_mut(&key) {
+            *count -= 1;
+            if *count == 0 {
+                state.content_counts.remove(&key);
+            }
+        }
+    }
+
+    /// Current result of each check
+    fn checks(&self) -> [(LoopCheck, Option<LoopDetection>); 5] {
+        [
+            (LoopCheck::MessageCount, self.check_message_count()),
+            (LoopCheck::ContentRepetition, self.check_content_repetition()),
+            (LoopCheck::PatternRepetition, self.check_pattern_loops()),
+            (LoopCheck::EscalatingContext, self.state.growth.escalation(&self.growth_limits)),
+            (LoopCheck::Silence, self.state.growth.silence(&self.growth_limits)),
+        ]
+    }
+
+    /// Absolute message count
+    fn check_message_count(&self) -> Option<LoopDetection> {
+        let message_count = self.state.message_count;
         if message_count >= self.max_messages_critical {
-            detections.push(LoopDetection {
+            Some(LoopDetection {
                 severity: LoopSeverity::Critical,
                 message: format!(
                     "Extremely high message count: {} messages (threshold: {})",
@@ -60,9 +660,9 @@ impl LoopDetector {
                 ),
                 repetition_count: 0,
                 pattern_size: 0,
-            });
+            })
         } else if message_count >= self.max_messages_warning {
-            detections.push(LoopDetection {
+            Some(LoopDetection {
                 severity: LoopSeverity::Warning,
                 message: format!(
                     "High message count: {} messages (threshold: {})",
@@ -70,52 +670,71 @@ impl LoopDetector {
                 ),
                 repetition_count: 0,
                 pattern_size: 0,
-            });
+            })
+        } else {
+            None
         }
+    }
 
-        // Check 2: Content hash-based repetition detection
-        if let Some(detection) = self.detect_content_repetition(messages) {
-            detections.push(detection);
-        }
+    /// Detect if the same content appears repeatedly
+    fn check_content_repetition(&self) -> Option<LoopDetection> {
+        let max_count = self.state.max_content_count;
 
-        // Check 3: Pattern-based loop detection (sequences of messages)
-        if let Some(detection) = self.detect_message_pattern_loops(messages) {
-            detections.push(detection);
+        if max_count >= self.min_repetitions * 2 {
+            return Some(LoopDetection {
+                severity: LoopSeverity::Critical,
+                message: format!(
+                    "Identical content repeated {} times (threshold: {})",
+                    max_count, self.min_repetitions * 2
+                ),
+                repetition_count: max_count,
+                pattern_size: 1,
+            });
+        } else if max_count >= self.min_repetitions {
+            return Some(LoopDetection {
+                severity: LoopSeverity::Warning,
+                message: format!(
+                    "Content repeated {} times (threshold: {})",
+                    max_count, self.min_repetitions
+                ),
+                repetition_count: max_count,
+                pattern_size: 1,
+            });
         }
 
-        detections
+        None
     }
 
-    /// Detect if the same content appears repeatedly
-    fn detect_content_repetition(&self, messages: &[(String, String)]) -> Option<LoopDetection> {
-        let mut content_counts: HashMap<u64, usize> = HashMap::new();
+    /// Detect repeating patterns of message sequences
+    /// Smaller patterns are reported first (2-message, 3-message, ...)
+    fn check_pattern_loops(&self) -> Option<LoopDetection> {
+        let message_count = self.state.message_count;
 
-        for (_, content) in messages {
-            let hash = self.hash_content(content);
-            *content_counts.entry(hash).or_insert(0) += 1;
-        }
+        for pattern_size in 2..=self.max_pattern_size.min(message_count / 4) {
+            if message_count < pattern_size * self.min_repetitions {
+                continue;
+            }
 
-        // Find the most repeated content
-        if let Some((_, &max_count)) = content_counts.iter().max_by_key(|(_, &count)| count) {
+            let max_count = self.state.max_pattern_counts.get(&pattern_size).copied().unwrap_or(0);
             if max_count >= self.min_repetitions * 2 {
                 return Some(LoopDetection {
                     severity: LoopSeverity::Critical,
                     message: format!(
-                        "Identical content repeated {} times (threshold: {})",
-                        max_count, self.min_repetitions * 2
+                        "Message pattern of {} messages repeated {} times (threshold: {})",
+                        pattern_size, max_count, self.min_repetitions * 2
                     ),
                     repetition_count: max_count,
-                    pattern_size: 1,
+                    pattern_size,
                 });
             } else if max_count >= self.min_repetitions {
                 return Some(LoopDetection {
                     severity: LoopSeverity::Warning,
                     message: format!(
-                        "Content repeated {} times (threshold: {})",
-                        max_count, self.min_repetitions
+                        "Message pattern of {} messages repeated {} times (threshold: {})",
+                        pattern_size, max_count, self.min_repetitions
                     ),
                     repetition_count: max_count,
-                    pattern_size: 1,
+                    pattern_size,
                 });
             }
         }
@@ -123,67 +742,94 @@ impl LoopDetector {
         None
     }
 
-    /// Detect repeating patterns of message sequences
-    fn detect_message_pattern_loops(&self, messages: &[(String, String)]) -> Option<LoopDetection> {
-        // Try different pattern sizes (2-message, 3-message, 4-message patterns, etc.)
-        for pattern_size in 2..=self.max_pattern_size.min(messages.len() / 4) {
-            if let Some(detection) = self.find_repeating_pattern(messages, pattern_size) {
-                return Some(detection);
+    /// Repeated content and message patterns, with where each first occurs
+    ///
+    /// Content is grouped the way the content repetition check groups it;
+    /// patterns cover the sizes the pattern check considers for this many
+    /// messages. Units occurring only once are left out.
+    fn repeated_units(&self, messages: &[(String, String)]) -> Vec<RepeatedUnit> {
+        let mut units = Vec::new();
+
+        // (first index, count) per content group, in order of first appearance
+        let mut groups: Vec<(usize, usize)> = Vec::new();
+        let mut exact: HashMap<u64, usize> = HashMap::new();
+        let mut similar: Vec<HashSet<String>> = Vec::new();
+        for (index, (_, content)) in messages.iter().enumerate() {
+            let group = if self.similarity_threshold < 1.0 {
+                let words = Self::word_set(content);
+                match similar.iter().position(|rep| Self::jaccard(rep, &words) >= self.similarity_threshold) {
+                    Some(group) => group,
+                    None => {
+                        similar.push(words);
+                        similar.len() - 1
+                    }
+                }
+            } else {
+                let next = exact.len();
+                *exact.entry(self.hash_content(content)).or_insert(next)
+            };
+            if group == groups.len() {
+                groups.push((index, 0));
             }
+            groups[group].1 += 1;
         }
+        units.extend(
+            groups
+                .into_iter()
+                .filter(|&(_, count)| count > 1)
+                .map(|(first_index, count)| RepeatedUnit { size: 1, first_index, count }),
+        );
 
-        None
-    }
+        let hashes: Vec<u64> = messages
+            .iter()
+            .map(|(role, content)| {
+                let mut hasher = DefaultHasher::new();
+                role.hash(&mut hasher);
+                content.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect();
 
-    /// Find if a pattern of N messages repeats
-    fn find_repeating_pattern(&self, messages: &[(String, String)], pattern_size: usize) -> Option<LoopDetection> {
-        if messages.len() < pattern_size * self.min_repetitions {
-            return None;
+        for pattern_size in 2..=self.max_pattern_size.min(messages.len() / 4) {
+            if messages.len() < pattern_size * self.min_repetitions {
+                continue;
+            }
+            let mut windows: HashMap<&[u64], (usize, usize)> = HashMap::new();
+            for (start, window) in hashes.windows(pattern_size).enumerate() {
+                windows.entry(window).or_insert((start, 0)).1 += 1;
+            }
+            let mut repeated: Vec<RepeatedUnit> = windows
+                .into_values()
+                .filter(|&(_, count)| count > 1)
+                .map(|(first_index, count)| RepeatedUnit { size: pattern_size, first_index, count })
+                .collect();
+            repeated.sort_by_key(|unit| unit.first_index);
+            units.extend(repeated);
         }
 
-        let mut pattern_counts: HashMap<Vec<u64>, usize> = HashMap::new();
-
-        // Create sliding windows of pattern_size
-        for window in messages.windows(pattern_size) {
-            let pattern_hash: Vec<u64> = window
-                .iter()
-                .map(|(role, content)| {
-                    let mut hasher = DefaultHasher::new();
-                    role.hash(&mut hasher);
-                    content.hash(&mut hasher);
-                    hasher.finish()
-                })
-                .collect();
+        units
+    }
 
-            *pattern_counts.entry(pattern_hash).or_insert(0) += 1;
+    /// First 80 characters of a message, on one line
+    pub(crate) fn snippet(content: &str) -> String {
+        let flat = content.split_whitespace().collect::<Vec<_>>().join(" ");
+        match flat.char_indices().nth(80) {
+            Some((end, _)) => format!("{}...", &flat[..end]),
+            None => flat,
         }
+    }
 
-        // Find the most repeated pattern
-        if let Some((_, &max_count)) = pattern_counts.iter().max_by_key(|(_, &count)| count) {
-            if max_count >= self.min_repetitions * 2 {
-                return Some(LoopDetection {
-                    severity: LoopSeverity::Critical,
-                    message: format!(
-                        "Message pattern of {} messages repeated {} times (threshold: {})",
-                        pattern_size, max_count, self.min_repetitions * 2
-                    ),
-                    repetition_count: max_count,
-                    pattern_size,
-                });
-            } else if max_count >= self.min_repetitions {
-                return Some(LoopDetection {
-                    severity: LoopSeverity::Warning,
-                    message: format!(
-                        "Message pattern of {} messages repeated {} times (threshold: {})",
-                        pattern_size, max_count, self.min_repetitions
-                    ),
-                    repetition_count: max_count,
-                    pattern_size,
-                });
-            }
-        }
+    fn word_set(content: &str) -> HashSet<String> {
+        content.split_whitespace().map(|w| w.to_lowercase()).collect()
+    }
 
-        None
+    /// Jaccard similarity of two word sets (1.0 when both are empty)
+    fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+        let union = a.union(b).count();
+        if union == 0 {
+            return 1.0;
+        }
+        a.intersection(b).count() as f64 / union as f64
     }
 
     /// Hash content for comparison (normalize whitespace)
@@ -273,6 +919,62 @@ mod tests {
         assert!(detections.is_empty());
     }
 
+    #[test]
+    fn test_builder_overrides_thresholds() {
+        let detector = LoopDetector::builder()
+            .max_messages_warning(5)
+            .max_messages_critical(8)
+            .min_repetitions(3)
+            .build();
+
+        let messages: Vec<(String, String)> = (0..6)
+            .map(|i| ("user".to_string(), format!("Message {}", i)))
+            .collect();
+        let detections = detector.analyze(&messages);
+        assert!(detections.iter().any(|d| d.severity == LoopSeverity::Warning && d.pattern_size == 0));
+
+        let repeated: Vec<(String, String)> = (0..3)
+            .map(|_| ("user".to_string(), "retry".to_string()))
+            .collect();
+        assert!(detector.analyze(&repeated).iter().any(|d| d.pattern_size == 1));
+    }
+
+    #[test]
+    fn test_similarity_threshold_catches_near_duplicates() {
+        let messages: Vec<(String, String)> = (0..12)
+            .map(|i| ("assistant".to_string(), format!("Retrying the failed build step now attempt {}", i)))
+            .collect();
+
+        // Exact matching sees 12 distinct messages
+        assert!(LoopDetector::new().analyze(&messages).is_empty());
+
+        let fuzzy = LoopDetector::builder().similarity_threshold(0.7).build();
+        let detection = fuzzy.analyze(&messages).into_iter().find(|d| d.pattern_size == 1);
+        assert_eq!(detection.unwrap().repetition_count, 12);
+    }
+
+    #[test]
+    fn test_from_config_file() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("loops.toml");
+
+        std::fs::write(&path, "min_repetitions = 2\nsimilarity_threshold = 0.8\nescalation_ratio = 3.5\nsilence_turns = 0\n")?;
+        let detector = LoopDetector::from_config_file(&path)?;
+        assert_eq!(detector.min_repetitions, 2);
+        assert_eq!(detector.max_messages_warning, 100);
+        assert_eq!(detector.similarity_threshold, 0.8);
+        assert_eq!(detector.growth_limits.escalation_window, ESCALATION_WINDOW);
+        assert_eq!(detector.growth_limits.escalation_ratio, 3.5);
+        assert_eq!(detector.growth_limits.silence_turns, 0);
+
+        std::fs::write(&path, "similarity_threshold = 1.5\n")?;
+        assert!(LoopDetector::from_config_file(&path).is_err());
+
+        std::fs::write(&path, "min_repetition = 2\n")?;
+        assert!(LoopDetector::from_config_file(&path).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_four_message_loop() {
         let detector = LoopDetector::new();
@@ -293,4 +995,180 @@ mod tests {
         assert!(detections.iter().any(|d| d.severity == LoopSeverity::Critical));
         assert!(detections.iter().any(|d| d.pattern_size == 4 || d.pattern_size == 2));
     }
+
+    #[test]
+    fn test_first_loop_index() {
+        let detector = LoopDetector::builder().min_repetitions(3).build();
+
+        let mut messages: Vec<(String, String)> = vec![
+            ("user".to_string(), "Set up the project".to_string()),
+            ("assistant".to_string(), "Done, the project builds.".to_string()),
+        ];
+        assert_eq!(detector.first_loop_index(&messages), None);
+
+        for _ in 0..4 {
+            messages.push(("user".to_string(), "Run the tests".to_string()));
+            messages.push(("assistant".to_string(), "Tests failed, retrying".to_string()));
+        }
+        assert_eq!(detector.first_loop_index(&messages), Some(2));
+
+        // A high message count alone has no loop boundary
+        let varied: Vec<(String, String)> = (0..150)
+            .map(|i| ("user".to_string(), format!("Message {}", i)))
+            .collect();
+        assert!(!detector.analyze(&varied).is_empty());
+        assert_eq!(detector.first_loop_index(&varied), None);
+    }
+
+    #[test]
+    fn test_explain_report() {
+        let detector = LoopDetector::builder().min_repetitions(3).build();
+
+        let mut messages = vec![("user".to_string(), "Fix the build".to_string())];
+        for _ in 0..6 {
+            messages.push(("user".to_string(), "Please read documentation in ~/Assistants/shared".to_string()));
+            messages.push(("assistant".to_string(), "x".repeat(100)));
+        }
+
+        let report = detector.explain(&messages);
+        for heading in ["## Message count", "## Most repeated content", "## Detected patterns", "## Severity"] {
+            assert!(report.contains(heading), "missing {}", heading);
+        }
+        assert!(report.contains("The conversation has 13 messages, within the normal range"));
+        assert!(report.contains("- 6 times, first at message 1 (user): \"Please read documentation in ~/Assistants/shared\""));
+        // Long messages are cut to 80 characters
+        assert!(report.contains(&format!("\"{}...\"", "x".repeat(80))));
+        assert!(report.contains("- **Critical**: Identical content repeated 6 times"));
+        assert!(report.contains("  > [user] Please read documentation"));
+        assert!(report.contains("Overall severity: **Critical**"));
+        assert!(report.contains("The first repeating unit starts at message 1."));
+
+        let quiet = LoopDetector::new().explain(&messages[..1]);
+        assert!(quiet.contains("No message content is repeated."));
+        assert!(quiet.contains("No loops detected."));
+    }
+
+    #[test]
+    fn test_window_bounds_repetition_counts() {
+        // The same message every 15 messages: 5 times in all, at most twice in any 20
+        let messages: Vec<(String, String)> = (0..75)
+            .map(|i| match i % 15 {
+                0 => ("user".to_string(), "Try again".to_string()),
+                _ => ("assistant".to_string(), format!("Step {}", i)),
+            })
+            .collect();
+
+        let mut builder = LoopDetector::builder();
+        builder.min_repetitions(3).max_messages_warning(1000).max_messages_critical(1000);
+        assert_eq!(builder.build().analyze(&messages).len(), 1);
+        assert!(builder.window(20).build().analyze(&messages).is_empty());
+
+        let mut detector = builder.build();
+        for (role, content) in &messages {
+            detector.push(role, content);
+        }
+        assert_eq!(detector.state.message_hashes.len(), 20);
+        assert!(detector.state.content_counts.len() <= 20);
+        assert!(detector.state.pattern_counts.values().all(|counts| counts.len() <= 20));
+    }
+
+    #[test]
+    fn test_push_alerts_once_per_escalation() {
+        let mut detector = LoopDetector::builder().min_repetitions(3).build();
+
+        let alerts: Vec<(usize, LoopSeverity)> = (1..=7)
+            .filter_map(|i| detector.push("assistant", "Retrying").map(|d| (i, d.severity)))
+            .collect();
+
+        // Warning at the 3rd repeat, Critical at the 6th, nothing in between or after
+        assert_eq!(alerts, [(3, LoopSeverity::Warning), (6, LoopSeverity::Critical)]);
+
+        detector.reset();
+        assert!(detector.detections().is_empty());
+        assert!(detector.push("assistant", "Retrying").is_none());
+    }
+
+    #[test]
+    fn test_streaming_matches_batch() {
+        let mut messages = Vec::new();
+        for i in 0..30 {
+            messages.push(("user".to_string(), "Run the tests".to_string()));
+            messages.push(("assistant".to_string(), format!("Tests failed, run {}", i % 3)));
+        }
+
+        let detector = LoopDetector::new();
+        let batch = detector.analyze(&messages);
+
+        let mut streaming = LoopDetector::new();
+        let pushed: Vec<LoopDetection> = messages
+            .iter()
+            .filter_map(|(role, content)| streaming.push(role, content))
+            .collect();
+
+        let summarize = |d: &[LoopDetection]| -> Vec<(LoopSeverity, usize, usize)> {
+            d.iter().map(|d| (d.severity, d.repetition_count, d.pattern_size)).collect()
+        };
+        assert_eq!(summarize(&streaming.detections()), summarize(&batch));
+        assert!(pushed.iter().any(|d| d.severity == LoopSeverity::Critical));
+        // analyze() doesn't touch the detector's own stream
+        assert!(detector.detections().is_empty());
+    }
+
+    #[test]
+    fn test_escalating_context() {
+        // Steady lengths, then the last 10 replies much longer
+        let conversation = |steady: usize, long: usize| -> Vec<(String, String)> {
+            (0..steady + 10)
+                .flat_map(|i| {
+                    let length = if i < steady { 20 } else { long };
+                    [
+                        ("user".to_string(), format!("Step {}", i)),
+                        ("assistant".to_string(), format!("{:03} {}", i, "x".repeat(length - 4))),
+                    ]
+                })
+                .collect()
+        };
+
+        assert!(LoopDetector::detect_escalating_context(&conversation(30, 20)).is_none());
+        // 10 of 40 replies at 4x: recent 80 vs overall 35
+        let warning = LoopDetector::detect_escalating_context(&conversation(30, 80)).unwrap();
+        assert_eq!(warning.severity, LoopSeverity::Warning);
+        // 10 of 100 replies at 40x: recent 800 vs overall 98, still only a warning
+        let steep = LoopDetector::detect_escalating_context(&conversation(90, 800)).unwrap();
+        assert_eq!(steep.severity, LoopSeverity::Warning);
+        // Too few replies to compare against
+        assert!(LoopDetector::detect_escalating_context(&conversation(0, 80)).is_none());
+
+        let mut builder = LoopDetector::builder();
+        builder.max_messages_warning(1000).max_messages_critical(1000);
+        let detections = builder.build().analyze(&conversation(90, 800));
+        assert!(detections.iter().all(|d| d.severity == LoopSeverity::Warning));
+        assert!(detections.iter().any(|d| d.message.starts_with("Assistant messages growing")));
+
+        // A higher ratio or a wider window, or off altogether
+        assert!(builder.escalation_ratio(5.0).build().analyze(&conversation(30, 80)).is_empty());
+        assert!(builder.escalation_ratio(2.0).escalation_window(40).build().analyze(&conversation(30, 80)).is_empty());
+        assert!(builder.escalation_window(0).build().analyze(&conversation(90, 800)).is_empty());
+    }
+
+    #[test]
+    fn test_silence_pattern() {
+        let mut messages: Vec<(String, String)> = vec![("user".to_string(), "Refactor the parser".to_string())];
+        messages.extend((0..19).map(|i| ("assistant".to_string(), format!("Working on step {}", i))));
+        assert!(LoopDetector::detect_silence_pattern(&messages).is_none());
+
+        // Tool calls and results are part of the assistant's turns
+        messages.extend((0..5).map(|i| ("tool".to_string(), format!("[tool result] {}", i))));
+        assert!(LoopDetector::detect_silence_pattern(&messages).is_none());
+
+        messages.push(("assistant".to_string(), "Done with step 19".to_string()));
+        let silence = LoopDetector::detect_silence_pattern(&messages).unwrap();
+        assert_eq!(silence.severity, LoopSeverity::Warning);
+        assert!(LoopDetector::new().analyze(&messages).iter().any(|d| d.message.starts_with("No user message")));
+        assert!(LoopDetector::builder().silence_turns(25).build().analyze(&messages).is_empty());
+        assert!(LoopDetector::builder().silence_turns(0).build().analyze(&messages).is_empty());
+
+        messages.push(("user".to_string(), "Looks good".to_string()));
+        assert!(LoopDetector::detect_silence_pattern(&messages).is_none());
+    }
 }
@@ -1,36 +1,421 @@
 // Plain-text JSONL export functionality
 // Writes sessions and messages to ~/Assistants/continuum-logs directory structure
 
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
 use color_eyre::{eyre::Context, Result};
 use serde_json::json;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs;
-use std::io::Write;
+use std::io::{BufRead, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::config::Config;
+use crate::layout::Layout;
+use crate::redaction::Redactor;
+use crate::search::SearchIndex;
+use crate::session_index;
+use crate::spool::ImportSpool;
+use crate::tokens::{TokenCounter, DEFAULT_ENCODING};
+use crate::{store, AssistantStats, SessionCost, SessionFilter, SessionRecord, SessionSort, SessionStats, StatsSummary};
+
+/// Environment variable that overrides the default log directory
+pub const LOG_DIR_ENV: &str = "CONTINUUM_LOG_DIR";
+
+/// Alternative spelling of [`LOG_DIR_ENV`]; `CONTINUUM_LOG_DIR` wins if both are set
+pub const LOGS_DIR_ENV: &str = "CONTINUUM_LOGS_DIR";
+
+/// Records `import_spool` appends per write, bounding what it holds in memory
+const IMPORT_BATCH: usize = 500;
+
+/// What an incremental import did to an existing messages.jsonl
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+    /// Stored messages already matched the source
+    Unchanged,
+    /// The source grew; this many new messages were appended
+    Appended(usize),
+    /// The file was rewritten from scratch with this many messages
+    Rewritten(usize),
+}
+
+/// Import refused because another stored session already has identical content
+///
+/// Returned (inside the eyre report) by [`PlainTextWriter::import_messages`];
+/// callers can `downcast_ref::<DuplicateSession>()` to tell it apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateSession {
+    pub existing_id: String,
+    pub assistant: String,
+}
+
+impl std::fmt::Display for DuplicateSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Same content is already stored as {} session '{}'",
+            self.assistant, self.existing_id
+        )
+    }
+}
+
+impl std::error::Error for DuplicateSession {}
+
+/// Temporary file next to `path` for an atomic replace
+/// Unique per process and call, so concurrent writers never share one
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("file");
+    path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Feed one message into a [`PlainTextWriter::content_hash`] fingerprint
+pub(crate) fn hash_message(hasher: &mut blake3::Hasher, role: &str, content: &str) {
+    // Length prefixes keep ("ab", "c") and ("a", "bc") apart
+    for field in [role, content] {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+}
+
+/// Write pretty-printed JSON to `path` via a temp file and rename
+///
+/// Readers see either the previous file or the new one in full, never a
+/// truncated document, even if the process is killed mid-write.
+pub(crate) fn write_json_atomic<T: serde::Serialize>(path: &Path, value: &T) -> Result<()> {
+    let tmp = temp_path(path);
+    let written = (|| -> Result<()> {
+        let mut file = fs::File::create(&tmp)?;
+        serde_json::to_writer_pretty(&mut file, value)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    })();
+
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Parse an RFC3339, SQLite-style ("2025-11-09 14:00:00", assumed UTC) or
+/// Unix-seconds timestamp
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    let timestamp = timestamp.trim();
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S%.f")
+                .ok()
+                .map(|t| t.and_utc())
+        })
+        .or_else(|| timestamp.parse::<i64>().ok().and_then(|secs| DateTime::from_timestamp(secs, 0)))
+}
+
+/// Time zone whose calendar decides the date directory a session is filed under
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateZone {
+    /// UTC, so a session keeps the date its normalized timestamp shows
+    #[default]
+    Utc,
+    /// The machine's local time zone
+    Local,
+    /// A fixed offset from UTC
+    Fixed(FixedOffset),
+}
+
+impl DateZone {
+    /// Civil date of an instant in this zone
+    pub fn date_of(&self, time: DateTime<Utc>) -> NaiveDate {
+        match self {
+            DateZone::Utc => time.date_naive(),
+            DateZone::Local => time.with_timezone(&Local).date_naive(),
+            DateZone::Fixed(offset) => time.with_timezone(offset).date_naive(),
+        }
+    }
+}
+
+impl std::str::FromStr for DateZone {
+    type Err = color_eyre::Report;
+
+    /// `utc`, `local`, or an offset such as `+05:30` or `-08:00`
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "utc" | "z" => Ok(DateZone::Utc),
+            "local" => Ok(DateZone::Local),
+            offset => offset
+                .parse::<FixedOffset>()
+                .map(DateZone::Fixed)
+                .map_err(|_| color_eyre::eyre::eyre!("Invalid time zone '{}': use utc, local or an offset like +05:30", value)),
+        }
+    }
+}
+
+/// Date directory of each of an assistant's sessions by ID, with where it was found stored
+type SessionDates = HashMap<String, (String, Option<PathBuf>)>;
 
 /// Plain-text session writer
+///
+/// With compression enabled, messages go to messages.jsonl.gz. Each append
+/// writes a complete gzip member onto the end of the file; concatenated members
+/// form a valid gzip stream, so appends stay cheap and crash-safe and readers
+/// (`store::load_messages`) decode the whole file with a multi-member decoder.
 pub struct PlainTextWriter {
     base_dir: PathBuf,
+    compress: bool,
+    zone: DateZone,
+    /// Set by `with_layout`, else read from the log directory on first use
+    layout: OnceLock<Layout>,
+    redactor: Option<Redactor>,
+    /// Session directories by content fingerprint, loaded by the first `find_session_by_hash`
+    content_hashes: Mutex<Option<HashMap<String, PathBuf>>>,
+    /// Dates `session_date` found (with the directory) or handed out, by assistant and session ID
+    session_dates: Mutex<HashMap<String, SessionDates>>,
 }
 
 impl PlainTextWriter {
     /// Create a new writer with default base directory
+    /// Uses $CONTINUUM_LOG_DIR (or $CONTINUUM_LOGS_DIR) when set, then `base_dir` from config.toml,
+    /// otherwise ~/Assistants/continuum-logs
     pub fn new() -> Result<Self> {
-        let home = std::env::var("HOME").context("HOME not set")?;
-        let base_dir = PathBuf::from(home).join("Assistants").join("continuum-logs");
-        Ok(PlainTextWriter { base_dir })
+        Ok(Self::from_config(&Config::load()?))
+    }
+
+    /// Create a writer for the log tree under `config.base_dir`
+    pub fn from_config(config: &Config) -> Self {
+        let writer = Self::with_base_dir(config.base_dir.clone())
+            .with_zone(config.timezone)
+            .with_redactor(config.redact.then(|| config.redactor.clone()));
+        match config.layout {
+            Some(ref layout) => writer.using_layout(layout.clone()),
+            None => writer,
+        }
+    }
+
+    /// Expand a leading `~` and resolve relative paths against the current directory
+    pub(crate) fn resolve_dir(value: &str) -> Result<PathBuf> {
+        let expanded = crate::config::expand_home(Path::new(value))?;
+
+        if expanded.is_absolute() {
+            Ok(expanded)
+        } else {
+            let cwd = std::env::current_dir().context("Failed to read current directory")?;
+            Ok(cwd.join(expanded))
+        }
     }
 
     /// Create a new writer with custom base directory
     pub fn with_base_dir(base_dir: PathBuf) -> Self {
-        PlainTextWriter { base_dir }
+        PlainTextWriter {
+            base_dir,
+            compress: false,
+            zone: DateZone::Utc,
+            layout: OnceLock::new(),
+            redactor: None,
+            content_hashes: Mutex::default(),
+            session_dates: Mutex::default(),
+        }
+    }
+
+    /// Lay sessions out by `template`, e.g. `{assistant}/{project}/{date}/{session}` (see [`Layout`])
+    /// The template is recorded in the log directory with the first session written.
+    pub fn with_layout(self, template: &str) -> Result<Self> {
+        Ok(self.using_layout(Layout::parse(template)?))
+    }
+
+    fn using_layout(mut self, layout: Layout) -> Self {
+        self.layout = OnceLock::from(layout);
+        self
+    }
+
+    /// Layout sessions are written in: the one given to `with_layout`, else the log directory's own
+    pub fn layout(&self) -> &Layout {
+        self.layout.get_or_init(|| Layout::load(&self.base_dir).unwrap_or_default())
+    }
+
+    /// File sessions under their start date in `zone` rather than in UTC
+    pub fn with_zone(mut self, zone: DateZone) -> Self {
+        self.zone = zone;
+        self
+    }
+
+    /// Date directory for a session starting at `timestamp` (see [`Self::extract_date_in`])
+    pub fn date_of(&self, timestamp: Option<&str>) -> String {
+        Self::extract_date_in(timestamp, self.zone)
+    }
+
+    /// Date directory for a session: the one it's already stored under, else its start date (see [`Self::date_of`])
+    /// A re-import under another timezone then updates the session in place rather than filing a second copy.
+    ///
+    /// An assistant's sessions are listed once and remembered, along with each
+    /// date handed out, so bulk imports don't walk the log tree per session.
+    pub fn session_date(&self, session_id: &str, assistant: &str, timestamp: Option<&str>) -> String {
+        let mut dates = self.session_dates.lock().unwrap_or_else(|e| e.into_inner());
+        let stored = dates.entry(assistant.to_string()).or_insert_with(|| {
+            store::list_assistant_sessions(&self.base_dir, assistant)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|location| (location.session_id, (location.date, Some(location.path))))
+                .collect()
+        });
+
+        let date = match stored.get(session_id) {
+            Some((date, None)) => return date.clone(),
+            Some((date, Some(path))) if path.is_dir() => return date.clone(),
+            // Moved since it was listed
+            Some(_) => match store::find_session(&self.base_dir, session_id, Some(assistant)) {
+                Ok(location) => location.date,
+                Err(_) => self.date_of(timestamp),
+            },
+            None => self.date_of(timestamp),
+        };
+        stored.insert(session_id.to_string(), (date.clone(), None));
+        date
+    }
+
+    /// Write new sessions' messages gzip-compressed (messages.jsonl.gz)
+    /// Sessions that already have a messages file keep their existing format.
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Mask secrets in every message written with `redactor` (see [`Redactor`])
+    /// `None` writes content as given, unless built with the `redact` feature.
+    pub fn with_redactor(mut self, redactor: Option<Redactor>) -> Self {
+        self.redactor = redactor;
+        self
+    }
+
+    /// Redactor messages go through before they are written, if any
+    pub fn redactor(&self) -> Option<&Redactor> {
+        self.redactor.as_ref().or_else(|| crate::redaction::default_redactor())
+    }
+
+    /// `content` as it is written, with secrets masked when redacting
+    pub(crate) fn redact<'a>(&self, content: &'a str) -> Cow<'a, str> {
+        match self.redactor() {
+            Some(redactor) => Cow::Owned(redactor.redact(content).0),
+            None => Cow::Borrowed(content),
+        }
+    }
+
+    /// Messages file to write for a session, and whether it is gzip-compressed
+    pub(crate) fn messages_target(&self, session_dir: &Path) -> (PathBuf, bool) {
+        let path = store::messages_file(session_dir).unwrap_or_else(|| {
+            let name = if self.compress { store::COMPRESSED_MESSAGES_FILE } else { store::MESSAGES_FILE };
+            session_dir.join(name)
+        });
+        let gzip = path.ends_with(store::COMPRESSED_MESSAGES_FILE);
+        (path, gzip)
+    }
+
+    /// Encode message records as JSONL, wrapped in a single gzip member if requested
+    pub(crate) fn encode_records(records: &[serde_json::Value], gzip: bool) -> Result<Vec<u8>> {
+        let mut lines = Vec::new();
+        for record in records {
+            serde_json::to_writer(&mut lines, record)?;
+            lines.push(b'\n');
+        }
+
+        if !gzip {
+            return Ok(lines);
+        }
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&lines)?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Append already-built records to a session's messages file in one write
+    pub(crate) fn append_records(&self, session_dir: &Path, records: &[serde_json::Value]) -> Result<()> {
+        let (messages_path, gzip) = self.messages_target(session_dir);
+
+        // Create directory if it doesn't exist
+        self.create_session_dir(session_dir)?;
+
+        // Open file in append mode
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&messages_path)
+            .with_context(|| format!("Failed to open {}", messages_path.display()))?;
+
+        file.write_all(&Self::encode_records(records, gzip)?)?;
+        Ok(())
     }
 
     /// Get the directory path for a session
-    fn session_dir(&self, assistant: &str, date: &str, session_id: &str) -> PathBuf {
-        self.base_dir.join(assistant).join(date).join(session_id)
+    pub(crate) fn session_dir(&self, assistant: &str, date: &str, session_id: &str) -> PathBuf {
+        self.layout().session_dir(&self.base_dir, assistant, date, session_id)
+    }
+
+    /// Add a session.json just written to its assistant's index (see [`session_index::record`])
+    /// The index is only a cache, so a failure is a warning: listing falls back to a scan while it is out of date.
+    fn record_in_index(&self, assistant: &str, date: &str, metadata: &serde_json::Value) {
+        if let Err(e) = session_index::record(&self.base_dir, assistant, date, metadata) {
+            eprintln!("⚠ Warning: Failed to update session index: {:#}", e);
+        }
+    }
+
+    /// Create a session directory, recording the layout first if this is the first session
+    pub(crate) fn create_session_dir(&self, session_dir: &Path) -> Result<()> {
+        self.layout().record(&self.base_dir)?;
+        fs::create_dir_all(session_dir).with_context(|| format!("Failed to create directory: {}", session_dir.display()))
+    }
+
+    /// session.json fields a layout needs that the path doesn't give (see [`Layout::sessions`])
+    pub(crate) fn layout_metadata(&self, metadata: &mut serde_json::Value, date: &str) {
+        if !self.layout().is_default() {
+            metadata["date"] = json!(date);
+        }
+    }
+
+    /// Move a session under `project` when the layout files sessions by project
+    pub(crate) fn file_under_project(&self, session_dir: &Path, assistant: &str, date: &str, session_id: &str, project: Option<&str>) -> Result<()> {
+        let target = self.layout().expand(&self.base_dir, assistant, date, session_id, project);
+        if target == session_dir || target.exists() || !session_dir.is_dir() {
+            return Ok(());
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        fs::rename(session_dir, &target)
+            .with_context(|| format!("Failed to move {} to {}", session_dir.display(), target.display()))?;
+        if let Some(parent) = session_dir.parent() {
+            crate::layout::remove_empty_dirs(&self.base_dir, parent);
+        }
+        Ok(())
+    }
+
+    /// Civil date of a timestamp in `zone`, as `YYYY-MM-DD`
+    ///
+    /// Accepts what [`parse_timestamp`] does; anything else falls back to
+    /// [`Self::extract_date`]. Without a timestamp, today's date in `zone`.
+    pub fn extract_date_in(timestamp: Option<&str>, zone: DateZone) -> String {
+        let time = match timestamp {
+            Some(ts) => parse_timestamp(ts),
+            None => Some(Utc::now()),
+        };
+        match time {
+            Some(time) => zone.date_of(time).format("%Y-%m-%d").to_string(),
+            None => Self::extract_date(timestamp),
+        }
     }
 
     /// Extract date from timestamp (handles both ISO8601 and SQLite formats)
+    /// Takes the date as written, whatever offset follows it; see [`Self::extract_date_in`].
     pub fn extract_date(timestamp: Option<&str>) -> String {
         if let Some(ts) = timestamp {
             // Handle ISO8601 format (YYYY-MM-DDTHH:MM:SS...)
@@ -55,7 +440,14 @@ impl PlainTextWriter {
         }
     }
 
+    /// Rewrite a source timestamp as RFC3339 UTC (`2025-11-09T14:00:00Z`)
+    /// Accepts what [`parse_timestamp`] does; anything else gives `None`
+    pub fn normalize_timestamp(timestamp: &str) -> Option<String> {
+        parse_timestamp(timestamp).map(|t| t.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+
     /// Write session metadata
+    #[allow(clippy::too_many_arguments)]
     pub fn write_session(
         &self,
         session_id: &str,
@@ -65,40 +457,44 @@ impl PlainTextWriter {
         status: &str,
         message_count: usize,
         skills: &[String],
+        cost: Option<&SessionCost>,
+        cwd: Option<&str>,
     ) -> Result<PathBuf> {
-        let date = Self::extract_date(start_time);
+        let date = self.session_date(session_id, assistant, start_time);
         let session_dir = self.session_dir(assistant, &date, session_id);
 
         // Create directory
-        fs::create_dir_all(&session_dir)
-            .with_context(|| format!("Failed to create directory: {}", session_dir.display()))?;
+        self.create_session_dir(&session_dir)?;
 
         // Write session.json
         let session_json_path = session_dir.join("session.json");
-        let created_at = chrono::Utc::now().to_rfc3339();
-
-        let mut metadata = json!({
-            "id": session_id,
-            "assistant": assistant,
-            "start_time": start_time,
-            "end_time": end_time,
-            "status": status,
-            "message_count": message_count,
-            "created_at": created_at,
-        });
+        let mut metadata = Self::session_metadata(
+            session_id,
+            assistant,
+            start_time,
+            end_time,
+            status,
+            message_count,
+            skills,
+            cost,
+            cwd,
+        );
 
-        if !skills.is_empty() {
-            metadata["skills"] = json!(skills);
+        // Re-importing a session must not drop its tags or content fingerprint
+        if let Ok(existing) = fs::read_to_string(&session_json_path) {
+            Self::carry_over(&mut metadata, &existing);
         }
+        self.layout_metadata(&mut metadata, &date);
 
-        let mut file = fs::File::create(&session_json_path)
-            .with_context(|| format!("Failed to create {}", session_json_path.display()))?;
-        serde_json::to_writer_pretty(&mut file, &metadata)?;
+        write_json_atomic(&session_json_path, &metadata)?;
+        self.record_in_index(assistant, &date, &metadata);
 
         Ok(session_dir)
     }
 
     /// Append a message to the messages.jsonl file
+    /// `model` is recorded on the message, for sessions that switch models part-way.
+    #[allow(clippy::too_many_arguments)]
     pub fn append_message(
         &self,
         session_id: &str,
@@ -108,33 +504,195 @@ impl PlainTextWriter {
         role: &str,
         content: &str,
         timestamp: Option<&str>,
+        tokens: Option<usize>,
+        model: Option<&str>,
     ) -> Result<()> {
         let session_dir = self.session_dir(assistant, date, session_id);
-        let messages_path = session_dir.join("messages.jsonl");
-
-        // Create directory if it doesn't exist
-        fs::create_dir_all(&session_dir)
-            .with_context(|| format!("Failed to create directory: {}", session_dir.display()))?;
 
-        // Open file in append mode
-        let mut file = fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&messages_path)
-            .with_context(|| format!("Failed to open {}", messages_path.display()))?;
+        // Mask secrets before they reach disk
+        let content = self.redact(content);
 
         // Write message as JSONL
-        let message = json!({
-            "id": message_id,
-            "role": role,
-            "content": content,
-            "timestamp": timestamp,
-        });
+        let message = Self::message_record(message_id, role, &content, timestamp, tokens, model);
+        self.append_records(&session_dir, &[message])
+    }
+
+    /// Append a tool call or result as a `tool` message that names its tool
+    #[allow(clippy::too_many_arguments)]
+    pub fn append_tool_message(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        message_id: usize,
+        tool_name: &str,
+        content: &str,
+        timestamp: Option<&str>,
+        tokens: Option<usize>,
+    ) -> Result<()> {
+        let session_dir = self.session_dir(assistant, date, session_id);
+        let message = Self::tool_record(message_id, tool_name, &self.redact(content), timestamp, tokens);
+        self.append_records(&session_dir, &[message])
+    }
 
-        serde_json::to_writer(&mut file, &message)?;
-        writeln!(file)?;
+    /// Write a session's messages, only appending what isn't stored yet
+    ///
+    /// If the stored messages are a prefix of `messages`, just the new tail is
+    /// appended. When the source is shorter or has diverged (or `force` is set),
+    /// messages.jsonl is truncated and rewritten. IDs are 1-based positions.
+    /// Each message is stamped with its entry in `timestamps`, or with
+    /// `timestamp` where it has none (`timestamps` may be empty).
+    ///
+    /// The content fingerprint is recorded in session.json. Unless `force` is
+    /// set, a [`DuplicateSession`] error is returned (and nothing written) if a
+    /// different stored session already has the same fingerprint, so call this
+    /// before `write_session` when importing a new session.
+    #[allow(clippy::too_many_arguments)]
+    pub fn import_messages(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        messages: &[(String, String)],
+        timestamps: &[Option<String>],
+        timestamp: Option<&str>,
+        force: bool,
+    ) -> Result<ImportOutcome> {
+        let spool = ImportSpool::from_messages(messages, timestamps)?;
+        self.import_spool(session_id, assistant, date, &spool, timestamp, force)
+    }
 
-        Ok(())
+    /// [`Self::import_messages`] for messages streamed into an [`ImportSpool`]
+    ///
+    /// The stored session is compared with and written from the spool a record
+    /// at a time, so memory use does not grow with the session.
+    #[allow(clippy::too_many_arguments)]
+    pub fn import_spool(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        spool: &ImportSpool,
+        timestamp: Option<&str>,
+        force: bool,
+    ) -> Result<ImportOutcome> {
+        let session_dir = self.session_dir(assistant, date, session_id);
+
+        // Secrets are masked before comparing, so re-imports still append onto the stored session
+        let redacted;
+        let spool = match self.redactor() {
+            Some(redactor) => {
+                redacted = spool.redacted(redactor)?;
+                &redacted
+            }
+            None => spool,
+        };
+
+        // Every empty session would share a fingerprint, so those aren't tracked
+        if spool.is_empty() {
+            return self.write_spooled(&session_dir, spool, timestamp, force);
+        }
+
+        let content_hash = spool.content_hash();
+        if !force {
+            if let Some(duplicate) = self.duplicate_of(session_id, assistant, &content_hash)? {
+                return Err(duplicate.into());
+            }
+        }
+
+        let outcome = self.write_spooled(&session_dir, spool, timestamp, force)?;
+        self.update_session_metadata(session_id, assistant, date, json!({ "content_hash": content_hash }))?;
+        if let Some(ref mut hashes) = *self.content_hashes.lock().unwrap_or_else(|e| e.into_inner()) {
+            hashes.insert(content_hash, session_dir);
+        }
+        Ok(outcome)
+    }
+
+    /// Another stored session with this content fingerprint, if there is one
+    pub(crate) fn duplicate_of(&self, session_id: &str, assistant: &str, content_hash: &str) -> Result<Option<DuplicateSession>> {
+        Ok(self
+            .find_session_by_hash(content_hash)?
+            .filter(|existing| existing.id != session_id || existing.assistant != assistant)
+            .map(|existing| DuplicateSession {
+                existing_id: existing.id,
+                assistant: existing.assistant,
+            }))
+    }
+
+    /// How many stored messages an import can keep and append after
+    /// `None` when messages.jsonl has to be rewritten: nothing is stored yet,
+    /// the stored messages aren't a prefix of the spooled ones, or `force` is set.
+    pub(crate) fn appendable_prefix(&self, session_dir: &Path, spool: &ImportSpool, force: bool) -> Result<Option<usize>> {
+        if force {
+            return Ok(None);
+        }
+        let Some(messages_path) = store::messages_file(session_dir) else {
+            return Ok(None);
+        };
+
+        let mut spooled = spool.messages()?;
+        let mut stored = 0;
+        for line in store::open_messages(&messages_path)?.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let old: store::StoredMessage = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid message in {}", messages_path.display()))?;
+            match spooled.next().transpose()? {
+                Some((role, content)) if old.role == role && old.content == content => stored += 1,
+                _ => return Ok(None),
+            }
+        }
+
+        Ok((stored > 0).then_some(stored))
+    }
+
+    /// The appending/rewriting half of [`Self::import_spool`]
+    /// Records without a timestamp of their own are stamped with `timestamp`.
+    fn write_spooled(
+        &self,
+        session_dir: &Path,
+        spool: &ImportSpool,
+        timestamp: Option<&str>,
+        force: bool,
+    ) -> Result<ImportOutcome> {
+        let stored = self.appendable_prefix(session_dir, spool, force)?;
+        if stored == Some(spool.len()) {
+            return Ok(ImportOutcome::Unchanged);
+        }
+
+        // Rewriting: drop whichever format was there and write in the configured one
+        if stored.is_none() {
+            for name in [store::MESSAGES_FILE, store::COMPRESSED_MESSAGES_FILE] {
+                let path = session_dir.join(name);
+                if path.exists() {
+                    fs::remove_file(&path)
+                        .with_context(|| format!("Failed to remove {}", path.display()))?;
+                }
+            }
+        }
+
+        let mut batch = Vec::with_capacity(IMPORT_BATCH);
+        for record in spool.records()?.skip(stored.unwrap_or(0)) {
+            let mut record = record?;
+            if record["timestamp"].is_null() {
+                record["timestamp"] = json!(timestamp);
+            }
+            batch.push(record);
+            if batch.len() == IMPORT_BATCH {
+                self.append_records(session_dir, &batch)?;
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() || spool.is_empty() {
+            self.append_records(session_dir, &batch)?;
+        }
+
+        Ok(match stored {
+            Some(stored) => ImportOutcome::Appended(spool.len() - stored),
+            None => ImportOutcome::Rewritten(spool.len()),
+        })
     }
 
     /// Update session metadata (useful for updating message count, end time, etc.)
@@ -157,67 +715,797 @@ impl PlainTextWriter {
         };
 
         // Merge updates
-        let mut merged = existing.as_object().unwrap().clone();
-        if let Some(updates_obj) = updates.as_object() {
-            for (key, value) in updates_obj {
-                merged.insert(key.clone(), value.clone());
-            }
-        }
+        let merged = Self::merge_metadata(existing, &updates);
 
         // Write back
-        let mut file = fs::File::create(&session_json_path)?;
-        serde_json::to_writer_pretty(&mut file, &merged)?;
+        write_json_atomic(&session_json_path, &merged)?;
+        self.record_in_index(assistant, date, &merged);
+
+        if self.layout().has_project() {
+            self.file_under_project(&session_dir, assistant, date, session_id, merged["project"].as_str())?;
+        }
 
         Ok(())
     }
 
-    /// Get the base directory
-    pub fn base_dir(&self) -> &Path {
-        &self.base_dir
+    /// Rebuild the search index from every stored messages.jsonl; returns the number of messages indexed
+    ///
+    /// The new index is built in a temporary file and renamed over the old one,
+    /// so a crash part-way through leaves the previous index in place.
+    pub fn reindex_all(&self, index: &mut SearchIndex) -> Result<usize> {
+        self.reindex(index, None, |_, _, _| {})
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+    /// [`Self::reindex_all`] for one assistant's sessions, or all of them,
+    /// calling `progress(done, total, session)` after each session
+    ///
+    /// Other assistants' entries are carried over from the current index.
+    pub fn reindex(
+        &self,
+        index: &mut SearchIndex,
+        assistant: Option<&str>,
+        progress: impl FnMut(usize, usize, &store::SessionLocation),
+    ) -> Result<usize> {
+        let sessions = match assistant {
+            Some(assistant) => store::list_assistant_sessions(&self.base_dir, assistant)?,
+            None => store::list_sessions(&self.base_dir)?,
+        };
 
-    #[test]
-    fn test_extract_date_iso8601() {
-        assert_eq!(
-            PlainTextWriter::extract_date(Some("2025-11-09T14:30:00Z")),
-            "2025-11-09"
-        );
+        let index_path = index.path();
+        let tmp = temp_path(&index_path);
+        let built = SearchIndex::open_file(&tmp, &self.base_dir).and_then(|fresh| {
+            let indexed = fresh.fill(&sessions, assistant.map(|a| (index_path.as_path(), a)), progress)?;
+            drop(fresh);
+            fs::rename(&tmp, &index_path).with_context(|| format!("Failed to replace search index: {}", index_path.display()))?;
+            Ok(indexed)
+        });
+        if built.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+        let indexed = built?;
+
+        *index = SearchIndex::open_file(&index_path, &self.base_dir)?;
+        Ok(indexed)
     }
 
-    #[test]
-    fn test_extract_date_sqlite() {
-        assert_eq!(
-            PlainTextWriter::extract_date(Some("2025-11-09 14:30:00")),
-            "2025-11-09"
-        );
+    /// Index a session's messages for search, with secrets masked as they were when written
+    pub fn index_session(&self, session_id: &str, assistant: &str, date: &str, messages: &[(String, String)]) -> Result<()> {
+        let messages = messages.iter().map(|(role, content)| Ok((role.clone(), self.redact(content).into_owned())));
+        SearchIndex::open_at(&self.base_dir)?.index_stream(session_id, assistant, date, messages)
     }
 
-    #[test]
-    fn test_write_session() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+    /// [`Self::index_session`] for the messages of an [`ImportSpool`]
+    pub fn index_spool(&self, session_id: &str, assistant: &str, date: &str, spool: &ImportSpool) -> Result<()> {
+        let messages = spool.messages()?.map(|message| {
+            let (role, content) = message?;
+            Ok((role, self.redact(&content).into_owned()))
+        });
+        SearchIndex::open_at(&self.base_dir)?.index_stream(session_id, assistant, date, messages)
+    }
 
-        let session_dir = writer.write_session(
-            "test-session-001",
-            "test-assistant",
-            Some("2025-11-09T14:00:00Z"),
-            None,
-            "active",
-            0,
-            &[],
-        )?;
+    /// Get the base directory
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
 
-        assert!(session_dir.join("session.json").exists());
+    /// Tags attached to a stored session
+    pub fn session_tags(&self, session_id: &str, assistant: &str, date: &str) -> Result<Vec<String>> {
+        let session_json_path = self.session_dir(assistant, date, session_id).join("session.json");
+        let content = fs::read_to_string(&session_json_path)
+            .with_context(|| format!("Failed to read {}", session_json_path.display()))?;
+        let metadata: serde_json::Value = serde_json::from_str(&content)?;
+        Ok(Self::tags_of(&metadata))
+    }
+
+    /// Attach a tag to a stored session (no-op if already present)
+    pub fn add_tag(&self, session_id: &str, assistant: &str, date: &str, tag: &str) -> Result<()> {
+        let tag = Self::normalize_tag(tag)?;
+        let mut tags = self.session_tags(session_id, assistant, date)?;
+        if !tags.contains(&tag) {
+            tags.push(tag);
+            self.update_session_metadata(session_id, assistant, date, json!({ "tags": tags }))?;
+        }
         Ok(())
     }
 
-    #[test]
+    /// Detach a tag from a stored session (no-op if not present)
+    pub fn remove_tag(&self, session_id: &str, assistant: &str, date: &str, tag: &str) -> Result<()> {
+        let tag = Self::normalize_tag(tag)?;
+        let mut tags = self.session_tags(session_id, assistant, date)?;
+        if tags.contains(&tag) {
+            tags.retain(|t| *t != tag);
+            self.update_session_metadata(session_id, assistant, date, json!({ "tags": tags }))?;
+        }
+        Ok(())
+    }
+
+    /// A stored session's note, if it has one
+    pub fn session_note(&self, session_id: &str, assistant: &str, date: &str) -> Result<Option<String>> {
+        let record = SessionRecord::from_dir(&self.session_dir(assistant, date, session_id))?;
+        Ok(record.note)
+    }
+
+    /// Write (or replace) a stored session's one-line note
+    /// Line breaks are folded into spaces; a blank note is an error (see [`Self::clear_note`]).
+    pub fn set_note(&self, session_id: &str, assistant: &str, date: &str, note: &str) -> Result<()> {
+        let note = note.split_whitespace().collect::<Vec<_>>().join(" ");
+        if note.is_empty() {
+            return Err(color_eyre::eyre::eyre!("Note must not be empty"));
+        }
+        // Fail on a missing session rather than starting a bare session.json
+        self.session_note(session_id, assistant, date)?;
+        self.update_session_metadata(session_id, assistant, date, json!({ "note": note }))
+    }
+
+    /// Remove a stored session's note (no-op if it has none)
+    pub fn clear_note(&self, session_id: &str, assistant: &str, date: &str) -> Result<()> {
+        let session_json_path = self.session_dir(assistant, date, session_id).join("session.json");
+        let content = fs::read_to_string(&session_json_path)
+            .with_context(|| format!("Failed to read {}", session_json_path.display()))?;
+        let mut metadata: serde_json::Value = serde_json::from_str(&content)?;
+        if metadata.as_object_mut().and_then(|object| object.remove("note")).is_some() {
+            write_json_atomic(&session_json_path, &metadata)?;
+            self.record_in_index(assistant, date, &metadata);
+        }
+        Ok(())
+    }
+
+    /// Walk the log tree and collect every session carrying `tag`
+    pub fn find_sessions_with_tag(&self, tag: &str) -> Result<Vec<SessionRecord>> {
+        let tag = Self::normalize_tag(tag)?;
+        let mut records = Vec::new();
+
+        for location in store::list_sessions(&self.base_dir)? {
+            if !location.path.join("session.json").exists() {
+                continue;
+            }
+            let record = SessionRecord::from_dir(&location.path)?;
+            if record.tags.contains(&tag) {
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Every stored session whose recorded working directory is `cwd`, newest first
+    /// A trailing `/` on either path is ignored.
+    pub fn find_sessions_by_cwd(&self, cwd: &str) -> Result<Vec<SessionRecord>> {
+        self.list_sessions(&SessionFilter { cwd: Some(cwd.to_string()), ..Default::default() })
+    }
+
+    /// Fingerprint of a session's messages: BLAKE3 over every role and content, hex-encoded
+    pub fn content_hash(messages: &[(String, String)]) -> String {
+        let mut hasher = blake3::Hasher::new();
+        for (role, content) in messages {
+            hash_message(&mut hasher, role, content);
+        }
+        hasher.finalize().to_hex().to_string()
+    }
+
+    /// Find the stored session whose recorded content fingerprint is `hash`
+    /// Sessions with a missing or unreadable session.json are skipped.
+    ///
+    /// Every fingerprint is read once (see [`Self::list_sessions`]) and kept, so
+    /// bulk imports don't list the log tree per session. A match is re-checked
+    /// against its session.json, and the fingerprints re-read if it moved on.
+    pub fn find_session_by_hash(&self, hash: &str) -> Result<Option<SessionRecord>> {
+        let mut hashes = self.content_hashes.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(found) = hashes.as_ref().and_then(|hashes| hashes.get(hash)) {
+            match SessionRecord::from_dir(found) {
+                Ok(record) if record.content_hash.as_deref() == Some(hash) => return Ok(Some(record)),
+                _ => *hashes = None,
+            }
+        }
+        let hashes = match *hashes {
+            Some(ref hashes) => hashes,
+            None => hashes.insert(
+                // Newest first, so the newest of several matches is the one kept
+                self.list_sessions(&SessionFilter::default())?
+                    .into_iter()
+                    .rev()
+                    .filter_map(|record| Some((record.content_hash.clone()?, record.session_dir(&self.base_dir))))
+                    .collect(),
+            ),
+        };
+        Ok(hashes.get(hash).and_then(|dir| SessionRecord::from_dir(dir).ok()))
+    }
+
+    /// Groups of stored sessions with identical messages, oldest session first in each
+    ///
+    /// Sessions of the same assistant are compared by their recorded `content_hash`, or by a hash of
+    /// their messages.jsonl when they have none (e.g. written by a wrapper).
+    /// Sessions without messages are left out.
+    pub fn duplicate_sessions(&self, assistant: Option<&str>) -> Result<Vec<Vec<SessionRecord>>> {
+        let filter = SessionFilter { assistant: assistant.map(str::to_string), ..Default::default() };
+        let mut by_hash: HashMap<(String, String), Vec<SessionRecord>> = HashMap::new();
+
+        for record in self.list_sessions(&filter)? {
+            let hash = match record.content_hash {
+                Some(ref hash) => hash.clone(),
+                None => {
+                    let session_dir = self.session_dir(&record.assistant, record.date.as_deref().unwrap_or_default(), &record.id);
+                    if store::messages_file(&session_dir).is_none() {
+                        continue;
+                    }
+                    let messages: Vec<(String, String)> =
+                        store::load_messages(&session_dir)?.into_iter().map(|m| (m.role, m.content)).collect();
+                    if messages.is_empty() {
+                        continue;
+                    }
+                    Self::content_hash(&messages)
+                }
+            };
+            by_hash.entry((record.assistant.clone(), hash)).or_default().push(record);
+        }
+
+        let mut groups: Vec<Vec<SessionRecord>> = by_hash.into_values().filter(|group| group.len() > 1).collect();
+        for group in &mut groups {
+            group.sort_by(|a, b| (&a.date, &a.start_time, &a.id).cmp(&(&b.date, &b.start_time, &b.id)));
+        }
+        groups.sort_by(|a, b| (&a[0].date, &a[0].id).cmp(&(&b[0].date, &b[0].id)));
+        Ok(groups)
+    }
+
+    /// Stored sessions matching `filter`, read from their session.json
+    ///
+    /// Each record's `date` is set to its date directory, and the filter's
+    /// criteria are applied in order, stopping at the first that fails (see
+    /// [`SessionFilter::matches`]). Sessions without a readable session.json
+    /// are skipped. An assistant's index.jsonl is used instead of the
+    /// session.json files while it is up to date (see [`session_index::fresh_records`]).
+    pub fn query(&self, filter: &SessionFilter) -> Result<Vec<SessionRecord>> {
+        let mut records = Vec::new();
+        for assistant in store::list_assistants(&self.base_dir)? {
+            if filter.assistant.as_deref().is_some_and(|a| !a.eq_ignore_ascii_case(&assistant)) {
+                continue;
+            }
+
+            records.extend(
+                self.assistant_records(&assistant, filter)
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .filter(|record| filter.matches(record)),
+            );
+        }
+
+        // store::list_sessions yields (assistant, date, id) order; re-sort newest first
+        records.sort_by(|a, b| (&b.date, &a.assistant, &a.id).cmp(&(&a.date, &b.assistant, &b.id)));
+        if filter.sort == SessionSort::Messages {
+            // Stable, so equal counts stay newest first
+            records.sort_by_key(|record| std::cmp::Reverse(record.message_count.unwrap_or(0)));
+        }
+
+        Ok(records
+            .into_iter()
+            .skip(filter.offset)
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    /// An assistant's sessions in `filter`'s date range, by date then ID, from its index while that is up to date
+    /// Sessions whose session.json can't be read are `Err`.
+    fn assistant_records(&self, assistant: &str, filter: &SessionFilter) -> Vec<Result<SessionRecord>> {
+        match session_index::fresh_records(&self.base_dir, assistant) {
+            Some(mut records) => {
+                records.sort_by(|a, b| (&a.date, &a.id).cmp(&(&b.date, &b.id)));
+                records.into_iter().map(Ok).collect()
+            }
+            None => store::list_assistant_sessions(&self.base_dir, assistant)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|location| filter.in_range(&location.date))
+                .map(|location| SessionRecord::from_dir(&location.path))
+                .collect(),
+        }
+    }
+
+    /// List stored sessions matching `filter` (see [`Self::query`])
+    pub fn list_sessions(&self, filter: &SessionFilter) -> Result<Vec<SessionRecord>> {
+        self.query(filter)
+    }
+
+    /// Compute statistics for one stored session
+    /// Falls back to session.json start/end times when messages carry no timestamps.
+    /// Errors if session.json is missing or unreadable.
+    pub fn session_stats(&self, assistant: &str, session_id: &str, date: &str) -> Result<SessionStats> {
+        self.record_stats(&SessionRecord::from_dir(&self.session_dir(assistant, date, session_id))?)
+    }
+
+    /// [`Self::session_stats`] for a session whose metadata is already loaded, e.g. from [`Self::query`]
+    /// Only the session's messages are read.
+    pub fn record_stats(&self, record: &SessionRecord) -> Result<SessionStats> {
+        let date = record.date.clone().unwrap_or_else(|| Self::extract_date(record.start_time.as_deref()));
+        let session_dir = self.session_dir(&record.assistant, &date, &record.id);
+        let messages = if store::messages_file(&session_dir).is_some() {
+            store::load_messages(&session_dir)?
+        } else {
+            Vec::new()
+        };
+
+        let timestamps = messages.iter().filter_map(|m| m.timestamp.as_deref());
+
+        Ok(SessionStats {
+            session_id: record.id.clone(),
+            assistant: record.assistant.clone(),
+            date,
+            status: serde_json::to_value(record.status)?.as_str().map(str::to_string),
+            message_count: messages.len(),
+            total_chars: messages.iter().map(|m| m.content.chars().count()).sum(),
+            approx_tokens: messages.iter().map(|m| m.content.len().div_ceil(4)).sum(),
+            tokens: messages.iter().filter_map(|m| m.tokens).sum(),
+            first_timestamp: timestamps.clone().min().map(str::to_string).or_else(|| record.start_time.clone()),
+            last_timestamp: timestamps.max().map(str::to_string).or_else(|| record.end_time.clone()),
+            total_cost_usd: record.cost_usd,
+            duration_ms: record.duration_ms,
+            models: messages.iter().filter_map(|m| m.model.clone()).fold(Default::default(), |mut models, model| {
+                *models.entry(model).or_default() += 1;
+                models
+            }),
+        })
+    }
+
+    /// Statistics for each stored session `filter` selects, in (assistant, date, ID) order
+    ///
+    /// Session metadata comes from each assistant's index while it is up to
+    /// date (see [`session_index::fresh_records`]), so only messages are read
+    /// per session. Sessions whose statistics couldn't be computed are `Err`
+    /// with their assistant. Sort, offset and limit are ignored.
+    pub fn stats_matching(&self, filter: &SessionFilter) -> Result<Vec<std::result::Result<SessionStats, String>>> {
+        let mut stats = Vec::new();
+        for assistant in store::list_assistants(&self.base_dir)? {
+            if filter.assistant.as_deref().is_some_and(|a| !a.eq_ignore_ascii_case(&assistant)) {
+                continue;
+            }
+
+            for record in self.assistant_records(&assistant, filter) {
+                match record {
+                    Ok(record) if !filter.matches(&record) => {}
+                    Ok(record) => stats.push(self.record_stats(&record).map_err(|_| assistant.clone())),
+                    Err(_) => stats.push(Err(assistant.clone())),
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Aggregate statistics across every stored session for an assistant
+    /// Sessions with a missing or corrupt session.json are counted in `skipped_sessions`
+    pub fn assistant_stats(&self, assistant: &str) -> Result<AssistantStats> {
+        Ok(self
+            .summary()?
+            .assistants
+            .remove(assistant)
+            .unwrap_or_else(|| AssistantStats {
+                assistant: assistant.to_string(),
+                ..Default::default()
+            }))
+    }
+
+    /// Sum the costs recorded across every session for an assistant
+    pub fn total_cost(&self, assistant: &str) -> Result<f64> {
+        Ok(self.assistant_stats(assistant)?.total_cost_usd)
+    }
+
+    /// Count an assistant's stored messages per model
+    /// Messages logged without a model (older logs, most importers) aren't counted.
+    pub fn model_usage_stats(&self, assistant: &str) -> Result<HashMap<String, usize>> {
+        self.model_usage_matching(&SessionFilter::new().assistant(assistant))
+    }
+
+    /// Like [`model_usage_stats`](Self::model_usage_stats), counting only the sessions `filter` selects
+    /// Sort, offset and limit are ignored.
+    pub fn model_usage_matching(&self, filter: &SessionFilter) -> Result<HashMap<String, usize>> {
+        let mut usage = HashMap::new();
+
+        for location in store::list_sessions(&self.base_dir)? {
+            if !filter.matches_location(&location).unwrap_or(false) || store::messages_file(&location.path).is_none() {
+                continue;
+            }
+
+            for model in store::load_messages(&location.path)?.into_iter().filter_map(|m| m.model) {
+                *usage.entry(model).or_default() += 1;
+            }
+        }
+
+        Ok(usage)
+    }
+
+    /// Walk the whole log tree and summarise it per assistant and per day
+    pub fn summary(&self) -> Result<StatsSummary> {
+        self.summary_since(None)
+    }
+
+    /// Like [`summary`](Self::summary), counting only sessions dated on or after `since`
+    pub fn summary_since(&self, since: Option<NaiveDate>) -> Result<StatsSummary> {
+        self.summary_matching(&SessionFilter { since, ..Default::default() })
+    }
+
+    /// Like [`summary`](Self::summary), counting only the sessions `filter` selects
+    /// Sort, offset and limit are ignored.
+    pub fn summary_matching(&self, filter: &SessionFilter) -> Result<StatsSummary> {
+        let mut summary = StatsSummary::default();
+
+        for stats in self.stats_matching(filter)? {
+            let name = match stats {
+                Ok(ref stats) => &stats.assistant,
+                Err(ref assistant) => assistant,
+            };
+            let assistant = summary.assistants.entry(name.clone()).or_insert_with(|| AssistantStats {
+                assistant: name.clone(),
+                ..Default::default()
+            });
+
+            let Ok(stats) = stats else {
+                assistant.skipped_sessions += 1;
+                summary.skipped_sessions += 1;
+                continue;
+            };
+
+            assistant.add(&stats);
+            let day = summary.days.entry(stats.date.clone()).or_default();
+            day.sessions += 1;
+            day.messages += stats.message_count;
+            day.total_cost_usd += stats.total_cost_usd.unwrap_or(0.0);
+
+            if let Some(cost) = stats.total_cost_usd {
+                let month = stats.date.get(..7).unwrap_or(&stats.date).to_string();
+                *summary
+                    .monthly_costs
+                    .entry(month)
+                    .or_default()
+                    .entry(stats.assistant.clone())
+                    .or_default() += cost;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Build the session.json document
+    /// Cost fields are omitted entirely when the assistant doesn't report them
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn session_metadata(
+        session_id: &str,
+        assistant: &str,
+        start_time: Option<&str>,
+        end_time: Option<&str>,
+        status: &str,
+        message_count: usize,
+        skills: &[String],
+        cost: Option<&SessionCost>,
+        cwd: Option<&str>,
+    ) -> serde_json::Value {
+        let created_at = chrono::Utc::now().to_rfc3339();
+
+        let mut metadata = json!({
+            "id": session_id,
+            "assistant": assistant,
+            "start_time": start_time,
+            "end_time": end_time,
+            "status": status,
+            "message_count": message_count,
+            "created_at": created_at,
+        });
+
+        if !skills.is_empty() {
+            metadata["skills"] = json!(skills);
+        }
+
+        if let Some(cost) = cost {
+            metadata = Self::merge_metadata(metadata, &cost.to_metadata());
+        }
+
+        if let Some(cwd) = cwd {
+            metadata["cwd"] = json!(cwd);
+        }
+
+        metadata
+    }
+
+    /// Token count recorded with imported messages (see [`TokenCounter`])
+    pub fn count_tokens(content: &str) -> usize {
+        TokenCounter::count(content, DEFAULT_ENCODING)
+    }
+
+    /// Build one messages.jsonl record
+    /// `tokens` and `model` are left out of the record when unknown
+    pub(crate) fn message_record(
+        message_id: usize,
+        role: &str,
+        content: &str,
+        timestamp: Option<&str>,
+        tokens: Option<usize>,
+        model: Option<&str>,
+    ) -> serde_json::Value {
+        let mut record = json!({
+            "id": message_id,
+            "role": role,
+            "content": content,
+            "timestamp": timestamp,
+        });
+        if let Some(tokens) = tokens {
+            record["tokens"] = json!(tokens);
+        }
+        if let Some(model) = model {
+            record["model"] = json!(model);
+        }
+        record
+    }
+
+    /// Build one messages.jsonl record for a `tool` message, with a `tool_name` field
+    pub(crate) fn tool_record(
+        message_id: usize,
+        tool_name: &str,
+        content: &str,
+        timestamp: Option<&str>,
+        tokens: Option<usize>,
+    ) -> serde_json::Value {
+        let mut record = Self::message_record(message_id, crate::tool_capture::TOOL_ROLE, content, timestamp, tokens, None);
+        record["tool_name"] = json!(tool_name);
+        record
+    }
+
+    /// Copy the fields `write_session` doesn't own (tags, note, content fingerprint, project)
+    /// from an existing session.json document into freshly built metadata
+    pub(crate) fn carry_over(metadata: &mut serde_json::Value, existing: &str) {
+        let Ok(existing) = serde_json::from_str::<serde_json::Value>(existing) else {
+            return;
+        };
+        let tags = Self::tags_of(&existing);
+        if !tags.is_empty() {
+            metadata["tags"] = json!(tags);
+        }
+        if let Some(note) = existing.get("note").filter(|n| n.is_string()) {
+            metadata["note"] = note.clone();
+        }
+        if let Some(hash) = existing.get("content_hash").filter(|h| h.is_string()) {
+            metadata["content_hash"] = hash.clone();
+        }
+        for field in ["cwd", "project"] {
+            if let (None, Some(value)) = (metadata.get(field), existing.get(field).filter(|v| v.is_string())) {
+                metadata[field] = value.clone();
+            }
+        }
+    }
+
+    fn tags_of(metadata: &serde_json::Value) -> Vec<String> {
+        metadata["tags"]
+            .as_array()
+            .map(|tags| tags.iter().filter_map(|t| t.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn normalize_tag(tag: &str) -> Result<String> {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return Err(color_eyre::eyre::eyre!("Tag must not be empty"));
+        }
+        Ok(tag.to_string())
+    }
+
+    /// Shallow-merge `updates` into existing session metadata
+    pub(crate) fn merge_metadata(
+        existing: serde_json::Value,
+        updates: &serde_json::Value,
+    ) -> serde_json::Value {
+        let mut merged = existing.as_object().cloned().unwrap_or_default();
+        if let Some(updates_obj) = updates.as_object() {
+            for (key, value) in updates_obj {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+        serde_json::Value::Object(merged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use crate::SessionStatus;
+
+    #[test]
+    fn test_extract_date_iso8601() {
+        assert_eq!(
+            PlainTextWriter::extract_date(Some("2025-11-09T14:30:00Z")),
+            "2025-11-09"
+        );
+    }
+
+    #[test]
+    fn test_extract_date_sqlite() {
+        assert_eq!(
+            PlainTextWriter::extract_date(Some("2025-11-09 14:30:00")),
+            "2025-11-09"
+        );
+    }
+
+    #[test]
+    fn test_extract_date_in_zone() -> Result<()> {
+        let pacific: DateZone = "-08:00".parse()?;
+        let tokyo: DateZone = "+09:00".parse()?;
+
+        // 23:30 in California is already the next day in UTC
+        assert_eq!(PlainTextWriter::extract_date_in(Some("2025-11-09T23:30:00-08:00"), pacific), "2025-11-09");
+        assert_eq!(PlainTextWriter::extract_date_in(Some("2025-11-09T23:30:00-08:00"), DateZone::Utc), "2025-11-10");
+        assert_eq!(PlainTextWriter::extract_date_in(Some("2025-11-10T07:59:59Z"), pacific), "2025-11-09");
+        assert_eq!(PlainTextWriter::extract_date_in(Some("2025-11-10T08:00:00Z"), pacific), "2025-11-10");
+        assert_eq!(PlainTextWriter::extract_date_in(Some("2025-11-09 15:00:00"), tokyo), "2025-11-10");
+        assert_eq!(PlainTextWriter::extract_date_in(Some("2025-11-09 14:59:59"), tokyo), "2025-11-09");
+
+        // Unparseable input falls back to the string split
+        assert_eq!(PlainTextWriter::extract_date_in(Some("2025-11-09Tlate"), pacific), "2025-11-09");
+        assert!("Mars/Olympus".parse::<DateZone>().is_err());
+        assert_eq!("LOCAL".parse::<DateZone>()?, DateZone::Local);
+
+        // Sessions and their messages land in the same local-date directory
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf()).with_zone(pacific);
+        let start = "2025-11-10T07:30:00Z";
+        let messages = vec![("user".to_string(), "late night".to_string())];
+        writer.import_messages("s1", "codex", &writer.date_of(Some(start)), &messages, &[], Some(start), false)?;
+        let session_dir = writer.write_session("s1", "codex", Some(start), None, "closed", 1, &[], None, None)?;
+        assert_eq!(session_dir, temp_dir.path().join("codex/2025-11-09/s1"));
+        assert!(session_dir.join("messages.jsonl").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reimport_in_another_zone_keeps_date() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let start = "2025-11-10T07:30:00Z";
+        let messages = vec![("user".to_string(), "late night".to_string())];
+
+        let utc = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let date = utc.session_date("s1", "codex", Some(start));
+        utc.import_messages("s1", "codex", &date, &messages, &[], Some(start), false)?;
+        utc.write_session("s1", "codex", Some(start), None, "closed", 1, &[], None, None)?;
+
+        // Under --tz -08:00 the start falls on the 9th, but the session stays where it was filed
+        let pacific = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf()).with_zone("-08:00".parse()?);
+        assert_eq!(pacific.date_of(Some(start)), "2025-11-09");
+        let date = pacific.session_date("s1", "codex", Some(start));
+        assert_eq!(date, "2025-11-10");
+        let outcome = pacific.import_messages("s1", "codex", &date, &messages, &[], Some(start), false)?;
+        assert_eq!(outcome, ImportOutcome::Unchanged);
+        let session_dir = pacific.write_session("s1", "codex", Some(start), None, "closed", 1, &[], None, None)?;
+        assert_eq!(session_dir, temp_dir.path().join("codex/2025-11-10/s1"));
+        assert!(!temp_dir.path().join("codex/2025-11-09").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_normalize_timestamp() {
+        let normalize = PlainTextWriter::normalize_timestamp;
+        assert_eq!(normalize("2025-11-09T16:30:00+02:00").as_deref(), Some("2025-11-09T14:30:00Z"));
+        assert_eq!(normalize("2025-11-09T14:30:00.250Z").as_deref(), Some("2025-11-09T14:30:00.250Z"));
+        assert_eq!(normalize("2025-11-09 14:30:00").as_deref(), Some("2025-11-09T14:30:00Z"));
+        assert_eq!(normalize("1762698600").as_deref(), Some("2025-11-09T14:30:00Z"));
+        assert_eq!(normalize("yesterday"), None);
+    }
+
+    #[test]
+    fn test_import_messages_per_message_timestamps() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let messages: Vec<(String, String)> =
+            ["one", "two", "three"].iter().map(|c| ("user".to_string(), c.to_string())).collect();
+        let timestamps = vec![Some("2025-11-09T10:00:00Z".to_string()), None, Some("2025-11-09T10:05:00Z".to_string())];
+
+        writer.import_messages("s1", "codex", "2025-11-09", &messages, &timestamps, Some("2025-11-09T09:00:00Z"), false)?;
+        let stored = store::load_messages(&temp_dir.path().join("codex/2025-11-09/s1"))?;
+        let stamps: Vec<Option<&str>> = stored.iter().map(|m| m.timestamp.as_deref()).collect();
+        assert_eq!(
+            stamps,
+            vec![Some("2025-11-09T10:00:00Z"), Some("2025-11-09T09:00:00Z"), Some("2025-11-09T10:05:00Z")]
+        );
+
+        // Appended messages keep their own timestamps too
+        let mut more = messages.clone();
+        more.push(("assistant".to_string(), "four".to_string()));
+        let mut more_timestamps = timestamps.clone();
+        more_timestamps.push(Some("2025-11-09T10:07:30Z".to_string()));
+        writer.import_messages("s1", "codex", "2025-11-09", &more, &more_timestamps, Some("2025-11-09T09:00:00Z"), false)?;
+        let stored = store::load_messages(&temp_dir.path().join("codex/2025-11-09/s1"))?;
+        assert_eq!(stored[3].timestamp.as_deref(), Some("2025-11-09T10:07:30Z"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_log_dir_env_override() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+
+        let other_dir = TempDir::new()?;
+
+        // Both variables are set and cleared in this one test so parallel tests can't race on them
+        std::env::set_var(LOG_DIR_ENV, temp_dir.path());
+        let writer = PlainTextWriter::new();
+        std::env::set_var(LOGS_DIR_ENV, other_dir.path());
+        let both = PlainTextWriter::new();
+        std::env::remove_var(LOG_DIR_ENV);
+        let logs_only = PlainTextWriter::new();
+        std::env::remove_var(LOGS_DIR_ENV);
+
+        assert_eq!(writer?.base_dir(), temp_dir.path());
+        assert_eq!(both?.base_dir(), temp_dir.path());
+        assert_eq!(logs_only?.base_dir(), other_dir.path());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_dir_expansion() -> Result<()> {
+        let home = std::env::var("HOME")?;
+        assert_eq!(
+            PlainTextWriter::resolve_dir("~/logs")?,
+            PathBuf::from(&home).join("logs")
+        );
+        assert_eq!(
+            PlainTextWriter::resolve_dir("relative/logs")?,
+            std::env::current_dir()?.join("relative/logs")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_session() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        let session_dir = writer.write_session(
+            "test-session-001",
+            "test-assistant",
+            Some("2025-11-09T14:00:00Z"),
+            None,
+            "active",
+            0,
+            &[],
+            None,
+            None,
+        )?;
+
+        assert!(session_dir.join("session.json").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_cost_persisted_or_omitted() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let read = |dir: PathBuf| -> Result<serde_json::Value> {
+            Ok(serde_json::from_str(&fs::read_to_string(dir.join("session.json"))?)?)
+        };
+
+        let cost = SessionCost {
+            total_cost_usd: 0.0421,
+            duration_ms: 18_250,
+            input_tokens: Some(1_200),
+            output_tokens: None,
+        };
+        let with_cost = writer.write_session(
+            "priced", "claude-code", Some("2025-11-09T14:00:00Z"), None, "closed", 2, &[], Some(&cost),
+            None,
+        )?;
+        let metadata = read(with_cost)?;
+        assert_eq!(metadata["cost_usd"], 0.0421);
+        assert_eq!(metadata["duration_ms"], 18_250);
+        assert_eq!(metadata["input_tokens"], 1_200);
+        assert!(metadata.get("output_tokens").is_none());
+
+        let without_cost = writer.write_session(
+            "unpriced", "codex", Some("2025-11-09T14:00:00Z"), None, "closed", 2, &[], None,
+            None,
+        )?;
+        let metadata = read(without_cost)?;
+        assert!(metadata.get("cost_usd").is_none());
+        assert!(metadata.get("duration_ms").is_none());
+
+        let record: SessionRecord = serde_json::from_value(metadata)?;
+        assert_eq!(record.cost_usd, None);
+        Ok(())
+    }
+
+    #[test]
     fn test_append_message() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
@@ -230,6 +1518,8 @@ mod tests {
             "user",
             "Test message",
             Some("2025-11-09T14:00:00Z"),
+            Some(PlainTextWriter::count_tokens("Test message")),
+            None,
         )?;
 
         let messages_path = temp_dir
@@ -239,6 +1529,491 @@ mod tests {
 
         let content = fs::read_to_string(messages_path)?;
         assert!(content.contains("Test message"));
+        assert!(content.contains(r#""tokens":2"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_model_usage_stats() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        writer.append_message("s1", "claude-code", "2025-11-08", 1, "user", "plan it", None, None, None)?;
+        writer.append_message("s1", "claude-code", "2025-11-08", 2, "assistant", "plan", None, None, Some("claude-opus-4-5"))?;
+        writer.append_message("s2", "claude-code", "2025-11-09", 1, "assistant", "done", None, None, Some("claude-sonnet-4-5"))?;
+        writer.append_message("s2", "claude-code", "2025-11-09", 2, "assistant", "more", None, None, Some("claude-sonnet-4-5"))?;
+
+        let stored = store::load_messages(&temp_dir.path().join("claude-code/2025-11-08/s1"))?;
+        assert_eq!(stored[0].model, None);
+        assert_eq!(stored[1].model.as_deref(), Some("claude-opus-4-5"));
+
+        let usage = writer.model_usage_stats("claude-code")?;
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage["claude-opus-4-5"], 1);
+        assert_eq!(usage["claude-sonnet-4-5"], 2);
+
+        let recent = SessionFilter { since: NaiveDate::from_ymd_opt(2025, 11, 9), ..SessionFilter::new().assistant("claude-code") };
+        assert_eq!(writer.model_usage_matching(&recent)?.len(), 1);
+        assert!(writer.model_usage_stats("codex")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_messages_incremental() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let msg = |role: &str, content: &str| (role.to_string(), content.to_string());
+        let stored = || store::load_messages(&temp_dir.path().join("codex/2025-11-09/s1"));
+
+        let mut source = vec![msg("user", "one"), msg("assistant", "two")];
+        let import = |source: &[(String, String)], force| {
+            writer.import_messages("s1", "codex", "2025-11-09", source, &[], None, force)
+        };
+
+        assert_eq!(import(&source, false)?, ImportOutcome::Rewritten(2));
+        assert_eq!(import(&source, false)?, ImportOutcome::Unchanged);
+        assert_eq!(stored()?.len(), 2);
+
+        // The source grew: only the tail is written
+        source.push(msg("user", "three"));
+        assert_eq!(import(&source, false)?, ImportOutcome::Appended(1));
+        let messages = stored()?;
+        assert_eq!(messages.iter().map(|m| m.id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(messages[2].content, "three");
+        assert!(messages.iter().all(|m| m.tokens == Some(1)));
+
+        // The source shrank: rewrite
+        source.truncate(1);
+        assert_eq!(import(&source, false)?, ImportOutcome::Rewritten(1));
+        assert_eq!(stored()?.len(), 1);
+
+        // --force always rewrites
+        assert_eq!(import(&source, true)?, ImportOutcome::Rewritten(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_json_replaced_atomically() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let session_dir = writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "active", 0, &[], None, None)?;
+        let session_json = session_dir.join("session.json");
+
+        // A reader polling alongside the writer must always find a complete document
+        let reader_path = session_json.clone();
+        let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let reader_done = done.clone();
+        let reader = std::thread::spawn(move || -> Result<usize> {
+            let mut reads = 0;
+            loop {
+                let finished = reader_done.load(Ordering::Relaxed);
+                let content = fs::read_to_string(&reader_path)?;
+                let metadata: serde_json::Value = serde_json::from_str(&content)
+                    .with_context(|| format!("Torn read: {:?}", content))?;
+                assert_eq!(metadata["id"], "s1");
+                reads += 1;
+                if finished {
+                    return Ok(reads);
+                }
+            }
+        });
+
+        for count in 1..=200 {
+            let padding = "x".repeat(count * 37 % 4096);
+            writer.update_session_metadata("s1", "codex", "2025-11-09", json!({"message_count": count, "note": padding}))?;
+        }
+        done.store(true, Ordering::Relaxed);
+        assert!(reader.join().unwrap()? > 0);
+
+        // Still pretty-printed, carrying the last update, with no temp files left behind
+        let content = fs::read_to_string(&session_json)?;
+        assert!(content.contains("\n  \"id\": \"s1\""));
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&content)?["message_count"], 200);
+        let leftovers: Vec<_> = fs::read_dir(&session_dir)?
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_session_by_hash_follows_later_imports() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let first = vec![("user".to_string(), "Fix the build".to_string())];
+        let second = vec![("user".to_string(), "Fix the tests".to_string())];
+        writer.import_messages("s1", "codex", "2025-11-09", &first, &[], None, false)?;
+        writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 1, &[], None, None)?;
+
+        // Loads the fingerprints; sessions imported afterwards are still found
+        assert_eq!(writer.find_session_by_hash(&PlainTextWriter::content_hash(&first))?.map(|r| r.id), Some("s1".to_string()));
+        writer.import_messages("s2", "codex", "2025-11-09", &second, &[], None, false)?;
+        writer.write_session("s2", "codex", Some("2025-11-09T11:00:00Z"), None, "closed", 1, &[], None, None)?;
+        assert_eq!(writer.find_session_by_hash(&PlainTextWriter::content_hash(&second))?.map(|r| r.id), Some("s2".to_string()));
+
+        // A session rewritten with other messages no longer matches its old fingerprint
+        writer.import_messages("s1", "codex", "2025-11-09", &[("user".to_string(), "Ship it".to_string())], &[], None, true)?;
+        assert!(writer.find_session_by_hash(&PlainTextWriter::content_hash(&first))?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_content_refused() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let messages = vec![
+            ("user".to_string(), "Fix the build".to_string()),
+            ("assistant".to_string(), "Fixed the missing import".to_string()),
+        ];
+        let hash = PlainTextWriter::content_hash(&messages);
+
+        writer.import_messages("s1", "codex", "2025-11-09", &messages, &[], None, false)?;
+        writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 2, &[], None, None)?;
+
+        // write_session keeps the fingerprint import_messages recorded
+        let found = writer.find_session_by_hash(&hash)?.expect("hash recorded");
+        assert_eq!(found.id, "s1");
+        assert_eq!(found.content_hash.as_deref(), Some(hash.as_str()));
+        assert!(writer.find_session_by_hash("nope")?.is_none());
+
+        // Re-importing the same session is fine; a copy under another ID is not
+        writer.import_messages("s1", "codex", "2025-11-09", &messages, &[], None, false)?;
+        let err = writer
+            .import_messages("copy", "codex", "2025-11-10", &messages, &[], None, false)
+            .unwrap_err();
+        let duplicate = err.downcast_ref::<DuplicateSession>().expect("DuplicateSession");
+        assert_eq!(duplicate.existing_id, "s1");
+        assert!(!temp_dir.path().join("codex/2025-11-10/copy").exists());
+
+        // --force bypasses the check
+        writer.import_messages("copy", "codex", "2025-11-10", &messages, &[], None, true)?;
+        assert_eq!(store::load_messages(&temp_dir.path().join("codex/2025-11-10/copy"))?.len(), 2);
+
+        // Field boundaries matter to the fingerprint
+        let shifted = vec![
+            ("user".to_string(), "Fix the buil".to_string()),
+            ("dassistant".to_string(), "Fixed the missing import".to_string()),
+        ];
+        assert_ne!(PlainTextWriter::content_hash(&shifted), hash);
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_sessions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let messages = vec![
+            ("user".to_string(), "Fix the build".to_string()),
+            ("assistant".to_string(), "Fixed the missing import".to_string()),
+        ];
+
+        for (id, date) in [("s1", "2025-11-09"), ("copy", "2025-11-10")] {
+            writer.import_messages(id, "codex", date, &messages, &[], None, true)?;
+            writer.write_session(id, "codex", Some(&format!("{}T10:00:00Z", date)), None, "closed", 2, &[], None, None)?;
+        }
+        // Written message by message, so no fingerprint is recorded
+        writer.write_session("w1", "codex", Some("2025-11-08T10:00:00Z"), None, "closed", 2, &[], None, None)?;
+        for (idx, (role, content)) in messages.iter().enumerate() {
+            writer.append_message("w1", "codex", "2025-11-08", idx + 1, role, content, None, None, None)?;
+        }
+        writer.import_messages("other", "codex", "2025-11-09", &messages[..1], &[], None, false)?;
+        writer.write_session("other", "codex", Some("2025-11-09T11:00:00Z"), None, "closed", 1, &[], None, None)?;
+        // Same messages under another assistant: not a copy
+        writer.import_messages("g1", "goose", "2025-11-09", &messages, &[], None, true)?;
+        writer.write_session("g1", "goose", Some("2025-11-09T10:00:00Z"), None, "closed", 2, &[], None, None)?;
+
+        let groups = writer.duplicate_sessions(None)?;
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<&str> = groups[0].iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["w1", "s1", "copy"]);
+        assert!(writer.duplicate_sessions(Some("goose"))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compressed_messages_round_trip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf()).with_compression(true);
+        let session_dir = temp_dir.path().join("codex/2025-11-09/gz");
+
+        writer.append_message("gz", "codex", "2025-11-09", 1, "user", "first", None, None, None)?;
+        writer.append_message("gz", "codex", "2025-11-09", 2, "assistant", "second", None, None, None)?;
+        assert!(session_dir.join(store::COMPRESSED_MESSAGES_FILE).exists());
+        assert!(!session_dir.join(store::MESSAGES_FILE).exists());
+
+        // Appends add gzip members; the reader sees one continuous stream
+        let source = vec![
+            ("user".to_string(), "first".to_string()),
+            ("assistant".to_string(), "second".to_string()),
+            ("user".to_string(), "third".to_string()),
+        ];
+        assert_eq!(
+            writer.import_messages("gz", "codex", "2025-11-09", &source, &[], None, false)?,
+            ImportOutcome::Appended(1)
+        );
+        let contents: Vec<String> = store::load_messages(&session_dir)?.into_iter().map(|m| m.content).collect();
+        assert_eq!(contents, vec!["first", "second", "third"]);
+
+        // Existing plain sessions keep their format
+        let plain = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        plain.append_message("gz", "codex", "2025-11-09", 4, "assistant", "fourth", None, None, None)?;
+        assert_eq!(store::load_messages(&session_dir)?.len(), 4);
+        assert!(!session_dir.join(store::MESSAGES_FILE).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tags_survive_reimport() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let start = Some("2025-11-09T14:00:00Z");
+
+        writer.write_session("s1", "codex", start, None, "closed", 1, &[], None, None)?;
+        writer.write_session("s2", "goose", start, None, "closed", 1, &[], None, None)?;
+        writer.add_tag("s1", "codex", "2025-11-09", "project:myapp")?;
+        writer.add_tag("s1", "codex", "2025-11-09", "topic:debugging")?;
+        writer.add_tag("s1", "codex", "2025-11-09", "project:myapp")?;
+        writer.add_tag("s2", "goose", "2025-11-09", " project:myapp ")?;
+
+        // Importing again rewrites session.json but keeps the tags
+        writer.write_session("s1", "codex", start, None, "closed", 4, &[], None, None)?;
+        assert_eq!(
+            writer.session_tags("s1", "codex", "2025-11-09")?,
+            vec!["project:myapp", "topic:debugging"]
+        );
+
+        let tagged = writer.find_sessions_with_tag("project:myapp")?;
+        let ids: Vec<&str> = tagged.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["s1", "s2"]);
+
+        writer.remove_tag("s1", "codex", "2025-11-09", "project:myapp")?;
+        assert_eq!(writer.session_tags("s1", "codex", "2025-11-09")?, vec!["topic:debugging"]);
+        assert_eq!(writer.find_sessions_with_tag("project:myapp")?.len(), 1);
+
+        assert!(writer.add_tag("s1", "codex", "2025-11-09", "  ").is_err());
+        assert!(writer.add_tag("missing", "codex", "2025-11-09", "x").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_and_assistant_stats() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let cost = SessionCost { total_cost_usd: 0.25, duration_ms: 1_000, ..Default::default() };
+
+        writer.write_session("s1", "codex", Some("2025-11-08T09:00:00Z"), None, "closed", 2, &[], Some(&cost), None)?;
+        writer.append_message("s1", "codex", "2025-11-08", 1, "user", "abcd", Some("2025-11-08T09:00:00Z"), Some(1), None)?;
+        writer.append_message("s1", "codex", "2025-11-08", 2, "assistant", "abcdefgh", Some("2025-11-08T09:05:00Z"), Some(2), None)?;
+        writer.write_session("s2", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 1, &[], None, None)?;
+        // Logged before token counts were recorded
+        writer.append_message("s2", "codex", "2025-11-09", 1, "user", "xyz", None, None, None)?;
+
+        // No session.json, and a corrupt one: both skipped
+        writer.append_message("orphan", "codex", "2025-11-09", 1, "user", "lost", None, None, None)?;
+        writer.append_message("broken", "goose", "2025-11-09", 1, "user", "lost", None, None, None)?;
+        fs::write(temp_dir.path().join("goose/2025-11-09/broken/session.json"), "{not json")?;
+
+        let stats = writer.session_stats("codex", "s1", "2025-11-08")?;
+        assert_eq!(stats.message_count, 2);
+        assert_eq!(stats.total_chars, 12);
+        assert_eq!(stats.approx_tokens, 3);
+        assert_eq!(stats.tokens, 3);
+        assert_eq!(stats.status.as_deref(), Some("closed"));
+        assert_eq!(stats.first_timestamp.as_deref(), Some("2025-11-08T09:00:00Z"));
+        assert_eq!(stats.last_timestamp.as_deref(), Some("2025-11-08T09:05:00Z"));
+        assert_eq!(stats.total_cost_usd, Some(0.25));
+
+        let totals = writer.assistant_stats("codex")?;
+        assert_eq!(totals.session_count, 2);
+        assert_eq!(totals.message_count, 3);
+        assert_eq!(totals.total_chars, 15);
+        assert_eq!(totals.tokens, 3);
+        assert_eq!(totals.total_cost_usd, 0.25);
+        assert_eq!(totals.skipped_sessions, 1);
+        assert_eq!(totals.first_timestamp.as_deref(), Some("2025-11-08T09:00:00Z"));
+        assert_eq!(writer.session_stats("codex", "s2", "2025-11-09")?.first_timestamp.as_deref(), Some("2025-11-09T10:00:00Z"));
+        assert_eq!(totals.last_timestamp.as_deref(), Some("2025-11-08T09:05:00Z"));
+
+        let summary = writer.summary()?;
+        assert_eq!(summary.skipped_sessions, 2);
+        assert_eq!(summary.assistants["goose"].session_count, 0);
+        assert_eq!(summary.days["2025-11-08"].messages, 2);
+        assert_eq!(summary.days["2025-11-09"].sessions, 1);
+        assert_eq!(summary.monthly_costs["2025-11"]["codex"], 0.25);
+        assert_eq!(writer.total_cost("codex")?, 0.25);
+        assert_eq!(summary.assistants["codex"].first_session_date.as_deref(), Some("2025-11-08"));
+        assert_eq!(summary.assistants["codex"].last_session_date.as_deref(), Some("2025-11-09"));
+
+        let recent = writer.summary_since(NaiveDate::from_ymd_opt(2025, 11, 9))?;
+        assert_eq!(recent.assistants["codex"].session_count, 1);
+        assert_eq!(recent.assistants["codex"].first_session_date.as_deref(), Some("2025-11-09"));
+        assert!(!recent.days.contains_key("2025-11-08"));
+
+        // Older sessions stored the cost as `total_cost_usd`
+        writer.write_session("legacy", "claude-code", Some("2025-10-01T08:00:00Z"), None, "closed", 0, &[], None, None)?;
+        writer.update_session_metadata("legacy", "claude-code", "2025-10-01", json!({ "total_cost_usd": 1.5 }))?;
+        assert_eq!(writer.total_cost("claude-code")?, 1.5);
+        let legacy = fs::read_to_string(temp_dir.path().join("claude-code/2025-10-01/legacy/session.json"))?;
+        assert_eq!(serde_json::from_str::<SessionRecord>(&legacy)?.cost_usd, Some(1.5));
+
+        assert_eq!(writer.assistant_stats("gemini-cli")?.session_count, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_sessions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        writer.write_session("s1", "codex", Some("2025-11-08T09:00:00Z"), None, "closed", 4, &[], None, None)?;
+        writer.write_session("s2", "codex", Some("2025-11-10T09:00:00Z"), None, "active", 1, &[], None, None)?;
+        writer.write_session("g1", "goose", Some("2025-11-09T09:00:00Z"), None, "closed", 7, &[], None, None)?;
+        writer.add_tag("g1", "goose", "2025-11-09", "review")?;
+        // Directories without a usable session.json are skipped
+        writer.append_message("orphan", "codex", "2025-11-09", 1, "user", "lost", None, None, None)?;
+        writer.append_message("broken", "goose", "2025-11-09", 1, "user", "lost", None, None, None)?;
+        fs::write(temp_dir.path().join("goose/2025-11-09/broken/session.json"), "{not json")?;
+
+        let ids = |records: &[SessionRecord]| records.iter().map(|r| r.id.clone()).collect::<Vec<_>>();
+
+        let all = writer.list_sessions(&SessionFilter::default())?;
+        assert_eq!(ids(&all), vec!["s2", "g1", "s1"]);
+        assert_eq!(all[0].status, SessionStatus::Active);
+        assert_eq!(all[1].message_count, Some(7));
+        assert_eq!(all[1].tags, vec!["review".to_string()]);
+        assert_eq!(all[2].date.as_deref(), Some("2025-11-08"));
+
+        let codex = SessionFilter { assistant: Some("CODEX".to_string()), ..Default::default() };
+        assert_eq!(ids(&writer.list_sessions(&codex)?), vec!["s2", "s1"]);
+
+        let day = |d: u32| NaiveDate::from_ymd_opt(2025, 11, d);
+        let ranged = SessionFilter { since: day(9), until: day(10), ..Default::default() };
+        assert_eq!(ids(&writer.list_sessions(&ranged)?), vec!["s2", "g1"]);
+
+        let by_messages = SessionFilter { sort: SessionSort::Messages, ..Default::default() };
+        assert_eq!(ids(&writer.list_sessions(&by_messages)?), vec!["g1", "s1", "s2"]);
+
+        let page = SessionFilter { offset: 1, limit: Some(1), ..Default::default() };
+        assert_eq!(ids(&writer.list_sessions(&page)?), vec!["g1"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_sessions_by_cwd() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        writer.write_session("s1", "codex", Some("2025-11-08T09:00:00Z"), None, "closed", 4, &[], None, Some("/home/me/app"))?;
+        writer.write_session("g1", "goose", Some("2025-11-09T09:00:00Z"), None, "closed", 2, &[], None, Some("/home/me/app/"))?;
+        writer.write_session("c1", "claude-code", Some("2025-11-10T09:00:00Z"), None, "closed", 1, &[], None, Some("/home/me/other"))?;
+        writer.write_session("s2", "codex", Some("2025-11-10T09:00:00Z"), None, "closed", 1, &[], None, None)?;
+
+        // Re-writing without a cwd keeps the recorded one
+        writer.write_session("s1", "codex", Some("2025-11-08T09:00:00Z"), None, "closed", 5, &[], None, None)?;
+
+        let ids = |records: Vec<SessionRecord>| records.into_iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(writer.find_sessions_by_cwd("/home/me/app")?), vec!["g1", "s1"]);
+        assert_eq!(ids(writer.find_sessions_by_cwd("/home/me/app/")?), vec!["g1", "s1"]);
+        assert!(writer.find_sessions_by_cwd("/home/me")?.is_empty());
+
+        let filter = SessionFilter {
+            assistant: Some("codex".to_string()),
+            cwd: Some("/home/me/app".to_string()),
+            ..Default::default()
+        };
+        let codex = writer.list_sessions(&filter)?;
+        assert_eq!(codex.len(), 1);
+        assert_eq!(codex[0].cwd.as_deref(), Some("/home/me/app"));
+        assert_eq!(codex[0].message_count, Some(5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_sessions_by_project() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        writer.write_session("c1", "claude-code", Some("2025-11-08T09:00:00Z"), None, "closed", 4, &[], None, None)?;
+        writer.update_session_metadata("c1", "claude-code", "2025-11-08", json!({ "project": "-home-me-app" }))?;
+        writer.write_session("c2", "claude-code", Some("2025-11-09T09:00:00Z"), None, "closed", 1, &[], None, None)?;
+        writer.write_session("s1", "codex", Some("2025-11-09T09:00:00Z"), None, "closed", 1, &[], None, Some("/home/me/app"))?;
+
+        // Re-importing keeps the recorded project
+        writer.write_session("c1", "claude-code", Some("2025-11-08T09:00:00Z"), None, "closed", 5, &[], None, None)?;
+
+        let ids = |project: &str| -> Result<Vec<String>> {
+            let filter = SessionFilter { project: Some(project.to_string()), ..Default::default() };
+            Ok(writer.list_sessions(&filter)?.into_iter().map(|r| r.id).collect())
+        };
+        assert_eq!(ids("-home-me-app")?, vec!["c1"]);
+        assert_eq!(ids("/home/me/app/")?, vec!["c1"]);
+        assert!(ids("-home-me")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_filter() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let cost = |usd| SessionCost { total_cost_usd: usd, ..Default::default() };
+
+        writer.write_session("cheap", "codex", Some("2025-11-08T09:00:00Z"), None, "closed", 2, &[], Some(&cost(0.25)), None)?;
+        writer.write_session("pricey", "codex", Some("2025-11-09T09:00:00Z"), None, "closed", 2, &[], Some(&cost(3.0)), None)?;
+        writer.write_session("live", "codex", Some("2025-11-10T09:00:00Z"), None, "active", 2, &[], None, None)?;
+        writer.write_session("g1", "goose", Some("2025-11-09T09:00:00Z"), None, "closed", 2, &[], None, None)?;
+        for (id, assistant, date) in [("cheap", "codex", "2025-11-08"), ("live", "codex", "2025-11-10"), ("g1", "goose", "2025-11-09")] {
+            writer.add_tag(id, assistant, date, "project:myapp")?;
+        }
+
+        let ids = |filter: SessionFilter| -> Result<Vec<String>> { Ok(writer.query(&filter)?.into_iter().map(|r| r.id).collect()) };
+        let day = |d| NaiveDate::from_ymd_opt(2025, 11, d).unwrap();
+
+        assert_eq!(ids(SessionFilter::new().assistant("codex").tag("project:myapp"))?, vec!["live", "cheap"]);
+        assert_eq!(ids(SessionFilter::new().tag(" project:myapp ").status(SessionStatus::Closed))?, vec!["g1", "cheap"]);
+        // Sessions without a cost count as free for max_cost but never reach a min_cost
+        assert_eq!(ids(SessionFilter::new().assistant("codex").max_cost(1.0))?, vec!["live", "cheap"]);
+        assert_eq!(ids(SessionFilter::new().min_cost(1.0))?, vec!["pricey"]);
+        assert_eq!(ids(SessionFilter::new().since(day(9)).until(day(9)).status(SessionStatus::Closed))?, vec!["pricey", "g1"]);
+
+        let summary = writer.summary_matching(&SessionFilter::new().tag("project:myapp"))?;
+        assert_eq!(summary.assistants["codex"].session_count, 2);
+        assert_eq!(summary.assistants["goose"].session_count, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_note() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        writer.write_session("s1", "codex", Some("2025-11-09T09:00:00Z"), None, "closed", 0, &[], None, None)?;
+        writer.write_session("s2", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 0, &[], None, None)?;
+        let index = SearchIndex::open_at(temp_dir.path())?;
+        index.index_session("s1", "codex", "2025-11-09", &[("user".to_string(), "Why does the worker hang?".to_string())])?;
+
+        assert_eq!(writer.session_note("s1", "codex", "2025-11-09")?, None);
+        writer.set_note("s1", "codex", "2025-11-09", "Solved the async bug\nin worker.rs")?;
+        assert_eq!(writer.session_note("s1", "codex", "2025-11-09")?.as_deref(), Some("Solved the async bug in worker.rs"));
+        assert!(writer.set_note("s1", "codex", "2025-11-09", "  ").is_err());
+        assert!(writer.set_note("missing", "codex", "2025-11-09", "note").is_err());
+
+        // Re-importing keeps the note, and note_contains ignores case
+        writer.write_session("s1", "codex", Some("2025-11-09T09:00:00Z"), None, "closed", 1, &[], None, None)?;
+        let ids = |filter: SessionFilter| -> Result<Vec<String>> { Ok(writer.query(&filter)?.into_iter().map(|r| r.id).collect()) };
+        assert_eq!(ids(SessionFilter::new().note_contains("ASYNC BUG"))?, vec!["s1"]);
+
+        // The note is indexed next to the messages and survives re-indexing them
+        index.index_note("s1", "codex", "2025-11-09", writer.session_note("s1", "codex", "2025-11-09")?.as_deref())?;
+        index.index_session("s1", "codex", "2025-11-09", &[("user".to_string(), "Why does the worker hang?".to_string())])?;
+        let hits = index.search("async")?;
+        assert_eq!((hits.len(), hits[0].message_id, hits[0].role.as_str()), (1, 0, crate::search::NOTE_ROLE));
+        assert_eq!(index.search("worker")?.len(), 2);
+
+        writer.clear_note("s1", "codex", "2025-11-09")?;
+        assert_eq!(writer.session_note("s1", "codex", "2025-11-09")?, None);
+        assert!(ids(SessionFilter::new().note_contains("async"))?.is_empty());
+        assert_eq!(index.rebuild_from_logs()?, 0);
+        assert!(index.search("async")?.is_empty());
         Ok(())
     }
 }
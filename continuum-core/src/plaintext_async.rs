@@ -0,0 +1,314 @@
+// Async plain-text JSONL writer
+// Mirrors PlainTextWriter using tokio::fs for callers running inside an async runtime
+
+use color_eyre::{eyre::Context, Result};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+use crate::{plaintext, session_index, PlainTextWriter, SessionCost};
+
+/// Async plain-text session writer (same layout and format as [`PlainTextWriter`])
+pub struct AsyncPlainTextWriter {
+    inner: PlainTextWriter,
+}
+
+impl AsyncPlainTextWriter {
+    /// Create a new writer with default base directory
+    pub fn new() -> Result<Self> {
+        Ok(AsyncPlainTextWriter {
+            inner: PlainTextWriter::new()?,
+        })
+    }
+
+    /// Create a writer for the log tree under `config.base_dir`
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        AsyncPlainTextWriter {
+            inner: PlainTextWriter::from_config(config),
+        }
+    }
+
+    /// Create a new writer with custom base directory
+    pub fn with_base_dir(base_dir: PathBuf) -> Self {
+        AsyncPlainTextWriter {
+            inner: PlainTextWriter::with_base_dir(base_dir),
+        }
+    }
+
+    /// Write new sessions' messages gzip-compressed (see [`PlainTextWriter::with_compression`])
+    pub fn with_compression(self, compress: bool) -> Self {
+        AsyncPlainTextWriter {
+            inner: self.inner.with_compression(compress),
+        }
+    }
+
+    /// Lay sessions out by `template` (see [`PlainTextWriter::with_layout`])
+    pub fn with_layout(self, template: &str) -> Result<Self> {
+        Ok(AsyncPlainTextWriter {
+            inner: self.inner.with_layout(template)?,
+        })
+    }
+
+    /// Mask secrets in every message written (see [`PlainTextWriter::with_redactor`])
+    pub fn with_redactor(self, redactor: Option<crate::Redactor>) -> Self {
+        AsyncPlainTextWriter {
+            inner: self.inner.with_redactor(redactor),
+        }
+    }
+
+    /// File sessions under their start date in `zone` (see [`PlainTextWriter::with_zone`])
+    pub fn with_zone(self, zone: crate::DateZone) -> Self {
+        AsyncPlainTextWriter {
+            inner: self.inner.with_zone(zone),
+        }
+    }
+
+    /// Extract date from timestamp (see [`PlainTextWriter::extract_date`])
+    pub fn extract_date(timestamp: Option<&str>) -> String {
+        PlainTextWriter::extract_date(timestamp)
+    }
+
+    /// Date directory for a session starting at `timestamp` (see [`PlainTextWriter::date_of`])
+    pub fn date_of(&self, timestamp: Option<&str>) -> String {
+        self.inner.date_of(timestamp)
+    }
+
+    /// Date directory for a session, reusing the one it's stored under (see [`PlainTextWriter::session_date`])
+    pub fn session_date(&self, session_id: &str, assistant: &str, timestamp: Option<&str>) -> String {
+        self.inner.session_date(session_id, assistant, timestamp)
+    }
+
+    /// Write session metadata
+    #[allow(clippy::too_many_arguments)]
+    pub async fn write_session(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        start_time: Option<&str>,
+        end_time: Option<&str>,
+        status: &str,
+        message_count: usize,
+        skills: &[String],
+        cost: Option<&SessionCost>,
+        cwd: Option<&str>,
+    ) -> Result<PathBuf> {
+        let date = self.session_date(session_id, assistant, start_time);
+        let session_dir = self.inner.session_dir(assistant, &date, session_id);
+
+        self.inner.layout().record(self.base_dir())?;
+        fs::create_dir_all(&session_dir)
+            .await
+            .with_context(|| format!("Failed to create directory: {}", session_dir.display()))?;
+
+        let session_json_path = session_dir.join("session.json");
+        let mut metadata = PlainTextWriter::session_metadata(
+            session_id,
+            assistant,
+            start_time,
+            end_time,
+            status,
+            message_count,
+            skills,
+            cost,
+            cwd,
+        );
+
+        // Re-importing a session must not drop its tags or content fingerprint
+        if let Ok(existing) = fs::read_to_string(&session_json_path).await {
+            PlainTextWriter::carry_over(&mut metadata, &existing);
+        }
+        self.inner.layout_metadata(&mut metadata, &date);
+
+        write_json_atomic(&session_json_path, &metadata).await?;
+        self.record_in_index(assistant, &date, &metadata).await;
+
+        Ok(session_dir)
+    }
+
+    /// Append a message to the messages.jsonl file
+    #[allow(clippy::too_many_arguments)]
+    pub async fn append_message(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        message_id: usize,
+        role: &str,
+        content: &str,
+        timestamp: Option<&str>,
+        tokens: Option<usize>,
+        model: Option<&str>,
+    ) -> Result<()> {
+        let message = PlainTextWriter::message_record(message_id, role, &self.inner.redact(content), timestamp, tokens, model);
+        self.append_record(session_id, assistant, date, message).await
+    }
+
+    /// Append a tool call or result as a `tool` message that names its tool
+    #[allow(clippy::too_many_arguments)]
+    pub async fn append_tool_message(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        message_id: usize,
+        tool_name: &str,
+        content: &str,
+        timestamp: Option<&str>,
+        tokens: Option<usize>,
+    ) -> Result<()> {
+        let message = PlainTextWriter::tool_record(message_id, tool_name, &self.inner.redact(content), timestamp, tokens);
+        self.append_record(session_id, assistant, date, message).await
+    }
+
+    async fn append_record(&self, session_id: &str, assistant: &str, date: &str, message: serde_json::Value) -> Result<()> {
+        let session_dir = self.inner.session_dir(assistant, date, session_id);
+        let (messages_path, gzip) = self.inner.messages_target(&session_dir);
+
+        self.inner.layout().record(self.base_dir())?;
+        fs::create_dir_all(&session_dir)
+            .await
+            .with_context(|| format!("Failed to create directory: {}", session_dir.display()))?;
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&messages_path)
+            .await
+            .with_context(|| format!("Failed to open {}", messages_path.display()))?;
+
+        // Serialize the whole line (or gzip member) first so it lands in a single write
+        let bytes = PlainTextWriter::encode_records(&[message], gzip)?;
+
+        file.write_all(&bytes).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+
+    /// Update session metadata (useful for updating message count, end time, etc.)
+    pub async fn update_session_metadata(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        updates: serde_json::Value,
+    ) -> Result<()> {
+        let session_dir = self.inner.session_dir(assistant, date, session_id);
+        let session_json_path = session_dir.join("session.json");
+
+        let existing: serde_json::Value = match fs::read_to_string(&session_json_path).await {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => json!({}),
+            Err(e) => return Err(e.into()),
+        };
+
+        let merged = PlainTextWriter::merge_metadata(existing, &updates);
+        write_json_atomic(&session_json_path, &merged).await?;
+        self.record_in_index(assistant, date, &merged).await;
+
+        if self.inner.layout().has_project() {
+            self.inner.file_under_project(&session_dir, assistant, date, session_id, merged["project"].as_str())?;
+        }
+        Ok(())
+    }
+
+    /// Async counterpart of the sync writer's index update (see [`session_index`])
+    /// The index is only a cache, so a failure is a warning.
+    async fn record_in_index(&self, assistant: &str, date: &str, metadata: &serde_json::Value) {
+        if let Err(e) = self.write_index_line(assistant, date, metadata).await {
+            eprintln!("⚠ Warning: Failed to update session index: {:#}", e);
+        }
+    }
+
+    async fn write_index_line(&self, assistant: &str, date: &str, metadata: &serde_json::Value) -> Result<()> {
+        let path = session_index::index_path(self.base_dir(), assistant);
+        let existing = match fs::read_to_string(&path).await {
+            Ok(existing) => existing,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let updated = session_index::upsert(&existing, metadata, date)?;
+
+        let tmp = plaintext::temp_path(&path);
+        let written = async {
+            if let Some(dir) = path.parent().filter(|dir| dir.ends_with(session_index::CUSTOM_LAYOUT_INDEX_DIR)) {
+                fs::create_dir_all(dir).await?;
+            }
+            fs::write(&tmp, updated).await?;
+            fs::rename(&tmp, &path).await
+        }
+        .await;
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp).await;
+        }
+        written.with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Get the base directory
+    pub fn base_dir(&self) -> &Path {
+        self.inner.base_dir()
+    }
+}
+
+/// Async counterpart of the sync writer's atomic JSON replace (temp file + rename)
+async fn write_json_atomic(path: &Path, value: &serde_json::Value) -> Result<()> {
+    let tmp = plaintext::temp_path(path);
+    let written = async {
+        let mut file = fs::File::create(&tmp).await?;
+        file.write_all(&serde_json::to_vec_pretty(value)?).await?;
+        file.sync_all().await?;
+        fs::rename(&tmp, path).await?;
+        Ok::<(), color_eyre::Report>(())
+    }
+    .await;
+
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp).await;
+    }
+    written.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_async_writer_matches_sync_layout() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = AsyncPlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        let session_dir = writer
+            .write_session("async-001", "claude-code", Some("2025-11-09T14:00:00Z"), None, "active", 0, &[], None, None)
+            .await?;
+        writer
+            .append_message("async-001", "claude-code", "2025-11-09", 1, "user", "First", None, None, None)
+            .await?;
+        writer
+            .append_message("async-001", "claude-code", "2025-11-09", 2, "assistant", "Second", None, None, None)
+            .await?;
+        writer
+            .append_tool_message("async-001", "claude-code", "2025-11-09", 3, "Bash", "TOOL_RESULT: ok", None, None)
+            .await?;
+        writer
+            .update_session_metadata("async-001", "claude-code", "2025-11-09", json!({"status": "closed", "message_count": 3}))
+            .await?;
+
+        assert_eq!(session_dir, temp_dir.path().join("claude-code/2025-11-09/async-001"));
+
+        let messages = crate::store::load_messages(&session_dir)?;
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1].content, "Second");
+        assert_eq!(messages[1].tool_name, None);
+        assert_eq!(messages[2].role, "tool");
+        assert_eq!(messages[2].tool_name.as_deref(), Some("Bash"));
+
+        let metadata: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(session_dir.join("session.json"))?)?;
+        assert_eq!(metadata["status"], "closed");
+        assert_eq!(metadata["message_count"], 3);
+        assert_eq!(metadata["id"], "async-001");
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+// Plain-text log reader
+// Loads sessions written by PlainTextWriter back as SessionRecord and Message values
+
+use color_eyre::Result;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::session_ops::check_path_component;
+use crate::{store, Message, SessionRecord};
+
+/// Read-only view of a log tree, the counterpart of [`crate::PlainTextWriter`]
+#[derive(Debug, Clone)]
+pub struct PlainTextReader {
+    base_dir: PathBuf,
+}
+
+impl PlainTextReader {
+    /// Reader for the default log directory (see `PlainTextWriter::new`)
+    pub fn new() -> Result<Self> {
+        Ok(Self::from_config(&Config::load()?))
+    }
+
+    /// Reader for the log tree under `config.base_dir`
+    pub fn from_config(config: &Config) -> Self {
+        Self::with_base_dir(config.base_dir.clone())
+    }
+
+    pub fn with_base_dir(base_dir: PathBuf) -> Self {
+        PlainTextReader { base_dir }
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    /// Stored sessions, optionally narrowed to one assistant (case-insensitive) and/or date directory
+    ///
+    /// Sorted by assistant, date, then session ID. Each record's `date` is set
+    /// to its date directory; sessions without a readable session.json are skipped.
+    pub fn list_sessions(&self, assistant: Option<&str>, date: Option<&str>) -> Result<Vec<SessionRecord>> {
+        Ok(store::list_sessions(&self.base_dir)?
+            .into_iter()
+            .filter(|location| assistant.is_none_or(|a| a.eq_ignore_ascii_case(&location.assistant)))
+            .filter(|location| date.is_none_or(|d| d == location.date))
+            .filter_map(|location| SessionRecord::from_dir(&location.path).ok())
+            .collect())
+    }
+
+    /// Metadata and messages of one stored session
+    pub fn read_session(&self, assistant: &str, date: &str, session_id: &str) -> Result<(SessionRecord, Vec<Message>)> {
+        check_path_component("assistant", assistant)?;
+        check_path_component("date", date)?;
+        check_path_component("session ID", session_id)?;
+
+        let session_dir = crate::Layout::load(&self.base_dir)?.session_dir(&self.base_dir, assistant, date, session_id);
+        let record = SessionRecord::from_dir(&session_dir)?;
+        let messages = record.read_messages(&self.base_dir)?;
+        Ok((record, messages))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PlainTextWriter, Role, SessionStatus};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "active", 2, &[], None, None)?;
+        writer.append_message("s1", "codex", "2025-11-09", 1, "user", "fix the build", Some("2025-11-09T10:00:00Z"), None, None)?;
+        writer.append_message("s1", "codex", "2025-11-09", 2, "assistant", "done", None, None, None)?;
+
+        let compressed = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf()).with_compression(true);
+        let messages = vec![("user".to_string(), "what changed?".to_string())];
+        compressed.import_messages("g1", "goose", "2025-11-10", &messages, &[], None, false)?;
+        compressed.write_session("g1", "goose", Some("2025-11-10T08:00:00Z"), None, "closed", 1, &[], None, None)?;
+
+        let reader = PlainTextReader::with_base_dir(temp_dir.path().to_path_buf());
+        let ids = |records: Vec<SessionRecord>| records.into_iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(reader.list_sessions(None, None)?), vec!["s1", "g1"]);
+        assert_eq!(ids(reader.list_sessions(Some("Goose"), None)?), vec!["g1"]);
+        assert_eq!(ids(reader.list_sessions(None, Some("2025-11-09"))?), vec!["s1"]);
+        assert!(reader.list_sessions(Some("codex"), Some("2025-11-10"))?.is_empty());
+
+        let (record, messages) = reader.read_session("codex", "2025-11-09", "s1")?;
+        assert_eq!(record.status, SessionStatus::Active);
+        assert_eq!(record.date.as_deref(), Some("2025-11-09"));
+        assert_eq!(
+            messages,
+            vec![
+                Message { role: Role::User, content: "fix the build".to_string(), timestamp: Some("2025-11-09T10:00:00Z".to_string()) },
+                Message { role: Role::Assistant, content: "done".to_string(), timestamp: None },
+            ]
+        );
+
+        let (record, messages) = reader.read_session("goose", "2025-11-10", "g1")?;
+        assert_eq!(record.status, SessionStatus::Closed);
+        assert_eq!(messages[0].content, "what changed?");
+
+        assert!(reader.read_session("codex", "2025-11-09", "missing").is_err());
+        assert!(reader.read_session("codex", "..", "s1").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,672 @@
+// Full-text search over the plain-text log store
+// Direct scanning of messages.jsonl files, plus an SQLite FTS5 index at <base_dir>/.index.db
+
+use chrono::NaiveDate;
+use color_eyre::{eyre::Context, Result};
+use regex::{Regex, RegexBuilder};
+use rusqlite::{params, Connection};
+use std::collections::{hash_map::Entry, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::store::{self, SessionLocation, StoredMessage};
+use crate::PlainTextWriter;
+
+/// File name of the index database inside the log directory
+pub const INDEX_FILE: &str = ".index.db";
+
+/// Role of the index entry holding a session's note; notes are entry 0, ahead of the messages
+pub const NOTE_ROLE: &str = "note";
+
+/// A single search match
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub session_id: String,
+    pub assistant: String,
+    pub date: String,
+    pub message_id: usize,
+    pub role: String,
+    /// Excerpt around the match, with matched terms wrapped in `**`
+    pub snippet: String,
+}
+
+/// A search match with the messages stored around it
+#[derive(Debug, Clone)]
+pub struct ContextualHit {
+    pub hit: SearchHit,
+    /// Up to `context` messages before the match, oldest first
+    pub context_before: Vec<StoredMessage>,
+    /// Up to `context` messages after the match
+    pub context_after: Vec<StoredMessage>,
+}
+
+/// Characters of context shown either side of a scanned match
+const SNIPPET_RADIUS: usize = 60;
+
+/// Matches message content for direct log scanning
+pub struct QueryMatcher {
+    regex: Regex,
+}
+
+impl QueryMatcher {
+    /// Build a matcher; `query` is a literal substring unless `use_regex` is set
+    pub fn new(query: &str, use_regex: bool, case_sensitive: bool) -> Result<Self> {
+        let pattern = if use_regex {
+            query.to_string()
+        } else {
+            regex::escape(query)
+        };
+
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .with_context(|| format!("Invalid search pattern: {}", query))?;
+
+        Ok(QueryMatcher { regex })
+    }
+
+    /// Byte range of the first match in `text`
+    pub fn find(&self, text: &str) -> Option<Range<usize>> {
+        self.regex.find(text).map(|m| m.range())
+    }
+}
+
+/// Restrictions applied while scanning the log store
+#[derive(Debug, Clone, Default)]
+pub struct ScanFilter {
+    pub assistant: Option<String>,
+    pub role: Option<String>,
+    pub session_id: Option<String>,
+    /// Only sessions filed on or after this day
+    pub since: Option<NaiveDate>,
+    /// Only sessions filed on or before this day
+    pub until: Option<NaiveDate>,
+}
+
+impl ScanFilter {
+    fn allows_assistant(&self, assistant: &str) -> bool {
+        self.assistant.as_deref().is_none_or(|a| a.eq_ignore_ascii_case(assistant))
+    }
+
+    fn allows_session(&self, session_id: &str) -> bool {
+        self.session_id.as_deref().is_none_or(|id| id == session_id)
+    }
+
+    fn allows_role(&self, role: &str) -> bool {
+        self.role.as_deref().is_none_or(|r| r.eq_ignore_ascii_case(role))
+    }
+
+    /// Whether a session filed under the `date` directory falls within `since`..=`until`
+    fn allows_date(&self, date: &str) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok_and(|date| {
+            self.since.is_none_or(|since| date >= since) && self.until.is_none_or(|until| date <= until)
+        })
+    }
+
+    /// Apply the filter to hits from any source (e.g. the FTS index)
+    pub fn allows(&self, hit: &SearchHit) -> bool {
+        self.allows_assistant(&hit.assistant)
+            && self.allows_session(&hit.session_id)
+            && self.allows_role(&hit.role)
+            && self.allows_date(&hit.date)
+    }
+}
+
+/// Scan every stored messages.jsonl for matching messages (no index required)
+pub fn scan_logs(base_dir: &Path, matcher: &QueryMatcher, filter: &ScanFilter) -> Result<Vec<SearchHit>> {
+    let mut hits = Vec::new();
+
+    for location in store::list_sessions(base_dir)? {
+        if !filter.allows_assistant(&location.assistant)
+            || !filter.allows_session(&location.session_id)
+            || !filter.allows_date(&location.date)
+            || store::messages_file(&location.path).is_none()
+        {
+            continue;
+        }
+
+        if filter.allows_role(NOTE_ROLE) {
+            if let Some(note) = session_note(&location) {
+                if let Some(range) = matcher.find(&note) {
+                    hits.push(SearchHit {
+                        session_id: location.session_id.clone(),
+                        assistant: location.assistant.clone(),
+                        date: location.date.clone(),
+                        message_id: 0,
+                        role: NOTE_ROLE.to_string(),
+                        snippet: make_snippet(&note, range),
+                    });
+                }
+            }
+        }
+
+        for message in store::load_messages(&location.path)? {
+            if !filter.allows_role(&message.role) {
+                continue;
+            }
+
+            if let Some(range) = matcher.find(&message.content) {
+                hits.push(SearchHit {
+                    session_id: location.session_id.clone(),
+                    assistant: location.assistant.clone(),
+                    date: location.date.clone(),
+                    message_id: message.id,
+                    role: message.role.clone(),
+                    snippet: make_snippet(&message.content, range),
+                });
+            }
+        }
+    }
+
+    Ok(hits)
+}
+
+/// The note in a session's session.json, if it has one and the file is readable
+fn session_note(location: &SessionLocation) -> Option<String> {
+    crate::SessionRecord::from_dir(&location.path).ok()?.note
+}
+
+/// Attach the `context` messages either side of each hit, re-read from its session's messages.jsonl
+/// Hits whose session is no longer on disk get no context.
+pub fn with_context(base_dir: &Path, hits: Vec<SearchHit>, context: usize) -> Result<Vec<ContextualHit>> {
+    let layout = crate::Layout::load(base_dir)?;
+    let mut sessions: HashMap<PathBuf, Vec<StoredMessage>> = HashMap::new();
+    let mut contextual = Vec::with_capacity(hits.len());
+
+    for hit in hits {
+        let session_dir = layout.session_dir(base_dir, &hit.assistant, &hit.date, &hit.session_id);
+        let messages = match sessions.entry(session_dir) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let messages = if context > 0 && store::messages_file(entry.key()).is_some() {
+                    store::load_messages(entry.key())?
+                } else {
+                    Vec::new()
+                };
+                entry.insert(messages)
+            }
+        };
+
+        let (context_before, context_after) = match messages.iter().position(|m| m.id == hit.message_id) {
+            Some(pos) => (
+                messages[pos.saturating_sub(context)..pos].to_vec(),
+                messages[pos + 1..(pos + 1 + context).min(messages.len())].to_vec(),
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
+        contextual.push(ContextualHit { hit, context_before, context_after });
+    }
+
+    Ok(contextual)
+}
+
+/// Excerpt around a match on a single line, with the match wrapped in `**`
+fn make_snippet(content: &str, range: Range<usize>) -> String {
+    let floor = |mut i: usize| {
+        while !content.is_char_boundary(i) {
+            i -= 1;
+        }
+        i
+    };
+    let ceil = |mut i: usize| {
+        while !content.is_char_boundary(i) {
+            i += 1;
+        }
+        i
+    };
+
+    let start = floor(range.start.saturating_sub(SNIPPET_RADIUS));
+    let end = ceil((range.end + SNIPPET_RADIUS).min(content.len()));
+
+    let snippet = format!(
+        "{}{}**{}**{}{}",
+        if start > 0 { "…" } else { "" },
+        &content[start..range.start],
+        &content[range.clone()],
+        &content[range.end..end],
+        if end < content.len() { "…" } else { "" },
+    );
+
+    snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// SQLite FTS5 index of stored messages
+pub struct SearchIndex {
+    conn: Connection,
+    base_dir: PathBuf,
+}
+
+impl SearchIndex {
+    /// Open (or create) the index in the default log directory
+    pub fn open() -> Result<Self> {
+        let writer = PlainTextWriter::new()?;
+        Self::open_at(writer.base_dir())
+    }
+
+    /// Open (or create) the index in a custom log directory
+    pub fn open_at(base_dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(base_dir)
+            .with_context(|| format!("Failed to create directory: {}", base_dir.display()))?;
+
+        Self::open_file(&base_dir.join(INDEX_FILE), base_dir)
+    }
+
+    /// Open (or create) the index database at `db_path` for sessions stored under `base_dir`
+    pub(crate) fn open_file(db_path: &Path, base_dir: &Path) -> Result<Self> {
+        let conn = Connection::open(db_path)
+            .with_context(|| format!("Failed to open search index: {}", db_path.display()))?;
+
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+                session_id UNINDEXED,
+                assistant UNINDEXED,
+                date UNINDEXED,
+                message_id UNINDEXED,
+                role UNINDEXED,
+                content
+            )",
+        )?;
+
+        Ok(SearchIndex {
+            conn,
+            base_dir: base_dir.to_path_buf(),
+        })
+    }
+
+    /// The index database file
+    pub fn path(&self) -> PathBuf {
+        self.base_dir.join(INDEX_FILE)
+    }
+
+    /// Index (or re-index) one session's messages
+    /// Messages are numbered from 1 in the order given, matching messages.jsonl ids
+    pub fn index_session(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        messages: &[(String, String)],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        Self::replace_session(&tx, session_id, assistant, date, messages.iter().map(|(role, content)| Ok((role, content))))?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// [`Self::index_session`] for messages read one at a time, e.g. from an [`crate::ImportSpool`]
+    pub fn index_stream(
+        &self,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        messages: impl Iterator<Item = Result<(String, String)>>,
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        Self::replace_session(&tx, session_id, assistant, date, messages)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Index (or, with `None`, drop) a session's note
+    /// Notes stay in the index when the session's messages are re-indexed.
+    pub fn index_note(&self, session_id: &str, assistant: &str, date: &str, note: Option<&str>) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        Self::replace_note(&tx, session_id, assistant, date, note)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove a session from the index (e.g. after it was discarded)
+    pub fn remove_session(&self, session_id: &str, assistant: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM messages_fts WHERE session_id = ?1 AND assistant = ?2",
+            params![session_id, assistant],
+        )?;
+        Ok(())
+    }
+
+    /// Search message content and session notes; terms are matched as an implicit AND
+    pub fn search(&self, query: &str) -> Result<Vec<SearchHit>> {
+        let fts_query = Self::quote_query(query);
+        if fts_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT session_id, assistant, date, message_id, role,
+                    snippet(messages_fts, 5, '**', '**', '…', 16)
+             FROM messages_fts
+             WHERE messages_fts MATCH ?1
+             ORDER BY rank",
+        )?;
+
+        let hits = stmt
+            .query_map([fts_query], |row| {
+                Ok(SearchHit {
+                    session_id: row.get(0)?,
+                    assistant: row.get(1)?,
+                    date: row.get(2)?,
+                    message_id: row.get::<_, i64>(3)? as usize,
+                    role: row.get(4)?,
+                    snippet: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(hits)
+    }
+
+    /// [`Self::search`], with up to `context` stored messages either side of each hit
+    pub fn search_with_context(&self, query: &str, context: usize) -> Result<Vec<ContextualHit>> {
+        with_context(&self.base_dir, self.search(query)?, context)
+    }
+
+    /// Drop the index contents and rebuild from every stored messages.jsonl and note
+    /// Returns the number of sessions whose messages were indexed
+    pub fn rebuild_from_logs(&self) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM messages_fts", [])?;
+
+        let mut indexed = 0;
+        for location in store::list_sessions(&self.base_dir)? {
+            Self::replace_note(&tx, &location.session_id, &location.assistant, &location.date, session_note(&location).as_deref())?;
+            if store::messages_file(&location.path).is_none() {
+                continue;
+            }
+
+            let messages = store::load_messages(&location.path)?
+                .into_iter()
+                .map(|m| Ok((m.role, m.content)));
+
+            Self::replace_session(&tx, &location.session_id, &location.assistant, &location.date, messages)?;
+            indexed += 1;
+        }
+
+        tx.commit()?;
+        Ok(indexed)
+    }
+
+    /// Index `sessions` into this (empty) index in one transaction, calling `progress(done, total, session)` after each
+    ///
+    /// With `keep`, entries in the index at that path belonging to other
+    /// assistants are copied across first. Returns the number of messages indexed.
+    pub(crate) fn fill(
+        &self,
+        sessions: &[SessionLocation],
+        keep: Option<(&Path, &str)>,
+        mut progress: impl FnMut(usize, usize, &SessionLocation),
+    ) -> Result<usize> {
+        if let Some((old_path, assistant)) = keep.filter(|(old_path, _)| old_path.is_file()) {
+            self.conn.execute("ATTACH DATABASE ?1 AS old", [old_path.to_string_lossy()])?;
+            let copied = self.conn.execute(
+                "INSERT INTO messages_fts (session_id, assistant, date, message_id, role, content)
+                 SELECT session_id, assistant, date, message_id, role, content FROM old.messages_fts WHERE assistant != ?1",
+                [assistant],
+            );
+            self.conn.execute("DETACH DATABASE old", [])?;
+            copied.with_context(|| format!("Failed to read search index: {}", old_path.display()))?;
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut indexed = 0;
+        for (done, location) in sessions.iter().enumerate() {
+            Self::replace_note(&tx, &location.session_id, &location.assistant, &location.date, session_note(location).as_deref())?;
+            if store::messages_file(&location.path).is_some() {
+                let messages = store::load_messages(&location.path)?;
+                indexed += messages.len();
+                let messages = messages.into_iter().map(|m| Ok((m.role, m.content)));
+                Self::replace_session(&tx, &location.session_id, &location.assistant, &location.date, messages)?;
+            }
+            progress(done + 1, sessions.len(), location);
+        }
+        tx.commit()?;
+
+        Ok(indexed)
+    }
+
+    fn replace_session<S: AsRef<str>>(
+        conn: &Connection,
+        session_id: &str,
+        assistant: &str,
+        date: &str,
+        messages: impl Iterator<Item = Result<(S, S)>>,
+    ) -> Result<()> {
+        conn.execute(
+            "DELETE FROM messages_fts WHERE session_id = ?1 AND assistant = ?2 AND message_id != 0",
+            params![session_id, assistant],
+        )?;
+
+        let mut stmt = conn.prepare(
+            "INSERT INTO messages_fts (session_id, assistant, date, message_id, role, content)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+
+        for (idx, message) in messages.enumerate() {
+            let (role, content) = message?;
+            stmt.execute(params![session_id, assistant, date, (idx + 1) as i64, role.as_ref(), content.as_ref()])?;
+        }
+
+        Ok(())
+    }
+
+    fn replace_note(conn: &Connection, session_id: &str, assistant: &str, date: &str, note: Option<&str>) -> Result<()> {
+        conn.execute(
+            "DELETE FROM messages_fts WHERE session_id = ?1 AND assistant = ?2 AND message_id = 0",
+            params![session_id, assistant],
+        )?;
+        if let Some(note) = note {
+            conn.execute(
+                "INSERT INTO messages_fts (session_id, assistant, date, message_id, role, content)
+                 VALUES (?1, ?2, ?3, 0, ?4, ?5)",
+                params![session_id, assistant, date, NOTE_ROLE, note],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Quote each whitespace-separated term so user input can't trip FTS5 syntax
+    fn quote_query(query: &str) -> String {
+        query
+            .split_whitespace()
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sample_messages() -> Vec<(String, String)> {
+        vec![
+            ("user".to_string(), "How do I configure the FTS5 tokenizer?".to_string()),
+            ("assistant".to_string(), "Use the tokenize option when creating the table.".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_index_and_search() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let index = SearchIndex::open_at(temp_dir.path())?;
+
+        index.index_session("session-1", "claude-code", "2025-11-09", &sample_messages())?;
+
+        let hits = index.search("tokenizer")?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "session-1");
+        assert_eq!(hits[0].message_id, 1);
+        assert_eq!(hits[0].role, "user");
+        assert!(hits[0].snippet.contains("**tokenizer**"));
+
+        // Punctuation in the query must not be treated as FTS5 syntax
+        assert!(index.search("FTS5 \"tokenizer?").is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reindex_replaces_session() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let index = SearchIndex::open_at(temp_dir.path())?;
+
+        index.index_session("session-1", "codex", "2025-11-09", &sample_messages())?;
+        index.index_session("session-1", "codex", "2025-11-09", &sample_messages())?;
+
+        assert_eq!(index.search("table")?.len(), 1);
+
+        index.remove_session("session-1", "codex")?;
+        assert!(index.search("table")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_logs_filters() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        for (idx, (role, content)) in sample_messages().iter().enumerate() {
+            writer.append_message("session-1", "codex", "2025-11-09", idx + 1, role, content, None, None, None)?;
+            writer.append_message("session-2", "goose", "2025-11-09", idx + 1, role, content, None, None, None)?;
+        }
+
+        let matcher = QueryMatcher::new("TOKENIZ", false, false)?;
+        assert_eq!(scan_logs(temp_dir.path(), &matcher, &ScanFilter::default())?.len(), 4);
+
+        let filter = ScanFilter {
+            assistant: Some("goose".to_string()),
+            role: Some("assistant".to_string()),
+            ..ScanFilter::default()
+        };
+        let hits = scan_logs(temp_dir.path(), &matcher, &filter)?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "session-2");
+        assert!(hits[0].snippet.contains("**tokeniz**e option"));
+
+        let by_session = ScanFilter {
+            session_id: Some("session-1".to_string()),
+            ..ScanFilter::default()
+        };
+        let hits = scan_logs(temp_dir.path(), &matcher, &by_session)?;
+        assert_eq!(hits.len(), 2);
+        assert!(hits.iter().all(|hit| hit.session_id == "session-1"));
+
+        let day = NaiveDate::from_ymd_opt(2025, 11, 9);
+        let on_the_day = ScanFilter { since: day, until: day, ..ScanFilter::default() };
+        assert_eq!(scan_logs(temp_dir.path(), &matcher, &on_the_day)?.len(), 4);
+        let later = ScanFilter { since: NaiveDate::from_ymd_opt(2025, 11, 10), ..ScanFilter::default() };
+        assert!(scan_logs(temp_dir.path(), &matcher, &later)?.is_empty());
+
+        let case_sensitive = QueryMatcher::new("TOKENIZ", false, true)?;
+        assert!(scan_logs(temp_dir.path(), &case_sensitive, &ScanFilter::default())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_matcher_regex() -> Result<()> {
+        let regex = QueryMatcher::new(r"FTS\d", true, true)?;
+        assert_eq!(regex.find("the FTS5 table"), Some(4..8));
+
+        // Without --regex, metacharacters are literal
+        let literal = QueryMatcher::new("FTS.", false, true)?;
+        assert!(literal.find("the FTS5 table").is_none());
+
+        assert!(QueryMatcher::new("(unclosed", true, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippet_truncates_long_content() {
+        let content = format!("{}needle{}", "é".repeat(100), "x".repeat(100));
+        let start = content.find("needle").unwrap();
+        let snippet = make_snippet(&content, start..start + 6);
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("**needle**"));
+    }
+
+    #[test]
+    fn test_search_with_context() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let contents = ["Set up the project", "Done.", "Now the tokenizer", "Which one?", "FTS5", "Configured."];
+        for (idx, content) in contents.iter().enumerate() {
+            let role = if idx % 2 == 0 { "user" } else { "assistant" };
+            writer.append_message("session-1", "codex", "2025-11-09", idx + 1, role, content, None, None, None)?;
+        }
+
+        let index = SearchIndex::open_at(temp_dir.path())?;
+        index.rebuild_from_logs()?;
+
+        let hits = index.search_with_context("tokenizer", 2)?;
+        assert_eq!(hits.len(), 1);
+        let ids = |messages: &[StoredMessage]| messages.iter().map(|m| m.id).collect::<Vec<_>>();
+        assert_eq!(ids(&hits[0].context_before), vec![1, 2]);
+        assert_eq!(ids(&hits[0].context_after), vec![4, 5]);
+
+        // Windows stop at the ends of the session
+        let hits = index.search_with_context("Configured", 2)?;
+        assert_eq!(ids(&hits[0].context_before), vec![4, 5]);
+        assert!(hits[0].context_after.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebuild_from_logs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        for (idx, (role, content)) in sample_messages().iter().enumerate() {
+            writer.append_message("session-2", "goose", "2025-11-08", idx + 1, role, content, None, None, None)?;
+        }
+
+        let index = SearchIndex::open_at(temp_dir.path())?;
+        assert_eq!(index.rebuild_from_logs()?, 1);
+
+        let hits = index.search("tokenize option")?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].assistant, "goose");
+        assert_eq!(hits[0].date, "2025-11-08");
+        assert_eq!(hits[0].message_id, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reindex_all() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        for (idx, (role, content)) in sample_messages().iter().enumerate() {
+            writer.append_message("session-1", "goose", "2025-11-08", idx + 1, role, content, None, None, None)?;
+        }
+        writer.append_message("session-2", "codex", "2025-11-09", 1, "user", "Rename the parser module", None, None, None)?;
+
+        // An entry whose session no longer exists is dropped by the rebuild
+        let mut index = SearchIndex::open_at(temp_dir.path())?;
+        index.index_session("gone", "goose", "2025-11-01", &[("user".to_string(), "stale tokenizer".to_string())])?;
+
+        assert_eq!(writer.reindex_all(&mut index)?, 3);
+        assert_eq!(index.search("tokenizer")?.len(), 1);
+        assert_eq!(index.search("parser")?.len(), 1);
+
+        // Reindexing one assistant keeps the others' entries
+        writer.append_message("session-2", "codex", "2025-11-09", 2, "assistant", "Renamed to lexer", None, None, None)?;
+        let mut seen = Vec::new();
+        let indexed = writer.reindex(&mut index, Some("codex"), |done, total, session| {
+            seen.push((done, total, session.session_id.clone()))
+        })?;
+        assert_eq!(indexed, 2);
+        assert_eq!(seen, vec![(1, 1, "session-2".to_string())]);
+        assert_eq!(index.search("tokenizer")?.len(), 1);
+        assert_eq!(index.search("lexer")?.len(), 1);
+
+        let leftovers: Vec<_> = std::fs::read_dir(temp_dir.path())?
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+        Ok(())
+    }
+}
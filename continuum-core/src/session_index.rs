@@ -0,0 +1,200 @@
+// Per-assistant session index
+// <base_dir>/<assistant>/index.jsonl keeps one summary line per stored session, so listing needn't open every session.json
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre::Context, Result};
+
+use crate::{plaintext, store, Layout, SessionRecord};
+
+/// Index file name inside each assistant directory
+pub const INDEX_FILE: &str = "index.jsonl";
+
+/// Hidden directory holding each assistant's index in custom layouts, as `<assistant>.jsonl`
+pub const CUSTOM_LAYOUT_INDEX_DIR: &str = ".index";
+
+/// `<base_dir>/<assistant>/index.jsonl`, or `<base_dir>/.index/<assistant>.jsonl` in a custom layout
+/// (see [`crate::Layout`]), where the assistant needn't have a directory of its own
+pub fn index_path(base_dir: &Path, assistant: &str) -> PathBuf {
+    if Layout::load(base_dir).is_ok_and(|layout| layout.is_default()) {
+        base_dir.join(assistant).join(INDEX_FILE)
+    } else {
+        base_dir.join(CUSTOM_LAYOUT_INDEX_DIR).join(format!("{}.jsonl", assistant))
+    }
+}
+
+/// `index` with the line for the session in `metadata` (a session.json document) added or replaced
+///
+/// Each line is the session's [`SessionRecord`] with `date` set to its date directory.
+/// Lines that no longer parse are dropped.
+pub(crate) fn upsert(index: &str, metadata: &serde_json::Value, date: &str) -> Result<String> {
+    let mut record: SessionRecord = serde_json::from_value(metadata.clone())?;
+    record.date = Some(date.to_string());
+
+    let mut updated = String::with_capacity(index.len() + 256);
+    for line in index.lines() {
+        let keep = serde_json::from_str::<SessionRecord>(line).is_ok_and(|r| r.id != record.id || r.date != record.date);
+        if keep {
+            updated.push_str(line);
+            updated.push('\n');
+        }
+    }
+    updated.push_str(&serde_json::to_string(&record)?);
+    updated.push('\n');
+    Ok(updated)
+}
+
+/// Record a session.json just written for `assistant` under `date`
+pub(crate) fn record(base_dir: &Path, assistant: &str, date: &str, metadata: &serde_json::Value) -> Result<()> {
+    let path = index_path(base_dir, assistant);
+    let existing = match fs::read_to_string(&path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    write_index(&path, &upsert(&existing, metadata, date)?)
+}
+
+/// Drop a deleted session's line from its assistant's index, if there is one
+pub(crate) fn remove(base_dir: &Path, assistant: &str, date: &str, session_id: &str) -> Result<()> {
+    let path = index_path(base_dir, assistant);
+    let existing = match fs::read_to_string(&path) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    let kept: String = existing
+        .lines()
+        .filter(|line| {
+            serde_json::from_str::<SessionRecord>(line)
+                .is_ok_and(|r| r.id != session_id || r.date.as_deref() != Some(date))
+        })
+        .flat_map(|line| [line, "\n"])
+        .collect();
+    write_index(&path, &kept)
+}
+
+/// Every session listed in an assistant's index
+pub fn load(base_dir: &Path, assistant: &str) -> Result<Vec<SessionRecord>> {
+    let path = index_path(base_dir, assistant);
+    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).with_context(|| format!("Invalid line in {}", path.display())))
+        .collect()
+}
+
+/// The index's records, if it exists and still matches the sessions on disk
+///
+/// The index is stale when a session directory was added or removed behind
+/// its back, or a session.json was written after it (e.g. by a command that
+/// edits metadata directly); callers then scan the directories instead.
+pub fn fresh_records(base_dir: &Path, assistant: &str) -> Option<Vec<SessionRecord>> {
+    let indexed_at = fs::metadata(index_path(base_dir, assistant)).and_then(|m| m.modified()).ok()?;
+    let records = load(base_dir, assistant).ok()?;
+    let sessions = store::list_assistant_sessions(base_dir, assistant).ok()?;
+
+    let indexed: HashSet<(&str, &str)> = records
+        .iter()
+        .map(|r| (r.date.as_deref().unwrap_or_default(), r.id.as_str()))
+        .collect();
+    let current = indexed.len() == records.len()
+        && sessions.len() == records.len()
+        && sessions.iter().all(|s| {
+            indexed.contains(&(s.date.as_str(), s.session_id.as_str()))
+                && fs::metadata(s.path.join("session.json"))
+                    .and_then(|m| m.modified())
+                    .is_ok_and(|modified| modified <= indexed_at)
+        });
+
+    current.then_some(records)
+}
+
+/// Rewrite every assistant's index from its session directories
+/// Sessions without a readable session.json are left out. Returns how many sessions were indexed.
+pub fn rebuild(base_dir: &Path) -> Result<usize> {
+    let mut indexed = 0;
+
+    for assistant in store::list_assistants(base_dir)? {
+        let mut lines = String::new();
+        for location in store::list_assistant_sessions(base_dir, &assistant)? {
+            if let Ok(record) = SessionRecord::from_dir(&location.path) {
+                lines.push_str(&serde_json::to_string(&record)?);
+                lines.push('\n');
+                indexed += 1;
+            }
+        }
+        write_index(&index_path(base_dir, &assistant), &lines)?;
+    }
+
+    Ok(indexed)
+}
+
+/// Replace an index file atomically (temp file + rename)
+fn write_index(path: &Path, content: &str) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| dir.ends_with(CUSTOM_LAYOUT_INDEX_DIR)) {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    }
+    let tmp = plaintext::temp_path(path);
+    let written = fs::write(&tmp, content).and_then(|_| fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlainTextWriter;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_index_follows_writes_and_detects_staleness() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base = temp_dir.path();
+        let writer = PlainTextWriter::with_base_dir(base.to_path_buf());
+
+        writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "active", 2, &[], None, None)?;
+        writer.write_session("s2", "codex", Some("2025-11-10T10:00:00Z"), None, "active", 5, &[], None, None)?;
+        writer.update_session_metadata("s1", "codex", "2025-11-09", json!({ "status": "closed", "message_count": 3 }))?;
+
+        let records = fresh_records(base, "codex").expect("index should be fresh");
+        assert_eq!(records.len(), 2);
+        let s1 = records.iter().find(|r| r.id == "s1").unwrap();
+        assert_eq!(s1.date.as_deref(), Some("2025-11-09"));
+        assert_eq!(s1.message_count, Some(3));
+        assert_eq!(fs::read_to_string(index_path(base, "codex"))?.lines().count(), 2);
+
+        // A session directory the index doesn't know about makes it stale
+        let stray = base.join("codex/2025-11-11/s3");
+        fs::create_dir_all(&stray)?;
+        fs::write(stray.join("session.json"), r#"{"id": "s3", "assistant": "codex", "status": "closed"}"#)?;
+        assert!(fresh_records(base, "codex").is_none());
+
+        assert_eq!(rebuild(base)?, 3);
+        assert_eq!(fresh_records(base, "codex").map(|r| r.len()), Some(3));
+        assert!(fresh_records(base, "goose").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_summary_reads_metadata_from_a_fresh_index() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base = temp_dir.path();
+        let writer = PlainTextWriter::with_base_dir(base.to_path_buf());
+        writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 0, &[], None, None)?;
+        writer.update_session_metadata("s1", "codex", "2025-11-09", json!({ "cost_usd": 0.5 }))?;
+
+        // Rewriting the index (not session.json) shows which of the two the summary read
+        let path = index_path(base, "codex");
+        fs::write(&path, fs::read_to_string(&path)?.replace("0.5", "0.75"))?;
+        assert_eq!(writer.summary()?.assistants["codex"].total_cost_usd, 0.75);
+        Ok(())
+    }
+}
@@ -0,0 +1,662 @@
+// Whole-session operations on the plain-text log store
+// Merging two stored sessions into one, diffing two versions of a session, and deleting sessions
+
+use chrono::{DateTime, Utc};
+use color_eyre::{eyre::{bail, Context}, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::plaintext::parse_timestamp;
+use crate::store::{self, SessionLocation, StoredMessage};
+use crate::{search, session_index, Message, MessageCompressor, PlainTextWriter, SearchIndex, SessionCost};
+
+/// A stored session, as located by [`store::find_session`]
+pub type SessionRef = SessionLocation;
+
+/// Merge two sessions of the same assistant into a new session `output_id`
+/// (see [`merge_all`]; `a` and `b` may be given in either order)
+pub fn merge_sessions(
+    a: &SessionRef,
+    b: &SessionRef,
+    output_id: &str,
+    compressor: &MessageCompressor,
+    writer: &PlainTextWriter,
+) -> Result<SessionRef> {
+    merge_all(&[a.clone(), b.clone()], output_id, compressor, writer)
+}
+
+/// Merge two or more sessions of the same assistant into a new session `output_id`
+///
+/// Sessions go in the order they started (those with no known start keep
+/// their place after the rest), and the combined messages are then ordered
+/// by timestamp (a message without one stays after the message before it).
+/// Messages are noise-filtered, renumbered from 1 and keep their original
+/// timestamps. The new session.json unions the sessions' skills and tags,
+/// sums their cost and records the sources under `merged_from`. The
+/// originals are left in place; see [`delete_session`].
+pub fn merge_all(
+    sessions: &[SessionRef],
+    output_id: &str,
+    compressor: &MessageCompressor,
+    writer: &PlainTextWriter,
+) -> Result<SessionRef> {
+    let Some(head) = sessions.first().filter(|_| sessions.len() >= 2) else {
+        bail!("Merging needs at least two sessions");
+    };
+    if let Some(other) = sessions.iter().find(|s| s.assistant != head.assistant) {
+        bail!("Cannot merge sessions from different assistants ({} and {})", head.assistant, other.assistant);
+    }
+    for (idx, session) in sessions.iter().enumerate() {
+        if sessions[..idx].iter().any(|earlier| earlier.path == session.path) {
+            bail!("Cannot merge session '{}' with itself", session.session_id);
+        }
+    }
+    let assistant = head.assistant.as_str();
+    if store::list_sessions(writer.base_dir())?
+        .iter()
+        .any(|s| s.assistant == assistant && s.session_id == output_id)
+    {
+        bail!("Session '{}' already exists for {}", output_id, assistant);
+    }
+
+    let mut parts = sessions.iter().map(SessionData::load).collect::<Result<Vec<_>>>()?;
+    // Stable, so sessions with the same (or no) start keep the order given
+    parts.sort_by_key(|part| part.start().map_or((1, None), |start| (0, Some(start))));
+
+    // Sort keys carry forward so untimed messages stay put; the sort is stable
+    let mut key = parts[0].start_time.as_deref().and_then(parse_timestamp);
+    let mut merged: Vec<(Option<DateTime<Utc>>, StoredMessage)> = Vec::new();
+    for message in parts.iter().flat_map(|part| &part.messages) {
+        key = message.timestamp.as_deref().and_then(parse_timestamp).or(key);
+        merged.push((key, message.clone()));
+    }
+    merged.sort_by_key(|(key, _)| *key);
+
+    let messages: Vec<StoredMessage> = merged
+        .into_iter()
+        .filter_map(|(_, message)| {
+            let content = compressor.compress(&message.content)?;
+            Some(StoredMessage { content, ..message })
+        })
+        .collect();
+
+    let last = &parts[parts.len() - 1];
+    let start_time = parts[0]
+        .start_time
+        .clone()
+        .or_else(|| messages.first().and_then(|m| m.timestamp.clone()));
+    let end_time = messages
+        .last()
+        .and_then(|m| m.timestamp.clone())
+        .or_else(|| last.metadata["end_time"].as_str().map(str::to_string));
+
+    let (mut skills, mut tags) = (Vec::new(), Vec::new());
+    for part in &parts {
+        union_into(&mut skills, part.strings("skills"));
+        union_into(&mut tags, part.strings("tags"));
+    }
+    let metadata: Vec<&Value> = parts.iter().map(|part| &part.metadata).collect();
+
+    let session_dir = writer.write_session(
+        output_id,
+        assistant,
+        start_time.as_deref(),
+        end_time.as_deref(),
+        "closed",
+        messages.len(),
+        &skills,
+        combined_cost(&metadata).as_ref(),
+        None,
+    )?;
+
+    let records: Vec<Value> = messages
+        .iter()
+        .enumerate()
+        .map(|(idx, m)| match m.tool_name {
+            Some(ref tool_name) => PlainTextWriter::tool_record(idx + 1, tool_name, &m.content, m.timestamp.as_deref(), m.tokens),
+            None => PlainTextWriter::message_record(idx + 1, &m.role, &m.content, m.timestamp.as_deref(), m.tokens, m.model.as_deref()),
+        })
+        .collect();
+    writer.append_records(&session_dir, &records)?;
+
+    let date = writer.session_date(output_id, assistant, start_time.as_deref());
+    let merged_from: Vec<&str> = parts.iter().map(|part| part.location.session_id.as_str()).collect();
+    let mut updates = json!({ "merged_from": merged_from });
+    if !tags.is_empty() {
+        updates["tags"] = json!(tags);
+    }
+    writer.update_session_metadata(output_id, assistant, &date, updates)?;
+
+    Ok(SessionRef {
+        assistant: assistant.to_string(),
+        date,
+        session_id: output_id.to_string(),
+        path: session_dir,
+    })
+}
+
+/// Delete a stored session's directory (and its date directory, if now empty)
+/// Returns the number of bytes the session's files took up
+pub fn delete_session(session: &SessionRef) -> Result<u64> {
+    let size = dir_size(&session.path)?;
+    fs::remove_dir_all(&session.path)
+        .with_context(|| format!("Failed to delete {}", session.path.display()))?;
+
+    if let Some(date_dir) = session.path.parent() {
+        // Only succeeds when no other session is filed under that date
+        let _ = fs::remove_dir(date_dir);
+    }
+    Ok(size)
+}
+
+/// What [`PlainTextWriter::delete_session_confirmed`] removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeletedSession {
+    /// The session directory, now gone
+    pub path: PathBuf,
+    pub message_count: usize,
+    pub date: String,
+    /// Bytes the session's files took up
+    pub size: u64,
+}
+
+/// Deletion refused by [`PlainTextWriter::delete_session`]
+///
+/// Returned inside the eyre report; callers can `downcast_ref::<DeleteError>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteError {
+    /// The session exists and can be deleted, but only through [`PlainTextWriter::delete_session_confirmed`]
+    ConfirmationRequired,
+}
+
+impl std::fmt::Display for DeleteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeleteError::ConfirmationRequired => write!(f, "Deleting a session needs confirmation"),
+        }
+    }
+}
+
+impl std::error::Error for DeleteError {}
+
+impl PlainTextWriter {
+    /// Check that a stored session can be deleted, without deleting it
+    ///
+    /// Fails as [`Self::delete_session_confirmed`] would (bad names, missing
+    /// session, a directory outside the log tree) and otherwise with
+    /// [`DeleteError::ConfirmationRequired`]: deleting takes an explicit
+    /// call to [`Self::delete_session_confirmed`], e.g. once the user agreed.
+    pub fn delete_session(&self, session_id: &str, assistant: &str, date: &str) -> Result<DeletedSession> {
+        self.deletable_session_dir(session_id, assistant, date)?;
+        Err(DeleteError::ConfirmationRequired.into())
+    }
+
+    /// Delete a stored session's directory and drop it from the search and session indexes
+    pub fn delete_session_confirmed(&self, session_id: &str, assistant: &str, date: &str) -> Result<DeletedSession> {
+        let path = self.deletable_session_dir(session_id, assistant, date)?;
+        let message_count = match store::messages_file(&path) {
+            Some(_) => store::load_messages(&path)?.len(),
+            None => 0,
+        };
+
+        let size = delete_session(&SessionRef {
+            assistant: assistant.to_string(),
+            date: date.to_string(),
+            session_id: session_id.to_string(),
+            path: path.clone(),
+        })?;
+        if let Some(parent) = path.parent() {
+            crate::layout::remove_empty_dirs(self.base_dir(), parent);
+        }
+
+        // The session is gone by now, so failing to update an index is only worth a warning
+        if self.base_dir().join(search::INDEX_FILE).exists() {
+            let removed = SearchIndex::open_at(self.base_dir()).and_then(|index| index.remove_session(session_id, assistant));
+            if let Err(e) = removed {
+                eprintln!("⚠ Warning: Failed to update search index: {}", e);
+            }
+        }
+        if let Err(e) = session_index::remove(self.base_dir(), assistant, date, session_id) {
+            eprintln!("⚠ Warning: Failed to update session index: {}", e);
+        }
+
+        Ok(DeletedSession { path, message_count, date: date.to_string(), size })
+    }
+
+    /// The session's directory, if it exists and lies inside the log tree
+    fn deletable_session_dir(&self, session_id: &str, assistant: &str, date: &str) -> Result<PathBuf> {
+        check_path_component("session ID", session_id)?;
+        check_path_component("assistant", assistant)?;
+        check_path_component("date", date)?;
+
+        let path = self.session_dir(assistant, date, session_id);
+        if !path.is_dir() {
+            bail!("No stored session '{}' under {}/{}", session_id, assistant, date);
+        }
+
+        // Never follow a symlinked session directory out of the log tree
+        let base_dir = self.base_dir().canonicalize()?;
+        let resolved = path.canonicalize()?;
+        if !resolved.starts_with(&base_dir) || resolved == base_dir {
+            bail!("Refusing to delete {}: outside {}", path.display(), base_dir.display());
+        }
+        Ok(path)
+    }
+}
+
+/// Reject a session ID, assistant or date that isn't a single plain path component
+///
+/// Guards user-supplied names before they are joined onto the log directory,
+/// so e.g. `../../` can never resolve outside it.
+pub fn check_path_component(kind: &str, name: &str) -> Result<()> {
+    let mut components = Path::new(name).components();
+    let plain = matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+        && !name.contains(['/', '\\']);
+    if !plain {
+        bail!("Invalid {} '{}'", kind, name);
+    }
+    Ok(())
+}
+
+/// Total size in bytes of the files under `dir`
+fn dir_size(dir: &Path) -> Result<u64> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            size += dir_size(&entry.path())?;
+        } else {
+            size += entry.metadata()?.len();
+        }
+    }
+    Ok(size)
+}
+
+/// Most LCS cells [`diff_sessions`] will fill before giving up on matching the middle
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// What changed between two versions of a session's messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionDiff<M = Message> {
+    /// Messages only in the new version, in order
+    pub added: Vec<M>,
+    /// Messages only in the old version, in order (empty when the session only grew)
+    pub removed: Vec<M>,
+    /// Messages present in both versions
+    pub unchanged_count: usize,
+}
+
+impl<M> SessionDiff<M> {
+    /// True when the new version is the old one with messages appended
+    pub fn is_append_only(&self) -> bool {
+        self.removed.is_empty()
+    }
+}
+
+/// Compare two versions of a session's messages
+///
+/// Works on [`Message`]s or on the `(role, content)` pairs used by the importers.
+/// The common prefix and suffix are matched directly, and the middle is
+/// aligned by longest common subsequence. If the middle is very large
+/// (see `MAX_DIFF_CELLS`), all of it is reported as removed and added.
+pub fn diff_sessions<M: PartialEq + Clone>(old: &[M], new: &[M]) -> SessionDiff<M> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let (old_rest, new_rest) = (&old[prefix..], &new[prefix..]);
+    let suffix = old_rest
+        .iter()
+        .rev()
+        .zip(new_rest.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old_rest[..old_rest.len() - suffix];
+    let new_mid = &new_rest[..new_rest.len() - suffix];
+
+    let mut diff = SessionDiff { added: Vec::new(), removed: Vec::new(), unchanged_count: prefix + suffix };
+
+    if old_mid.is_empty() || new_mid.is_empty() || old_mid.len() * new_mid.len() > MAX_DIFF_CELLS {
+        diff.removed = old_mid.to_vec();
+        diff.added = new_mid.to_vec();
+        return diff;
+    }
+
+    // lcs[i][j]: longest common subsequence of old_mid[i..] and new_mid[j..]
+    let (n, m) = (old_mid.len(), new_mid.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_mid[i] == new_mid[j] {
+            diff.unchanged_count += 1;
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.removed.push(old_mid[i].clone());
+            i += 1;
+        } else {
+            diff.added.push(new_mid[j].clone());
+            j += 1;
+        }
+    }
+    diff.removed.extend_from_slice(&old_mid[i..]);
+    diff.added.extend_from_slice(&new_mid[j..]);
+    diff
+}
+
+/// A session's location, metadata and messages, loaded for merging
+struct SessionData {
+    location: SessionRef,
+    metadata: Value,
+    start_time: Option<String>,
+    messages: Vec<StoredMessage>,
+}
+
+impl SessionData {
+    fn load(location: &SessionRef) -> Result<Self> {
+        let session_json = location.path.join("session.json");
+        let metadata: Value = match fs::read_to_string(&session_json) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", session_json.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => json!({}),
+            Err(e) => return Err(e.into()),
+        };
+        let messages = store::load_messages(&location.path)?;
+        let start_time = metadata["start_time"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| messages.iter().find_map(|m| m.timestamp.clone()));
+
+        Ok(SessionData {
+            location: location.clone(),
+            metadata,
+            start_time,
+            messages,
+        })
+    }
+
+    /// Earliest known point in the session, used to decide which one goes first
+    fn start(&self) -> Option<DateTime<Utc>> {
+        let from_messages = self
+            .messages
+            .iter()
+            .filter_map(|m| m.timestamp.as_deref().and_then(parse_timestamp))
+            .min();
+        let declared = self.start_time.as_deref().and_then(parse_timestamp);
+        match (declared, from_messages) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn strings(&self, key: &str) -> Vec<String> {
+        self.metadata[key]
+            .as_array()
+            .map(|items| items.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn union_into(target: &mut Vec<String>, items: Vec<String>) {
+    for item in items {
+        if !target.contains(&item) {
+            target.push(item);
+        }
+    }
+}
+
+/// Sum of the sessions' recorded cost, or `None` if none has one
+fn combined_cost(metadata: &[&Value]) -> Option<SessionCost> {
+    fn cost_of(metadata: &Value) -> Option<f64> {
+        metadata["cost_usd"].as_f64().or_else(|| metadata["total_cost_usd"].as_f64())
+    }
+    fn add(x: Option<u64>, y: Option<u64>) -> Option<u64> {
+        match (x, y) {
+            (Some(x), Some(y)) => Some(x + y),
+            (x, y) => x.or(y),
+        }
+    }
+
+    if metadata.iter().all(|m| cost_of(m).is_none()) {
+        return None;
+    }
+    Some(metadata.iter().fold(SessionCost::default(), |total, m| SessionCost {
+        total_cost_usd: total.total_cost_usd + cost_of(m).unwrap_or(0.0),
+        duration_ms: total.duration_ms + m["duration_ms"].as_u64().unwrap_or(0),
+        input_tokens: add(total.input_tokens, m["input_tokens"].as_u64()),
+        output_tokens: add(total.output_tokens, m["output_tokens"].as_u64()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Role;
+    use tempfile::TempDir;
+
+    fn write_fixture(
+        writer: &PlainTextWriter,
+        session_id: &str,
+        start: &str,
+        messages: &[(&str, &str, &str)],
+        extra: Value,
+    ) -> Result<SessionRef> {
+        writer.write_session(session_id, "claude-code", Some(start), None, "closed", messages.len(), &[], None, None)?;
+        let date = writer.date_of(Some(start));
+        for (idx, (role, content, timestamp)) in messages.iter().enumerate() {
+            writer.append_message(session_id, "claude-code", &date, idx + 1, role, content, Some(timestamp), None, None)?;
+        }
+        writer.update_session_metadata(session_id, "claude-code", &date, extra)?;
+        store::find_session(writer.base_dir(), session_id, Some("claude-code"))
+    }
+
+    #[test]
+    fn test_merge_orders_by_timestamp_and_swaps() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        let later = write_fixture(
+            &writer,
+            "later",
+            "2025-11-10T09:00:00Z",
+            &[
+                ("user", "Picking up where we left off", "2025-11-10T09:00:00Z"),
+                ("assistant", "Thanks!", "2025-11-10T09:00:05Z"),
+            ],
+            json!({"tags": ["project:x"], "cost_usd": 0.25}),
+        )?;
+        let earlier = write_fixture(
+            &writer,
+            "earlier",
+            "2025-11-09T22:00:00Z",
+            &[
+                ("user", "Start the migration", "2025-11-09T22:00:00Z"),
+                ("assistant", "Migration started, step one done", "2025-11-09T22:01:00Z"),
+            ],
+            json!({"tags": ["project:x", "urgent"], "cost_usd": 0.5}),
+        )?;
+
+        // Later session passed first: the merge still puts the earlier one first
+        let merged = merge_sessions(&later, &earlier, "combined", &MessageCompressor::new(), &writer)?;
+        assert_eq!(merged.date, "2025-11-09");
+        assert_eq!(merged.path, temp_dir.path().join("claude-code/2025-11-09/combined"));
+
+        let messages = store::load_messages(&merged.path)?;
+        let contents: Vec<&str> = messages.iter().map(|m| m.content.as_str()).collect();
+        // "Thanks!" is noise and gets filtered out
+        assert_eq!(
+            contents,
+            ["Start the migration", "Migration started, step one done", "Picking up where we left off"]
+        );
+        assert_eq!(messages.iter().map(|m| m.id).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(messages[2].timestamp.as_deref(), Some("2025-11-10T09:00:00Z"));
+
+        let metadata: Value = serde_json::from_str(&fs::read_to_string(merged.path.join("session.json"))?)?;
+        assert_eq!(metadata["merged_from"], json!(["earlier", "later"]));
+        assert_eq!(metadata["tags"], json!(["project:x", "urgent"]));
+        assert_eq!(metadata["cost_usd"], 0.75);
+        assert_eq!(metadata["message_count"], 3);
+        assert_eq!(metadata["end_time"], "2025-11-10T09:00:00Z");
+
+        // Originals are untouched until deleted explicitly
+        assert!(later.path.exists());
+        let size = fs::metadata(later.path.join("session.json"))?.len() + fs::metadata(later.path.join("messages.jsonl"))?.len();
+        assert_eq!(delete_session(&later)?, size);
+        assert!(!later.path.exists());
+        assert!(!temp_dir.path().join("claude-code/2025-11-10").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_rejects_existing_output_and_mixed_assistants() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        let a = write_fixture(&writer, "a", "2025-11-09T10:00:00Z", &[("user", "First part", "2025-11-09T10:00:00Z")], json!({}))?;
+        let b = write_fixture(&writer, "b", "2025-11-09T11:00:00Z", &[("user", "Second part", "2025-11-09T11:00:00Z")], json!({}))?;
+
+        let err = merge_sessions(&a, &b, "a", &MessageCompressor::new(), &writer).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+
+        let other = SessionRef { assistant: "codex".to_string(), ..b.clone() };
+        assert!(merge_sessions(&a, &other, "ab", &MessageCompressor::new(), &writer).is_err());
+        assert!(merge_sessions(&a, &a, "ab", &MessageCompressor::new(), &writer).is_err());
+        assert!(merge_all(std::slice::from_ref(&a), "ab", &MessageCompressor::new(), &writer).is_err());
+        assert!(merge_all(&[a.clone(), b.clone(), other], "ab", &MessageCompressor::new(), &writer).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_all_combines_several_sessions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        let late = write_fixture(&writer, "late", "2025-11-09T12:00:00Z", &[("user", "Third", "2025-11-09T12:00:00Z")], json!({"cost_usd": 0.5}))?;
+        let early = write_fixture(
+            &writer,
+            "early",
+            "2025-11-09T10:00:00Z",
+            &[("user", "First", "2025-11-09T10:00:00Z"), ("assistant", "Second", "2025-11-09T10:01:00Z")],
+            json!({"cost_usd": 0.25, "tags": ["bug"]}),
+        )?;
+        let middle = write_fixture(&writer, "middle", "2025-11-09T11:00:00Z", &[("assistant", "Between", "2025-11-09T11:00:00Z")], json!({"tags": ["bug", "ui"]}))?;
+
+        let merged = merge_all(&[late, early, middle], "all", &MessageCompressor::new(), &writer)?;
+        let messages = store::load_messages(&merged.path)?;
+        let contents: Vec<&str> = messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["First", "Second", "Between", "Third"]);
+        assert_eq!(messages.iter().map(|m| m.id).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+        let metadata: Value = serde_json::from_str(&fs::read_to_string(merged.path.join("session.json"))?)?;
+        assert_eq!(metadata["message_count"], 4);
+        assert_eq!(metadata["merged_from"], json!(["early", "middle", "late"]));
+        assert_eq!(metadata["tags"], json!(["bug", "ui"]));
+        assert_eq!(metadata["cost_usd"], 0.75);
+
+        // The originals are untouched
+        assert_eq!(store::find_session(writer.base_dir(), "early", None)?.session_id, "early");
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_session_needs_confirmation() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let session = write_fixture(
+            &writer,
+            "doomed",
+            "2025-11-09T10:00:00Z",
+            &[("user", "remove me", "2025-11-09T10:00:00Z"), ("assistant", "ok", "2025-11-09T10:00:05Z")],
+            json!({}),
+        )?;
+        let index = SearchIndex::open_at(writer.base_dir())?;
+        index.rebuild_from_logs()?;
+
+        let refused = writer.delete_session("doomed", "claude-code", "2025-11-09").unwrap_err();
+        assert_eq!(refused.downcast_ref::<DeleteError>(), Some(&DeleteError::ConfirmationRequired));
+        assert!(session.path.exists());
+        assert!(writer.delete_session("missing", "claude-code", "2025-11-09").unwrap_err().downcast_ref::<DeleteError>().is_none());
+        assert!(writer.delete_session_confirmed("../doomed", "claude-code", "2025-11-09").is_err());
+
+        let deleted = writer.delete_session_confirmed("doomed", "claude-code", "2025-11-09")?;
+        assert_eq!(deleted.path, session.path);
+        assert_eq!(deleted.message_count, 2);
+        assert_eq!(deleted.date, "2025-11-09");
+        assert!(!session.path.exists());
+        assert!(index.search("remove")?.is_empty());
+        assert!(session_index::load(writer.base_dir(), "claude-code")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_session_survives_a_broken_search_index() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let session = write_fixture(&writer, "doomed", "2025-11-09T10:00:00Z", &[("user", "remove me", "2025-11-09T10:00:00Z")], json!({}))?;
+        std::fs::write(writer.base_dir().join(search::INDEX_FILE), "not a database")?;
+
+        let deleted = writer.delete_session_confirmed("doomed", "claude-code", "2025-11-09")?;
+        assert_eq!(deleted.message_count, 1);
+        assert!(!session.path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_path_component_rejects_traversal() {
+        assert!(check_path_component("session", "abc-123").is_ok());
+        assert!(check_path_component("session", "rollout.2025-11-09").is_ok());
+
+        for bad in ["", ".", "..", "../../", "a/b", "a\\b", "/etc"] {
+            assert!(check_path_component("session", bad).is_err(), "{:?} accepted", bad);
+        }
+    }
+
+    #[test]
+    fn test_diff_sessions_append_and_edit() {
+        let pair = |role: &str, text: &str| (role.to_string(), text.to_string());
+        let old = vec![pair("user", "a"), pair("assistant", "b"), pair("user", "c")];
+
+        let grown = vec![pair("user", "a"), pair("assistant", "b"), pair("user", "c"), pair("assistant", "d")];
+        let diff = diff_sessions(&old, &grown);
+        assert_eq!(diff.added, vec![pair("assistant", "d")]);
+        assert!(diff.is_append_only());
+        assert_eq!(diff.unchanged_count, 3);
+
+        let edited = vec![pair("user", "a"), pair("assistant", "B"), pair("user", "c")];
+        let diff = diff_sessions(&old, &edited);
+        assert_eq!(diff.removed, vec![pair("assistant", "b")]);
+        assert_eq!(diff.added, vec![pair("assistant", "B")]);
+        assert_eq!(diff.unchanged_count, 2);
+
+        let same = diff_sessions(&old, &old);
+        assert!(same.added.is_empty() && same.removed.is_empty());
+        assert_eq!(same.unchanged_count, 3);
+    }
+
+    #[test]
+    fn test_diff_sessions_messages_realign_around_insertions() {
+        let msg = |role: Role, text: &str| Message { role, content: text.to_string(), timestamp: None };
+        let old = vec![msg(Role::User, "one"), msg(Role::Assistant, "two"), msg(Role::User, "three"), msg(Role::Assistant, "four")];
+        let new = vec![
+            msg(Role::User, "one"),
+            msg(Role::Assistant, "inserted"),
+            msg(Role::Assistant, "two"),
+            msg(Role::Assistant, "four"),
+        ];
+
+        let diff = diff_sessions(&old, &new);
+        assert_eq!(diff.added, vec![msg(Role::Assistant, "inserted")]);
+        assert_eq!(diff.removed, vec![msg(Role::User, "three")]);
+        assert_eq!(diff.unchanged_count, 3);
+    }
+}
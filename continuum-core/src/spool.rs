@@ -0,0 +1,165 @@
+// Spooling an import's messages to a temporary file
+// Lets imports stream sessions of any size through the noise filter in constant memory
+
+use color_eyre::{eyre::Context, Result};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::fs;
+use std::io::{BufRead, BufWriter, Write};
+use tempfile::NamedTempFile;
+
+use crate::plaintext::{hash_message, PlainTextWriter};
+use crate::redaction::Redactor;
+use crate::store::open_messages;
+
+/// Messages of one import, written to a temporary JSONL file as they arrive
+///
+/// Importers push each message once it has been filtered, then hand the spool
+/// to [`PlainTextWriter::import_spool`], which reads it back to append or
+/// rewrite the stored session. Only the content fingerprint and a few counters
+/// are kept in memory. The file is removed when the spool is dropped.
+pub struct ImportSpool {
+    // Declared before `temp` so buffered writes are flushed before the file is removed
+    file: RefCell<BufWriter<fs::File>>,
+    temp: NamedTempFile,
+    hasher: blake3::Hasher,
+    len: usize,
+    first_timestamp: Option<String>,
+}
+
+impl ImportSpool {
+    /// Empty spool in the system temp directory
+    /// The file gets an unpredictable name and is readable by its owner only.
+    pub fn new() -> Result<Self> {
+        let temp = tempfile::Builder::new()
+            .prefix("continuum-import-")
+            .suffix(".jsonl")
+            .tempfile()
+            .context("Failed to create import spool")?;
+        let file = temp.reopen().with_context(|| format!("Failed to open {}", temp.path().display()))?;
+
+        Ok(ImportSpool {
+            file: RefCell::new(BufWriter::new(file)),
+            temp,
+            hasher: blake3::Hasher::new(),
+            len: 0,
+            first_timestamp: None,
+        })
+    }
+
+    /// Spool holding `messages`, each stamped with its entry in `timestamps` (which may be shorter)
+    pub fn from_messages(messages: &[(String, String)], timestamps: &[Option<String>]) -> Result<Self> {
+        let mut spool = Self::new()?;
+        for (idx, (role, content)) in messages.iter().enumerate() {
+            spool.push(role, content, timestamps.get(idx).and_then(Option::as_deref), None)?;
+        }
+        Ok(spool)
+    }
+
+    /// Add the next message, as messages.jsonl will store it
+    /// `model` is the model the source says wrote it, if any.
+    pub fn push(&mut self, role: &str, content: &str, timestamp: Option<&str>, model: Option<&str>) -> Result<()> {
+        self.len += 1;
+        hash_message(&mut self.hasher, role, content);
+        if self.first_timestamp.is_none() {
+            self.first_timestamp = timestamp.map(str::to_string);
+        }
+
+        let record = PlainTextWriter::message_record(
+            self.len,
+            role,
+            content,
+            timestamp,
+            Some(PlainTextWriter::count_tokens(content)),
+            model,
+        );
+        let mut file = self.file.borrow_mut();
+        serde_json::to_writer(&mut *file, &record)?;
+        file.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Number of messages pushed
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Timestamp of the first message that carries one
+    pub fn first_timestamp(&self) -> Option<&str> {
+        self.first_timestamp.as_deref()
+    }
+
+    /// Fingerprint of the spooled messages (see [`PlainTextWriter::content_hash`])
+    pub fn content_hash(&self) -> String {
+        self.hasher.clone().finalize().to_hex().to_string()
+    }
+
+    /// The spooled messages.jsonl records, in order
+    pub fn records(&self) -> Result<impl Iterator<Item = Result<Value>>> {
+        self.file.borrow_mut().flush()?;
+        let lines = open_messages(self.temp.path())?.lines();
+        Ok(lines.map(|line| Ok(serde_json::from_str(&line?)?)))
+    }
+
+    /// A copy of this spool with secrets in each message masked by `redactor`
+    pub(crate) fn redacted(&self, redactor: &Redactor) -> Result<ImportSpool> {
+        let mut redacted = ImportSpool::new()?;
+        for record in self.records()? {
+            let record = record?;
+            let content = redactor.redact(record["content"].as_str().unwrap_or_default()).0;
+            redacted.push(
+                record["role"].as_str().unwrap_or_default(),
+                &content,
+                record["timestamp"].as_str(),
+                record["model"].as_str(),
+            )?;
+        }
+        Ok(redacted)
+    }
+
+    /// Role and content of each spooled message, in order
+    pub fn messages(&self) -> Result<impl Iterator<Item = Result<(String, String)>>> {
+        Ok(self.records()?.map(|record| {
+            let record = record?;
+            let field = |name: &str| record[name].as_str().unwrap_or_default().to_string();
+            Ok((field("role"), field("content")))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spool_round_trip() -> Result<()> {
+        let messages = vec![
+            ("user".to_string(), "hello".to_string()),
+            ("assistant".to_string(), "hi there".to_string()),
+        ];
+        let spool = ImportSpool::from_messages(&messages, &[None, Some("2025-11-09T10:00:00Z".to_string())])?;
+        let path = spool.temp.path().to_path_buf();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+        }
+
+        assert_eq!(spool.len(), 2);
+        assert_eq!(spool.first_timestamp(), Some("2025-11-09T10:00:00Z"));
+        assert_eq!(spool.content_hash(), PlainTextWriter::content_hash(&messages));
+        assert_eq!(spool.messages()?.collect::<Result<Vec<_>>>()?, messages);
+
+        let records = spool.records()?.collect::<Result<Vec<_>>>()?;
+        assert_eq!(records[1]["id"], 2);
+        assert_eq!(records[1]["timestamp"], "2025-11-09T10:00:00Z");
+
+        drop(spool);
+        assert!(!path.exists());
+        Ok(())
+    }
+}
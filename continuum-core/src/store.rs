@@ -0,0 +1,337 @@
+// Read-back access to the plain-text log store
+// Locates stored sessions and loads their messages.jsonl (or messages.jsonl.gz) files
+
+use color_eyre::{eyre::Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use flate2::read::MultiGzDecoder;
+
+use crate::layout::Layout;
+
+/// Plain messages file inside a session directory
+pub const MESSAGES_FILE: &str = "messages.jsonl";
+
+/// Gzip-compressed messages file (see `PlainTextWriter::with_compression`)
+pub const COMPRESSED_MESSAGES_FILE: &str = "messages.jsonl.gz";
+
+/// A message as stored in messages.jsonl
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub id: usize,
+    pub role: String,
+    pub content: String,
+    pub timestamp: Option<String>,
+    /// Tool a `tool` message called or came from, when the importer knew it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    /// Exact token count (see `TokenCounter`); absent in older logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<usize>,
+    /// Model that wrote the message, when the importer knew it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Location of a stored session within the log tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionLocation {
+    pub assistant: String,
+    pub date: String,
+    pub session_id: String,
+    pub path: PathBuf,
+}
+
+/// Subdirectories of `dir`, skipping hidden ones (e.g. the search index)
+pub(crate) fn visible_dirs(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+        if path.is_dir() && !name.starts_with('.') {
+            dirs.push((name, path));
+        }
+    }
+    Ok(dirs)
+}
+
+/// Names of the assistants with sessions under the base directory, sorted
+/// In the default layout these are the top-level directories.
+pub fn list_assistants(base_dir: &Path) -> Result<Vec<String>> {
+    if !base_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let layout = Layout::load(base_dir)?;
+    if !layout.is_default() {
+        let mut assistants: Vec<String> = layout.sessions(base_dir)?.into_iter().map(|s| s.assistant).collect();
+        assistants.sort();
+        assistants.dedup();
+        return Ok(assistants);
+    }
+    let mut assistants: Vec<String> = visible_dirs(base_dir)?.into_iter().map(|(name, _)| name).collect();
+    assistants.sort();
+    Ok(assistants)
+}
+
+/// Every stored session directory of one assistant, sorted by date then session ID
+pub fn list_assistant_sessions(base_dir: &Path, assistant: &str) -> Result<Vec<SessionLocation>> {
+    if !Layout::load(base_dir)?.is_default() {
+        return Ok(list_sessions(base_dir)?.into_iter().filter(|s| s.assistant == assistant).collect());
+    }
+
+    let assistant_dir = base_dir.join(assistant);
+    let mut sessions = Vec::new();
+
+    if !assistant_dir.is_dir() {
+        return Ok(sessions);
+    }
+
+    for (date, date_dir) in visible_dirs(&assistant_dir)? {
+        for (session_id, path) in visible_dirs(&date_dir)? {
+            sessions.push(SessionLocation {
+                assistant: assistant.to_string(),
+                date: date.clone(),
+                session_id,
+                path,
+            });
+        }
+    }
+
+    sessions.sort_by(|a, b| (&a.date, &a.session_id).cmp(&(&b.date, &b.session_id)));
+    Ok(sessions)
+}
+
+/// List every stored session directory under the base directory
+/// Layout: <base_dir>/<assistant>/<YYYY-MM-DD>/<session_id>/, or the one
+/// recorded in the directory (see [`Layout`]). Hidden entries (e.g. the search
+/// index) are skipped. Results are sorted by assistant, date, then session ID.
+pub fn list_sessions(base_dir: &Path) -> Result<Vec<SessionLocation>> {
+    let layout = Layout::load(base_dir)?;
+    if !layout.is_default() {
+        let mut sessions = layout.sessions(base_dir)?;
+        sessions.sort_by(|a, b| (&a.assistant, &a.date, &a.session_id).cmp(&(&b.assistant, &b.date, &b.session_id)));
+        return Ok(sessions);
+    }
+
+    let mut sessions = Vec::new();
+    for assistant in list_assistants(base_dir)? {
+        sessions.extend(list_assistant_sessions(base_dir, &assistant)?);
+    }
+    Ok(sessions)
+}
+
+/// Find a stored session by ID, optionally restricted to one assistant
+/// Errors if the ID is unknown or exists under more than one assistant
+pub fn find_session(base_dir: &Path, session_id: &str, assistant: Option<&str>) -> Result<SessionLocation> {
+    let mut matches: Vec<SessionLocation> = list_sessions(base_dir)?
+        .into_iter()
+        .filter(|s| s.session_id == session_id)
+        .filter(|s| assistant.is_none_or(|a| a.eq_ignore_ascii_case(&s.assistant)))
+        .collect();
+
+    match matches.len() {
+        0 => Err(color_eyre::eyre::eyre!(
+            "No stored session '{}'{} in {}",
+            session_id,
+            assistant.map(|a| format!(" for '{}'", a)).unwrap_or_default(),
+            base_dir.display()
+        )),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let assistants: Vec<&str> = matches.iter().map(|s| s.assistant.as_str()).collect();
+            Err(color_eyre::eyre::eyre!(
+                "Session '{}' exists under multiple assistants ({}); pick one with --assistant",
+                session_id,
+                assistants.join(", ")
+            ))
+        }
+    }
+}
+
+/// Path of a session's messages file, plain or compressed, if either exists
+pub fn messages_file(session_dir: &Path) -> Option<PathBuf> {
+    [MESSAGES_FILE, COMPRESSED_MESSAGES_FILE]
+        .iter()
+        .map(|name| session_dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// Load all messages from a stored session directory
+/// Reads messages.jsonl, or messages.jsonl.gz (which may hold several gzip members)
+pub fn load_messages(session_dir: &Path) -> Result<Vec<StoredMessage>> {
+    let messages_path = messages_file(session_dir).unwrap_or_else(|| session_dir.join(MESSAGES_FILE));
+
+    let mut messages = Vec::new();
+    for line in open_messages(&messages_path)?.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message: StoredMessage = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid message in {}", messages_path.display()))?;
+        messages.push(message);
+    }
+
+    Ok(messages)
+}
+
+/// Open a messages file for line-by-line reading, decompressing messages.jsonl.gz
+pub fn open_messages(messages_path: &Path) -> Result<BufReader<Box<dyn Read>>> {
+    let file = fs::File::open(messages_path)
+        .with_context(|| format!("Failed to open {}", messages_path.display()))?;
+
+    let reader: Box<dyn Read> = if messages_path.ends_with(COMPRESSED_MESSAGES_FILE) {
+        Box::new(MultiGzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    Ok(BufReader::new(reader))
+}
+
+/// Return the last `n` messages, preserving their original order
+pub fn tail_messages(messages: &[StoredMessage], n: usize) -> &[StoredMessage] {
+    &messages[messages.len().saturating_sub(n)..]
+}
+
+/// Find the most recent stored session for an assistant
+/// (see [`list_assistant_sessions`])
+pub fn find_latest_session(base_dir: &Path, assistant: &str) -> Result<SessionLocation> {
+    let sessions = list_assistant_sessions(base_dir, assistant)?;
+
+    if sessions.is_empty() {
+        return Err(color_eyre::eyre::eyre!(
+            "No stored sessions for '{}' in {}",
+            assistant,
+            base_dir.display()
+        ));
+    }
+
+    // Date directories sort lexically; break ties within a day by mtime
+    let mut latest: Option<(std::time::SystemTime, SessionLocation)> = None;
+
+    for session in sessions {
+        let Some(messages_path) = messages_file(&session.path) else {
+            continue;
+        };
+        let modified = fs::metadata(&messages_path)?.modified()?;
+
+        let is_newer = match &latest {
+            None => true,
+            Some((m, latest)) => (&session.date, modified) > (&latest.date, *m),
+        };
+        if is_newer {
+            latest = Some((modified, session));
+        }
+    }
+
+    latest
+        .map(|(_, session)| session)
+        .ok_or_else(|| color_eyre::eyre::eyre!("No stored sessions found for '{}'", assistant))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlainTextWriter;
+    use tempfile::TempDir;
+
+    fn write_fixture(writer: &PlainTextWriter, session_id: &str, date: &str, count: usize) -> Result<()> {
+        for i in 1..=count {
+            let role = if i % 2 == 1 { "user" } else { "assistant" };
+            writer.append_message(
+                session_id,
+                "claude-code",
+                date,
+                i,
+                role,
+                &format!("message {}", i),
+                None,
+                None,
+                None,
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_tail_latest_session_in_order() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        write_fixture(&writer, "older", "2025-11-08", 3)?;
+        write_fixture(&writer, "newer", "2025-11-09", 12)?;
+
+        let latest = find_latest_session(temp_dir.path(), "claude-code")?;
+        assert!(latest.path.ends_with("2025-11-09/newer"));
+        let session_dir = latest.path;
+
+        let messages = load_messages(&session_dir)?;
+        let tail = tail_messages(&messages, 4);
+        let ids: Vec<usize> = tail.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![9, 10, 11, 12]);
+        assert_eq!(tail[3].content, "message 12");
+
+        // Asking for more than exist returns everything
+        assert_eq!(tail_messages(&messages, 50).len(), 12);
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_sessions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        write_fixture(&writer, "b-session", "2025-11-09", 1)?;
+        write_fixture(&writer, "a-session", "2025-11-08", 1)?;
+        fs::create_dir_all(temp_dir.path().join(".hidden/2025-11-09/x"))?;
+
+        let sessions = list_sessions(temp_dir.path())?;
+        let ids: Vec<&str> = sessions.iter().map(|s| s.session_id.as_str()).collect();
+        assert_eq!(ids, vec!["a-session", "b-session"]);
+        assert_eq!(sessions[0].assistant, "claude-code");
+        assert_eq!(sessions[0].date, "2025-11-08");
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_session_disambiguation() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        write_fixture(&writer, "shared", "2025-11-09", 1)?;
+        writer.append_message("shared", "codex", "2025-11-09", 1, "user", "hi", None, None, None)?;
+
+        assert!(find_session(temp_dir.path(), "shared", None).is_err());
+        assert!(find_session(temp_dir.path(), "missing", None).is_err());
+
+        let location = find_session(temp_dir.path(), "shared", Some("codex"))?;
+        assert_eq!(location.assistant, "codex");
+        assert!(location.path.ends_with("codex/2025-11-09/shared"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_latest_session_under_custom_layout() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf()).with_layout("{date}/{assistant}/{session}")?;
+        for (session_id, date) in [("older", "2025-11-08"), ("newer", "2025-11-09")] {
+            write_fixture(&writer, session_id, date, 2)?;
+            writer.write_session(session_id, "claude-code", Some(&format!("{}T10:00:00Z", date)), None, "closed", 2, &[], None, None)?;
+        }
+
+        let latest = find_latest_session(temp_dir.path(), "claude-code")?;
+        assert_eq!((latest.session_id.as_str(), latest.date.as_str()), ("newer", "2025-11-09"));
+        assert_eq!(latest.assistant, "claude-code");
+        assert!(latest.path.ends_with("2025-11-09/claude-code/newer"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_latest_session_missing_assistant() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(find_latest_session(temp_dir.path(), "codex").is_err());
+    }
+}
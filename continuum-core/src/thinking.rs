@@ -0,0 +1,48 @@
+// Thinking block capture
+// Keeps Claude's extended-thinking blocks as `thinking` messages instead of dropping them
+
+use serde_json::Value;
+
+/// Environment variable that turns thinking capture on in the wrappers ("1", "true" or "yes")
+pub const INCLUDE_THINKING_ENV: &str = "CONTINUUM_INCLUDE_THINKING";
+
+/// Role recorded for captured thinking blocks (see [`crate::Role::Thinking`])
+pub const THINKING_ROLE: &str = "thinking";
+
+/// Whether the wrappers should capture thinking blocks
+pub fn thinking_from_env() -> bool {
+    std::env::var(INCLUDE_THINKING_ENV)
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Text of a Claude `thinking` content block; `None` for other blocks and empty thoughts
+///
+/// `redacted_thinking` blocks only carry encrypted data, so they are skipped too.
+pub fn claude_thinking_text(block: &Value) -> Option<&str> {
+    if block.get("type").and_then(|v| v.as_str()) != Some("thinking") {
+        return None;
+    }
+
+    block
+        .get("thinking")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_claude_thinking_blocks() {
+        let block = json!({"type": "thinking", "thinking": "  The user wants a fix.\n", "signature": "abc"});
+        assert_eq!(claude_thinking_text(&block), Some("The user wants a fix."));
+
+        assert_eq!(claude_thinking_text(&json!({"type": "thinking", "thinking": ""})), None);
+        assert_eq!(claude_thinking_text(&json!({"type": "redacted_thinking", "data": "xyz"})), None);
+        assert_eq!(claude_thinking_text(&json!({"type": "text", "text": "hi"})), None);
+    }
+}
@@ -0,0 +1,106 @@
+// Cross-assistant timeline of one day
+// Interleaves the messages of every session filed under a date by timestamp
+
+use chrono::{DateTime, NaiveDate, Utc};
+use color_eyre::Result;
+use serde_json::Value;
+
+use crate::plaintext::parse_timestamp;
+use crate::store::{self, StoredMessage};
+
+/// One message on a [`day_timeline`]
+#[derive(Debug, Clone)]
+pub struct TimelineEntry {
+    /// When the message was logged, or the closest earlier time in its session
+    pub time: Option<DateTime<Utc>>,
+    pub assistant: String,
+    pub session_id: String,
+    pub message: StoredMessage,
+}
+
+impl TimelineEntry {
+    /// The message on one line, cut to `max_chars` characters
+    pub fn snippet(&self, max_chars: usize) -> String {
+        let flat = self.message.content.split_whitespace().collect::<Vec<_>>().join(" ");
+        match flat.char_indices().nth(max_chars) {
+            Some((end, _)) => format!("{}...", &flat[..end]),
+            None => flat,
+        }
+    }
+}
+
+/// Every message of the sessions filed under `date`, oldest first
+///
+/// A message without a timestamp takes the time of the message before it in
+/// its session (or the session's start time), so it stays in place; messages
+/// with no time at all sort first. `assistant` narrows to one assistant.
+pub fn day_timeline(base_dir: &std::path::Path, date: NaiveDate, assistant: Option<&str>) -> Result<Vec<TimelineEntry>> {
+    let day = date.format("%Y-%m-%d").to_string();
+    let mut entries = Vec::new();
+
+    for session in store::list_sessions(base_dir)?
+        .into_iter()
+        .filter(|s| s.date == day)
+        .filter(|s| assistant.is_none_or(|a| a.eq_ignore_ascii_case(&s.assistant)))
+    {
+        if store::messages_file(&session.path).is_none() {
+            continue;
+        }
+
+        let start_time = std::fs::read_to_string(session.path.join("session.json"))
+            .ok()
+            .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+            .and_then(|metadata| metadata["start_time"].as_str().and_then(parse_timestamp));
+
+        let mut time = start_time;
+        for message in store::load_messages(&session.path)? {
+            time = message.timestamp.as_deref().and_then(parse_timestamp).or(time);
+            entries.push(TimelineEntry {
+                time,
+                assistant: session.assistant.clone(),
+                session_id: session.session_id.clone(),
+                message,
+            });
+        }
+    }
+
+    // Stable, so messages logged in the same instant keep their session order
+    entries.sort_by_key(|entry| entry.time);
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlainTextWriter;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_day_timeline_interleaves_sessions() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+        let stamp = |t: &str| Some(format!("2025-11-09T{}Z", t));
+
+        let codex = vec![("user".to_string(), "fix the build".to_string()), ("assistant".to_string(), "done".to_string())];
+        writer.import_messages("c1", "codex", "2025-11-09", &codex, &[stamp("10:00:00"), None], None, false)?;
+        writer.write_session("c1", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 2, &[], None, None)?;
+
+        let goose = vec![("user".to_string(), "what changed?".to_string())];
+        writer.import_messages("g1", "goose", "2025-11-09", &goose, &[stamp("09:30:00")], None, false)?;
+        writer.import_messages("g2", "goose", "2025-11-10", &goose, &[stamp("10:30:00")], None, true)?;
+
+        let day = NaiveDate::from_ymd_opt(2025, 11, 9).unwrap();
+        let timeline = day_timeline(temp_dir.path(), day, None)?;
+        let order: Vec<(&str, &str)> =
+            timeline.iter().map(|e| (e.session_id.as_str(), e.message.content.as_str())).collect();
+        assert_eq!(order, vec![("g1", "what changed?"), ("c1", "fix the build"), ("c1", "done")]);
+
+        // The untimed reply inherits the time of the message before it
+        assert_eq!(timeline[2].time, timeline[1].time);
+
+        let codex_only = day_timeline(temp_dir.path(), day, Some("Codex"))?;
+        assert_eq!(codex_only.len(), 2);
+        assert_eq!(codex_only[0].snippet(3), "fix...");
+        Ok(())
+    }
+}
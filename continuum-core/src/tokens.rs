@@ -0,0 +1,54 @@
+// Token counting for stored messages
+// Exact BPE token counts (tiktoken encodings), where MessageCompressor only estimates
+
+use tiktoken_rs::{cl100k_base_singleton, o200k_base_singleton, CoreBPE};
+
+/// Encoding used for Claude, Codex and any model not known to use another one
+pub const DEFAULT_ENCODING: &str = "cl100k_base";
+
+/// Counts tokens with the tiktoken encoding that matches a model
+pub struct TokenCounter;
+
+impl TokenCounter {
+    /// Number of tokens in `content`
+    ///
+    /// `model` is an encoding name (`cl100k_base`, `o200k_base`) or a model
+    /// name: GPT-4o and the o-series use `o200k_base`, everything else
+    /// `cl100k_base`. Special-token text such as `<|endoftext|>` is counted
+    /// as ordinary text.
+    pub fn count(content: &str, model: &str) -> usize {
+        Self::encoding(model).encode_ordinary(content).len()
+    }
+
+    fn encoding(model: &str) -> &'static CoreBPE {
+        let model = model.to_lowercase();
+        let o200k = model == "o200k_base"
+            || model.starts_with("gpt-4o")
+            || model.starts_with("gpt-4.1")
+            || ["o1", "o3", "o4"].iter().any(|series| model == *series || model.starts_with(&format!("{}-", series)));
+
+        if o200k {
+            o200k_base_singleton()
+        } else {
+            cl100k_base_singleton()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_by_encoding() {
+        assert_eq!(TokenCounter::count("", DEFAULT_ENCODING), 0);
+        assert_eq!(TokenCounter::count("hello world", DEFAULT_ENCODING), 2);
+        assert_eq!(TokenCounter::count("hello world", "o200k_base"), 2);
+        assert_eq!(TokenCounter::count("<|endoftext|>", DEFAULT_ENCODING), 7);
+
+        // The encodings split some text differently
+        let text = "Continuum stores assistant conversations as plain-text JSONL.";
+        assert_eq!(TokenCounter::count(text, "gpt-4o-mini"), TokenCounter::count(text, "o200k_base"));
+        assert_eq!(TokenCounter::count(text, "claude-sonnet-4"), TokenCounter::count(text, DEFAULT_ENCODING));
+    }
+}
@@ -0,0 +1,223 @@
+// Tool call capture
+// Turns tool calls and tool results into `tool` messages instead of dropping them
+
+use color_eyre::{eyre::eyre, Result};
+use serde_json::Value;
+
+use crate::compression::floor_char_boundary;
+use crate::CodexPayload;
+
+/// Environment variable that turns tool capture on in the wrappers ("1", "true" or "yes")
+pub const INCLUDE_TOOLS_ENV: &str = "CONTINUUM_INCLUDE_TOOLS";
+
+/// Environment variable capping the stored length of each tool result, in bytes
+pub const MAX_TOOL_BYTES_ENV: &str = "CONTINUUM_MAX_TOOL_BYTES";
+
+/// Role recorded for captured tool calls and results (see [`crate::Role::Tool`])
+pub const TOOL_ROLE: &str = "tool";
+
+/// Prefix of a stored tool call message
+pub const TOOL_USE_PREFIX: &str = "TOOL_USE: ";
+
+/// Prefix of a stored tool result message
+pub const TOOL_RESULT_PREFIX: &str = "TOOL_RESULT: ";
+
+/// Settings for recording tool activity as messages
+///
+/// Calls are stored as `TOOL_USE: <name> -> <input>` and results as
+/// `TOOL_RESULT: <output>`, both with role `tool`. Results can be huge (file
+/// dumps, build logs), so they can be capped at a byte length.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToolCapture {
+    /// Longest tool result to store; `None` keeps results whole
+    pub max_result_bytes: Option<usize>,
+}
+
+impl ToolCapture {
+    pub fn new(max_result_bytes: Option<usize>) -> Self {
+        ToolCapture { max_result_bytes }
+    }
+
+    /// Capture settings from the environment, or `None` if capture is off
+    pub fn from_env() -> Result<Option<Self>> {
+        if !crate::config::env_flag(INCLUDE_TOOLS_ENV) {
+            return Ok(None);
+        }
+
+        let max_result_bytes = match std::env::var(MAX_TOOL_BYTES_ENV) {
+            Ok(value) => Some(
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|_| eyre!("{} must be a number of bytes, got '{}'", MAX_TOOL_BYTES_ENV, value))?,
+            ),
+            Err(_) => None,
+        };
+
+        Ok(Some(ToolCapture::new(max_result_bytes)))
+    }
+
+    /// Message text for a tool call
+    pub fn format_call(&self, name: &str, input: &str) -> String {
+        format!("{}{} -> {}", TOOL_USE_PREFIX, name, input)
+    }
+
+    /// Message text for a tool result, truncated to the configured cap
+    /// Returns `None` for empty output
+    pub fn format_result(&self, output: &str) -> Option<String> {
+        let output = output.trim_end();
+        if output.is_empty() {
+            return None;
+        }
+
+        match self.max_result_bytes {
+            Some(max) if output.len() > max => {
+                let end = floor_char_boundary(output, max);
+                Some(format!(
+                    "{}{}... [truncated {} bytes]",
+                    TOOL_RESULT_PREFIX,
+                    &output[..end],
+                    output.len() - end
+                ))
+            }
+            _ => Some(format!("{}{}", TOOL_RESULT_PREFIX, output)),
+        }
+    }
+
+    /// Message text for a Claude `tool_use` or `tool_result` content block
+    /// Returns `None` for any other block type
+    pub fn format_claude_block(&self, block: &Value) -> Option<String> {
+        match block.get("type").and_then(|v| v.as_str())? {
+            "tool_use" => {
+                let name = block.get("name").and_then(|v| v.as_str()).unwrap_or("unknown");
+                let input = block.get("input").map(Value::to_string).unwrap_or_default();
+                Some(self.format_call(name, &input))
+            }
+            "tool_result" => self.format_result(&result_text(block.get("content")?)),
+            _ => None,
+        }
+    }
+
+    /// Message text for a Codex `function_call` / `function_call_output` payload
+    /// (and their `custom_tool_call` equivalents); `None` for anything else
+    pub fn format_codex_payload(&self, payload: &CodexPayload) -> Option<String> {
+        match payload.payload_type.as_deref()? {
+            "function_call" | "custom_tool_call" => {
+                let name = payload.name.as_deref().unwrap_or("unknown");
+                let input = payload.arguments.as_deref().or(payload.input.as_deref()).unwrap_or("");
+                Some(self.format_call(name, input))
+            }
+            "function_call_output" | "custom_tool_call_output" => {
+                self.format_result(&result_text(payload.output.as_ref()?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Message text for a Gemini `functionCall` or `functionResponse` content part
+    /// Returns `None` for text and any other part
+    pub fn format_gemini_part(&self, part: &Value) -> Option<String> {
+        if let Some(call) = part.get("functionCall") {
+            let name = call.get("name").and_then(|v| v.as_str()).unwrap_or("unknown");
+            let args = call.get("args").map(Value::to_string).unwrap_or_default();
+            return Some(self.format_call(name, &args));
+        }
+
+        let response = part.get("functionResponse")?.get("response")?;
+        match response.get("output") {
+            Some(output) => self.format_result(&result_text(output)),
+            None => self.format_result(&response.to_string()),
+        }
+    }
+
+    /// Messages for an entry of a Gemini message's `toolCalls`: the call, then its results
+    pub fn format_gemini_tool_call(&self, call: &Value) -> Vec<String> {
+        let name = call.get("name").and_then(|v| v.as_str()).unwrap_or("unknown");
+        let args = call.get("args").map(Value::to_string).unwrap_or_default();
+
+        let results = call.get("result").and_then(|r| r.as_array()).into_iter().flatten();
+        std::iter::once(self.format_call(name, &args))
+            .chain(results.filter_map(|part| self.format_gemini_part(part)))
+            .collect()
+    }
+}
+
+/// Text of a tool result: either a plain string or an array of text blocks
+pub fn result_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_claude_blocks() {
+        let capture = ToolCapture::default();
+
+        let call = json!({"type": "tool_use", "name": "Bash", "input": {"command": "ls"}});
+        assert_eq!(
+            capture.format_claude_block(&call).as_deref(),
+            Some(r#"TOOL_USE: Bash -> {"command":"ls"}"#)
+        );
+
+        let result = json!({"type": "tool_result", "content": [{"type": "text", "text": "a.txt"}, {"type": "text", "text": "b.txt"}]});
+        assert_eq!(capture.format_claude_block(&result).as_deref(), Some("TOOL_RESULT: a.txt\nb.txt"));
+
+        assert!(capture.format_claude_block(&json!({"type": "text", "text": "hi"})).is_none());
+        assert!(capture.format_claude_block(&json!({"type": "tool_result", "content": ""})).is_none());
+    }
+
+    #[test]
+    fn test_result_truncated_on_char_boundary() {
+        let capture = ToolCapture::new(Some(5));
+
+        assert_eq!(capture.format_result("abc").as_deref(), Some("TOOL_RESULT: abc"));
+        // "é" is two bytes, so a 5-byte cap can't split it
+        assert_eq!(
+            capture.format_result("abcdé and more").as_deref(),
+            Some("TOOL_RESULT: abcd... [truncated 11 bytes]")
+        );
+    }
+
+    #[test]
+    fn test_codex_payloads() -> Result<()> {
+        let capture = ToolCapture::default();
+
+        let call: CodexPayload = serde_json::from_value(json!({
+            "type": "function_call",
+            "name": "shell",
+            "arguments": "{\"command\":[\"ls\"]}",
+            "call_id": "call_1"
+        }))?;
+        assert_eq!(
+            capture.format_codex_payload(&call).as_deref(),
+            Some(r#"TOOL_USE: shell -> {"command":["ls"]}"#)
+        );
+
+        let output: CodexPayload = serde_json::from_value(json!({
+            "type": "function_call_output",
+            "call_id": "call_1",
+            "output": "Cargo.toml\nsrc"
+        }))?;
+        assert_eq!(capture.format_codex_payload(&output).as_deref(), Some("TOOL_RESULT: Cargo.toml\nsrc"));
+
+        let message: CodexPayload = serde_json::from_value(json!({
+            "type": "message",
+            "role": "user",
+            "content": [{"type": "input_text", "text": "hi"}]
+        }))?;
+        assert!(capture.format_codex_payload(&message).is_none());
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
 // Core type definitions for Continuum
 
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use color_eyre::{eyre::Context, Result};
 use serde::{Deserialize, Serialize};
 
 /// Role of a message in a conversation
@@ -10,10 +14,11 @@ pub enum Role {
     Assistant,
     System,
     Tool,
+    Thinking,
 }
 
 /// Normalized message format used internally
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Message {
     pub role: Role,
     pub content: String,
@@ -29,6 +34,19 @@ pub enum SessionStatus {
     Compacted,
 }
 
+impl std::str::FromStr for SessionStatus {
+    type Err = color_eyre::Report;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "active" => Ok(SessionStatus::Active),
+            "closed" => Ok(SessionStatus::Closed),
+            "compacted" => Ok(SessionStatus::Compacted),
+            _ => Err(color_eyre::eyre::eyre!("Invalid session status '{}': use active, closed or compacted", value)),
+        }
+    }
+}
+
 /// Session metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionRecord {
@@ -37,6 +55,441 @@ pub struct SessionRecord {
     pub start_time: Option<String>,
     pub end_time: Option<String>,
     pub status: SessionStatus,
+    /// Cost reported by the assistant (older session.json files call it `total_cost_usd`)
+    #[serde(default, alias = "total_cost_usd", skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Fingerprint of the imported messages (see `PlainTextWriter::content_hash`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_count: Option<MessageCount>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// One-line note written with `PlainTextWriter::set_note`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Working directory the assistant ran in, when the wrapper or log recorded it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Claude Code project directory name (`-home-me-app`), for Claude Code sessions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Date directory the session is stored under; not part of session.json
+    /// except in custom layouts, filled in by `PlainTextWriter::list_sessions`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+}
+
+impl SessionRecord {
+    /// Read `<path>/session.json` for a session directory `<base>/<assistant>/<date>/<id>`
+    ///
+    /// `id` and `assistant` missing from the JSON are taken from the path, and
+    /// `date` is the date directory unless session.json records one (as it
+    /// does in custom layouts, see [`crate::Layout`]).
+    pub fn from_dir(path: &Path) -> Result<SessionRecord> {
+        let session_json_path = path.join("session.json");
+        let content = std::fs::read_to_string(&session_json_path)
+            .with_context(|| format!("Failed to read {}", session_json_path.display()))?;
+        let mut metadata: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid {}", session_json_path.display()))?;
+
+        let component = |dir: Option<&Path>| dir.and_then(|d| d.file_name()).map(|n| n.to_string_lossy().into_owned());
+        let date_dir = path.parent();
+        if let Some(object) = metadata.as_object_mut() {
+            for (key, value) in [("id", component(Some(path))), ("assistant", component(date_dir.and_then(Path::parent)))] {
+                if let (false, Some(value)) = (object.contains_key(key), value) {
+                    object.insert(key.to_string(), value.into());
+                }
+            }
+        }
+
+        let mut record: SessionRecord = serde_json::from_value(metadata)
+            .with_context(|| format!("Invalid {}", session_json_path.display()))?;
+        // Custom layouts record the date, since the parent needn't be a date directory
+        if record.date.is_none() {
+            record.date = component(date_dir);
+        }
+        Ok(record)
+    }
+
+    /// Directory of this session under `base_dir`
+    /// Without a known `date`, it's wherever the session is stored, else the date of `start_time`.
+    pub fn session_dir(&self, base_dir: &Path) -> PathBuf {
+        let date = match &self.date {
+            Some(date) => date.clone(),
+            None => match crate::store::find_session(base_dir, &self.id, Some(&self.assistant)) {
+                Ok(location) => return location.path,
+                Err(_) => crate::PlainTextWriter::extract_date(self.start_time.as_deref()),
+            },
+        };
+        crate::Layout::load(base_dir).unwrap_or_default().session_dir(base_dir, &self.assistant, &date, &self.id)
+    }
+
+    /// Path of this session's messages file: messages.jsonl, or messages.jsonl.gz if that's what exists
+    pub fn messages_path(&self, base_dir: &Path) -> PathBuf {
+        let session_dir = self.session_dir(base_dir);
+        crate::store::messages_file(&session_dir).unwrap_or_else(|| session_dir.join("messages.jsonl"))
+    }
+
+    /// Whether this session recorded `cwd` as its working directory
+    /// A trailing `/` on either path is ignored.
+    pub fn ran_in(&self, cwd: &str) -> bool {
+        self.cwd
+            .as_deref()
+            .is_some_and(|recorded| recorded.trim_end_matches('/') == cwd.trim_end_matches('/'))
+    }
+
+    /// Whether this session belongs to `project`, given as a Claude Code
+    /// project directory name or the working directory it encodes
+    pub fn in_project(&self, project: &str) -> bool {
+        self.project.as_deref().is_some_and(|recorded| {
+            recorded == project || recorded == crate::adapters::claude_code::ClaudeCodeAdapter::encode_project_path(project.trim_end_matches('/'))
+        })
+    }
+
+    /// Read every stored message of this session
+    pub fn read_messages(&self, base_dir: &Path) -> Result<Vec<Message>> {
+        crate::store::load_messages(&self.session_dir(base_dir))?
+            .into_iter()
+            .map(|stored| {
+                let role = serde_json::from_value(serde_json::Value::String(stored.role.clone()))
+                    .with_context(|| format!("Unknown role '{}' in session {}", stored.role, self.id))?;
+                Ok(Message { role, content: stored.content, timestamp: stored.timestamp })
+            })
+            .collect()
+    }
+}
+
+/// Cost, duration and token usage reported by an assistant for a session
+/// Only some assistants report this (e.g. Claude's stream-json `result` event)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionCost {
+    pub total_cost_usd: f64,
+    pub duration_ms: u64,
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+impl SessionCost {
+    /// session.json fields for this cost; token counts are omitted when unknown
+    pub fn to_metadata(&self) -> serde_json::Value {
+        let mut metadata = serde_json::json!({
+            "cost_usd": self.total_cost_usd,
+            "duration_ms": self.duration_ms,
+        });
+        if let Some(tokens) = self.input_tokens {
+            metadata["input_tokens"] = tokens.into();
+        }
+        if let Some(tokens) = self.output_tokens {
+            metadata["output_tokens"] = tokens.into();
+        }
+        metadata
+    }
+}
+
+/// Number of messages in a session or aggregate
+pub type MessageCount = usize;
+
+/// Statistics for a single stored session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub session_id: String,
+    pub assistant: String,
+    pub date: String,
+    pub status: Option<String>,
+    pub message_count: MessageCount,
+    pub total_chars: usize,
+    /// Rough estimate: 1 token ~= 4 characters
+    pub approx_tokens: usize,
+    /// Sum of the exact token counts recorded with the messages (older messages have none)
+    #[serde(default)]
+    pub tokens: usize,
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+    /// Cost reported by the assistant, if it records one (see [`SessionCost`])
+    pub total_cost_usd: Option<f64>,
+    pub duration_ms: Option<u64>,
+    /// Messages per model, for messages logged with one
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub models: std::collections::BTreeMap<String, usize>,
+}
+
+/// Which stored sessions `PlainTextWriter::query` returns, and in what order
+///
+/// Dates compare against the session's date directory. `offset` and `limit`
+/// are applied after sorting, for paging through long listings. Build one
+/// field by field or with the builder methods, e.g.
+/// `SessionFilter::new().assistant("codex").tag("project:myapp").max_cost(1.0)`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionFilter {
+    /// Assistant name, matched case-insensitively
+    pub assistant: Option<String>,
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub sort: SessionSort,
+    pub offset: usize,
+    pub limit: Option<usize>,
+    /// Working directory recorded in session.json (see `PlainTextWriter::find_sessions_by_cwd`)
+    pub cwd: Option<String>,
+    /// Claude Code project (see [`SessionRecord::in_project`])
+    pub project: Option<String>,
+    /// Tag the session must carry
+    pub tag: Option<String>,
+    pub status: Option<SessionStatus>,
+    /// Least recorded cost in USD; sessions without a cost don't match
+    pub min_cost: Option<f64>,
+    /// Most recorded cost in USD; sessions without a cost count as free
+    pub max_cost: Option<f64>,
+    /// Text the session's note must contain, ignoring case
+    pub note_contains: Option<String>,
+}
+
+impl SessionFilter {
+    /// A filter that lets every session through
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn assistant(mut self, assistant: impl Into<String>) -> Self {
+        self.assistant = Some(assistant.into());
+        self
+    }
+
+    /// Sessions dated on or after `date`
+    pub fn since(mut self, date: NaiveDate) -> Self {
+        self.since = Some(date);
+        self
+    }
+
+    /// Sessions dated on or before `date`
+    pub fn until(mut self, date: NaiveDate) -> Self {
+        self.until = Some(date);
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into().trim().to_string());
+        self
+    }
+
+    pub fn status(mut self, status: SessionStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn cwd(mut self, cwd: impl Into<String>) -> Self {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    pub fn project(mut self, project: impl Into<String>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    pub fn min_cost(mut self, usd: f64) -> Self {
+        self.min_cost = Some(usd);
+        self
+    }
+
+    pub fn max_cost(mut self, usd: f64) -> Self {
+        self.max_cost = Some(usd);
+        self
+    }
+
+    pub fn note_contains(mut self, text: impl Into<String>) -> Self {
+        self.note_contains = Some(text.into());
+        self
+    }
+
+    pub fn sort(mut self, sort: SessionSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether a session filed under the `date` directory falls within `since`..=`until`
+    pub fn in_range(&self, date: &str) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok_and(|date| {
+            self.since.is_none_or(|since| date >= since) && self.until.is_none_or(|until| date <= until)
+        })
+    }
+
+    /// Whether any criterion needs session.json, i.e. more than the assistant and date
+    pub fn needs_metadata(&self) -> bool {
+        self.cwd.is_some()
+            || self.project.is_some()
+            || self.tag.is_some()
+            || self.status.is_some()
+            || self.min_cost.is_some()
+            || self.max_cost.is_some()
+            || self.note_contains.is_some()
+    }
+
+    /// Whether `record` passes every criterion, checked in order up to the first that fails
+    /// Sorting, `offset` and `limit` only apply to whole listings.
+    pub fn matches(&self, record: &SessionRecord) -> bool {
+        self.assistant.as_deref().is_none_or(|a| a.eq_ignore_ascii_case(&record.assistant))
+            && record.date.as_deref().is_some_and(|date| self.in_range(date))
+            && self.status.is_none_or(|status| record.status == status)
+            && self.tag.as_deref().is_none_or(|tag| record.tags.iter().any(|t| t == tag))
+            && self.min_cost.is_none_or(|min| record.cost_usd.is_some_and(|cost| cost >= min))
+            && self.max_cost.is_none_or(|max| record.cost_usd.unwrap_or(0.0) <= max)
+            && self.note_contains.as_deref().is_none_or(|text| {
+                record.note.as_deref().is_some_and(|note| note.to_lowercase().contains(&text.to_lowercase()))
+            })
+            && self.cwd.as_deref().is_none_or(|cwd| record.ran_in(cwd))
+            && self.project.as_deref().is_none_or(|project| record.in_project(project))
+    }
+
+    /// [`Self::matches`] for a session directory, reading its session.json only if a criterion needs it
+    /// Fails when that session.json is missing or unreadable.
+    pub fn matches_location(&self, location: &crate::store::SessionLocation) -> Result<bool> {
+        if self.assistant.as_deref().is_some_and(|a| !a.eq_ignore_ascii_case(&location.assistant)) || !self.in_range(&location.date) {
+            return Ok(false);
+        }
+        if !self.needs_metadata() {
+            return Ok(true);
+        }
+        Ok(self.matches(&SessionRecord::from_dir(&location.path)?))
+    }
+}
+
+/// Order of a session listing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SessionSort {
+    /// Newest date first, then by assistant and session ID
+    #[default]
+    Date,
+    /// Most messages first (ties newest first)
+    Messages,
+}
+
+/// Statistics aggregated across every stored session for one assistant
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssistantStats {
+    pub assistant: String,
+    #[serde(rename = "sessions")]
+    pub session_count: usize,
+    #[serde(rename = "messages")]
+    pub message_count: MessageCount,
+    pub total_chars: usize,
+    pub approx_tokens: usize,
+    /// Exact token counts recorded with the messages
+    #[serde(default)]
+    pub tokens: usize,
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+    /// Earliest and latest session date directories (YYYY-MM-DD)
+    #[serde(default)]
+    pub first_session_date: Option<String>,
+    #[serde(default)]
+    pub last_session_date: Option<String>,
+    /// Sum of recorded costs; sessions without cost metadata contribute nothing
+    pub total_cost_usd: f64,
+    pub duration_ms: u64,
+    /// Sessions left out because their session.json was missing or unreadable
+    pub skipped_sessions: usize,
+    /// Messages per model across the sessions, for messages logged with one
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub models: std::collections::BTreeMap<String, usize>,
+}
+
+impl AssistantStats {
+    /// Fold one session's statistics into the aggregate
+    pub fn add(&mut self, session: &SessionStats) {
+        self.session_count += 1;
+        self.message_count += session.message_count;
+        self.total_chars += session.total_chars;
+        self.approx_tokens += session.approx_tokens;
+        self.tokens += session.tokens;
+        self.total_cost_usd += session.total_cost_usd.unwrap_or(0.0);
+        self.duration_ms += session.duration_ms.unwrap_or(0);
+        self.first_timestamp = min_timestamp(self.first_timestamp.take(), session.first_timestamp.clone());
+        self.last_timestamp = max_timestamp(self.last_timestamp.take(), session.last_timestamp.clone());
+        self.first_session_date = min_timestamp(self.first_session_date.take(), Some(session.date.clone()));
+        self.last_session_date = max_timestamp(self.last_session_date.take(), Some(session.date.clone()));
+        for (model, count) in &session.models {
+            *self.models.entry(model.clone()).or_default() += count;
+        }
+    }
+}
+
+/// Per-assistant totals as printed by `continuum stats --format json`
+///
+/// A deliberately small, stable shape for scripts; [`AssistantStats`] carries more.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AssistantSummary {
+    pub session_count: usize,
+    pub message_count: MessageCount,
+    pub total_characters: usize,
+    pub total_cost_usd: f64,
+    pub first_session_date: Option<String>,
+    pub last_session_date: Option<String>,
+}
+
+impl From<&AssistantStats> for AssistantSummary {
+    fn from(stats: &AssistantStats) -> Self {
+        AssistantSummary {
+            session_count: stats.session_count,
+            message_count: stats.message_count,
+            total_characters: stats.total_chars,
+            total_cost_usd: stats.total_cost_usd,
+            first_session_date: stats.first_session_date.clone(),
+            last_session_date: stats.last_session_date.clone(),
+        }
+    }
+}
+
+/// Statistics for all sessions filed under one date directory
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DayStats {
+    pub sessions: usize,
+    pub messages: MessageCount,
+    pub total_cost_usd: f64,
+}
+
+/// Whole-store summary: per assistant, per day, and how many sessions were skipped
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsSummary {
+    pub assistants: std::collections::BTreeMap<String, AssistantStats>,
+    pub days: std::collections::BTreeMap<String, DayStats>,
+    /// Recorded cost per calendar month (YYYY-MM), broken down by assistant
+    pub monthly_costs: std::collections::BTreeMap<String, std::collections::BTreeMap<String, f64>>,
+    pub skipped_sessions: usize,
+}
+
+// RFC3339 timestamps in a single offset compare correctly as strings
+fn min_timestamp(a: Option<String>, b: Option<String>) -> Option<String> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn max_timestamp(a: Option<String>, b: Option<String>) -> Option<String> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
 }
 
 // Codex-specific log format types
@@ -52,8 +505,19 @@ pub struct CodexLogEntry {
 
 #[derive(Debug, Deserialize)]
 pub struct CodexPayload {
+    /// Item kind: "message", "reasoning", "function_call", "function_call_output", ...
+    #[serde(rename = "type")]
+    pub payload_type: Option<String>,
     pub role: Option<String>,
     pub content: Option<Vec<CodexContent>>,
+    /// Tool name (function_call / custom_tool_call)
+    pub name: Option<String>,
+    /// JSON-encoded arguments (function_call)
+    pub arguments: Option<String>,
+    /// Raw input (custom_tool_call)
+    pub input: Option<String>,
+    /// Tool output (function_call_output / custom_tool_call_output)
+    pub output: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -62,3 +526,38 @@ pub struct CodexContent {
     pub content_type: String,
     pub text: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_session_record_from_dir() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base = temp_dir.path();
+        let session_dir = base.join("codex/2025-11-08/s1");
+        std::fs::create_dir_all(&session_dir)?;
+        // Older session.json files lack the id and assistant
+        std::fs::write(session_dir.join("session.json"), r#"{"start_time": null, "end_time": null, "status": "closed"}"#)?;
+        std::fs::write(
+            session_dir.join("messages.jsonl"),
+            "{\"id\":1,\"role\":\"user\",\"content\":\"hi\",\"timestamp\":null}\n\
+             {\"id\":2,\"role\":\"assistant\",\"content\":\"hello\",\"timestamp\":\"2025-11-08T09:00:00Z\"}\n",
+        )?;
+
+        let record = SessionRecord::from_dir(&session_dir)?;
+        assert_eq!(record.id, "s1");
+        assert_eq!(record.assistant, "codex");
+        assert_eq!(record.date.as_deref(), Some("2025-11-08"));
+        assert_eq!(record.messages_path(base), session_dir.join("messages.jsonl"));
+
+        let messages = record.read_messages(base)?;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].role, Role::Assistant);
+        assert_eq!(messages[1].timestamp.as_deref(), Some("2025-11-08T09:00:00Z"));
+
+        assert!(SessionRecord::from_dir(&base.join("codex/2025-11-08/missing")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,412 @@
+// Stored session validation
+// Checks messages.jsonl records and session.json against the shape PlainTextWriter writes,
+// and the session's consistency: message IDs must run 1, 2, 3..., timestamps must not go
+// backwards, and session.json's message_count must match the messages file
+
+use chrono::{DateTime, FixedOffset};
+use color_eyre::{eyre::{bail, Context}, Result};
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use crate::plaintext::{temp_path, write_json_atomic};
+use crate::store::{self, messages_file, open_messages};
+use crate::PlainTextWriter;
+
+/// Roles a stored message may have (see [`crate::Role`])
+pub const KNOWN_ROLES: &[&str] = &["user", "assistant", "system", "tool", "thinking"];
+
+/// Statuses a session.json may have (see [`crate::SessionStatus`])
+pub const KNOWN_STATUSES: &[&str] = &["active", "closed", "compacted"];
+
+/// A problem that makes a stored session inconsistent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// session.json is missing or is not a JSON object
+    MissingMetadata,
+    /// The messages file could not be read
+    Unreadable(String),
+    /// A messages file line (1-based) that is not JSON
+    InvalidJson { line: usize, message: String },
+    /// A record or session.json without the shape `PlainTextWriter` writes
+    Format { line: Option<usize>, message: String },
+    /// A message whose ID is not one more than the previous message's
+    IdOutOfSequence { line: usize, expected: u64, found: Option<u64> },
+    /// session.json's message_count disagrees with the messages file
+    CountMismatch { recorded: Option<u64>, actual: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::MissingMetadata => write!(f, "missing or unreadable session.json"),
+            ValidationError::Unreadable(message) => write!(f, "cannot read messages: {}", message),
+            ValidationError::InvalidJson { line, message } => write!(f, "line {}: invalid JSON: {}", line, message),
+            ValidationError::Format { line: Some(line), message } => write!(f, "line {}: {}", line, message),
+            ValidationError::Format { line: None, message } => write!(f, "session.json: {}", message),
+            ValidationError::IdOutOfSequence { line, expected, found: Some(found) } => {
+                write!(f, "line {}: message ID {} where {} was expected", line, found, expected)
+            }
+            ValidationError::IdOutOfSequence { line, expected, found: None } => {
+                write!(f, "line {}: no message ID where {} was expected", line, expected)
+            }
+            ValidationError::CountMismatch { recorded: Some(recorded), actual } => {
+                write!(f, "session.json says {} messages, the messages file has {}", recorded, actual)
+            }
+            ValidationError::CountMismatch { recorded: None, actual } => {
+                write!(f, "session.json has no message_count, the messages file has {}", actual)
+            }
+        }
+    }
+}
+
+/// Something unusual that still leaves the session readable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationWarning {
+    /// A message timestamped before the message above it
+    TimestampWentBack { line: usize, previous: String, timestamp: String },
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationWarning::TimestampWentBack { line, previous, timestamp } => {
+                write!(f, "line {}: timestamp {} is before the previous message's {}", line, timestamp, previous)
+            }
+        }
+    }
+}
+
+/// Outcome of checking one session directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub session_dir: PathBuf,
+    /// True when there are no errors (warnings are allowed)
+    pub is_valid: bool,
+    pub errors: Vec<ValidationError>,
+    pub warnings: Vec<ValidationWarning>,
+}
+
+impl ValidationReport {
+    /// True when `SessionValidator::fix` can repair every error
+    pub fn is_fixable(&self) -> bool {
+        self.errors.iter().all(|e| {
+            matches!(e, ValidationError::IdOutOfSequence { .. } | ValidationError::CountMismatch { .. })
+        })
+    }
+}
+
+/// Checks and repairs the consistency of stored sessions
+pub struct SessionValidator;
+
+impl SessionValidator {
+    /// Check a session directory's messages file against itself and its session.json
+    /// A session with no messages file yet is valid if session.json counts 0 messages.
+    pub fn validate(session_dir: &Path) -> ValidationReport {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+
+        let metadata = fs::read_to_string(session_dir.join("session.json"))
+            .ok()
+            .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+            .filter(Value::is_object);
+        match metadata {
+            Some(ref metadata) => errors.extend(
+                check_session_metadata(metadata)
+                    .into_iter()
+                    .map(|message| ValidationError::Format { line: None, message }),
+            ),
+            None => errors.push(ValidationError::MissingMetadata),
+        }
+
+        let mut actual = 0;
+        if let Some(messages_path) = messages_file(session_dir) {
+            if let Err(e) = check_messages(&messages_path, &mut actual, &mut errors, &mut warnings) {
+                errors.push(ValidationError::Unreadable(e.to_string()));
+            }
+        }
+
+        if let Some(ref metadata) = metadata {
+            let recorded = metadata["message_count"].as_u64();
+            if recorded != Some(actual as u64) {
+                errors.push(ValidationError::CountMismatch { recorded, actual });
+            }
+        }
+
+        ValidationReport {
+            session_dir: session_dir.to_path_buf(),
+            is_valid: errors.is_empty(),
+            errors,
+            warnings,
+        }
+    }
+
+    /// Renumber message IDs from 1 and set session.json's message_count to match
+    /// Refuses sessions with errors it cannot repair, such as lines that are not
+    /// JSON. Returns whether any file was rewritten.
+    pub fn fix(session_dir: &Path) -> Result<bool> {
+        let report = Self::validate(session_dir);
+        if report.is_valid {
+            return Ok(false);
+        }
+        if !report.is_fixable() {
+            bail!("{} has problems --fix cannot repair", session_dir.display());
+        }
+
+        let mut count = 0;
+        if let Some(messages_path) = messages_file(session_dir) {
+            let mut records = Vec::new();
+            for line in open_messages(&messages_path)?.lines() {
+                let line = line?;
+                if !line.trim().is_empty() {
+                    records.push(serde_json::from_str::<Value>(&line)?);
+                }
+            }
+            count = records.len();
+
+            let renumbered = records.iter().enumerate().any(|(idx, r)| r["id"].as_u64() != Some(idx as u64 + 1));
+            if renumbered {
+                for (idx, record) in records.iter_mut().enumerate() {
+                    record["id"] = json!(idx + 1);
+                }
+                let gzip = messages_path.ends_with(store::COMPRESSED_MESSAGES_FILE);
+                let encoded = PlainTextWriter::encode_records(&records, gzip)?;
+                let tmp = temp_path(&messages_path);
+                let written = fs::write(&tmp, &encoded).and_then(|_| fs::rename(&tmp, &messages_path));
+                if written.is_err() {
+                    let _ = fs::remove_file(&tmp);
+                }
+                written.with_context(|| format!("Failed to write {}", messages_path.display()))?;
+            }
+        }
+
+        let metadata_path = session_dir.join("session.json");
+        let metadata: Value = serde_json::from_str(&fs::read_to_string(&metadata_path)?)?;
+        if metadata["message_count"].as_u64() != Some(count as u64) {
+            let updates = json!({ "message_count": count });
+            write_json_atomic(&metadata_path, &PlainTextWriter::merge_metadata(metadata, &updates))?;
+        }
+        Ok(true)
+    }
+}
+
+/// Walk the messages file, counting records and collecting per-line problems
+fn check_messages(
+    messages_path: &Path,
+    count: &mut usize,
+    errors: &mut Vec<ValidationError>,
+    warnings: &mut Vec<ValidationWarning>,
+) -> Result<()> {
+    let mut previous: Option<(DateTime<FixedOffset>, String)> = None;
+    for (index, line) in open_messages(messages_path)?.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_number = index + 1;
+        *count += 1;
+
+        let record = match serde_json::from_str::<Value>(&line) {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(ValidationError::InvalidJson { line: line_number, message: e.to_string() });
+                continue;
+            }
+        };
+        errors.extend(
+            check_message(&record)
+                .into_iter()
+                .map(|message| ValidationError::Format { line: Some(line_number), message }),
+        );
+
+        let expected = *count as u64;
+        let found = record["id"].as_u64();
+        if found != Some(expected) {
+            errors.push(ValidationError::IdOutOfSequence { line: line_number, expected, found });
+        }
+
+        let Some(timestamp) = record["timestamp"].as_str() else {
+            continue;
+        };
+        let Ok(parsed) = DateTime::parse_from_rfc3339(timestamp) else {
+            continue;
+        };
+        if let Some((before, ref previous_text)) = previous {
+            if parsed < before {
+                warnings.push(ValidationWarning::TimestampWentBack {
+                    line: line_number,
+                    previous: previous_text.clone(),
+                    timestamp: timestamp.to_string(),
+                });
+            }
+        }
+        previous = Some((parsed, timestamp.to_string()));
+    }
+    Ok(())
+}
+
+/// Problems with one messages.jsonl record (empty if it is well-formed)
+pub fn check_message(record: &Value) -> Vec<String> {
+    let Some(fields) = record.as_object() else {
+        return vec!["record is not a JSON object".to_string()];
+    };
+    let mut problems = Vec::new();
+
+    if !fields.get("id").is_some_and(Value::is_u64) {
+        problems.push("`id` must be a non-negative integer".to_string());
+    }
+    match fields.get("role").and_then(Value::as_str) {
+        Some(role) if KNOWN_ROLES.contains(&role) => {}
+        Some(role) => problems.push(format!("unknown role '{}'", role)),
+        None => problems.push("`role` must be a string".to_string()),
+    }
+    if !fields.get("content").is_some_and(Value::is_string) {
+        problems.push("`content` must be a string".to_string());
+    }
+    if !fields.get("timestamp").is_none_or(|t| t.is_string() || t.is_null()) {
+        problems.push("`timestamp` must be a string or null".to_string());
+    }
+    if !fields.get("tool_name").is_none_or(Value::is_string) {
+        problems.push("`tool_name` must be a string".to_string());
+    }
+    if !fields.get("tokens").is_none_or(Value::is_u64) {
+        problems.push("`tokens` must be a non-negative integer".to_string());
+    }
+
+    problems
+}
+
+/// Problems with a session.json (empty if it has every field `write_session` writes)
+pub fn check_session_metadata(metadata: &Value) -> Vec<String> {
+    let Some(fields) = metadata.as_object() else {
+        return vec!["session.json is not a JSON object".to_string()];
+    };
+    let mut problems = Vec::new();
+
+    for key in ["id", "assistant", "created_at"] {
+        if !fields.get(key).is_some_and(Value::is_string) {
+            problems.push(format!("`{}` must be a string", key));
+        }
+    }
+    for key in ["start_time", "end_time"] {
+        if !fields.get(key).is_some_and(|t| t.is_string() || t.is_null()) {
+            problems.push(format!("`{}` must be a string or null", key));
+        }
+    }
+    match fields.get("status").and_then(Value::as_str) {
+        Some(status) if KNOWN_STATUSES.contains(&status) => {}
+        Some(status) => problems.push(format!("unknown status '{}'", status)),
+        None => problems.push("`status` must be a string".to_string()),
+    }
+    if !fields.get("message_count").is_some_and(Value::is_u64) {
+        problems.push("`message_count` must be a non-negative integer".to_string());
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_message() {
+        let valid = json!({"id": 1, "role": "user", "content": "hi", "timestamp": null, "tokens": 1});
+        assert!(check_message(&valid).is_empty());
+        assert!(check_message(&json!({"id": 2, "role": "tool", "content": "", "tool_name": "Bash"})).is_empty());
+
+        let problems = check_message(&json!({"id": "1", "role": "robot", "timestamp": 5}));
+        assert_eq!(
+            problems,
+            vec![
+                "`id` must be a non-negative integer",
+                "unknown role 'robot'",
+                "`content` must be a string",
+                "`timestamp` must be a string or null",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_reports_format_lines() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        let session_dir =
+            writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 2, &[], None, None)?;
+        writer.append_message("s1", "codex", "2025-11-09", 1, "user", "hello", None, None, None)?;
+        writer.append_message("s1", "codex", "2025-11-09", 2, "assistant", "hi", None, Some(1), None)?;
+        assert!(SessionValidator::validate(&session_dir).is_valid);
+
+        // A hand edit that breaks the third record
+        let messages_path = session_dir.join("messages.jsonl");
+        let mut text = fs::read_to_string(&messages_path)?;
+        text.push_str("{\"id\": 3, \"role\": \"user\"}\nnot json\n");
+        fs::write(&messages_path, text)?;
+        fs::write(session_dir.join("session.json"), r#"{"id": "s1", "status": "done", "message_count": 4}"#)?;
+
+        let errors = SessionValidator::validate(&session_dir).errors;
+        let lines: Vec<Option<usize>> = errors
+            .iter()
+            .filter_map(|e| match e {
+                ValidationError::Format { line, .. } => Some(*line),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(lines, vec![None, None, None, None, None, Some(3)]);
+        assert!(errors.iter().any(|e| e.to_string() == "line 3: `content` must be a string"));
+        assert!(errors.iter().any(|e| e.to_string() == "session.json: unknown status 'done'"));
+        assert!(matches!(errors.last(), Some(ValidationError::InvalidJson { line: 4, .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_and_fix_session() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let writer = PlainTextWriter::with_base_dir(temp_dir.path().to_path_buf());
+
+        let session_dir =
+            writer.write_session("s1", "codex", Some("2025-11-09T10:00:00Z"), None, "closed", 3, &[], None, None)?;
+        writer.append_message("s1", "codex", "2025-11-09", 1, "user", "one", Some("2025-11-09T10:00:00Z"), None, None)?;
+        writer.append_message("s1", "codex", "2025-11-09", 2, "assistant", "two", Some("2025-11-09T10:01:00Z"), None, None)?;
+        writer.append_message("s1", "codex", "2025-11-09", 3, "user", "three", Some("2025-11-09T10:02:00Z"), None, None)?;
+        let report = SessionValidator::validate(&session_dir);
+        assert!(report.is_valid, "{:?}", report.errors);
+        assert!(!SessionValidator::fix(&session_dir)?);
+
+        // A gap in the IDs, a clock that went back, and a stale count
+        writer.append_message("s1", "codex", "2025-11-09", 7, "assistant", "four", Some("2025-11-09T09:59:00Z"), None, None)?;
+        let report = SessionValidator::validate(&session_dir);
+        assert!(!report.is_valid);
+        assert_eq!(
+            report.errors,
+            vec![
+                ValidationError::IdOutOfSequence { line: 4, expected: 4, found: Some(7) },
+                ValidationError::CountMismatch { recorded: Some(3), actual: 4 },
+            ]
+        );
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.warnings[0].to_string().starts_with("line 4: timestamp 2025-11-09T09:59:00Z"));
+
+        assert!(SessionValidator::fix(&session_dir)?);
+        let report = SessionValidator::validate(&session_dir);
+        assert!(report.is_valid, "{:?}", report.errors);
+        assert_eq!(report.warnings.len(), 1);
+        let ids: Vec<usize> = store::load_messages(&session_dir)?.iter().map(|m| m.id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+
+        // Lines that are not JSON are left for a person to look at
+        let messages_path = session_dir.join("messages.jsonl");
+        let mut text = fs::read_to_string(&messages_path)?;
+        text.push_str("not json\n");
+        fs::write(&messages_path, text)?;
+        let report = SessionValidator::validate(&session_dir);
+        assert!(matches!(report.errors[0], ValidationError::InvalidJson { line: 5, .. }));
+        assert!(!report.is_fixable());
+        assert!(SessionValidator::fix(&session_dir).is_err());
+        Ok(())
+    }
+}
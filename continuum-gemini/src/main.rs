@@ -3,11 +3,26 @@
 
 use std::process::{Command, Stdio};
 use color_eyre::{eyre::Context, Result};
+use continuum_core::adapters::gemini;
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
-    let args: Vec<String> = std::env::args().skip(1).collect();
+    // A broken config file shouldn't stop the assistant from starting
+    let mut config = continuum_core::Config::load().unwrap_or_else(|e| {
+        eprintln!("\u{26a0} Warning: Ignoring continuum config: {:#}", e);
+        continuum_core::Config::default()
+    });
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Our own flags; gemini would reject them
+    if let Some(level) = continuum_core::take_noise_flag(&mut args)? {
+        config.noise_filter.level = level;
+    }
+    if continuum_core::take_redact_flag(&mut args) {
+        config.redact = true;
+    }
 
     // Find the real gemini binary
     let gemini_path = which::which("gemini")
@@ -48,19 +63,16 @@ fn main() -> Result<()> {
         gemini_path_str
     };
 
-    // Check for no-save marker file
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    let marker_path = std::path::Path::new(&home).join(".continuum-nosave");
-    let skip_saving = marker_path.exists();
+    // Skipped via $CONTINUUM_NOSAVE or the single-use ~/.continuum-nosave marker
+    let skip_saving = continuum_core::config::should_skip_saving();
 
     if skip_saving {
-        let _ = std::fs::remove_file(&marker_path);
         eprintln!("\u{26a0} This conversation will NOT be saved to continuum logs");
     }
 
     // Snapshot session files BEFORE running gemini
-    let gemini_tmp = std::path::PathBuf::from(&home).join(".gemini/tmp");
-    let before_sessions = snapshot_session_files(&gemini_tmp);
+    let gemini_tmp = config.assistant_path("gemini-cli").unwrap_or_default();
+    let before_sessions = gemini::session_files(&gemini_tmp);
 
     // Spawn gemini as a child process
     let status = Command::new(&real_gemini)
@@ -74,15 +86,15 @@ fn main() -> Result<()> {
 
     // After gemini exits, find new or modified session files
     if !skip_saving {
-        let after_sessions = snapshot_session_files(&gemini_tmp);
+        let after_sessions = gemini::session_files(&gemini_tmp);
         let new_sessions = find_changed_sessions(&before_sessions, &after_sessions);
 
         for session_path in new_sessions {
             eprintln!("\n\u{1f4dd} Importing session to continuum logs...");
-            match import_session_to_continuum(&session_path) {
+            match import_session_to_continuum(&session_path, &config) {
                 Ok(dir) => {
                     if !prompt_save_conversation()? {
-                        let _ = std::fs::remove_dir_all(dir);
+                        discard_session(&dir, "gemini-cli", &config);
                         eprintln!("\u{2717} Conversation discarded");
                     } else {
                         eprintln!("\u{2713} Conversation saved");
@@ -101,40 +113,6 @@ fn main() -> Result<()> {
 /// Snapshot of session files with their modification times
 type SessionSnapshot = Vec<(std::path::PathBuf, std::time::SystemTime)>;
 
-/// Collect all session JSON files under ~/.gemini/tmp/*/chats/
-fn snapshot_session_files(gemini_tmp: &std::path::Path) -> SessionSnapshot {
-    let mut sessions = Vec::new();
-
-    if !gemini_tmp.exists() {
-        return sessions;
-    }
-
-    // Walk through project directories
-    if let Ok(projects) = std::fs::read_dir(gemini_tmp) {
-        for project_entry in projects.flatten() {
-            let chats_dir = project_entry.path().join("chats");
-            if !chats_dir.is_dir() {
-                continue;
-            }
-
-            if let Ok(files) = std::fs::read_dir(&chats_dir) {
-                for file_entry in files.flatten() {
-                    let path = file_entry.path();
-                    if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                        if let Ok(meta) = std::fs::metadata(&path) {
-                            if let Ok(modified) = meta.modified() {
-                                sessions.push((path, modified));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    sessions
-}
-
 /// Find sessions that are new or modified compared to the before snapshot
 fn find_changed_sessions(
     before: &SessionSnapshot,
@@ -155,35 +133,22 @@ fn find_changed_sessions(
         .collect()
 }
 
-/// Gemini session JSON structure
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct GeminiSession {
-    session_id: String,
-    start_time: Option<String>,
-    last_updated: Option<String>,
-    messages: Vec<GeminiMessage>,
-}
-
-#[derive(serde::Deserialize)]
-struct GeminiMessage {
-    timestamp: Option<String>,
-    #[serde(rename = "type")]
-    msg_type: String,
-    content: serde_json::Value, // String for gemini/info, array for user
-}
-
-fn import_session_to_continuum(session_path: &std::path::Path) -> Result<std::path::PathBuf> {
-    use continuum_core::{MessageCompressor, PlainTextWriter};
+fn import_session_to_continuum(session_path: &std::path::Path, config: &continuum_core::Config) -> Result<std::path::PathBuf> {
+    use continuum_core::tool_capture::INCLUDE_TOOLS_ENV;
+    use continuum_core::{ImportSpool, MessageCompressor, PlainTextWriter, ToolCapture};
 
-    let writer = PlainTextWriter::new()?;
-    let compressor = MessageCompressor::new();
+    let writer = PlainTextWriter::from_config(config);
+    let compressor = MessageCompressor::from_config(&config.noise_filter).unwrap_or_else(|e| {
+        eprintln!("\u{26a0} Warning: Ignoring noise config: {:#}", e);
+        MessageCompressor::new()
+    });
+    // Tool calls and results are only logged when CONTINUUM_INCLUDE_TOOLS is set
+    let tools = ToolCapture::from_env().unwrap_or_else(|e| {
+        eprintln!("\u{26a0} Warning: Ignoring {}: {:#}", INCLUDE_TOOLS_ENV, e);
+        None
+    });
 
-    // Read and parse the session JSON
-    let raw = std::fs::read_to_string(session_path)
-        .with_context(|| format!("Failed to read {}", session_path.display()))?;
-    let session: GeminiSession = serde_json::from_str(&raw)
-        .with_context(|| format!("Failed to parse {}", session_path.display()))?;
+    let session = gemini::parse_session(session_path, tools.as_ref())?;
 
     // Build known skill names from ~/.gemini/skills/ directory
     let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
@@ -199,64 +164,37 @@ fn import_session_to_continuum(session_path: &std::path::Path) -> Result<std::pa
         }
     }
 
-    // Extract user and gemini messages
-    let mut messages: Vec<(String, String)> = Vec::new();
+    // Check for /skill-name at the start of user messages
     let mut skills: Vec<String> = Vec::new();
-
-    for msg in &session.messages {
-        match msg.msg_type.as_str() {
-            "user" => {
-                // Content is array of {text: "..."}
-                if let Some(arr) = msg.content.as_array() {
-                    let text: String = arr
-                        .iter()
-                        .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
-                        .collect::<Vec<_>>()
-                        .join("\n");
-                    if !text.is_empty() {
-                        // Check for /skill-name at the start of user messages
-                        let trimmed = text.trim();
-                        if trimmed.starts_with('/') {
-                            if let Some(cmd) = trimmed.split_whitespace().next() {
-                                let skill_name = &cmd[1..]; // strip leading /
-                                if known_skills.iter().any(|s| s == skill_name)
-                                    && !skills.contains(&skill_name.to_string())
-                                {
-                                    skills.push(skill_name.to_string());
-                                }
-                            }
-                        }
-                        messages.push(("user".to_string(), text));
-                    }
-                }
+    for (_, text, _) in session.messages.iter().filter(|(role, _, _)| role == "user") {
+        if let Some(skill_name) = text.split_whitespace().next().and_then(|cmd| cmd.strip_prefix('/')) {
+            if known_skills.iter().any(|s| s == skill_name) && !skills.iter().any(|s| s == skill_name) {
+                skills.push(skill_name.to_string());
             }
-            "gemini" => {
-                // Content is a plain string
-                if let Some(text) = msg.content.as_str() {
-                    if !text.is_empty() {
-                        messages.push(("assistant".to_string(), text.to_string()));
-                    }
-                }
-            }
-            // Skip "info" and other types
-            _ => {}
         }
     }
 
-    // Compress messages
-    let compressed = compressor.compress_batch(&messages);
-    let message_count = compressed.len();
+    // Compress messages into a spool, keeping each one's own timestamp
+    let mut spool = ImportSpool::new()?;
+    for (role, content, timestamp) in &session.messages {
+        let timestamp = timestamp.as_deref().and_then(PlainTextWriter::normalize_timestamp);
+        if let Some(cleaned) = compressor.compress(content) {
+            spool.push(role, &cleaned, timestamp.as_deref(), None)?;
+        }
+    }
+    let message_count = spool.len();
 
     if message_count == 0 {
         return Err(color_eyre::eyre::eyre!("No messages to import"));
     }
 
-    let start_time = session.start_time.as_deref()
-        .unwrap_or_else(|| session.messages.first()
-            .and_then(|m| m.timestamp.as_deref())
-            .unwrap_or("unknown"));
-    let end_time = session.last_updated.as_deref();
-    let date = PlainTextWriter::extract_date(Some(start_time));
+    let start_time = session.start_time.as_deref().unwrap_or("unknown");
+    let end_time = session.end_time.as_deref();
+    let date = writer.session_date(&session.session_id, "gemini-cli", Some(start_time));
+
+    // A resumed session only gets its new messages appended, whether the
+    // stored log is plain or compressed
+    writer.import_spool(&session.session_id, "gemini-cli", &date, &spool, Some(start_time), false)?;
 
     // Write session metadata
     let session_dir = writer.write_session(
@@ -267,31 +205,15 @@ fn import_session_to_continuum(session_path: &std::path::Path) -> Result<std::pa
         "closed",
         message_count,
         &skills,
+        None,
+        None,
     )?;
 
-    // Clear any existing messages.jsonl so resumed sessions don't duplicate
-    let messages_path = session_dir.join("messages.jsonl");
-    if messages_path.exists() {
-        std::fs::remove_file(&messages_path)?;
-    }
-
-    // Write messages
-    for (idx, (role, content)) in compressed.iter().enumerate() {
-        let timestamp = session.messages
-            .iter()
-            .filter(|m| m.msg_type == "user" || m.msg_type == "gemini")
-            .nth(idx)
-            .and_then(|m| m.timestamp.as_deref());
-
-        writer.append_message(
-            &session.session_id,
-            "gemini-cli",
-            &date,
-            idx + 1,
-            role,
-            content,
-            timestamp,
-        )?;
+    // Keep the search index fresh; a failure here shouldn't lose the import
+    if config.search_index {
+        if let Err(e) = writer.index_spool(&session.session_id, "gemini-cli", &date, &spool) {
+            eprintln!("\u{26a0} Warning: Failed to update search index: {}", e);
+        }
     }
 
     eprintln!("\u{2713} Saved {} messages to continuum logs", message_count);
@@ -315,3 +237,14 @@ fn prompt_save_conversation() -> Result<bool> {
         _ => Ok(true),
     }
 }
+
+/// Delete a discarded session, dropping it from the search and session indexes
+fn discard_session(session_dir: &std::path::Path, assistant: &str, config: &continuum_core::Config) {
+    let writer = continuum_core::PlainTextWriter::from_config(config);
+    let result = continuum_core::SessionRecord::from_dir(session_dir).and_then(|record| {
+        writer.delete_session_confirmed(&record.id, assistant, record.date.as_deref().unwrap_or_default())
+    });
+    if let Err(e) = result {
+        eprintln!("\u{26a0} Warning: Failed to discard session: {}", e);
+    }
+}
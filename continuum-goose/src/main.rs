@@ -3,13 +3,32 @@
 
 use std::process::{Command, Stdio};
 use color_eyre::{eyre::Context, Result};
+use continuum_core::{DuplicateSession, ImportOutcome};
+use rusqlite::types::Value;
 use rusqlite::Connection;
 
 fn main() -> Result<()> {
     color_eyre::install()?;
 
+    // A broken config file shouldn't stop the assistant from starting
+    let mut config = continuum_core::Config::load().unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring continuum config: {:#}", e);
+        continuum_core::Config::default()
+    });
+
+    // The directory goose runs in, recorded as the session's cwd
+    let cwd = std::env::current_dir().ok().map(|dir| dir.to_string_lossy().into_owned());
+
     // Get all arguments passed to continuum-goose
-    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Our own flags; goose would reject them
+    if let Some(level) = continuum_core::take_noise_flag(&mut args)? {
+        config.noise_filter.level = level;
+    }
+    if continuum_core::take_redact_flag(&mut args) {
+        config.redact = true;
+    }
 
     // Find the real goose binary
     let goose_path = which::which("goose")
@@ -53,21 +72,20 @@ fn main() -> Result<()> {
         goose_path_str
     };
 
-    // Check for no-save marker file
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    let marker_path = std::path::Path::new(&home).join(".continuum-nosave");
-    let skip_saving = marker_path.exists();
+    // Skipped via $CONTINUUM_NOSAVE or the single-use ~/.continuum-nosave marker
+    let skip_saving = continuum_core::config::should_skip_saving();
 
     if skip_saving {
-        // Delete marker file immediately
-        let _ = std::fs::remove_file(&marker_path);
         eprintln!("⚠ This conversation will NOT be saved to continuum logs");
     }
 
-    // Get the latest session ID BEFORE running goose
-    let db_path = std::path::PathBuf::from(&home).join(".local/share/goose/sessions/sessions.db");
+    // Get the latest session (and when it was last updated) BEFORE running goose
+    let db_path = config
+        .assistant_path("goose")
+        .unwrap_or_default()
+        .join(continuum_core::adapters::goose::SESSIONS_DB_FILE);
 
-    let before_session = find_latest_session_id(&db_path);
+    let before_session = find_latest_session(&db_path);
 
     // Spawn goose as a child process
     let status = Command::new(&real_goose)
@@ -79,43 +97,34 @@ fn main() -> Result<()> {
         .context("Failed to spawn goose process")?
         .wait()?;
 
-    // After goose exits, find the latest session ID
-    let after_session = find_latest_session_id(&db_path);
+    // After goose exits, find the latest session again
+    let after_session = find_latest_session(&db_path);
 
-    // Import the session if it's different from before (and we're not skipping)
+    // Import a new session, or one resumed during this run (same ID, newer
+    // updated_at); a resumed session only gets its new messages appended
     let mut session_dir: Option<std::path::PathBuf> = None;
     if !skip_saving {
-        if let (Some(before), Some(after)) = (before_session, after_session.clone()) {
-            if before != after {
-                eprintln!("\n📝 Importing session to continuum logs...");
-                match import_session_to_continuum(&db_path, &after) {
-                    Ok(dir) => {
-                        session_dir = Some(dir);
-                    }
-                    Err(e) => {
-                        eprintln!("⚠ Warning: Failed to import session: {}", e);
-                    }
-                }
-            }
-        } else if let Some(after) = after_session {
-            // First session ever
+        if let Some((after_id, _)) = after_session.as_ref().filter(|after| before_session.as_ref() != Some(*after)) {
             eprintln!("\n📝 Importing session to continuum logs...");
-            match import_session_to_continuum(&db_path, &after) {
-                Ok(dir) => {
+            match import_session_to_continuum(&db_path, after_id, &config, cwd.as_deref()) {
+                Ok((dir, true)) => {
                     session_dir = Some(dir);
                 }
-                Err(e) => {
-                    eprintln!("⚠ Warning: Failed to import session: {}", e);
-                }
+                // The session was saved on a previous run, so there is nothing to discard
+                Ok((_, false)) => {}
+                Err(e) => match e.downcast_ref::<DuplicateSession>() {
+                    Some(duplicate) => eprintln!("⏭ Not saved again: {}", duplicate),
+                    None => eprintln!("⚠ Warning: Failed to import session: {}", e),
+                },
             }
         }
     }
 
-    // Post-conversation review prompt (if session was saved)
+    // Post-conversation review prompt (if a new session was saved)
     if let Some(ref dir) = session_dir {
         if !prompt_save_conversation()? {
             // User chose to discard - delete the session directory
-            let _ = std::fs::remove_dir_all(dir);
+            discard_session(dir, "goose", &config);
             eprintln!("✗ Conversation discarded");
         } else {
             eprintln!("✓ Conversation saved");
@@ -125,38 +134,52 @@ fn main() -> Result<()> {
     std::process::exit(status.code().unwrap_or(1))
 }
 
-fn find_latest_session_id(db_path: &std::path::Path) -> Option<String> {
+/// ID and `updated_at` of the most recently updated session
+fn find_latest_session(db_path: &std::path::Path) -> Option<(String, Value)> {
     if !db_path.exists() {
         return None;
     }
 
     let conn = Connection::open(db_path).ok()?;
 
-    let session_id: String = conn
-        .query_row(
-            "SELECT id FROM sessions ORDER BY updated_at DESC LIMIT 1",
-            [],
-            |row| row.get(0),
-        )
-        .ok()?;
-
-    Some(session_id)
+    conn.query_row(
+        "SELECT id, updated_at FROM sessions ORDER BY updated_at DESC LIMIT 1",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .ok()
 }
 
-fn import_session_to_continuum(db_path: &std::path::Path, session_id: &str) -> Result<std::path::PathBuf> {
-    use continuum_core::{MessageCompressor, PlainTextWriter};
+/// Import a session, returning its directory and whether this run created it
+fn import_session_to_continuum(
+    db_path: &std::path::Path,
+    session_id: &str,
+    config: &continuum_core::Config,
+    cwd: Option<&str>,
+) -> Result<(std::path::PathBuf, bool)> {
+    use continuum_core::tool_capture::{INCLUDE_TOOLS_ENV, TOOL_ROLE};
+    use continuum_core::{ImportSpool, MessageCompressor, PlainTextWriter, ToolCapture};
     use continuum_core::adapters::goose::parse_goose_content;
 
-    let writer = PlainTextWriter::new()?;
-    let compressor = MessageCompressor::new();
-    let mut messages: Vec<(String, String)> = Vec::new();
-    let start_time = chrono::Utc::now().to_rfc3339();
+    let writer = PlainTextWriter::from_config(config);
+    let compressor = MessageCompressor::from_config(&config.noise_filter).unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring noise config: {:#}", e);
+        MessageCompressor::new()
+    });
+    // Tool calls and results are only logged when CONTINUUM_INCLUDE_TOOLS is set
+    let tools = ToolCapture::from_env().unwrap_or_else(|e| {
+        eprintln!("⚠ Warning: Ignoring {}: {:#}", INCLUDE_TOOLS_ENV, e);
+        None
+    });
+    let mut stream = compressor.stream();
+    let mut spool = ImportSpool::new()?;
+    let mut first_timestamp: Option<String> = None;
 
     // Query messages from database
     let conn = Connection::open(db_path)?;
 
     let mut stmt = conn.prepare(
-        "SELECT role, content_json FROM messages
+        "SELECT role, content_json, timestamp FROM messages
          WHERE session_id = ?1
          ORDER BY id ASC"
     )?;
@@ -165,27 +188,44 @@ fn import_session_to_continuum(db_path: &std::path::Path, session_id: &str) -> R
         Ok((
             row.get::<_, String>(0)?,
             row.get::<_, String>(1)?,
+            row.get::<_, Option<String>>(2)?,
         ))
     })?;
 
     for row_result in rows {
-        let (role, content_json) = row_result?;
-        let content = parse_goose_content(&content_json)?;
-
-        if !content.is_empty() {
-            messages.push((role, content));
+        let (role, content_json, timestamp) = row_result?;
+        let content = parse_goose_content(&content_json, tools.as_ref())?;
+        let tool_messages = content.tool_messages.into_iter().map(|text| (TOOL_ROLE.to_string(), text));
+        let messages = Some((role, content.text)).filter(|(_, text)| !text.is_empty()).into_iter().chain(tool_messages);
+
+        let timestamp = timestamp.as_deref().and_then(PlainTextWriter::normalize_timestamp);
+        for (role, content) in messages {
+            if first_timestamp.is_none() {
+                first_timestamp.clone_from(&timestamp);
+            }
+            if let Some(cleaned) = stream.compress(&role, &content) {
+                spool.push(&role, &cleaned, timestamp.as_deref(), None)?;
+            }
         }
     }
 
-    // Compress messages
-    let compressed = compressor.compress_batch(&messages);
-    let message_count = compressed.len();
+    // File the session under its first message's day
+    let start_time = first_timestamp.unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let message_count = spool.len();
 
     if message_count == 0 {
         return Err(color_eyre::eyre::eyre!("No messages to import"));
     }
 
-    let date = PlainTextWriter::extract_date(Some(&start_time));
+    let date = writer.session_date(session_id, "goose", Some(&start_time));
+
+    // Only a session this run creates may be discarded afterwards; a rewrite
+    // of one saved earlier (shorter source, new noise level) must not be
+    let existed = continuum_core::store::find_session(writer.base_dir(), session_id, Some("goose")).is_ok();
+
+    // Write messages first (a resumed session only appends what's new); this
+    // refuses content already stored under another session before anything is created
+    let outcome = writer.import_spool(session_id, "goose", &date, &spool, Some(&start_time), false)?;
 
     // Write session
     let session_dir = writer.write_session(
@@ -196,24 +236,24 @@ fn import_session_to_continuum(db_path: &std::path::Path, session_id: &str) -> R
         "closed",
         message_count,
         &[],
+        None,
+        cwd,
     )?;
 
-    // Write messages
-    for (idx, (role, content)) in compressed.iter().enumerate() {
-        writer.append_message(
-            session_id,
-            "goose",
-            &date,
-            idx + 1,
-            role,
-            content,
-            Some(&start_time),
-        )?;
+    // Keep the search index fresh; a failure here shouldn't lose the import
+    if config.search_index {
+        if let Err(e) = writer.index_spool(session_id, "goose", &date, &spool) {
+            eprintln!("⚠ Warning: Failed to update search index: {}", e);
+        }
     }
 
-    eprintln!("✓ Saved {} messages to continuum logs", message_count);
+    match outcome {
+        ImportOutcome::Unchanged => eprintln!("✓ Continuum logs already up to date"),
+        ImportOutcome::Appended(count) => eprintln!("✓ Appended {} new messages to continuum logs", count),
+        ImportOutcome::Rewritten(_) => eprintln!("✓ Saved {} messages to continuum logs", message_count),
+    }
 
-    Ok(session_dir)
+    Ok((session_dir, !existed))
 }
 
 /// Prompt user whether to save the conversation
@@ -233,3 +273,14 @@ fn prompt_save_conversation() -> Result<bool> {
         _ => Ok(true), // Default to save (Y or Enter)
     }
 }
+
+/// Delete a discarded session, dropping it from the search and session indexes
+fn discard_session(session_dir: &std::path::Path, assistant: &str, config: &continuum_core::Config) {
+    let writer = continuum_core::PlainTextWriter::from_config(config);
+    let result = continuum_core::SessionRecord::from_dir(session_dir).and_then(|record| {
+        writer.delete_session_confirmed(&record.id, assistant, record.date.as_deref().unwrap_or_default())
+    });
+    if let Err(e) = result {
+        eprintln!("⚠ Warning: Failed to discard session: {}", e);
+    }
+}